@@ -0,0 +1,793 @@
+//! Broadcom BCM2835 mini-UART 驱动 (`brcm,bcm2835-aux-uart`)
+//!
+//! 树莓派 AUX 外设中的精简版 16550：寄存器按 4 字节间隔排列，另有 AUX_MU_CNTL/STAT，
+//! 收发各有 8 字节 FIFO，只支持 7/8 位数据、无校验、1 位停止位，不支持回环。
+//! 波特率跟随 VPU 核心时钟：`baud = core_clk / (8 * (BAUD + 1))`。
+//!
+//! 整个 AUX 外设共用一个使能寄存器（AUX_ENABLES），mini-UART 在使能前不可访问。
+//! 通过 [`MiniUart::new_with_aux`] 传入 AUX 基地址时，驱动在 `open`/`close` 中管理该使能位；
+//! 只映射了 UART 寄存器时使用 [`MiniUart::new`]，由固件或时钟驱动负责使能。
+
+use core::{num::NonZeroU32, ptr::NonNull};
+
+use alloc::sync::Arc;
+
+use rdif_serial::{BSerial, InterfaceRaw, SerialDyn, SetBackError, TIrqHandler, TSender};
+use tock_registers::{interfaces::*, register_bitfields, register_structs, registers::*};
+
+use crate::{
+    claim::{self, Claim, ClaimError, Region},
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, IrqCause, Stats},
+    Capabilities, Caps, Config, ConfigError, DataBits, Features, FifoThreshold, InterruptMask,
+    Parity, RawReciever, RawSender, StopBits, Suspend, TransferError,
+};
+
+register_bitfields! [
+    u32,
+
+    /// AUX Enables
+    AUX_ENABLES [
+        MINI_UART OFFSET(0) NUMBITS(1) [],
+        SPI1 OFFSET(1) NUMBITS(1) [],
+        SPI2 OFFSET(2) NUMBITS(1) []
+    ],
+
+    /// Mini UART I/O Data
+    IO [
+        DATA OFFSET(0) NUMBITS(8) []
+    ],
+
+    /// Mini UART Interrupt Enable
+    IER [
+        RX OFFSET(0) NUMBITS(1) [],
+        TX OFFSET(1) NUMBITS(1) []
+    ],
+
+    /// Mini UART Interrupt Identify（写入时为 FIFO 清除）
+    IIR [
+        NOT_PENDING OFFSET(0) NUMBITS(1) [],
+        ID OFFSET(1) NUMBITS(2) [
+            None = 0b00,
+            TxEmpty = 0b01,
+            RxReady = 0b10
+        ],
+        FIFO_ENABLED OFFSET(6) NUMBITS(2) []
+    ],
+
+    /// Mini UART Line Control
+    LCR [
+        DATA_SIZE OFFSET(0) NUMBITS(2) [
+            SevenBit = 0b00,
+            EightBit = 0b11
+        ],
+        BREAK OFFSET(6) NUMBITS(1) [],
+        DLAB OFFSET(7) NUMBITS(1) []
+    ],
+
+    /// Mini UART Modem Control
+    MCR [
+        RTS OFFSET(1) NUMBITS(1) []
+    ],
+
+    /// Mini UART Line Status
+    LSR [
+        DATA_READY OFFSET(0) NUMBITS(1) [],
+        RX_OVERRUN OFFSET(1) NUMBITS(1) [],
+        TX_EMPTY OFFSET(5) NUMBITS(1) [],
+        TX_IDLE OFFSET(6) NUMBITS(1) []
+    ],
+
+    /// Mini UART Extra Control
+    CNTL [
+        RX_ENABLE OFFSET(0) NUMBITS(1) [],
+        TX_ENABLE OFFSET(1) NUMBITS(1) [],
+        RTS_AUTO_FLOW OFFSET(2) NUMBITS(1) [],
+        CTS_AUTO_FLOW OFFSET(3) NUMBITS(1) []
+    ],
+
+    /// Mini UART Extra Status
+    STAT [
+        RX_AVAILABLE OFFSET(0) NUMBITS(1) [],
+        TX_SPACE OFFSET(1) NUMBITS(1) [],
+        RX_IDLE OFFSET(2) NUMBITS(1) [],
+        TX_IDLE OFFSET(3) NUMBITS(1) [],
+        RX_OVERRUN OFFSET(4) NUMBITS(1) [],
+        TX_FULL OFFSET(5) NUMBITS(1) [],
+        TX_DONE OFFSET(9) NUMBITS(1) [],
+        RX_LEVEL OFFSET(16) NUMBITS(4) [],
+        TX_LEVEL OFFSET(24) NUMBITS(4) []
+    ],
+
+    /// Mini UART Baudrate
+    BAUD [
+        BAUD OFFSET(0) NUMBITS(16) []
+    ]
+];
+
+register_structs! {
+    pub MiniUartRegisters {
+        (0x00 => io: ReadWrite<u32, IO::Register>),           // 数据寄存器
+        (0x04 => ier: ReadWrite<u32, IER::Register>),         // 中断使能寄存器
+        (0x08 => iir: ReadWrite<u32, IIR::Register>),         // 中断标识 / FIFO 清除寄存器
+        (0x0c => lcr: ReadWrite<u32, LCR::Register>),         // 线路控制寄存器
+        (0x10 => mcr: ReadWrite<u32, MCR::Register>),         // 调制解调器控制寄存器
+        (0x14 => lsr: ReadOnly<u32, LSR::Register>),          // 线路状态寄存器
+        (0x18 => msr: ReadOnly<u32>),                         // 调制解调器状态寄存器
+        (0x1c => scratch: ReadWrite<u32>),                    // 暂存寄存器
+        (0x20 => cntl: ReadWrite<u32, CNTL::Register>),       // 额外控制寄存器
+        (0x24 => stat: ReadOnly<u32, STAT::Register>),        // 额外状态寄存器
+        (0x28 => baud: ReadWrite<u32, BAUD::Register>),       // 波特率寄存器
+        (0x2c => @END),
+    }
+}
+
+// SAFETY: 寄存器访问均为单次 32 位读写
+unsafe impl Sync for MiniUartRegisters {}
+
+/// mini-UART 寄存器相对 AUX 基地址的偏移
+const MINI_UART_OFFSET: usize = 0x40;
+
+/// AUX_ENABLES 相对 AUX 基地址的偏移
+const AUX_ENABLES_OFFSET: usize = 0x04;
+
+/// 收发 FIFO 深度
+const FIFO_DEPTH: u16 = 8;
+
+/// 等待发送完成的最大轮询次数
+const BUSY_SPIN_LIMIT: usize = 1_000_000;
+
+/// BCM2835 mini-UART 驱动结构体
+pub struct MiniUart {
+    base: Reg,
+    aux_enables: Option<NonNull<ReadWrite<u32, AUX_ENABLES::Register>>>,
+    clock_freq: u32,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    tx_pause: TxPause,
+    tx: Option<MiniUartSender>,
+    rx: Option<MiniUartReciever>,
+    irq: Option<MiniUartIrqHandler>,
+    /// 通过 `try_new` 系列创建时持有的区域占用，与各句柄共享
+    claim: Option<Arc<Claim>>,
+}
+
+unsafe impl Send for MiniUart {}
+
+impl MiniUart {
+    /// 使用 mini-UART 寄存器基地址创建实例，`clock_freq` 为 VPU 核心时钟
+    ///
+    /// 不占用寄存器区域，需要防止同一设备被重复创建时使用 [`Self::try_new`]
+    pub fn new(base: NonNull<u8>, clock_freq: u32) -> Self {
+        Self::with_parts(base, None, clock_freq, None)
+    }
+
+    /// 使用 AUX 外设基地址创建实例，驱动负责 AUX_ENABLES 中的 mini-UART 使能位
+    ///
+    /// 不占用寄存器区域，占用的版本为 [`Self::try_new_with_aux`]
+    pub fn new_with_aux(aux_base: NonNull<u8>, clock_freq: u32) -> Self {
+        let (base, enables) = Self::aux_parts(aux_base);
+        Self::with_parts(base, Some(enables), clock_freq, None)
+    }
+
+    pub fn new_boxed(base: NonNull<u8>, clock_freq: u32) -> BSerial {
+        SerialDyn::new_boxed(Self::new(base, clock_freq))
+    }
+
+    /// 占用 mini-UART 寄存器区域后创建实例，区域与已有实例重叠时返回错误
+    pub fn try_new(base: NonNull<u8>, clock_freq: u32) -> Result<Self, ClaimError> {
+        let claim = claim::claim(Region::mmio(base, size_of::<MiniUartRegisters>()))?;
+        Ok(Self::with_parts(base, None, clock_freq, Some(claim)))
+    }
+
+    /// 占用从 AUX 基地址到 mini-UART 寄存器末尾的区域后创建实例
+    pub fn try_new_with_aux(aux_base: NonNull<u8>, clock_freq: u32) -> Result<Self, ClaimError> {
+        let claim = claim::claim(Region::mmio(
+            aux_base,
+            MINI_UART_OFFSET + size_of::<MiniUartRegisters>(),
+        ))?;
+        let (base, enables) = Self::aux_parts(aux_base);
+        Ok(Self::with_parts(
+            base,
+            Some(enables),
+            clock_freq,
+            Some(claim),
+        ))
+    }
+
+    pub fn try_new_boxed(base: NonNull<u8>, clock_freq: u32) -> Result<BSerial, ClaimError> {
+        Ok(SerialDyn::new_boxed(Self::try_new(base, clock_freq)?))
+    }
+
+    /// 通过 `try_new` 系列创建时持有的寄存器区域占用
+    pub fn claim(&self) -> Option<&Claim> {
+        self.claim.as_deref()
+    }
+
+    /// AUX 基地址下 mini-UART 寄存器和 AUX_ENABLES 的地址
+    fn aux_parts(
+        aux_base: NonNull<u8>,
+    ) -> (NonNull<u8>, NonNull<ReadWrite<u32, AUX_ENABLES::Register>>) {
+        // SAFETY: mini-UART 和 AUX_ENABLES 都位于 AUX 外设的寄存器区间内
+        unsafe {
+            (
+                aux_base.add(MINI_UART_OFFSET),
+                aux_base.add(AUX_ENABLES_OFFSET).cast(),
+            )
+        }
+    }
+
+    fn with_parts(
+        base: NonNull<u8>,
+        aux_enables: Option<NonNull<ReadWrite<u32, AUX_ENABLES::Register>>>,
+        clock_freq: u32,
+        claim: Option<Arc<Claim>>,
+    ) -> Self {
+        let base = Reg(base.cast());
+        let tx_pause = TxPause::default();
+
+        Self {
+            base,
+            aux_enables,
+            clock_freq,
+            tx_pause: tx_pause.clone(),
+            tx: Some(MiniUartSender {
+                base,
+                stats: None,
+                tx_pause: tx_pause.clone(),
+                _claim: claim.clone(),
+            }),
+            rx: Some(MiniUartReciever {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            irq: Some(MiniUartIrqHandler {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            claim,
+        }
+    }
+
+    fn registers(&self) -> &MiniUartRegisters {
+        self.base.registers()
+    }
+
+    /// 打开或关闭 AUX 中的 mini-UART 使能位，不影响 SPI1/SPI2
+    fn set_aux_enabled(&self, enabled: bool) {
+        if let Some(enables) = self.aux_enables {
+            // SAFETY: 地址在构造时由调用方保证有效
+            let enables = unsafe { enables.as_ref() };
+            enables.modify(AUX_ENABLES::MINI_UART.val(enabled as u32));
+        }
+    }
+
+    /// 挂接统计计数器，只对尚未取出的收发和中断句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+        if let Some(irq) = self.irq.as_mut() {
+            irq.stats = Some(stats);
+        }
+    }
+
+    /// 计算波特率寄存器值：BAUD = core_clk / (8 * baud) - 1
+    fn divisor_for(&self, baudrate: u32) -> Result<u32, ConfigError> {
+        if baudrate == 0 || self.clock_freq == 0 {
+            return Err(ConfigError::InvalidBaudrate);
+        }
+
+        let div = (self.clock_freq as u64 + 4 * baudrate as u64) / (8 * baudrate as u64);
+        if div == 0 || div > 0x1_0000 {
+            return Err(ConfigError::InvalidBaudrate);
+        }
+        Ok(div as u32 - 1)
+    }
+
+    /// 等待发送 FIFO 和移位寄存器都为空
+    fn wait_tx_idle(&self) -> bool {
+        for _ in 0..BUSY_SPIN_LIMIT {
+            if self.registers().lsr.is_set(LSR::TX_IDLE) {
+                return true;
+            }
+            core::hint::spin_loop();
+        }
+        false
+    }
+
+    /// 初始化 mini-UART
+    fn init(&self) {
+        self.set_aux_enabled(true);
+
+        let regs = self.registers();
+        regs.cntl.set(0);
+        regs.ier.set(0);
+
+        // 清空收发 FIFO
+        regs.iir.set(0b110);
+        regs.lcr.modify(LCR::DLAB::CLEAR + LCR::BREAK::CLEAR);
+        regs.mcr.write(MCR::RTS::CLEAR);
+
+        regs.cntl.write(CNTL::RX_ENABLE::SET + CNTL::TX_ENABLE::SET);
+    }
+}
+
+impl InterfaceRaw for MiniUart {
+    type IrqHandler = MiniUartIrqHandler;
+
+    type Sender = crate::Sender;
+
+    type Reciever = crate::Reciever;
+
+    fn base_addr(&self) -> usize {
+        self.base.0.as_ptr() as usize
+    }
+
+    fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        // 校验会拒绝校验位和 2 位停止位
+        self.capabilities().validate(config)?;
+        let div = config
+            .baudrate
+            .map(|baudrate| self.divisor_for(baudrate))
+            .transpose()?;
+
+        if !self.wait_tx_idle() {
+            return Err(ConfigError::Timeout);
+        }
+
+        let regs = self.registers();
+        if let Some(div) = div {
+            regs.baud.write(BAUD::BAUD.val(div));
+        }
+        match config.data_bits {
+            Some(DataBits::Seven) => regs.lcr.modify(LCR::DATA_SIZE::SevenBit),
+            Some(_) => regs.lcr.modify(LCR::DATA_SIZE::EightBit),
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    fn baudrate(&self) -> u32 {
+        let div = self.registers().baud.read(BAUD::BAUD);
+        self.clock_freq / (8 * (div + 1))
+    }
+
+    fn data_bits(&self) -> DataBits {
+        if self.registers().lcr.read(LCR::DATA_SIZE) & 1 != 0 {
+            DataBits::Eight
+        } else {
+            DataBits::Seven
+        }
+    }
+
+    fn stop_bits(&self) -> StopBits {
+        StopBits::One
+    }
+
+    fn parity(&self) -> Parity {
+        Parity::None
+    }
+
+    fn clock_freq(&self) -> Option<NonZeroU32> {
+        self.clock_freq.try_into().ok()
+    }
+
+    fn open(&mut self) {
+        self.init()
+    }
+
+    fn close(&mut self) {
+        let regs = self.registers();
+        regs.ier.set(0);
+        regs.cntl.set(0);
+        self.set_aux_enabled(false);
+    }
+
+    // mini-UART 不支持回环
+    fn enable_loopback(&mut self) {}
+
+    fn disable_loopback(&mut self) {}
+
+    fn is_loopback_enabled(&self) -> bool {
+        false
+    }
+
+    fn set_irq_mask(&mut self, mask: InterruptMask) {
+        let rx = mask.contains(InterruptMask::RX_AVAILABLE) as u32;
+        let tx = mask.contains(InterruptMask::TX_EMPTY) as u32;
+        self.registers()
+            .ier
+            .write(IER::RX.val(rx) + IER::TX.val(tx));
+    }
+
+    fn get_irq_mask(&self) -> InterruptMask {
+        let ier = self.registers().ier.extract();
+        let mut mask = InterruptMask::empty();
+
+        if ier.is_set(IER::RX) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if ier.is_set(IER::TX) {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        mask
+    }
+
+    fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+        self.irq.take()
+    }
+
+    fn take_tx(&mut self) -> Option<Self::Sender> {
+        self.tx.take().map(crate::Sender::MiniUartSender)
+    }
+
+    fn take_rx(&mut self) -> Option<Self::Reciever> {
+        self.rx.take().map(crate::Reciever::MiniUartReciever)
+    }
+
+    fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+        let tx = match tx {
+            crate::Sender::MiniUartSender(s) => s,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    tx.external_base().unwrap_or(0), // 不匹配的发送器类型
+                ));
+            }
+        };
+
+        if self.base != tx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                tx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.tx = Some(tx);
+        Ok(())
+    }
+
+    fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+        let rx = match rx {
+            crate::Reciever::MiniUartReciever(r) => r,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    rx.external_base().unwrap_or(0), // 不匹配的接收器类型
+                ));
+            }
+        };
+
+        if self.base != rx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                rx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.rx = Some(rx);
+        Ok(())
+    }
+}
+
+impl Caps for MiniUart {
+    fn capabilities(&self) -> Capabilities {
+        let (min_baudrate, max_baudrate) =
+            Capabilities::baudrate_range(self.clock_freq, 8, 0x1_0000);
+
+        Capabilities {
+            fifo_depth: FIFO_DEPTH,
+            min_baudrate,
+            max_baudrate,
+            data_bits: &[DataBits::Seven, DataBits::Eight],
+            stop_bits: &[StopBits::One],
+            parity: &[Parity::None],
+            features: Features::HW_FLOW_CONTROL,
+        }
+    }
+}
+
+impl FifoThreshold for MiniUart {}
+
+impl Flush for MiniUart {
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError> {
+        // 写 IIR 的 bit 1/2 分别清空接收/发送 FIFO
+        let clear = match queue {
+            FlushQueue::Rx => 0b010,
+            FlushQueue::Tx => 0b100,
+            FlushQueue::Both => 0b110,
+        };
+        self.registers().iir.set(clear);
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), FlushError> {
+        if self.wait_tx_idle() {
+            Ok(())
+        } else {
+            Err(FlushError::Timeout)
+        }
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// mini-UART 挂起时保存的寄存器上下文
+#[derive(Debug, Clone, Copy)]
+pub struct MiniUartContext {
+    ier: u32,
+    lcr: u32,
+    mcr: u32,
+    cntl: u32,
+    baud: u32,
+}
+
+impl Suspend for MiniUart {
+    type Context = MiniUartContext;
+
+    fn save_context(&self) -> Self::Context {
+        let regs = self.registers();
+        MiniUartContext {
+            ier: regs.ier.get(),
+            lcr: regs.lcr.get(),
+            mcr: regs.mcr.get(),
+            cntl: regs.cntl.get(),
+            baud: regs.baud.get(),
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        // 寄存器只有在 AUX 使能后才可写
+        self.set_aux_enabled(true);
+
+        let regs = self.registers();
+        regs.cntl.set(0);
+        regs.ier.set(0);
+
+        regs.baud.set(ctx.baud);
+        regs.lcr.set(ctx.lcr);
+        regs.mcr.set(ctx.mcr);
+        regs.iir.set(0b110);
+
+        regs.ier.set(ctx.ier);
+        regs.cntl.set(ctx.cntl);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Reg(NonNull<MiniUartRegisters>);
+
+unsafe impl Send for Reg {}
+
+impl Reg {
+    fn registers(&self) -> &MiniUartRegisters {
+        unsafe { self.0.as_ref() }
+    }
+}
+
+pub struct MiniUartSender {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    tx_pause: TxPause,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for MiniUartSender {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl TSender for MiniUartSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        RawSender::write_byte(self, byte)
+    }
+}
+
+impl RawSender for MiniUartSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return false;
+        };
+        if !self.base.registers().lsr.is_set(LSR::TX_EMPTY) {
+            return false;
+        }
+
+        self.base.registers().io.write(IO::DATA.val(byte as _));
+        if let Some(stats) = self.stats {
+            stats.record_tx(1);
+        }
+
+        true
+    }
+}
+
+pub struct MiniUartReciever {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for MiniUartReciever {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl MiniUartReciever {
+    fn read_raw(&mut self) -> Option<Result<u8, TransferError>> {
+        // 读取 LSR 会清除溢出标志，必须与 DATA_READY 一起读出
+        let lsr = self.base.registers().lsr.extract();
+        if !lsr.is_set(LSR::DATA_READY) {
+            return None;
+        }
+
+        let data = self.base.registers().io.read(IO::DATA) as u8;
+        if lsr.is_set(LSR::RX_OVERRUN) {
+            Some(Err(TransferError::Overrun(data)))
+        } else {
+            Some(Ok(data))
+        }
+    }
+}
+
+impl RawReciever for MiniUartReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        let result = self.read_raw()?;
+        if let Some(stats) = self.stats {
+            stats.record_rx(&result);
+        }
+        Some(result)
+    }
+}
+
+pub struct MiniUartIrqHandler {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+unsafe impl Sync for MiniUartIrqHandler {}
+
+impl TIrqHandler for MiniUartIrqHandler {
+    /// 中断为电平触发，状态随 FIFO 变化自动清除；
+    /// 没有待发送数据时需要调用方关闭 `TX_EMPTY` 中断。
+    fn clean_interrupt_status(&self) -> InterruptMask {
+        let iir = self.base.registers().iir.extract();
+        let mut mask = InterruptMask::empty();
+
+        if iir.is_set(IIR::NOT_PENDING) {
+            return mask;
+        }
+
+        let cause = match iir.read_as_enum(IIR::ID) {
+            Some(IIR::ID::Value::RxReady) => {
+                mask |= InterruptMask::RX_AVAILABLE;
+                Some(IrqCause::Rx)
+            }
+            Some(IIR::ID::Value::TxEmpty) => {
+                mask |= InterruptMask::TX_EMPTY;
+                Some(IrqCause::Tx)
+            }
+            _ => None,
+        };
+
+        if let (Some(stats), Some(cause)) = (self.stats, cause) {
+            stats.record_irq(cause);
+        }
+
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake_mmio::FakeMmio;
+
+    fn uart(regs: &FakeMmio<11>, clock_freq: u32) -> MiniUart {
+        // 发送空闲，配置时无需等待
+        regs.set(0x14, LSR::TX_IDLE::SET.value);
+        MiniUart::new(regs.base(), clock_freq)
+    }
+
+    #[test]
+    fn baud_divisor_rounds_to_nearest() {
+        let regs = FakeMmio::<11>::new();
+        let mut uart = uart(&regs, 500_000_000);
+
+        // 500 MHz / (8 * 115200) = 542.5，取整为 543
+        uart.set_config(&Config::new().baudrate(115200)).unwrap();
+        assert_eq!(regs.get(0x28), 542);
+        assert_eq!(uart.baudrate(), 115101);
+    }
+
+    #[test]
+    fn baud_divisor_rounds_down_below_half() {
+        // 250 MHz / (8 * 115200) = 271.3，取整为 271
+        let regs = FakeMmio::<11>::new();
+        let mut uart = uart(&regs, 250_000_000);
+        uart.set_config(&Config::new().baudrate(115200)).unwrap();
+        assert_eq!(regs.get(0x28), 270);
+        assert_eq!(uart.baudrate(), 115313);
+    }
+
+    #[test]
+    fn unsupported_format_is_rejected() {
+        let regs = FakeMmio::<11>::new();
+        let mut uart = uart(&regs, 250_000_000);
+
+        assert_eq!(
+            uart.set_config(&Config::new().parity(Parity::Even)),
+            Err(ConfigError::UnsupportedParity)
+        );
+        assert_eq!(
+            uart.set_config(&Config::new().stop_bits(StopBits::Two)),
+            Err(ConfigError::UnsupportedStopBits)
+        );
+        uart.set_config(&Config::new().data_bits(DataBits::Seven))
+            .unwrap();
+        assert_eq!(uart.data_bits(), DataBits::Seven);
+    }
+
+    #[test]
+    fn aux_enable_bit_follows_open_close() {
+        let regs = FakeMmio::<27>::new();
+        let mut uart = MiniUart::new_with_aux(regs.base(), 250_000_000);
+        regs.set(AUX_ENABLES_OFFSET, AUX_ENABLES::SPI1::SET.value);
+
+        uart.open();
+        assert_eq!(
+            regs.get(AUX_ENABLES_OFFSET),
+            (AUX_ENABLES::SPI1::SET + AUX_ENABLES::MINI_UART::SET).value
+        );
+        assert_eq!(
+            regs.get(MINI_UART_OFFSET + 0x20),
+            (CNTL::RX_ENABLE::SET + CNTL::TX_ENABLE::SET).value
+        );
+
+        uart.close();
+        assert_eq!(regs.get(AUX_ENABLES_OFFSET), AUX_ENABLES::SPI1::SET.value);
+    }
+
+    #[test]
+    fn irq_decodes_iir_id() {
+        let regs = FakeMmio::<11>::new();
+        let mut uart = uart(&regs, 250_000_000);
+        let irq = uart.irq_handler().unwrap();
+
+        regs.set(0x08, IIR::ID::RxReady.value);
+        assert_eq!(
+            irq.clean_interrupt_status().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+        regs.set(0x08, IIR::NOT_PENDING::SET.value | IIR::ID::TxEmpty.value);
+        assert!(irq.clean_interrupt_status().is_empty());
+    }
+
+    #[test]
+    fn overrun_comes_from_same_lsr_read() {
+        let regs = FakeMmio::<11>::new();
+        let uart = uart(&regs, 250_000_000);
+        let mut rx = uart.rx.unwrap();
+
+        regs.set(0x14, (LSR::DATA_READY::SET + LSR::RX_OVERRUN::SET).value);
+        regs.set(0x00, 0x5a);
+        assert_eq!(
+            RawReciever::read_byte(&mut rx),
+            Some(Err(TransferError::Overrun(0x5a)))
+        );
+        regs.set(0x14, 0);
+        assert_eq!(RawReciever::read_byte(&mut rx), None);
+    }
+}
@@ -0,0 +1,938 @@
+//! Cadence UART 驱动 (`xlnx,xuartps` / `cdns,uart-r1p8`)
+//!
+//! 用于 Xilinx Zynq-7000 和 ZynqMP。波特率由 CD 和 BDIV 两级分频产生：
+//! `baud = clk / (CD * (BDIV + 1))`，收发各有 64 字节 FIFO，并带有接收超时中断。
+
+use core::{num::NonZeroU32, ptr::NonNull};
+
+use alloc::sync::Arc;
+
+use rdif_serial::{BSerial, InterfaceRaw, SerialDyn, SetBackError, TIrqHandler, TSender};
+use tock_registers::{
+    interfaces::*, register_bitfields, register_structs, registers::*, LocalRegisterCopy,
+};
+
+use crate::{
+    caps::{baud_error_ok, ALL_PARITY, ALL_STOP_BITS},
+    claim::{self, Claim, ClaimError, Region},
+    fifo::{FifoError, FifoThreshold, FifoThresholds, FifoTriggers},
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, IrqCause, Stats},
+    Capabilities, Caps, Config, ConfigError, DataBits, Features, InterruptMask, Parity,
+    RawReciever, RawSender, StopBits, Suspend, TransferError,
+};
+
+register_bitfields! [
+    u32,
+
+    /// Control Register
+    CR [
+        RXRST OFFSET(0) NUMBITS(1) [],
+        TXRST OFFSET(1) NUMBITS(1) [],
+        RX_EN OFFSET(2) NUMBITS(1) [],
+        RX_DIS OFFSET(3) NUMBITS(1) [],
+        TX_EN OFFSET(4) NUMBITS(1) [],
+        TX_DIS OFFSET(5) NUMBITS(1) [],
+        TORST OFFSET(6) NUMBITS(1) [],
+        STARTBRK OFFSET(7) NUMBITS(1) [],
+        STOPBRK OFFSET(8) NUMBITS(1) []
+    ],
+
+    /// Mode Register
+    MR [
+        CLKSEL OFFSET(0) NUMBITS(1) [],
+        CHRL OFFSET(1) NUMBITS(2) [
+            EightBit = 0b00,
+            SevenBit = 0b10,
+            SixBit = 0b11
+        ],
+        PAR OFFSET(3) NUMBITS(3) [
+            Even = 0b000,
+            Odd = 0b001,
+            Space = 0b010,
+            Mark = 0b011,
+            None = 0b100
+        ],
+        NBSTOP OFFSET(6) NUMBITS(2) [
+            One = 0b00,
+            OneAndHalf = 0b01,
+            Two = 0b10
+        ],
+        CHMODE OFFSET(8) NUMBITS(2) [
+            Normal = 0b00,
+            AutoEcho = 0b01,
+            LocalLoopback = 0b10,
+            RemoteLoopback = 0b11
+        ]
+    ],
+
+    /// Interrupt Enable / Disable / Mask / Status Registers
+    IXR [
+        RXTRIG OFFSET(0) NUMBITS(1) [],
+        RXEMPTY OFFSET(1) NUMBITS(1) [],
+        RXFULL OFFSET(2) NUMBITS(1) [],
+        TXEMPTY OFFSET(3) NUMBITS(1) [],
+        TXFULL OFFSET(4) NUMBITS(1) [],
+        RXOVR OFFSET(5) NUMBITS(1) [],
+        FRAMING OFFSET(6) NUMBITS(1) [],
+        PARITY OFFSET(7) NUMBITS(1) [],
+        TOUT OFFSET(8) NUMBITS(1) [],
+        DMS OFFSET(9) NUMBITS(1) [],
+        TTRIG OFFSET(10) NUMBITS(1) [],
+        TNFUL OFFSET(11) NUMBITS(1) [],
+        TOVR OFFSET(12) NUMBITS(1) []
+    ],
+
+    /// Baud Rate Generator Register
+    BAUDGEN [
+        CD OFFSET(0) NUMBITS(16) []
+    ],
+
+    /// Receiver Timeout Register
+    RXTOUT [
+        RTO OFFSET(0) NUMBITS(8) []
+    ],
+
+    /// Receiver FIFO Trigger Level Register
+    RXWM [
+        RTRIG OFFSET(0) NUMBITS(6) []
+    ],
+
+    /// Channel Status Register
+    SR [
+        RXTRIG OFFSET(0) NUMBITS(1) [],
+        RXEMPTY OFFSET(1) NUMBITS(1) [],
+        RXFULL OFFSET(2) NUMBITS(1) [],
+        TXEMPTY OFFSET(3) NUMBITS(1) [],
+        TXFULL OFFSET(4) NUMBITS(1) [],
+        RACTIVE OFFSET(10) NUMBITS(1) [],
+        TACTIVE OFFSET(11) NUMBITS(1) [],
+        FDELT OFFSET(12) NUMBITS(1) [],
+        TTRIG OFFSET(13) NUMBITS(1) [],
+        TNFUL OFFSET(14) NUMBITS(1) []
+    ],
+
+    /// Transmit / Receive FIFO
+    FIFO [
+        DATA OFFSET(0) NUMBITS(8) []
+    ],
+
+    /// Baud Rate Divider Register
+    BAUDDIV [
+        BDIV OFFSET(0) NUMBITS(8) []
+    ]
+];
+
+register_structs! {
+    pub CadenceRegisters {
+        (0x00 => cr: ReadWrite<u32, CR::Register>),           // 控制寄存器
+        (0x04 => mr: ReadWrite<u32, MR::Register>),           // 模式寄存器
+        (0x08 => ier: WriteOnly<u32, IXR::Register>),         // 中断使能寄存器
+        (0x0c => idr: WriteOnly<u32, IXR::Register>),         // 中断禁用寄存器
+        (0x10 => imr: ReadOnly<u32, IXR::Register>),          // 中断屏蔽寄存器
+        (0x14 => isr: ReadWrite<u32, IXR::Register>),         // 中断状态寄存器（写 1 清除）
+        (0x18 => baudgen: ReadWrite<u32, BAUDGEN::Register>), // 波特率发生器寄存器
+        (0x1c => rxtout: ReadWrite<u32, RXTOUT::Register>),   // 接收超时寄存器
+        (0x20 => rxwm: ReadWrite<u32, RXWM::Register>),       // 接收 FIFO 触发级别寄存器
+        (0x24 => modemcr: ReadWrite<u32>),                    // 调制解调器控制寄存器
+        (0x28 => modemsr: ReadWrite<u32>),                    // 调制解调器状态寄存器
+        (0x2c => sr: ReadOnly<u32, SR::Register>),            // 通道状态寄存器
+        (0x30 => fifo: ReadWrite<u32, FIFO::Register>),       // 收发 FIFO
+        (0x34 => bauddiv: ReadWrite<u32, BAUDDIV::Register>), // 波特率分频寄存器
+        (0x38 => flowdel: ReadWrite<u32>),                    // 流控延迟寄存器
+        (0x3c => @END),
+    }
+}
+
+// SAFETY: 寄存器访问均为单次 32 位读写
+unsafe impl Sync for CadenceRegisters {}
+
+/// 收发 FIFO 深度
+const FIFO_DEPTH: u16 = 64;
+
+/// 等待发送完成或复位结束的最大轮询次数
+const BUSY_SPIN_LIMIT: usize = 1_000_000;
+
+/// BDIV 的取值范围
+const BDIV_MIN: u32 = 4;
+const BDIV_MAX: u32 = 255;
+
+/// 接收超时，单位为 4 个位时间
+const RX_TIMEOUT: u32 = 10;
+
+/// RXTOUT 的计数单位（位时间）
+const RX_TIMEOUT_UNIT: u16 = 4;
+
+/// 接收错误中断位，由 `read_byte` 读取并清除
+const RX_ERRORS: u32 = IXR::RXOVR::SET.value | IXR::FRAMING::SET.value | IXR::PARITY::SET.value;
+
+/// Cadence UART 驱动结构体
+pub struct Cadence {
+    base: Reg,
+    clock_freq: u32,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    tx_pause: TxPause,
+    tx: Option<CadenceSender>,
+    rx: Option<CadenceReciever>,
+    irq: Option<CadenceIrqHandler>,
+    /// 通过 `try_new` 系列创建时持有的区域占用，与各句柄共享
+    claim: Option<Arc<Claim>>,
+}
+
+impl Cadence {
+    /// 不占用寄存器区域，需要防止同一设备被重复创建时使用 [`Self::try_new`]
+    pub fn new(base: NonNull<u8>, clock_freq: u32) -> Self {
+        Self::with_claim(base, clock_freq, None)
+    }
+
+    pub fn new_boxed(base: NonNull<u8>, clock_freq: u32) -> BSerial {
+        SerialDyn::new_boxed(Self::new(base, clock_freq))
+    }
+
+    /// 占用寄存器区域后创建实例，区域与已有实例重叠时返回错误
+    pub fn try_new(base: NonNull<u8>, clock_freq: u32) -> Result<Self, ClaimError> {
+        let claim = claim::claim(Region::mmio(base, size_of::<CadenceRegisters>()))?;
+        Ok(Self::with_claim(base, clock_freq, Some(claim)))
+    }
+
+    pub fn try_new_boxed(base: NonNull<u8>, clock_freq: u32) -> Result<BSerial, ClaimError> {
+        Ok(SerialDyn::new_boxed(Self::try_new(base, clock_freq)?))
+    }
+
+    /// 通过 `try_new` 系列创建时持有的寄存器区域占用
+    pub fn claim(&self) -> Option<&Claim> {
+        self.claim.as_deref()
+    }
+
+    fn with_claim(base: NonNull<u8>, clock_freq: u32, claim: Option<Arc<Claim>>) -> Self {
+        let base = Reg(base.cast());
+        let tx_pause = TxPause::default();
+
+        Self {
+            base,
+            clock_freq,
+            tx_pause: tx_pause.clone(),
+            tx: Some(CadenceSender {
+                base,
+                stats: None,
+                tx_pause: tx_pause.clone(),
+                _claim: claim.clone(),
+            }),
+            rx: Some(CadenceReciever {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            irq: Some(CadenceIrqHandler {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            claim,
+        }
+    }
+
+    fn registers(&self) -> &CadenceRegisters {
+        self.base.registers()
+    }
+
+    /// 挂接统计计数器，只对尚未取出的收发和中断句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+        if let Some(irq) = self.irq.as_mut() {
+            irq.stats = Some(stats);
+        }
+    }
+
+    /// 搜索误差最小的 (CD, BDIV) 组合
+    fn divisors_for(&self, baudrate: u32) -> Result<(u32, u32), ConfigError> {
+        if baudrate == 0 || self.clock_freq == 0 {
+            return Err(ConfigError::InvalidBaudrate);
+        }
+
+        let clk = self.clock_freq as u64;
+        let baud = baudrate as u64;
+        let mut best: Option<(u32, u32, u64)> = None;
+
+        for bdiv in BDIV_MIN..=BDIV_MAX {
+            let div = baud * (bdiv as u64 + 1);
+            let cd = (clk + div / 2) / div;
+            if cd == 0 || cd > 0xFFFF {
+                continue;
+            }
+
+            let actual = clk / (cd * (bdiv as u64 + 1));
+            let error = actual.abs_diff(baud);
+            if best.is_none_or(|(_, _, e)| error < e) {
+                best = Some((cd as u32, bdiv, error));
+            }
+        }
+
+        match best {
+            Some((cd, bdiv, error)) if baud_error_ok(error, baud) => Ok((cd, bdiv)),
+            _ => Err(ConfigError::InvalidBaudrate),
+        }
+    }
+
+    /// 在当前 MR 的基础上计算应用配置后的值，未设置的项保持不变
+    fn mode_for(&self, config: &Config) -> LocalRegisterCopy<u32, MR::Register> {
+        let mut mr = self.registers().mr.extract();
+
+        if let Some(bits) = config.data_bits {
+            let chrl = match bits {
+                DataBits::Six => MR::CHRL::SixBit,
+                DataBits::Seven => MR::CHRL::SevenBit,
+                // 5 位数据已由能力校验拒绝
+                DataBits::Five | DataBits::Eight => MR::CHRL::EightBit,
+            };
+            mr.modify(chrl);
+        }
+
+        if let Some(stop_bits) = config.stop_bits {
+            let nbstop = match stop_bits {
+                StopBits::One => MR::NBSTOP::One,
+                StopBits::Two => MR::NBSTOP::Two,
+            };
+            mr.modify(nbstop);
+        }
+
+        if let Some(parity) = config.parity {
+            let par = match parity {
+                Parity::None => MR::PAR::None,
+                Parity::Even => MR::PAR::Even,
+                Parity::Odd => MR::PAR::Odd,
+                Parity::Mark => MR::PAR::Mark,
+                Parity::Space => MR::PAR::Space,
+            };
+            mr.modify(par);
+        }
+
+        mr
+    }
+
+    /// 等待发送 FIFO 和移位寄存器都为空
+    fn wait_tx_idle(&self) -> bool {
+        for _ in 0..BUSY_SPIN_LIMIT {
+            let sr = self.registers().sr.extract();
+            if sr.is_set(SR::TXEMPTY) && !sr.is_set(SR::TACTIVE) {
+                return true;
+            }
+            core::hint::spin_loop();
+        }
+        false
+    }
+
+    /// 复位指定方向的收发逻辑和 FIFO，复位位由硬件自动清零
+    fn reset_fifos(&self, queue: FlushQueue) {
+        let reset = match queue {
+            FlushQueue::Rx => CR::RXRST::SET,
+            FlushQueue::Tx => CR::TXRST::SET,
+            FlushQueue::Both => CR::TXRST::SET + CR::RXRST::SET,
+        };
+        self.registers().cr.modify(reset);
+        for _ in 0..BUSY_SPIN_LIMIT {
+            if !self.registers().cr.any_matching_bits_set(reset) {
+                break;
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /// 初始化 Cadence UART
+    fn init(&self) {
+        let regs = self.registers();
+
+        // 关闭并清除全部中断
+        regs.idr.set(0x1FFF);
+        regs.isr.set(0x1FFF);
+
+        regs.cr.write(CR::TX_DIS::SET + CR::RX_DIS::SET);
+        self.reset_fifos(FlushQueue::Both);
+
+        // 接收 FIFO 中有 1 个字节即触发，空闲超过超时时间同样触发
+        regs.rxwm.write(RXWM::RTRIG.val(1));
+        regs.rxtout.write(RXTOUT::RTO.val(RX_TIMEOUT));
+
+        regs.cr
+            .write(CR::TX_EN::SET + CR::RX_EN::SET + CR::TORST::SET + CR::STOPBRK::SET);
+    }
+}
+
+impl InterfaceRaw for Cadence {
+    type IrqHandler = CadenceIrqHandler;
+
+    type Sender = crate::Sender;
+
+    type Reciever = crate::Reciever;
+
+    fn base_addr(&self) -> usize {
+        self.base.0.as_ptr() as usize
+    }
+
+    fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        self.capabilities().validate(config)?;
+        let divisors = config
+            .baudrate
+            .map(|baudrate| self.divisors_for(baudrate))
+            .transpose()?;
+        let mut mr = self.mode_for(config);
+
+        if !self.wait_tx_idle() {
+            return Err(ConfigError::Timeout);
+        }
+
+        let regs = self.registers();
+        let original_cr = regs.cr.get();
+
+        // 修改波特率前必须关闭收发
+        regs.cr.write(CR::TX_DIS::SET + CR::RX_DIS::SET);
+
+        // 除数按未分频的参考时钟计算
+        if let Some((cd, bdiv)) = divisors {
+            mr.modify(MR::CLKSEL::CLEAR);
+            regs.baudgen.write(BAUDGEN::CD.val(cd));
+            regs.bauddiv.write(BAUDDIV::BDIV.val(bdiv));
+            self.reset_fifos(FlushQueue::Both);
+        }
+        regs.mr.set(mr.get());
+
+        // 恢复原来的收发使能状态
+        let enable = CR::TX_EN::SET.value | CR::RX_EN::SET.value;
+        let disable = CR::TX_DIS::SET.value | CR::RX_DIS::SET.value;
+        regs.cr.set(original_cr & (enable | disable));
+
+        Ok(())
+    }
+
+    fn baudrate(&self) -> u32 {
+        let regs = self.registers();
+        let cd = regs.baudgen.read(BAUDGEN::CD);
+        let bdiv = regs.bauddiv.read(BAUDDIV::BDIV);
+        if cd == 0 {
+            return 0;
+        }
+
+        let mut clk = self.clock_freq;
+        if regs.mr.is_set(MR::CLKSEL) {
+            clk /= 8;
+        }
+        clk / (cd * (bdiv + 1))
+    }
+
+    fn data_bits(&self) -> DataBits {
+        match self.registers().mr.read_as_enum(MR::CHRL) {
+            Some(MR::CHRL::Value::SixBit) => DataBits::Six,
+            Some(MR::CHRL::Value::SevenBit) => DataBits::Seven,
+            _ => DataBits::Eight,
+        }
+    }
+
+    fn stop_bits(&self) -> StopBits {
+        match self.registers().mr.read_as_enum(MR::NBSTOP) {
+            Some(MR::NBSTOP::Value::Two) => StopBits::Two,
+            _ => StopBits::One,
+        }
+    }
+
+    fn parity(&self) -> Parity {
+        match self.registers().mr.read(MR::PAR) {
+            0b000 => Parity::Even,
+            0b001 => Parity::Odd,
+            0b010 => Parity::Space,
+            0b011 => Parity::Mark,
+            _ => Parity::None,
+        }
+    }
+
+    fn clock_freq(&self) -> Option<NonZeroU32> {
+        self.clock_freq.try_into().ok()
+    }
+
+    fn open(&mut self) {
+        self.init()
+    }
+
+    fn close(&mut self) {
+        self.registers().idr.set(0x1FFF);
+        self.registers().cr.write(CR::TX_DIS::SET + CR::RX_DIS::SET);
+    }
+
+    fn enable_loopback(&mut self) {
+        self.registers().mr.modify(MR::CHMODE::LocalLoopback);
+    }
+
+    fn disable_loopback(&mut self) {
+        self.registers().mr.modify(MR::CHMODE::Normal);
+    }
+
+    fn is_loopback_enabled(&self) -> bool {
+        self.registers().mr.matches_all(MR::CHMODE::LocalLoopback)
+    }
+
+    fn set_irq_mask(&mut self, mask: InterruptMask) {
+        let rx = IXR::RXTRIG::SET + IXR::TOUT::SET;
+        let tx = IXR::TXEMPTY::SET;
+        let regs = self.registers();
+
+        if mask.contains(InterruptMask::RX_AVAILABLE) {
+            regs.ier.write(rx);
+        } else {
+            regs.idr.write(rx);
+        }
+        if mask.contains(InterruptMask::TX_EMPTY) {
+            regs.ier.write(tx);
+        } else {
+            regs.idr.write(tx);
+        }
+    }
+
+    fn get_irq_mask(&self) -> InterruptMask {
+        let imr = self.registers().imr.extract();
+        let mut mask = InterruptMask::empty();
+
+        if imr.is_set(IXR::RXTRIG) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if imr.is_set(IXR::TXEMPTY) {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        mask
+    }
+
+    fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+        self.irq.take()
+    }
+
+    fn take_tx(&mut self) -> Option<Self::Sender> {
+        self.tx.take().map(crate::Sender::CadenceSender)
+    }
+
+    fn take_rx(&mut self) -> Option<Self::Reciever> {
+        self.rx.take().map(crate::Reciever::CadenceReciever)
+    }
+
+    fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+        let tx = match tx {
+            crate::Sender::CadenceSender(s) => s,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    tx.external_base().unwrap_or(0), // 不匹配的发送器类型
+                ));
+            }
+        };
+
+        if self.base != tx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                tx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.tx = Some(tx);
+        Ok(())
+    }
+
+    fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+        let rx = match rx {
+            crate::Reciever::CadenceReciever(r) => r,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    rx.external_base().unwrap_or(0), // 不匹配的接收器类型
+                ));
+            }
+        };
+
+        if self.base != rx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                rx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.rx = Some(rx);
+        Ok(())
+    }
+}
+
+impl Caps for Cadence {
+    fn capabilities(&self) -> Capabilities {
+        let (min_baudrate, _) = Capabilities::baudrate_range(self.clock_freq, BDIV_MAX + 1, 0xFFFF);
+        let (_, max_baudrate) = Capabilities::baudrate_range(self.clock_freq, BDIV_MIN + 1, 1);
+
+        Capabilities {
+            fifo_depth: FIFO_DEPTH,
+            min_baudrate,
+            max_baudrate,
+            data_bits: &[DataBits::Six, DataBits::Seven, DataBits::Eight],
+            stop_bits: ALL_STOP_BITS,
+            parity: ALL_PARITY,
+            features: Features::LOOPBACK,
+        }
+    }
+}
+
+/// 接收触发级别 1..=63 字节，接收超时以 4 个位时间为单位，发送触发级别不可配置
+impl FifoThreshold for Cadence {
+    fn fifo_triggers(&self) -> FifoTriggers {
+        let regs = self.registers();
+        FifoTriggers {
+            rx: Some(regs.rxwm.read(RXWM::RTRIG) as u16),
+            tx: None,
+            rx_timeout: Some(regs.rxtout.read(RXTOUT::RTO) as u16 * RX_TIMEOUT_UNIT),
+        }
+    }
+
+    fn set_fifo_thresholds(&mut self, req: &FifoThresholds) -> Result<FifoTriggers, FifoError> {
+        let req = req.resolve(FIFO_DEPTH, &self.fifo_triggers())?;
+        let regs = self.registers();
+
+        // RTRIG 为 0 时关闭触发中断，不能达到 FIFO 深度
+        if let Some(rx) = req.rx {
+            regs.rxwm
+                .write(RXWM::RTRIG.val(rx.clamp(1, FIFO_DEPTH - 1) as u32));
+        }
+        // RTO 为 0 时关闭超时
+        if let Some(bits) = req.rx_timeout {
+            let rto = (bits / RX_TIMEOUT_UNIT).clamp(1, 0xFF);
+            regs.rxtout.write(RXTOUT::RTO.val(rto as u32));
+        }
+
+        Ok(self.fifo_triggers())
+    }
+}
+
+impl Flush for Cadence {
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError> {
+        self.reset_fifos(queue);
+        if queue.rx() {
+            // 清除 FIFO 复位前残留的接收错误和超时状态
+            self.registers().isr.set(RX_ERRORS | IXR::TOUT::SET.value);
+        }
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), FlushError> {
+        if self.wait_tx_idle() {
+            Ok(())
+        } else {
+            Err(FlushError::Timeout)
+        }
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// Cadence UART 挂起时保存的寄存器上下文
+#[derive(Debug, Clone, Copy)]
+pub struct CadenceContext {
+    cr: u32,
+    mr: u32,
+    imr: u32,
+    baudgen: u32,
+    bauddiv: u32,
+    rxtout: u32,
+    rxwm: u32,
+    modemcr: u32,
+    flowdel: u32,
+}
+
+impl Suspend for Cadence {
+    type Context = CadenceContext;
+
+    fn save_context(&self) -> Self::Context {
+        let regs = self.registers();
+        CadenceContext {
+            cr: regs.cr.get(),
+            mr: regs.mr.get(),
+            imr: regs.imr.get(),
+            baudgen: regs.baudgen.get(),
+            bauddiv: regs.bauddiv.get(),
+            rxtout: regs.rxtout.get(),
+            rxwm: regs.rxwm.get(),
+            modemcr: regs.modemcr.get(),
+            flowdel: regs.flowdel.get(),
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        let regs = self.registers();
+
+        // 恢复期间保持收发关闭、中断屏蔽
+        regs.idr.set(0x1FFF);
+        regs.cr.write(CR::TX_DIS::SET + CR::RX_DIS::SET);
+
+        regs.mr.set(ctx.mr);
+        regs.baudgen.set(ctx.baudgen);
+        regs.bauddiv.set(ctx.bauddiv);
+        self.reset_fifos(FlushQueue::Both);
+        regs.rxtout.set(ctx.rxtout);
+        regs.rxwm.set(ctx.rxwm);
+        regs.modemcr.set(ctx.modemcr);
+        regs.flowdel.set(ctx.flowdel);
+
+        // 清除上电过程中残留的中断，再恢复中断使能
+        regs.isr.set(0x1FFF);
+        regs.ier.set(ctx.imr);
+
+        // 最后恢复收发使能
+        let enable = CR::TX_EN::SET.value | CR::RX_EN::SET.value;
+        let disable = CR::TX_DIS::SET.value | CR::RX_DIS::SET.value;
+        regs.cr.set(ctx.cr & (enable | disable));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Reg(NonNull<CadenceRegisters>);
+
+unsafe impl Send for Reg {}
+
+impl Reg {
+    fn registers(&self) -> &CadenceRegisters {
+        unsafe { self.0.as_ref() }
+    }
+}
+
+pub struct CadenceSender {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    tx_pause: TxPause,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for CadenceSender {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl TSender for CadenceSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        RawSender::write_byte(self, byte)
+    }
+}
+
+impl RawSender for CadenceSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return false;
+        };
+        if self.base.registers().sr.is_set(SR::TXFULL) {
+            return false;
+        }
+
+        self.base.registers().fifo.write(FIFO::DATA.val(byte as _));
+        if let Some(stats) = self.stats {
+            stats.record_tx(1);
+        }
+
+        true
+    }
+}
+
+pub struct CadenceReciever {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for CadenceReciever {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl CadenceReciever {
+    fn read_raw(&mut self) -> Option<Result<u8, TransferError>> {
+        let regs = self.base.registers();
+        if regs.sr.is_set(SR::RXEMPTY) {
+            return None;
+        }
+
+        // 错误状态只在 ISR 中给出，读取后写 1 清除
+        let errors = LocalRegisterCopy::<u32, IXR::Register>::new(regs.isr.get() & RX_ERRORS);
+        if errors.get() != 0 {
+            regs.isr.set(errors.get());
+        }
+
+        let data = regs.fifo.read(FIFO::DATA) as u8;
+
+        let result = if errors.is_set(IXR::PARITY) {
+            Err(TransferError::Parity)
+        } else if errors.is_set(IXR::FRAMING) {
+            Err(TransferError::Framing)
+        } else if errors.is_set(IXR::RXOVR) {
+            Err(TransferError::Overrun(data))
+        } else {
+            Ok(data)
+        };
+
+        Some(result)
+    }
+}
+
+impl RawReciever for CadenceReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        let result = self.read_raw()?;
+        if let Some(stats) = self.stats {
+            stats.record_rx(&result);
+        }
+        Some(result)
+    }
+}
+
+pub struct CadenceIrqHandler {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+unsafe impl Sync for CadenceIrqHandler {}
+
+impl TIrqHandler for CadenceIrqHandler {
+    fn clean_interrupt_status(&self) -> InterruptMask {
+        let regs = self.base.registers();
+        let pending = regs.isr.get() & regs.imr.get();
+        let isr = LocalRegisterCopy::<u32, IXR::Register>::new(pending);
+        let mut mask = InterruptMask::empty();
+
+        // 接收超时同样表示有数据待读取
+        if isr.is_set(IXR::RXTRIG) || isr.is_set(IXR::TOUT) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if isr.is_set(IXR::TXEMPTY) {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        if let Some(stats) = self.stats {
+            let causes = [
+                (IXR::RXTRIG, IrqCause::Rx),
+                (IXR::TXEMPTY, IrqCause::Tx),
+                (IXR::TOUT, IrqCause::RxTimeout),
+                (IXR::RXOVR, IrqCause::LineStatus),
+                (IXR::FRAMING, IrqCause::LineStatus),
+                (IXR::PARITY, IrqCause::LineStatus),
+                (IXR::DMS, IrqCause::ModemStatus),
+            ];
+            for (field, cause) in causes {
+                if isr.is_set(field) {
+                    stats.record_irq(cause);
+                }
+            }
+        }
+
+        regs.isr.set(pending);
+
+        // 超时中断需要重新启动计数器才能再次触发
+        if isr.is_set(IXR::TOUT) {
+            regs.cr.modify(CR::TORST::SET);
+        }
+
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fake_mmio::FakeMmio, FifoLevel};
+
+    fn uart(regs: &FakeMmio<15>, clock_freq: u32) -> Cadence {
+        // 发送 FIFO 为空，配置时无需等待
+        regs.set(0x2c, SR::TXEMPTY::SET.value);
+        Cadence::new(regs.base(), clock_freq)
+    }
+
+    #[test]
+    fn divisor_search_picks_closest() {
+        let regs = FakeMmio::<15>::new();
+        let mut uart = uart(&regs, 100_000_000);
+        regs.set(0x04, MR::CLKSEL::SET.value);
+
+        uart.set_config(&Config::new().baudrate(115200)).unwrap();
+        // 100 MHz / (124 * 7) = 115207
+        assert_eq!((regs.get(0x18), regs.get(0x34)), (124, 6));
+        assert_eq!(regs.get(0x04) & MR::CLKSEL::SET.value, 0);
+        assert_eq!(uart.baudrate(), 115207);
+    }
+
+    #[test]
+    fn divisor_error_above_three_percent_is_rejected() {
+        let regs = FakeMmio::<15>::new();
+        let mut uart = uart(&regs, 1_000_000);
+
+        // 最接近的是 1 MHz / (1 * 7) = 142857，误差 4.8%
+        assert_eq!(
+            uart.set_config(&Config::new().baudrate(150000)),
+            Err(ConfigError::InvalidBaudrate)
+        );
+        assert_eq!((regs.get(0x18), regs.get(0x34)), (0, 0));
+
+        uart.set_config(&Config::new().baudrate(9600)).unwrap();
+        let error = uart.baudrate().abs_diff(9600);
+        assert!(baud_error_ok(error as u64, 9600));
+    }
+
+    #[test]
+    fn read_clears_only_reported_errors() {
+        let regs = FakeMmio::<15>::new();
+        let uart = uart(&regs, 100_000_000);
+        let mut rx = uart.rx.unwrap();
+
+        regs.set(0x14, IXR::PARITY::SET.value | IXR::RXTRIG::SET.value);
+        regs.set(0x30, 0x33);
+        assert_eq!(
+            RawReciever::read_byte(&mut rx),
+            Some(Err(TransferError::Parity))
+        );
+        // ISR 写 1 清除，只写回错误位
+        assert_eq!(regs.get(0x14), IXR::PARITY::SET.value);
+
+        regs.set(0x2c, SR::RXEMPTY::SET.value);
+        assert_eq!(RawReciever::read_byte(&mut rx), None);
+    }
+
+    #[test]
+    fn rx_timeout_irq_acks_and_restarts_counter() {
+        let regs = FakeMmio::<15>::new();
+        let mut uart = uart(&regs, 100_000_000);
+        let irq = uart.irq_handler().unwrap();
+
+        regs.set(0x10, IXR::RXTRIG::SET.value | IXR::TOUT::SET.value);
+        regs.set(0x14, IXR::TOUT::SET.value | IXR::TXEMPTY::SET.value);
+        assert_eq!(
+            irq.clean_interrupt_status().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+        assert_eq!(regs.get(0x14), IXR::TOUT::SET.value);
+        assert_ne!(regs.get(0x00) & CR::TORST::SET.value, 0);
+        assert_eq!(
+            uart.get_irq_mask().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+    }
+
+    #[test]
+    fn fifo_thresholds_are_clamped() {
+        let regs = FakeMmio::<15>::new();
+        let mut uart = uart(&regs, 100_000_000);
+
+        let req = FifoThresholds::new()
+            .rx(FifoLevel::Bytes(FIFO_DEPTH))
+            .rx_timeout(40);
+        let triggers = uart.set_fifo_thresholds(&req).unwrap();
+        assert_eq!(regs.get(0x20), 63);
+        assert_eq!(regs.get(0x1c), 10);
+        assert_eq!((triggers.rx, triggers.rx_timeout), (Some(63), Some(40)));
+    }
+}
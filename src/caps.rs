@@ -0,0 +1,138 @@
+//! 硬件能力描述
+//!
+//! 不同 UART 的 FIFO 深度、波特率范围和可选功能差别很大。每个驱动实例通过
+//! [`Caps`] 报告自身支持的能力，`set_config` 在写入任何寄存器之前先用
+//! [`Capabilities::validate`] 校验配置，不支持的设置直接返回对应的 [`ConfigError`]。
+
+use bitflags::bitflags;
+
+use crate::{Config, ConfigError, DataBits, Parity, StopBits};
+
+bitflags! {
+    /// 可选硬件功能
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Features: u32 {
+        /// 硬件 RTS/CTS 自动流控
+        const HW_FLOW_CONTROL = 1 << 0;
+        /// DMA 请求信号
+        const DMA = 1 << 1;
+        /// IrDA SIR 编解码
+        const IRDA = 1 << 2;
+        /// RS-485 收发方向自动控制
+        const RS485 = 1 << 3;
+        /// 9 位数据（地址/数据）模式
+        const NINE_BIT = 1 << 4;
+        /// 内部回环
+        const LOOPBACK = 1 << 5;
+    }
+}
+
+/// 全部数据位设置
+pub const ALL_DATA_BITS: &[DataBits] = &[
+    DataBits::Five,
+    DataBits::Six,
+    DataBits::Seven,
+    DataBits::Eight,
+];
+
+/// 全部停止位设置
+pub const ALL_STOP_BITS: &[StopBits] = &[StopBits::One, StopBits::Two];
+
+/// 全部奇偶校验设置
+pub const ALL_PARITY: &[Parity] = &[
+    Parity::None,
+    Parity::Even,
+    Parity::Odd,
+    Parity::Mark,
+    Parity::Space,
+];
+
+/// 分频后的波特率误差是否在 3% 以内，超过时无法可靠通信
+///
+/// `error` 与 `target` 使用相同的单位，分数分频可以把分母乘进两边避免除法。
+pub(crate) fn baud_error_ok(error: u64, target: u64) -> bool {
+    error * 100 <= target * 3
+}
+
+/// 驱动实例的硬件能力
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// 接收/发送 FIFO 深度（字节），无 FIFO 时为 1
+    pub fifo_depth: u16,
+    /// 当前时钟下可设置的最小波特率
+    pub min_baudrate: u32,
+    /// 当前时钟下可设置的最大波特率
+    pub max_baudrate: u32,
+    /// 支持的数据位
+    pub data_bits: &'static [DataBits],
+    /// 支持的停止位
+    pub stop_bits: &'static [StopBits],
+    /// 支持的奇偶校验
+    pub parity: &'static [Parity],
+    /// 可选硬件功能
+    pub features: Features,
+}
+
+impl Capabilities {
+    /// 根据过采样倍数和除数范围计算波特率上下限
+    ///
+    /// 返回 `(min, max)`，时钟为 0 时返回 `(0, 0)`。
+    pub const fn baudrate_range(
+        clock_freq: u32,
+        oversampling: u32,
+        max_divisor: u32,
+    ) -> (u32, u32) {
+        if clock_freq == 0 || oversampling == 0 || max_divisor == 0 {
+            return (0, 0);
+        }
+        let max = clock_freq / oversampling;
+        let min = (clock_freq as u64).div_ceil(oversampling as u64 * max_divisor as u64) as u32;
+        (min, max)
+    }
+
+    pub fn supports_baudrate(&self, baudrate: u32) -> bool {
+        baudrate != 0 && (self.min_baudrate..=self.max_baudrate).contains(&baudrate)
+    }
+
+    pub fn supports_data_bits(&self, bits: DataBits) -> bool {
+        self.data_bits.contains(&bits)
+    }
+
+    pub fn supports_stop_bits(&self, bits: StopBits) -> bool {
+        self.stop_bits.contains(&bits)
+    }
+
+    pub fn supports_parity(&self, parity: Parity) -> bool {
+        self.parity.contains(&parity)
+    }
+
+    /// 校验配置中已设置的每一项，返回第一个不支持的项对应的错误
+    pub fn validate(&self, config: &Config) -> Result<(), ConfigError> {
+        if let Some(baudrate) = config.baudrate {
+            if !self.supports_baudrate(baudrate) {
+                return Err(ConfigError::InvalidBaudrate);
+            }
+        }
+        if let Some(data_bits) = config.data_bits {
+            if !self.supports_data_bits(data_bits) {
+                return Err(ConfigError::UnsupportedDataBits);
+            }
+        }
+        if let Some(stop_bits) = config.stop_bits {
+            if !self.supports_stop_bits(stop_bits) {
+                return Err(ConfigError::UnsupportedStopBits);
+            }
+        }
+        if let Some(parity) = config.parity {
+            if !self.supports_parity(parity) {
+                return Err(ConfigError::UnsupportedParity);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 硬件能力查询
+pub trait Caps {
+    fn capabilities(&self) -> Capabilities;
+}
@@ -0,0 +1,197 @@
+//! 寄存器区域占用登记
+//!
+//! 各 MMIO 和端口驱动的 `try_new` 系列构造函数先在全局登记表中占用寄存器区域，
+//! 与已登记区域重叠时返回 [`ClaimError::Busy`]。`new` 系列不参与登记，可用于
+//! earlycon 交接等需要在同一地址上再创建实例的场景。
+//!
+//! 占用凭证由驱动和取出的收发端、中断句柄以 `Arc` 共享，只有全部丢弃后区域才会
+//! 释放，不会出现旧句柄仍在访问 FIFO 时又创建出新实例的情况。
+
+use core::{
+    cell::UnsafeCell,
+    ptr::NonNull,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use alloc::{sync::Arc, vec::Vec};
+
+/// 设备寄存器区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    /// MMIO 区域 `base..base + len`
+    Mmio { base: usize, len: usize },
+    /// x86 I/O 端口区域 `base..base + len`
+    Port { base: u16, len: u16 },
+}
+
+impl Region {
+    pub fn mmio(base: NonNull<u8>, len: usize) -> Self {
+        Self::Mmio {
+            base: base.as_ptr() as usize,
+            len,
+        }
+    }
+
+    pub fn port(base: u16, len: u16) -> Self {
+        Self::Port { base, len }
+    }
+
+    /// 两个区域是否重叠，MMIO 与端口地址空间互不影响
+    pub fn overlaps(&self, other: &Region) -> bool {
+        fn overlap(a: usize, a_len: usize, b: usize, b_len: usize) -> bool {
+            a < b.saturating_add(b_len) && b < a.saturating_add(a_len)
+        }
+
+        match (*self, *other) {
+            (
+                Self::Mmio {
+                    base: a,
+                    len: a_len,
+                },
+                Self::Mmio {
+                    base: b,
+                    len: b_len,
+                },
+            ) => overlap(a, a_len, b, b_len),
+            (
+                Self::Port {
+                    base: a,
+                    len: a_len,
+                },
+                Self::Port {
+                    base: b,
+                    len: b_len,
+                },
+            ) => overlap(a as usize, a_len as usize, b as usize, b_len as usize),
+            _ => false,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimError {
+    #[error("region overlaps claimed region {0:?}")]
+    Busy(Region),
+}
+
+/// 全局登记表，用自旋锁保护
+struct Registry {
+    lock: AtomicBool,
+    regions: UnsafeCell<Vec<Region>>,
+}
+
+// SAFETY: regions 只在持有 lock 时访问
+unsafe impl Sync for Registry {}
+
+impl Registry {
+    fn with<R>(&self, f: impl FnOnce(&mut Vec<Region>) -> R) -> R {
+        while self
+            .lock
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        let result = f(unsafe { &mut *self.regions.get() });
+        self.lock.store(false, Ordering::Release);
+        result
+    }
+}
+
+static REGISTRY: Registry = Registry {
+    lock: AtomicBool::new(false),
+    regions: UnsafeCell::new(Vec::new()),
+};
+
+/// 区域占用凭证，析构时释放
+#[derive(Debug)]
+pub struct Claim {
+    region: Region,
+}
+
+impl Claim {
+    /// 占用 `region`，与已登记区域重叠时返回该区域
+    pub fn new(region: Region) -> Result<Self, ClaimError> {
+        REGISTRY.with(|regions| {
+            if let Some(busy) = regions.iter().find(|r| r.overlaps(&region)) {
+                return Err(ClaimError::Busy(*busy));
+            }
+            regions.push(region);
+            Ok(Self { region })
+        })
+    }
+
+    pub fn region(&self) -> Region {
+        self.region
+    }
+}
+
+impl Drop for Claim {
+    fn drop(&mut self) {
+        REGISTRY.with(|regions| regions.retain(|region| *region != self.region));
+    }
+}
+
+/// 占用驱动的寄存器区域，返回在驱动和各句柄间共享的凭证
+pub(crate) fn claim(region: Region) -> Result<Arc<Claim>, ClaimError> {
+    Claim::new(region).map(Arc::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fake_mmio::FakeMmio, pl011::Pl011, InterfaceRaw};
+
+    #[test]
+    fn overlapping_claim_fails_until_released() {
+        let region = Region::Mmio {
+            base: 0xdead_0000,
+            len: 0x100,
+        };
+        let claim = Claim::new(region).unwrap();
+        let inside = Region::Mmio {
+            base: 0xdead_0080,
+            len: 0x10,
+        };
+        assert_eq!(Claim::new(inside).err(), Some(ClaimError::Busy(region)));
+        // 紧邻的区域不重叠
+        let next = Claim::new(Region::Mmio {
+            base: 0xdead_0100,
+            len: 0x100,
+        })
+        .unwrap();
+        // 地址相同但类型不同的区域互不影响
+        let port = Claim::new(Region::port(0xdead, 8)).unwrap();
+
+        drop(claim);
+        assert!(Claim::new(inside).is_ok());
+        drop((next, port));
+    }
+
+    #[test]
+    fn handles_keep_claim_after_driver_drop() {
+        let regs = FakeMmio::<{ 0x1000 / 4 }>::new();
+        let mut uart = Pl011::try_new(regs.base(), 24_000_000).unwrap();
+        assert!(Pl011::try_new(regs.base(), 24_000_000).is_err());
+
+        // 驱动析构后取出的发送端仍在使用寄存器，区域保持占用
+        let tx = uart.take_tx().unwrap();
+        drop(uart);
+        assert!(Pl011::try_new(regs.base(), 24_000_000).is_err());
+
+        drop(tx);
+        assert!(Pl011::try_new(regs.base(), 24_000_000).is_ok());
+    }
+
+    #[test]
+    fn new_does_not_claim() {
+        let regs = FakeMmio::<{ 0x1000 / 4 }>::new();
+        let early = Pl011::new(regs.base(), 24_000_000);
+        assert!(early.claim().is_none());
+
+        // earlycon 实例仍在时，完整驱动可以在同一地址上占用
+        let uart = Pl011::try_new(regs.base(), 24_000_000).unwrap();
+        assert!(uart.claim().is_some());
+        let _again = Pl011::new(regs.base(), 24_000_000);
+    }
+}
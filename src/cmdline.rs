@@ -0,0 +1,742 @@
+//! Linux 风格串口参数解析与格式化
+//!
+//! 支持以下几种来自内核命令行的写法：
+//! - 串口设置：`115200n8`、`115200n8r`、`9600,e,7,2`
+//! - 控制台：`console=ttyS0,115200n8`
+//! - earlycon：`earlycon=pl011,mmio32,0x9000000,115200n8`
+//!
+//! 未出现在字符串中的项在 [`Config`] 中保持为 `None`，由调用方决定默认值。
+
+use core::fmt::{self, Display};
+
+use crate::{Config, DataBits, Parity, StopBits};
+
+/// 参数解析错误
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    #[error("Empty option string")]
+    Empty,
+    #[error("Invalid baudrate")]
+    InvalidBaudrate,
+    #[error("Invalid parity `{0}`")]
+    InvalidParity(char),
+    #[error("Invalid data bits")]
+    InvalidDataBits,
+    #[error("Invalid stop bits")]
+    InvalidStopBits,
+    #[error("Invalid flow control")]
+    InvalidFlowControl,
+    #[error("Unexpected trailing characters")]
+    TrailingCharacters,
+    #[error("Unknown earlycon driver")]
+    UnknownDriver,
+    #[error("Invalid address")]
+    InvalidAddress,
+    #[error("Invalid console index")]
+    InvalidIndex,
+}
+
+/// 串口设置，对应 `115200n8r` 这类字符串
+#[derive(Debug, Clone, Default)]
+pub struct SerialOptions {
+    pub config: Config,
+    /// 是否启用 RTS/CTS 硬件流控（`r` 后缀）
+    pub flow_control: bool,
+}
+
+impl SerialOptions {
+    /// 解析串口设置
+    ///
+    /// 紧凑写法为 `<baud>[parity][bits][r]`，与 Linux `uart_parse_options` 一致；
+    /// 逗号写法为 `<baud>[,parity[,bits[,stop[,r]]]]`，可以额外指定停止位。
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        if s.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        if s.contains(',') {
+            Self::parse_separated(s)
+        } else {
+            Self::parse_compact(s)
+        }
+    }
+
+    fn parse_compact(s: &str) -> Result<Self, ParseError> {
+        let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let mut opts = SerialOptions {
+            config: Config::new().baudrate(parse_baudrate(&s[..digits])?),
+            flow_control: false,
+        };
+
+        let mut rest = s[digits..].chars();
+        if let Some(c) = rest.next() {
+            opts.config.parity = Some(parse_parity(c)?);
+        }
+        if let Some(c) = rest.next() {
+            opts.config.data_bits = Some(parse_data_bits_char(c)?);
+        }
+        if let Some(c) = rest.next() {
+            if c != 'r' {
+                return Err(ParseError::InvalidFlowControl);
+            }
+            opts.flow_control = true;
+        }
+        if rest.next().is_some() {
+            return Err(ParseError::TrailingCharacters);
+        }
+
+        Ok(opts)
+    }
+
+    fn parse_separated(s: &str) -> Result<Self, ParseError> {
+        let mut fields = s.split(',');
+        let mut opts = SerialOptions {
+            config: Config::new().baudrate(parse_baudrate(fields.next().unwrap_or(""))?),
+            flow_control: false,
+        };
+
+        if let Some(f) = fields.next() {
+            opts.config.parity = Some(parse_parity(
+                single_char(f).ok_or(ParseError::InvalidParity(f.chars().next().unwrap_or(' ')))?,
+            )?);
+        }
+        if let Some(f) = fields.next() {
+            let c = single_char(f).ok_or(ParseError::InvalidDataBits)?;
+            opts.config.data_bits = Some(parse_data_bits_char(c)?);
+        }
+        if let Some(f) = fields.next() {
+            opts.config.stop_bits = Some(match f {
+                "1" => StopBits::One,
+                "2" => StopBits::Two,
+                _ => return Err(ParseError::InvalidStopBits),
+            });
+        }
+        if let Some(f) = fields.next() {
+            if f != "r" {
+                return Err(ParseError::InvalidFlowControl);
+            }
+            opts.flow_control = true;
+        }
+        if fields.next().is_some() {
+            return Err(ParseError::TrailingCharacters);
+        }
+
+        Ok(opts)
+    }
+}
+
+/// 格式化为可被 [`SerialOptions::parse`] 解析的字符串
+///
+/// 能用紧凑写法表达时输出 `115200n8r`，需要两位停止位时输出 `9600,e,7,2`。
+/// 解析要求波特率必须存在，未设置波特率时省略该字段，输出只能用于显示。
+impl Display for SerialOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = &self.config;
+        if let Some(baudrate) = c.baudrate {
+            write!(f, "{baudrate}")?;
+        }
+
+        if c.stop_bits == Some(StopBits::Two) {
+            let parity = parity_char(c.parity.unwrap_or(Parity::None));
+            let bits = c.data_bits.unwrap_or(DataBits::Eight) as u8;
+            write!(f, ",{parity},{bits},2")?;
+            if self.flow_control {
+                write!(f, ",r")?;
+            }
+            return Ok(());
+        }
+
+        // 紧凑写法中后面的字段依赖前面的字段存在
+        let need_bits = c.data_bits.is_some() || self.flow_control;
+        let need_parity = c.parity.is_some() || need_bits;
+        if need_parity {
+            write!(f, "{}", parity_char(c.parity.unwrap_or(Parity::None)))?;
+        }
+        if need_bits {
+            write!(f, "{}", c.data_bits.unwrap_or(DataBits::Eight) as u8)?;
+        }
+        if self.flow_control {
+            write!(f, "r")?;
+        }
+        Ok(())
+    }
+}
+
+/// 控制台参数，对应 `console=ttyS0,115200n8`
+#[derive(Debug, Clone)]
+pub struct ConsoleSpec<'a> {
+    /// 设备名（不含编号），如 `ttyS`、`ttyAMA`
+    pub name: &'a str,
+    /// 设备编号
+    pub index: Option<u32>,
+    pub options: Option<SerialOptions>,
+}
+
+impl<'a> ConsoleSpec<'a> {
+    /// 解析控制台参数，`console=` 前缀可省略
+    pub fn parse(s: &'a str) -> Result<Self, ParseError> {
+        let s = s.strip_prefix("console=").unwrap_or(s);
+        let (device, options) = match s.split_once(',') {
+            Some((device, options)) => (device, Some(SerialOptions::parse(options)?)),
+            None => (s, None),
+        };
+        if device.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        let split = device
+            .rfind(|c: char| !c.is_ascii_digit())
+            .map_or(0, |i| i + 1);
+        let (name, index) = device.split_at(split);
+        let index = if index.is_empty() {
+            None
+        } else {
+            // 只含数字，解析失败只可能是超出范围
+            Some(index.parse().map_err(|_| ParseError::InvalidIndex)?)
+        };
+
+        Ok(Self {
+            name,
+            index,
+            options,
+        })
+    }
+}
+
+/// earlycon 对应的驱动
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EarlyconDriver {
+    Pl011,
+    Ns16550,
+    Sifive,
+    ImxUart,
+    Lpuart,
+    Bcm2835Aux,
+    Exynos,
+    AppleS5l,
+    Meson,
+    Scif,
+}
+
+impl EarlyconDriver {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pl011" => Some(Self::Pl011),
+            "uart" | "uart8250" | "ns16550" | "ns16550a" => Some(Self::Ns16550),
+            "sifive" => Some(Self::Sifive),
+            "ec_imx6q" | "ec_imx21" => Some(Self::ImxUart),
+            "lpuart32" => Some(Self::Lpuart),
+            "bcm2835aux" => Some(Self::Bcm2835Aux),
+            "s5pv210" | "exynos4210" => Some(Self::Exynos),
+            "apple_s5l" => Some(Self::AppleS5l),
+            "meson" => Some(Self::Meson),
+            "scif" => Some(Self::Scif),
+            _ => None,
+        }
+    }
+
+    /// 未指定访问方式时的默认值
+    fn default_access(&self) -> AccessWidth {
+        match self {
+            Self::Pl011
+            | Self::Sifive
+            | Self::ImxUart
+            | Self::Lpuart
+            | Self::Bcm2835Aux
+            | Self::Exynos
+            | Self::AppleS5l
+            | Self::Meson => AccessWidth::Mmio32,
+            // SCIF 寄存器为 8/16 位宽，按驱动自身的寄存器宽度访问
+            Self::Scif => AccessWidth::Mmio16,
+            Self::Ns16550 => AccessWidth::Mmio8,
+        }
+    }
+}
+
+/// 寄存器访问方式，对应 earlycon 的 iotype 字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessWidth {
+    /// x86 IO 端口
+    Io,
+    Mmio8,
+    Mmio16,
+    Mmio32,
+    Mmio32Be,
+}
+
+impl AccessWidth {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "io" => Some(Self::Io),
+            "mmio" => Some(Self::Mmio8),
+            "mmio16" => Some(Self::Mmio16),
+            "mmio32" => Some(Self::Mmio32),
+            "mmio32be" => Some(Self::Mmio32Be),
+            _ => None,
+        }
+    }
+
+    /// 寄存器间距（字节），可直接用作 `Ns16550::new_mmio` 的 `reg_width`
+    pub fn reg_width(&self) -> usize {
+        match self {
+            Self::Io | Self::Mmio8 => 1,
+            Self::Mmio16 => 2,
+            Self::Mmio32 | Self::Mmio32Be => 4,
+        }
+    }
+}
+
+/// earlycon 参数，对应 `earlycon=pl011,mmio32,0x9000000,115200n8`
+#[derive(Debug, Clone)]
+pub struct EarlyconSpec {
+    pub driver: EarlyconDriver,
+    pub access: AccessWidth,
+    /// 寄存器基地址或 IO 端口；只给出驱动名时为 `None`，需要从 stdout-path 获取
+    pub base: Option<usize>,
+    pub options: Option<SerialOptions>,
+}
+
+impl EarlyconSpec {
+    /// 解析 earlycon 参数，`earlycon=` 前缀可省略
+    ///
+    /// 格式为 `<name>[,<iotype>],<addr>[,<options>]` 或单独的 `<name>`。
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let s = s.strip_prefix("earlycon=").unwrap_or(s);
+        if s.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        let mut fields = s.splitn(2, ',');
+        let driver = EarlyconDriver::from_name(fields.next().unwrap_or(""))
+            .ok_or(ParseError::UnknownDriver)?;
+        let mut spec = EarlyconSpec {
+            driver,
+            access: driver.default_access(),
+            base: None,
+            options: None,
+        };
+
+        let Some(rest) = fields.next() else {
+            return Ok(spec);
+        };
+
+        let (first, rest) = split_field(rest);
+        let (addr, rest) = match AccessWidth::from_name(first) {
+            Some(access) => {
+                spec.access = access;
+                split_field(rest.ok_or(ParseError::InvalidAddress)?)
+            }
+            None => (first, rest),
+        };
+        spec.base = Some(parse_address(addr)?);
+
+        if let Some(options) = rest {
+            spec.options = Some(SerialOptions::parse(options)?);
+        }
+
+        Ok(spec)
+    }
+}
+
+fn split_field(s: &str) -> (&str, Option<&str>) {
+    match s.split_once(',') {
+        Some((field, rest)) => (field, Some(rest)),
+        None => (s, None),
+    }
+}
+
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    let c = chars.next()?;
+    chars.next().is_none().then_some(c)
+}
+
+fn parse_baudrate(s: &str) -> Result<u32, ParseError> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseError::InvalidBaudrate);
+    }
+    match s.parse() {
+        Ok(0) | Err(_) => Err(ParseError::InvalidBaudrate),
+        Ok(baudrate) => Ok(baudrate),
+    }
+}
+
+fn parse_address(s: &str) -> Result<usize, ParseError> {
+    let parsed = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+    parsed.map_err(|_| ParseError::InvalidAddress)
+}
+
+fn parse_parity(c: char) -> Result<Parity, ParseError> {
+    match c {
+        'n' => Ok(Parity::None),
+        'o' => Ok(Parity::Odd),
+        'e' => Ok(Parity::Even),
+        'm' => Ok(Parity::Mark),
+        's' => Ok(Parity::Space),
+        _ => Err(ParseError::InvalidParity(c)),
+    }
+}
+
+fn parity_char(parity: Parity) -> char {
+    match parity {
+        Parity::None => 'n',
+        Parity::Odd => 'o',
+        Parity::Even => 'e',
+        Parity::Mark => 'm',
+        Parity::Space => 's',
+    }
+}
+
+fn parse_data_bits_char(c: char) -> Result<DataBits, ParseError> {
+    match c {
+        '5' => Ok(DataBits::Five),
+        '6' => Ok(DataBits::Six),
+        '7' => Ok(DataBits::Seven),
+        '8' => Ok(DataBits::Eight),
+        _ => Err(ParseError::InvalidDataBits),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::string::ToString;
+
+    use super::*;
+
+    fn opts(s: &str) -> SerialOptions {
+        SerialOptions::parse(s).unwrap()
+    }
+
+    fn err(s: &str) -> ParseError {
+        SerialOptions::parse(s).unwrap_err()
+    }
+
+    #[test]
+    fn compact_baud_only() {
+        let o = opts("115200");
+        assert_eq!(o.config.baudrate, Some(115200));
+        assert_eq!(o.config.parity, None);
+        assert_eq!(o.config.data_bits, None);
+        assert_eq!(o.config.stop_bits, None);
+        assert!(!o.flow_control);
+    }
+
+    #[test]
+    fn compact_full() {
+        let o = opts("115200n8");
+        assert_eq!(o.config.baudrate, Some(115200));
+        assert_eq!(o.config.parity, Some(Parity::None));
+        assert_eq!(o.config.data_bits, Some(DataBits::Eight));
+        assert!(!o.flow_control);
+
+        let o = opts("9600e7r");
+        assert_eq!(o.config.baudrate, Some(9600));
+        assert_eq!(o.config.parity, Some(Parity::Even));
+        assert_eq!(o.config.data_bits, Some(DataBits::Seven));
+        assert!(o.flow_control);
+    }
+
+    #[test]
+    fn compact_parity_only() {
+        assert_eq!(opts("38400o").config.parity, Some(Parity::Odd));
+    }
+
+    #[test]
+    fn every_parity_letter() {
+        for (c, p) in [
+            ('n', Parity::None),
+            ('o', Parity::Odd),
+            ('e', Parity::Even),
+            ('m', Parity::Mark),
+            ('s', Parity::Space),
+        ] {
+            let mut buf = [0u8; 8];
+            let s = c.encode_utf8(&mut buf);
+            let o = opts(&["1200", s, "8"].concat());
+            assert_eq!(o.config.parity, Some(p));
+        }
+    }
+
+    #[test]
+    fn every_data_bits() {
+        for (s, bits) in [
+            ("5", DataBits::Five),
+            ("6", DataBits::Six),
+            ("7", DataBits::Seven),
+            ("8", DataBits::Eight),
+        ] {
+            assert_eq!(opts(&["1200n", s].concat()).config.data_bits, Some(bits));
+            assert_eq!(opts(&["1200,n,", s].concat()).config.data_bits, Some(bits));
+        }
+    }
+
+    #[test]
+    fn separated_form() {
+        let o = opts("9600,e,7,2");
+        assert_eq!(o.config.baudrate, Some(9600));
+        assert_eq!(o.config.parity, Some(Parity::Even));
+        assert_eq!(o.config.data_bits, Some(DataBits::Seven));
+        assert_eq!(o.config.stop_bits, Some(StopBits::Two));
+        assert!(!o.flow_control);
+
+        let o = opts("57600,n,8,1,r");
+        assert_eq!(o.config.stop_bits, Some(StopBits::One));
+        assert!(o.flow_control);
+
+        let o = opts("4800,m");
+        assert_eq!(o.config.parity, Some(Parity::Mark));
+        assert_eq!(o.config.data_bits, None);
+    }
+
+    #[test]
+    fn invalid_options() {
+        assert_eq!(err(""), ParseError::Empty);
+        assert_eq!(err("n8"), ParseError::InvalidBaudrate);
+        assert_eq!(err("0n8"), ParseError::InvalidBaudrate);
+        assert_eq!(err("99999999999n8"), ParseError::InvalidBaudrate);
+        assert_eq!(err("115200x8"), ParseError::InvalidParity('x'));
+        assert_eq!(err("115200N8"), ParseError::InvalidParity('N'));
+        assert_eq!(err("115200n9"), ParseError::InvalidDataBits);
+        assert_eq!(err("115200n4"), ParseError::InvalidDataBits);
+        assert_eq!(err("115200n8x"), ParseError::InvalidFlowControl);
+        assert_eq!(err("115200n8rr"), ParseError::TrailingCharacters);
+        assert_eq!(err(",n,8"), ParseError::InvalidBaudrate);
+        assert_eq!(err("9600,x,8"), ParseError::InvalidParity('x'));
+        assert_eq!(err("9600,,8"), ParseError::InvalidParity(' '));
+        assert_eq!(err("9600,ne,8"), ParseError::InvalidParity('n'));
+        assert_eq!(err("9600,n,88"), ParseError::InvalidDataBits);
+        assert_eq!(err("9600,n,8,3"), ParseError::InvalidStopBits);
+        assert_eq!(err("9600,n,8,1,x"), ParseError::InvalidFlowControl);
+        assert_eq!(err("9600,n,8,1,r,1"), ParseError::TrailingCharacters);
+        assert_eq!(err("+9600"), ParseError::InvalidBaudrate);
+    }
+
+    #[test]
+    fn format_compact() {
+        assert_eq!(opts("115200").to_string(), "115200");
+        assert_eq!(opts("115200n8").to_string(), "115200n8");
+        assert_eq!(opts("9600e7r").to_string(), "9600e7r");
+        assert_eq!(opts("38400o").to_string(), "38400o");
+        // 逗号写法在不需要停止位时格式化为紧凑写法
+        assert_eq!(opts("9600,e,7").to_string(), "9600e7");
+        assert_eq!(opts("9600,e,7,1,r").to_string(), "9600e7r");
+    }
+
+    #[test]
+    fn format_fills_required_fields() {
+        let o = SerialOptions {
+            config: Config::new().baudrate(19200).data_bits(DataBits::Seven),
+            flow_control: false,
+        };
+        assert_eq!(o.to_string(), "19200n7");
+
+        let o = SerialOptions {
+            config: Config::new().baudrate(19200),
+            flow_control: true,
+        };
+        assert_eq!(o.to_string(), "19200n8r");
+    }
+
+    #[test]
+    fn format_omits_missing_baudrate() {
+        let o = SerialOptions {
+            config: Config::new().parity(Parity::Even),
+            flow_control: false,
+        };
+        assert_eq!(o.to_string(), "e");
+        assert_eq!(SerialOptions::default().to_string(), "");
+    }
+
+    #[test]
+    fn format_two_stop_bits() {
+        assert_eq!(opts("9600,e,7,2").to_string(), "9600,e,7,2");
+        assert_eq!(opts("9600,n,8,2,r").to_string(), "9600,n,8,2,r");
+    }
+
+    #[test]
+    fn format_round_trip() {
+        for s in [
+            "115200",
+            "115200n8",
+            "115200n8r",
+            "1200s5",
+            "9600,e,7,2",
+            "300,m,6,2,r",
+        ] {
+            let o = opts(s);
+            let formatted = o.to_string();
+            let again = opts(&formatted);
+            assert_eq!(o.config.baudrate, again.config.baudrate, "{s}");
+            assert_eq!(o.config.parity, again.config.parity, "{s}");
+            assert_eq!(o.config.data_bits, again.config.data_bits, "{s}");
+            assert_eq!(o.flow_control, again.flow_control, "{s}");
+            assert_eq!(formatted, s);
+        }
+    }
+
+    #[test]
+    fn console_spec() {
+        let c = ConsoleSpec::parse("console=ttyS0,115200n8").unwrap();
+        assert_eq!(c.name, "ttyS");
+        assert_eq!(c.index, Some(0));
+        let o = c.options.unwrap();
+        assert_eq!(o.config.baudrate, Some(115200));
+        assert_eq!(o.config.data_bits, Some(DataBits::Eight));
+
+        let c = ConsoleSpec::parse("ttyAMA12").unwrap();
+        assert_eq!(c.name, "ttyAMA");
+        assert_eq!(c.index, Some(12));
+        assert!(c.options.is_none());
+
+        let c = ConsoleSpec::parse("hvc,9600,e,7,2").unwrap();
+        assert_eq!(c.name, "hvc");
+        assert_eq!(c.index, None);
+        assert_eq!(c.options.unwrap().config.stop_bits, Some(StopBits::Two));
+    }
+
+    #[test]
+    fn console_spec_invalid() {
+        assert_eq!(
+            ConsoleSpec::parse("console=").unwrap_err(),
+            ParseError::Empty
+        );
+        assert_eq!(
+            ConsoleSpec::parse(",115200").unwrap_err(),
+            ParseError::Empty
+        );
+        assert_eq!(
+            ConsoleSpec::parse("ttyS0,115200q").unwrap_err(),
+            ParseError::InvalidParity('q')
+        );
+        assert_eq!(
+            ConsoleSpec::parse("ttyS99999999999").unwrap_err(),
+            ParseError::InvalidIndex
+        );
+    }
+
+    #[test]
+    fn earlycon_pl011() {
+        let e = EarlyconSpec::parse("earlycon=pl011,mmio32,0x9000000,115200n8").unwrap();
+        assert_eq!(e.driver, EarlyconDriver::Pl011);
+        assert_eq!(e.access, AccessWidth::Mmio32);
+        assert_eq!(e.base, Some(0x900_0000));
+        assert_eq!(e.options.unwrap().config.baudrate, Some(115200));
+
+        let e = EarlyconSpec::parse("pl011,0x9000000").unwrap();
+        assert_eq!(e.access, AccessWidth::Mmio32);
+        assert_eq!(e.base, Some(0x900_0000));
+        assert!(e.options.is_none());
+
+        let e = EarlyconSpec::parse("pl011").unwrap();
+        assert_eq!(e.base, None);
+    }
+
+    #[test]
+    fn earlycon_sifive() {
+        let e = EarlyconSpec::parse("sifive,0x10010000").unwrap();
+        assert_eq!(e.driver, EarlyconDriver::Sifive);
+        assert_eq!(e.access, AccessWidth::Mmio32);
+        assert_eq!(e.base, Some(0x1001_0000));
+    }
+
+    #[test]
+    fn earlycon_nxp() {
+        let e = EarlyconSpec::parse("ec_imx6q,0x30890000,115200").unwrap();
+        assert_eq!(e.driver, EarlyconDriver::ImxUart);
+        assert_eq!(e.access, AccessWidth::Mmio32);
+
+        let e = EarlyconSpec::parse("lpuart32,0x44380000").unwrap();
+        assert_eq!(e.driver, EarlyconDriver::Lpuart);
+        assert_eq!(e.base, Some(0x4438_0000));
+    }
+
+    #[test]
+    fn earlycon_bcm2835aux() {
+        let e = EarlyconSpec::parse("bcm2835aux,0x3f215040").unwrap();
+        assert_eq!(e.driver, EarlyconDriver::Bcm2835Aux);
+        assert_eq!(e.access, AccessWidth::Mmio32);
+        assert_eq!(e.base, Some(0x3f21_5040));
+    }
+
+    #[test]
+    fn earlycon_samsung() {
+        let e = EarlyconSpec::parse("exynos4210,0x13820000").unwrap();
+        assert_eq!(e.driver, EarlyconDriver::Exynos);
+        assert_eq!(e.access, AccessWidth::Mmio32);
+
+        let e = EarlyconSpec::parse("apple_s5l,0x235200000").unwrap();
+        assert_eq!(e.driver, EarlyconDriver::AppleS5l);
+        assert_eq!(e.base, Some(0x2_3520_0000));
+    }
+
+    #[test]
+    fn earlycon_meson() {
+        let e = EarlyconSpec::parse("meson,0xff803000").unwrap();
+        assert_eq!(e.driver, EarlyconDriver::Meson);
+        assert_eq!(e.access, AccessWidth::Mmio32);
+        assert_eq!(e.base, Some(0xff80_3000));
+    }
+
+    #[test]
+    fn earlycon_scif() {
+        let e = EarlyconSpec::parse("scif,0xe6e88000").unwrap();
+        assert_eq!(e.driver, EarlyconDriver::Scif);
+        assert_eq!(e.access, AccessWidth::Mmio16);
+        assert_eq!(e.base, Some(0xe6e8_8000));
+    }
+
+    #[test]
+    fn earlycon_8250() {
+        let e = EarlyconSpec::parse("uart8250,io,0x3f8,9600,e,7,2").unwrap();
+        assert_eq!(e.driver, EarlyconDriver::Ns16550);
+        assert_eq!(e.access, AccessWidth::Io);
+        assert_eq!(e.base, Some(0x3f8));
+        let o = e.options.unwrap();
+        assert_eq!(o.config.stop_bits, Some(StopBits::Two));
+
+        let e = EarlyconSpec::parse("uart,mmio32be,0xff5e0000").unwrap();
+        assert_eq!(e.access, AccessWidth::Mmio32Be);
+        assert_eq!(e.access.reg_width(), 4);
+
+        let e = EarlyconSpec::parse("ns16550a,0x10000000,115200").unwrap();
+        assert_eq!(e.access, AccessWidth::Mmio8);
+        assert_eq!(e.access.reg_width(), 1);
+        assert_eq!(e.base, Some(0x1000_0000));
+
+        let e = EarlyconSpec::parse("uart8250,mmio16,4096").unwrap();
+        assert_eq!(e.access, AccessWidth::Mmio16);
+        assert_eq!(e.base, Some(4096));
+    }
+
+    #[test]
+    fn earlycon_invalid() {
+        assert_eq!(
+            EarlyconSpec::parse("earlycon=").unwrap_err(),
+            ParseError::Empty
+        );
+        assert_eq!(
+            EarlyconSpec::parse("foo,0x1000").unwrap_err(),
+            ParseError::UnknownDriver
+        );
+        assert_eq!(
+            EarlyconSpec::parse("pl011,mmio32").unwrap_err(),
+            ParseError::InvalidAddress
+        );
+        assert_eq!(
+            EarlyconSpec::parse("pl011,mmio64,0x1000").unwrap_err(),
+            ParseError::InvalidAddress
+        );
+        assert_eq!(
+            EarlyconSpec::parse("pl011,0xzz").unwrap_err(),
+            ParseError::InvalidAddress
+        );
+        assert_eq!(
+            EarlyconSpec::parse("pl011,0x1000,115200z8").unwrap_err(),
+            ParseError::InvalidParity('z')
+        );
+    }
+}
@@ -0,0 +1,486 @@
+//! AArch64 DCC（Debug Communications Channel）控制台
+//!
+//! 通过调试器（OpenOCD、Trace32 等）读写 DBGDTRTX_EL0/DBGDTRRX_EL0，
+//! MDCCSR_EL0 的 TXfull/RXfull 位指示通道状态。与 Linux `hvc_dcc` 相同，每个 32 位字传输一个字节。
+//!
+//! 系统寄存器访问经过 [`DccRegs`] 抽象，主机测试中可以替换为模拟通道。
+
+use alloc::sync::Arc;
+
+use rdif_serial::{InterfaceRaw, SetBackError, TIrqHandler, TReciever, TSender};
+
+use crate::{
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, Stats},
+    Capabilities, Caps, Config, ConfigError, DataBits, Features, FifoThreshold, InterruptMask,
+    Parity, RawReciever, RawSender, StopBits, Suspend, TransferError,
+};
+
+#[cfg(target_arch = "aarch64")]
+use rdif_serial::{BSerial, SerialDyn};
+
+/// MDCCSR_EL0.TXfull
+const MDCCSR_TX_FULL: u64 = 1 << 29;
+/// MDCCSR_EL0.RXfull
+const MDCCSR_RX_FULL: u64 = 1 << 30;
+/// 等待调试器取走发送字的最大轮询次数
+const DRAIN_SPIN_LIMIT: usize = 1_000_000;
+
+/// DCC 系统寄存器访问接口
+pub trait DccRegs: Send + Sync + 'static {
+    /// 读取 MDCCSR_EL0
+    fn mdccsr(&self) -> u64;
+    /// 写入 DBGDTRTX_EL0
+    fn write_dtrtx(&self, value: u32);
+    /// 读取 DBGDTRRX_EL0
+    fn read_dtrrx(&self) -> u32;
+}
+
+/// 直接访问当前核心的系统寄存器
+#[cfg(target_arch = "aarch64")]
+pub struct SysRegs;
+
+#[cfg(target_arch = "aarch64")]
+impl DccRegs for SysRegs {
+    fn mdccsr(&self) -> u64 {
+        let value: u64;
+        unsafe { core::arch::asm!("mrs {}, mdccsr_el0", out(reg) value, options(nomem, nostack)) };
+        value
+    }
+
+    fn write_dtrtx(&self, value: u32) {
+        unsafe {
+            core::arch::asm!(
+                "msr dbgdtrtx_el0, {}",
+                in(reg) value as u64,
+                options(nomem, nostack),
+            );
+            core::arch::asm!("isb", options(nomem, nostack));
+        }
+    }
+
+    fn read_dtrrx(&self) -> u32 {
+        let value: u64;
+        unsafe {
+            core::arch::asm!("mrs {}, dbgdtrrx_el0", out(reg) value, options(nomem, nostack))
+        };
+        value as u32
+    }
+}
+
+#[derive(Clone)]
+struct Channel(Arc<dyn DccRegs>);
+
+impl Channel {
+    fn addr(&self) -> usize {
+        Arc::as_ptr(&self.0) as *const u8 as usize
+    }
+
+    fn tx_full(&self) -> bool {
+        self.0.mdccsr() & MDCCSR_TX_FULL != 0
+    }
+
+    fn rx_full(&self) -> bool {
+        self.0.mdccsr() & MDCCSR_RX_FULL != 0
+    }
+}
+
+impl PartialEq for Channel {
+    fn eq(&self, other: &Self) -> bool {
+        self.addr() == other.addr()
+    }
+}
+
+/// DCC 控制台驱动结构体
+pub struct Dcc {
+    channel: Channel,
+    baudrate: u32,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    tx_pause: TxPause,
+    tx: Option<DccSender>,
+    rx: Option<DccReciever>,
+    irq: Option<DccIrqHandler>,
+}
+
+impl Dcc {
+    #[cfg(target_arch = "aarch64")]
+    pub fn new() -> Self {
+        Self::with_regs(Arc::new(SysRegs))
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    pub fn new_boxed() -> BSerial {
+        SerialDyn::new_boxed(Self::new())
+    }
+
+    /// 使用自定义寄存器访问实现创建实例
+    pub fn with_regs(regs: Arc<dyn DccRegs>) -> Self {
+        let channel = Channel(regs);
+        let tx_pause = TxPause::default();
+
+        Self {
+            tx_pause: tx_pause.clone(),
+            tx: Some(DccSender {
+                channel: channel.clone(),
+                stats: None,
+                tx_pause: tx_pause.clone(),
+            }),
+            rx: Some(DccReciever {
+                channel: channel.clone(),
+                stats: None,
+            }),
+            irq: Some(DccIrqHandler),
+            channel,
+            baudrate: 115200,
+        }
+    }
+
+    /// 挂接统计计数器，只对尚未取出的收发句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+impl Default for Dcc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InterfaceRaw for Dcc {
+    type IrqHandler = DccIrqHandler;
+
+    type Sender = crate::Sender;
+
+    type Reciever = crate::Reciever;
+
+    fn base_addr(&self) -> usize {
+        self.channel.addr()
+    }
+
+    fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        self.capabilities().validate(config)?;
+        if let Some(baudrate) = config.baudrate {
+            self.baudrate = baudrate;
+        }
+        Ok(())
+    }
+
+    /// DCC 没有真实波特率，返回最近一次配置的值
+    fn baudrate(&self) -> u32 {
+        self.baudrate
+    }
+
+    fn data_bits(&self) -> DataBits {
+        DataBits::Eight
+    }
+
+    fn stop_bits(&self) -> StopBits {
+        StopBits::One
+    }
+
+    fn parity(&self) -> Parity {
+        Parity::None
+    }
+
+    fn clock_freq(&self) -> Option<core::num::NonZeroU32> {
+        None
+    }
+
+    fn open(&mut self) {}
+
+    fn close(&mut self) {}
+
+    fn enable_loopback(&mut self) {}
+
+    fn disable_loopback(&mut self) {}
+
+    fn is_loopback_enabled(&self) -> bool {
+        false
+    }
+
+    // DCC 中断（COMMTX/COMMRX）的连线与平台相关，这里只支持轮询
+    fn set_irq_mask(&mut self, _mask: InterruptMask) {}
+
+    fn get_irq_mask(&self) -> InterruptMask {
+        InterruptMask::empty()
+    }
+
+    fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+        self.irq.take()
+    }
+
+    fn take_tx(&mut self) -> Option<Self::Sender> {
+        self.tx.take().map(crate::Sender::DccSender)
+    }
+
+    fn take_rx(&mut self) -> Option<Self::Reciever> {
+        self.rx.take().map(crate::Reciever::DccReciever)
+    }
+
+    fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+        let tx = match tx {
+            crate::Sender::DccSender(s) => s,
+            _ => {
+                return Err(SetBackError::new(
+                    self.channel.addr(),
+                    tx.external_base().unwrap_or(0), // 不匹配的发送器类型
+                ));
+            }
+        };
+
+        if self.channel != tx.channel {
+            return Err(SetBackError::new(self.channel.addr(), tx.channel.addr()));
+        }
+
+        self.tx = Some(tx);
+        Ok(())
+    }
+
+    fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+        let rx = match rx {
+            crate::Reciever::DccReciever(r) => r,
+            _ => {
+                return Err(SetBackError::new(
+                    self.channel.addr(),
+                    rx.external_base().unwrap_or(0), // 不匹配的接收器类型
+                ));
+            }
+        };
+
+        if self.channel != rx.channel {
+            return Err(SetBackError::new(self.channel.addr(), rx.channel.addr()));
+        }
+
+        self.rx = Some(rx);
+        Ok(())
+    }
+}
+
+impl Caps for Dcc {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            fifo_depth: 1,
+            min_baudrate: 1,
+            max_baudrate: u32::MAX,
+            data_bits: &[DataBits::Eight],
+            stop_bits: &[StopBits::One],
+            parity: &[Parity::None],
+            features: Features::empty(),
+        }
+    }
+}
+
+impl FifoThreshold for Dcc {}
+
+/// 收发各只有一个字的寄存器：接收方向读出待取的字，已写入发送寄存器的字无法撤回
+impl Flush for Dcc {
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError> {
+        if queue.rx() && self.channel.rx_full() {
+            self.channel.0.read_dtrrx();
+        }
+        if queue.tx() && self.channel.tx_full() {
+            return Err(FlushError::TxUnsupported);
+        }
+        Ok(())
+    }
+
+    /// 等待调试器取走发送寄存器中的字，没有调试器连接时超时
+    fn drain(&mut self) -> Result<(), FlushError> {
+        for _ in 0..DRAIN_SPIN_LIMIT {
+            if !self.channel.tx_full() {
+                return Ok(());
+            }
+            core::hint::spin_loop();
+        }
+        Err(FlushError::Timeout)
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// DCC 挂起时保存的上下文
+#[derive(Debug, Clone, Copy)]
+pub struct DccContext {
+    baudrate: u32,
+}
+
+impl Suspend for Dcc {
+    type Context = DccContext;
+
+    fn save_context(&self) -> Self::Context {
+        DccContext {
+            baudrate: self.baudrate,
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        self.baudrate = ctx.baudrate;
+    }
+}
+
+pub struct DccSender {
+    channel: Channel,
+    stats: Option<&'static Stats>,
+    tx_pause: TxPause,
+}
+
+impl AttachStats for DccSender {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl TSender for DccSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        RawSender::write_byte(self, byte)
+    }
+}
+
+impl RawSender for DccSender {
+    /// 调试器未取走上一个字时返回 false
+    fn write_byte(&mut self, byte: u8) -> bool {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return false;
+        };
+        if self.channel.tx_full() {
+            return false;
+        }
+        self.channel.0.write_dtrtx(byte as u32);
+        if let Some(stats) = self.stats {
+            stats.record_tx(1);
+        }
+        true
+    }
+}
+
+pub struct DccReciever {
+    channel: Channel,
+    stats: Option<&'static Stats>,
+}
+
+impl AttachStats for DccReciever {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl TReciever for DccReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        RawReciever::read_byte(self)
+    }
+}
+
+impl RawReciever for DccReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        if !self.channel.rx_full() {
+            return None;
+        }
+        let result = Ok(self.channel.0.read_dtrrx() as u8);
+        if let Some(stats) = self.stats {
+            stats.record_rx(&result);
+        }
+        Some(result)
+    }
+}
+
+/// DCC 只支持轮询，处理函数总是返回空
+pub struct DccIrqHandler;
+
+impl TIrqHandler for DccIrqHandler {
+    fn clean_interrupt_status(&self) -> InterruptMask {
+        InterruptMask::empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::{collections::VecDeque, sync::Mutex, vec::Vec};
+
+    use super::{Dcc, DccRegs, MDCCSR_RX_FULL, MDCCSR_TX_FULL};
+    use alloc::sync::Arc;
+    use rdif_serial::{InterfaceRaw, TReciever, TSender};
+
+    #[derive(Default)]
+    struct FakeState {
+        /// 调试器尚未取走的字
+        tx: Option<u32>,
+        output: Vec<u32>,
+        input: VecDeque<u32>,
+    }
+
+    /// 模拟调试器一侧的通道
+    #[derive(Default)]
+    struct FakeChannel(Mutex<FakeState>);
+
+    impl FakeChannel {
+        /// 调试器取走发送寄存器中的字
+        fn drain(&self) {
+            let mut state = self.0.lock().unwrap();
+            if let Some(word) = state.tx.take() {
+                state.output.push(word);
+            }
+        }
+    }
+
+    impl DccRegs for FakeChannel {
+        fn mdccsr(&self) -> u64 {
+            let state = self.0.lock().unwrap();
+            let mut value = 0;
+            if state.tx.is_some() {
+                value |= MDCCSR_TX_FULL;
+            }
+            if !state.input.is_empty() {
+                value |= MDCCSR_RX_FULL;
+            }
+            value
+        }
+
+        fn write_dtrtx(&self, value: u32) {
+            self.0.lock().unwrap().tx = Some(value);
+        }
+
+        fn read_dtrrx(&self) -> u32 {
+            self.0.lock().unwrap().input.pop_front().unwrap()
+        }
+    }
+
+    #[test]
+    fn write_waits_for_debugger() {
+        let channel = Arc::new(FakeChannel::default());
+        let mut console = Dcc::with_regs(channel.clone());
+        let mut tx = console.take_tx().unwrap();
+
+        assert!(tx.write_byte(b'a'));
+        assert!(!tx.write_byte(b'b'));
+        assert_eq!(tx.write_bytes(b"bc"), 0);
+
+        channel.drain();
+        assert_eq!(tx.write_bytes(b"bc"), 1);
+        channel.drain();
+        assert_eq!(channel.0.lock().unwrap().output, [b'a' as u32, b'b' as u32]);
+    }
+
+    #[test]
+    fn read_when_full() {
+        let channel = Arc::new(FakeChannel::default());
+        let mut console = Dcc::with_regs(channel.clone());
+        let mut rx = console.take_rx().unwrap();
+
+        assert!(rx.read_byte().is_none());
+        channel.0.lock().unwrap().input.push_back(b'z' as u32);
+        assert_eq!(rx.read_byte(), Some(Ok(b'z')));
+        assert!(rx.read_byte().is_none());
+    }
+}
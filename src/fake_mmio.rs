@@ -0,0 +1,38 @@
+//! 主机测试用的寄存器内存
+//!
+//! 驱动直接按 MMIO 访问这块内存，读写没有硬件副作用；测试通过偏移量预置状态
+//! 并检查驱动写入的值。
+
+extern crate std;
+
+use core::ptr::NonNull;
+use std::boxed::Box;
+
+/// `N` 个 32 位寄存器，测试结束时不释放，驱动可以持有指针到任意时刻
+///
+/// 复制出的句柄指向同一块内存，可以交给另一个线程模拟硬件改变状态位。
+#[derive(Clone, Copy)]
+pub struct FakeMmio<const N: usize>(NonNull<u32>);
+
+// SAFETY: 内存泄漏后一直有效，所有访问都是单次 32 位 volatile 读写
+unsafe impl<const N: usize> Send for FakeMmio<N> {}
+
+impl<const N: usize> FakeMmio<N> {
+    pub fn new() -> Self {
+        Self(NonNull::from(Box::leak(Box::new([0u32; N]))).cast())
+    }
+
+    pub fn base(&self) -> NonNull<u8> {
+        self.0.cast()
+    }
+
+    pub fn get(&self, offset: usize) -> u32 {
+        assert!(offset / 4 < N);
+        unsafe { self.0.add(offset / 4).read_volatile() }
+    }
+
+    pub fn set(&self, offset: usize, value: u32) {
+        assert!(offset / 4 < N);
+        unsafe { self.0.add(offset / 4).write_volatile(value) }
+    }
+}
@@ -0,0 +1,348 @@
+//! FIFO 触发级别
+//!
+//! 各 UART 的触发级别编码差别很大：16550 只有 1/4/8/14 字节四档，PL011 按 FIFO 的
+//! 八分之几，SCIF 有独立的收发档位，Cadence、i.MX、LPUART 等则直接写字节数。
+//! [`FifoThreshold`] 以字节数或 FIFO 深度的分数表达请求，驱动从硬件支持的级别中选择
+//! 不超过请求值的最大一档（低于最小档时取最小档），并返回实际生效的级别。
+//!
+//! 与 [`Caps`]、[`Suspend`](crate::Suspend) 一样，`BSerial` 只转发 `rdif-serial`
+//! 自身接口中的方法；本 trait 是对象安全的，通用代码可以通过具体驱动或
+//! `&mut dyn FifoThreshold` 调用。
+//!
+//! `BSerial` 上无法提供取回本 trait 的访问器：`SerialDyn` 把驱动放在私有字段里，
+//! 也没有实现 `DriverGeneric::raw_any`，把 `dyn Interface` 向下转型最多得到
+//! `SerialDyn<T>`，仍然拿不到驱动。而驱动在 `open` 中会把触发级别恢复为默认值，
+//! 装箱前直接设置也会丢失。需要装箱时用 [`WithFifoThresholds`] 包装驱动，
+//! 每次 `open` 之后重新写入请求的级别。
+
+use core::num::NonZeroU32;
+
+use rdif_serial::{
+    BSerial, Config, ConfigError, DataBits, InterfaceRaw, InterruptMask, Parity, SerialDyn,
+    SetBackError, StopBits,
+};
+
+use crate::Caps;
+
+/// 触发级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FifoLevel {
+    /// 字节数
+    Bytes(u16),
+    /// FIFO 深度的分数 `分子/分母`
+    Fraction(u8, u8),
+}
+
+impl FifoLevel {
+    /// 按 FIFO 深度换算为字节数，分数四舍五入
+    pub fn to_bytes(self, depth: u16) -> Result<u16, FifoError> {
+        match self {
+            Self::Bytes(bytes) => Ok(bytes),
+            Self::Fraction(num, den) => {
+                if den == 0 || num > den {
+                    return Err(FifoError::InvalidFraction);
+                }
+                let bytes = (depth as u32 * num as u32 + den as u32 / 2) / den as u32;
+                Ok(bytes as u16)
+            }
+        }
+    }
+}
+
+/// 触发级别请求，未设置的项保持不变
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FifoThresholds {
+    /// 接收 FIFO 中字节数达到该级别时触发接收中断
+    pub rx: Option<FifoLevel>,
+    /// 发送 FIFO 中剩余字节数不超过该级别时触发发送中断
+    pub tx: Option<FifoLevel>,
+    /// 接收 FIFO 未达到触发级别、且线路空闲超过该位时间数时触发超时中断
+    pub rx_timeout: Option<u16>,
+}
+
+impl FifoThresholds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rx(mut self, level: FifoLevel) -> Self {
+        self.rx = Some(level);
+        self
+    }
+
+    pub fn tx(mut self, level: FifoLevel) -> Self {
+        self.tx = Some(level);
+        self
+    }
+
+    pub fn rx_timeout(mut self, bits: u16) -> Self {
+        self.rx_timeout = Some(bits);
+        self
+    }
+
+    /// 校验请求并把分数换算为字节数
+    ///
+    /// `current` 中为 `None` 的项不可配置，请求这些项时返回对应错误。
+    pub(crate) fn resolve(
+        &self,
+        depth: u16,
+        current: &FifoTriggers,
+    ) -> Result<ResolvedThresholds, FifoError> {
+        if self.rx.is_some() && current.rx.is_none() {
+            return Err(FifoError::RxUnsupported);
+        }
+        if self.tx.is_some() && current.tx.is_none() {
+            return Err(FifoError::TxUnsupported);
+        }
+        if self.rx_timeout.is_some() && current.rx_timeout.is_none() {
+            return Err(FifoError::RxTimeoutUnsupported);
+        }
+
+        Ok(ResolvedThresholds {
+            rx: self.rx.map(|l| l.to_bytes(depth)).transpose()?,
+            tx: self.tx.map(|l| l.to_bytes(depth)).transpose()?,
+            rx_timeout: self.rx_timeout,
+        })
+    }
+}
+
+/// 换算为字节数后的请求，`None` 表示保持不变
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ResolvedThresholds {
+    pub rx: Option<u16>,
+    pub tx: Option<u16>,
+    pub rx_timeout: Option<u16>,
+}
+
+/// 当前生效的触发级别，`None` 表示该项不可配置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FifoTriggers {
+    /// 接收触发级别（字节）
+    pub rx: Option<u16>,
+    /// 发送触发级别（字节）
+    pub tx: Option<u16>,
+    /// 接收超时（位时间）
+    pub rx_timeout: Option<u16>,
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FifoError {
+    #[error("RX trigger level is not configurable")]
+    RxUnsupported,
+    #[error("TX trigger level is not configurable")]
+    TxUnsupported,
+    #[error("RX timeout is not configurable")]
+    RxTimeoutUnsupported,
+    #[error("invalid FIFO level fraction")]
+    InvalidFraction,
+}
+
+/// FIFO 触发级别配置
+///
+/// 默认实现对应没有可配置触发级别的设备：全部报告为不可配置，任何请求都返回错误。
+pub trait FifoThreshold: Caps {
+    /// 读取当前生效的触发级别
+    fn fifo_triggers(&self) -> FifoTriggers {
+        FifoTriggers::default()
+    }
+
+    /// 设置触发级别，返回实际生效的级别
+    ///
+    /// 请求整体校验通过后才写入寄存器。
+    fn set_fifo_thresholds(&mut self, req: &FifoThresholds) -> Result<FifoTriggers, FifoError> {
+        req.resolve(self.capabilities().fifo_depth, &self.fifo_triggers())?;
+        Ok(self.fifo_triggers())
+    }
+}
+
+/// 在 `open` 之后应用触发级别的驱动包装，用于装箱为 `BSerial`
+pub struct WithFifoThresholds<D> {
+    dev: D,
+    req: FifoThresholds,
+}
+
+impl<D: InterfaceRaw + FifoThreshold> WithFifoThresholds<D> {
+    /// 立即应用一次 `req` 以校验请求，之后每次 `open` 都重新应用
+    pub fn new(mut dev: D, req: FifoThresholds) -> Result<Self, FifoError> {
+        dev.set_fifo_thresholds(&req)?;
+        Ok(Self { dev, req })
+    }
+
+    pub fn inner(&self) -> &D {
+        &self.dev
+    }
+
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    pub fn into_boxed(self) -> BSerial {
+        SerialDyn::new_boxed(self)
+    }
+}
+
+impl<D: InterfaceRaw + FifoThreshold> InterfaceRaw for WithFifoThresholds<D> {
+    type IrqHandler = D::IrqHandler;
+    type Sender = D::Sender;
+    type Reciever = D::Reciever;
+
+    fn base_addr(&self) -> usize {
+        self.dev.base_addr()
+    }
+
+    fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        self.dev.set_config(config)
+    }
+
+    fn baudrate(&self) -> u32 {
+        self.dev.baudrate()
+    }
+
+    fn data_bits(&self) -> DataBits {
+        self.dev.data_bits()
+    }
+
+    fn stop_bits(&self) -> StopBits {
+        self.dev.stop_bits()
+    }
+
+    fn parity(&self) -> Parity {
+        self.dev.parity()
+    }
+
+    fn clock_freq(&self) -> Option<NonZeroU32> {
+        self.dev.clock_freq()
+    }
+
+    fn open(&mut self) {
+        self.dev.open();
+        // 请求已在构造时校验通过
+        let _ = self.dev.set_fifo_thresholds(&self.req);
+    }
+
+    fn close(&mut self) {
+        self.dev.close()
+    }
+
+    fn enable_loopback(&mut self) {
+        self.dev.enable_loopback()
+    }
+
+    fn disable_loopback(&mut self) {
+        self.dev.disable_loopback()
+    }
+
+    fn is_loopback_enabled(&self) -> bool {
+        self.dev.is_loopback_enabled()
+    }
+
+    fn set_irq_mask(&mut self, mask: InterruptMask) {
+        self.dev.set_irq_mask(mask)
+    }
+
+    fn get_irq_mask(&self) -> InterruptMask {
+        self.dev.get_irq_mask()
+    }
+
+    fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+        self.dev.irq_handler()
+    }
+
+    fn take_tx(&mut self) -> Option<Self::Sender> {
+        self.dev.take_tx()
+    }
+
+    fn take_rx(&mut self) -> Option<Self::Reciever> {
+        self.dev.take_rx()
+    }
+
+    fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+        self.dev.set_tx(tx)
+    }
+
+    fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+        self.dev.set_rx(rx)
+    }
+}
+
+/// 从升序排列的硬件档位中选择不超过 `want` 的最大一档，低于最小档时取最小档
+pub(crate) fn select_level(levels: &[u16], want: u16) -> u16 {
+    levels
+        .iter()
+        .copied()
+        .rev()
+        .find(|&level| level <= want)
+        .unwrap_or(levels[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fraction_rounds_to_nearest_byte() {
+        assert_eq!(FifoLevel::Fraction(1, 2).to_bytes(16), Ok(8));
+        assert_eq!(FifoLevel::Fraction(7, 8).to_bytes(32), Ok(28));
+        assert_eq!(FifoLevel::Fraction(1, 3).to_bytes(16), Ok(5));
+        assert_eq!(FifoLevel::Bytes(40).to_bytes(16), Ok(40));
+        assert_eq!(
+            FifoLevel::Fraction(3, 2).to_bytes(16),
+            Err(FifoError::InvalidFraction)
+        );
+        assert_eq!(
+            FifoLevel::Fraction(0, 0).to_bytes(16),
+            Err(FifoError::InvalidFraction)
+        );
+    }
+
+    #[test]
+    fn select_rounds_down_and_clamps() {
+        let levels = [1, 4, 8, 14];
+        assert_eq!(select_level(&levels, 0), 1);
+        assert_eq!(select_level(&levels, 7), 4);
+        assert_eq!(select_level(&levels, 8), 8);
+        assert_eq!(select_level(&levels, 100), 14);
+    }
+
+    #[test]
+    fn resolve_rejects_unsupported_items() {
+        let current = FifoTriggers {
+            rx: Some(1),
+            tx: None,
+            rx_timeout: None,
+        };
+        let req = FifoThresholds::new().rx(FifoLevel::Fraction(1, 2));
+        assert_eq!(req.resolve(16, &current).unwrap().rx, Some(8));
+
+        let req = req.tx(FifoLevel::Bytes(2));
+        assert_eq!(req.resolve(16, &current), Err(FifoError::TxUnsupported));
+
+        let req = FifoThresholds::new().rx_timeout(40);
+        assert_eq!(
+            req.resolve(16, &current),
+            Err(FifoError::RxTimeoutUnsupported)
+        );
+    }
+
+    #[test]
+    fn wrapper_reapplies_thresholds_after_open() {
+        use crate::{
+            fake_mmio::FakeMmio,
+            meson::{MesonBaudSource, MesonUart},
+        };
+
+        let regs = FakeMmio::<6>::new();
+        let dev = || MesonUart::new(regs.base(), 24_000_000, MesonBaudSource::Xtal);
+
+        assert!(matches!(
+            WithFifoThresholds::new(dev(), FifoThresholds::new().rx_timeout(40)),
+            Err(FifoError::RxTimeoutUnsupported)
+        ));
+
+        let req = FifoThresholds::new().rx(FifoLevel::Bytes(8));
+        let mut serial = WithFifoThresholds::new(dev(), req).unwrap().into_boxed();
+        // MISC 的接收级别位于低 8 位，open 默认写入 1
+        serial.open().unwrap();
+        assert_eq!(regs.get(0x10) & 0xFF, 8);
+    }
+}
@@ -0,0 +1,61 @@
+//! 清空与排空
+//!
+//! 对应 termios 的 `tcflush`/`tcdrain`：[`Flush::flush`] 丢弃尚未读取的接收数据或尚未
+//! 发出的发送数据，[`Flush::drain`] 等待发送数据全部离开移位寄存器。驱动自身的软件
+//! 缓冲区（如 virtio 队列、NS16550 暂存的线路错误）与硬件 FIFO 一并处理。
+//!
+//! 收发端被取出后硬件 FIFO 仍由驱动访问；清空软件缓冲区时调用方需保证没有并发收发，
+//! 与 `open`/`close` 的要求相同。
+
+use crate::reconfig::TxPause;
+
+/// 清空的方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushQueue {
+    /// 接收方向（`TCIFLUSH`）
+    Rx,
+    /// 发送方向（`TCOFLUSH`）
+    Tx,
+    /// 收发两个方向（`TCIOFLUSH`）
+    Both,
+}
+
+impl FlushQueue {
+    pub fn rx(self) -> bool {
+        matches!(self, Self::Rx | Self::Both)
+    }
+
+    pub fn tx(self) -> bool {
+        matches!(self, Self::Tx | Self::Both)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushError {
+    #[error("timed out waiting for transmitter to drain")]
+    Timeout,
+    #[error("pending transmit data cannot be discarded")]
+    TxUnsupported,
+}
+
+/// FIFO 清空与发送排空
+pub trait Flush {
+    /// 丢弃指定方向上尚未处理的数据
+    ///
+    /// 硬件无法撤回已排队的发送数据时返回 [`FlushError::TxUnsupported`]，
+    /// 此时接收方向（若请求）已经清空。
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError>;
+
+    /// 等待发送 FIFO 和移位寄存器全部发出
+    ///
+    /// 看不到移位寄存器状态的硬件（SiFive、UART Lite）只能等到发送 FIFO 为空，
+    /// 具体见各驱动的说明。
+    fn drain(&mut self) -> Result<(), FlushError>;
+
+    /// 与发送端共享的写入暂停标志，供 [`crate::Reconfigure`] 在收发端取出时使用
+    ///
+    /// 返回 `None` 时收发端必须归还后才能重新配置。
+    fn tx_pause(&self) -> Option<&TxPause> {
+        None
+    }
+}
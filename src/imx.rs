@@ -0,0 +1,952 @@
+//! NXP i.MX UART 驱动 (`fsl,imx-uart` / `fsl,imx21-uart` / `fsl,imx6q-uart`)
+//!
+//! 用于 i.MX6、i.MX8M 等 SoC。参考时钟先经 UFCR.RFDIV 分频，
+//! 再由 UBIR/UBMR 组成的分数分频器产生波特率：
+//! `baud = ref_clk * (UBIR + 1) / (16 * (UBMR + 1))`。收发各有 32 字节 FIFO。
+
+use core::{num::NonZeroU32, ptr::NonNull};
+
+use alloc::sync::Arc;
+
+use rdif_serial::{BSerial, InterfaceRaw, SerialDyn, SetBackError, TIrqHandler, TSender};
+use tock_registers::{interfaces::*, register_bitfields, register_structs, registers::*};
+
+use crate::{
+    caps::{baud_error_ok, ALL_STOP_BITS},
+    claim::{self, Claim, ClaimError, Region},
+    fifo::{FifoError, FifoThreshold, FifoThresholds, FifoTriggers},
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, IrqCause, Stats},
+    Capabilities, Caps, Config, ConfigError, DataBits, Features, InterruptMask, Parity,
+    RawReciever, RawSender, StopBits, Suspend, TransferError,
+};
+
+register_bitfields! [
+    u32,
+
+    /// Receiver Register
+    URXD [
+        DATA OFFSET(0) NUMBITS(8) [],
+        PRERR OFFSET(10) NUMBITS(1) [],
+        BRK OFFSET(11) NUMBITS(1) [],
+        FRMERR OFFSET(12) NUMBITS(1) [],
+        OVRRUN OFFSET(13) NUMBITS(1) [],
+        ERR OFFSET(14) NUMBITS(1) [],
+        CHARRDY OFFSET(15) NUMBITS(1) []
+    ],
+
+    /// Transmitter Register
+    UTXD [
+        DATA OFFSET(0) NUMBITS(8) []
+    ],
+
+    /// Control Register 1
+    UCR1 [
+        UARTEN OFFSET(0) NUMBITS(1) [],
+        TXMPTYEN OFFSET(6) NUMBITS(1) [],
+        RRDYEN OFFSET(9) NUMBITS(1) [],
+        TRDYEN OFFSET(13) NUMBITS(1) []
+    ],
+
+    /// Control Register 2
+    UCR2 [
+        SRST OFFSET(0) NUMBITS(1) [],
+        RXEN OFFSET(1) NUMBITS(1) [],
+        TXEN OFFSET(2) NUMBITS(1) [],
+        ATEN OFFSET(3) NUMBITS(1) [],
+        WS OFFSET(5) NUMBITS(1) [],
+        STPB OFFSET(6) NUMBITS(1) [],
+        PROE OFFSET(7) NUMBITS(1) [],
+        PREN OFFSET(8) NUMBITS(1) [],
+        IRTS OFFSET(14) NUMBITS(1) []
+    ],
+
+    /// Control Register 3
+    UCR3 [
+        RXDMUXSEL OFFSET(2) NUMBITS(1) []
+    ],
+
+    /// FIFO Control Register
+    UFCR [
+        RXTL OFFSET(0) NUMBITS(6) [],
+        RFDIV OFFSET(7) NUMBITS(3) [
+            Div6 = 0b000,
+            Div5 = 0b001,
+            Div4 = 0b010,
+            Div3 = 0b011,
+            Div2 = 0b100,
+            Div1 = 0b101,
+            Div7 = 0b110
+        ],
+        TXTL OFFSET(10) NUMBITS(6) []
+    ],
+
+    /// Status Register 1
+    USR1 [
+        AGTIM OFFSET(8) NUMBITS(1) [],
+        RRDY OFFSET(9) NUMBITS(1) [],
+        FRAMERR OFFSET(10) NUMBITS(1) [],
+        TRDY OFFSET(13) NUMBITS(1) [],
+        PARITYERR OFFSET(15) NUMBITS(1) []
+    ],
+
+    /// Status Register 2
+    USR2 [
+        RDR OFFSET(0) NUMBITS(1) [],
+        ORE OFFSET(1) NUMBITS(1) [],
+        BRCD OFFSET(2) NUMBITS(1) [],
+        TXDC OFFSET(3) NUMBITS(1) [],
+        TXFE OFFSET(14) NUMBITS(1) []
+    ],
+
+    /// Test Register
+    UTS [
+        TXFULL OFFSET(4) NUMBITS(1) [],
+        RXEMPTY OFFSET(5) NUMBITS(1) [],
+        TXEMPTY OFFSET(6) NUMBITS(1) [],
+        LOOP OFFSET(12) NUMBITS(1) []
+    ]
+];
+
+register_structs! {
+    pub ImxUartRegisters {
+        (0x00 => urxd: ReadOnly<u32, URXD::Register>),        // 接收寄存器
+        (0x04 => _reserved0),
+        (0x40 => utxd: WriteOnly<u32, UTXD::Register>),       // 发送寄存器
+        (0x44 => _reserved1),
+        (0x80 => ucr1: ReadWrite<u32, UCR1::Register>),       // 控制寄存器 1
+        (0x84 => ucr2: ReadWrite<u32, UCR2::Register>),       // 控制寄存器 2
+        (0x88 => ucr3: ReadWrite<u32, UCR3::Register>),       // 控制寄存器 3
+        (0x8c => ucr4: ReadWrite<u32>),                       // 控制寄存器 4
+        (0x90 => ufcr: ReadWrite<u32, UFCR::Register>),       // FIFO 控制寄存器
+        (0x94 => usr1: ReadWrite<u32, USR1::Register>),       // 状态寄存器 1（写 1 清除）
+        (0x98 => usr2: ReadWrite<u32, USR2::Register>),       // 状态寄存器 2（写 1 清除）
+        (0x9c => uesc: ReadWrite<u32>),                       // 转义字符寄存器
+        (0xa0 => utim: ReadWrite<u32>),                       // 转义定时器寄存器
+        (0xa4 => ubir: ReadWrite<u32>),                       // BRM 增量寄存器
+        (0xa8 => ubmr: ReadWrite<u32>),                       // BRM 模数寄存器
+        (0xac => ubrc: ReadOnly<u32>),                        // 波特率计数寄存器
+        (0xb0 => onems: ReadWrite<u32>),                      // 1ms 寄存器
+        (0xb4 => uts: ReadWrite<u32, UTS::Register>),         // 测试寄存器
+        (0xb8 => @END),
+    }
+}
+
+// SAFETY: 寄存器访问均为单次 32 位读写
+unsafe impl Sync for ImxUartRegisters {}
+
+/// 收发 FIFO 深度
+const FIFO_DEPTH: u16 = 32;
+
+/// 等待发送完成的最大轮询次数
+const BUSY_SPIN_LIMIT: usize = 1_000_000;
+
+/// UBIR/UBMR 的最大值（加 1 后）
+const BRM_MAX: u64 = 0x1_0000;
+
+/// 发送 FIFO 低于该级别时 TRDY 置位
+const TX_TRIGGER: u32 = 2;
+
+/// UCR2 中与帧格式相关的位
+const UCR2_FORMAT: u32 =
+    UCR2::WS::SET.value | UCR2::STPB::SET.value | UCR2::PROE::SET.value | UCR2::PREN::SET.value;
+
+/// i.MX UART 驱动结构体
+pub struct ImxUart {
+    base: Reg,
+    clock_freq: u32,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    tx_pause: TxPause,
+    tx: Option<ImxUartSender>,
+    rx: Option<ImxUartReciever>,
+    irq: Option<ImxUartIrqHandler>,
+    /// 通过 `try_new` 系列创建时持有的区域占用，与各句柄共享
+    claim: Option<Arc<Claim>>,
+}
+
+impl ImxUart {
+    /// 不占用寄存器区域，需要防止同一设备被重复创建时使用 [`Self::try_new`]
+    pub fn new(base: NonNull<u8>, clock_freq: u32) -> Self {
+        Self::with_claim(base, clock_freq, None)
+    }
+
+    pub fn new_boxed(base: NonNull<u8>, clock_freq: u32) -> BSerial {
+        SerialDyn::new_boxed(Self::new(base, clock_freq))
+    }
+
+    /// 占用寄存器区域后创建实例，区域与已有实例重叠时返回错误
+    pub fn try_new(base: NonNull<u8>, clock_freq: u32) -> Result<Self, ClaimError> {
+        let claim = claim::claim(Region::mmio(base, size_of::<ImxUartRegisters>()))?;
+        Ok(Self::with_claim(base, clock_freq, Some(claim)))
+    }
+
+    pub fn try_new_boxed(base: NonNull<u8>, clock_freq: u32) -> Result<BSerial, ClaimError> {
+        Ok(SerialDyn::new_boxed(Self::try_new(base, clock_freq)?))
+    }
+
+    /// 通过 `try_new` 系列创建时持有的寄存器区域占用
+    pub fn claim(&self) -> Option<&Claim> {
+        self.claim.as_deref()
+    }
+
+    fn with_claim(base: NonNull<u8>, clock_freq: u32, claim: Option<Arc<Claim>>) -> Self {
+        let base = Reg(base.cast());
+        let tx_pause = TxPause::default();
+
+        Self {
+            base,
+            clock_freq,
+            tx_pause: tx_pause.clone(),
+            tx: Some(ImxUartSender {
+                base,
+                stats: None,
+                tx_pause: tx_pause.clone(),
+                _claim: claim.clone(),
+            }),
+            rx: Some(ImxUartReciever {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            irq: Some(ImxUartIrqHandler {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            claim,
+        }
+    }
+
+    fn registers(&self) -> &ImxUartRegisters {
+        self.base.registers()
+    }
+
+    /// 挂接统计计数器，只对尚未取出的收发和中断句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+        if let Some(irq) = self.irq.as_mut() {
+            irq.stats = Some(stats);
+        }
+    }
+
+    /// 当前 RFDIV 分频后的参考时钟
+    fn ref_clk(&self) -> u32 {
+        let div = match self.registers().ufcr.read_as_enum(UFCR::RFDIV) {
+            Some(UFCR::RFDIV::Value::Div1) => 1,
+            Some(UFCR::RFDIV::Value::Div2) => 2,
+            Some(UFCR::RFDIV::Value::Div3) => 3,
+            Some(UFCR::RFDIV::Value::Div4) => 4,
+            Some(UFCR::RFDIV::Value::Div5) => 5,
+            Some(UFCR::RFDIV::Value::Div6) => 6,
+            Some(UFCR::RFDIV::Value::Div7) | None => 7,
+        };
+        self.clock_freq / div
+    }
+
+    /// 搜索误差最小的 (UBIR, UBMR)：(UBIR + 1) / (UBMR + 1) = 16 * baud / ref_clk
+    ///
+    /// 参考时钟固定为不分频（RFDIV = 1）。对每个模数取最接近的增量，
+    /// 误差相同时优先选择较小的模数，找到精确值即停止。
+    fn divisors_for(&self, baudrate: u32) -> Result<(u32, u32), ConfigError> {
+        if baudrate == 0 || self.clock_freq == 0 {
+            return Err(ConfigError::InvalidBaudrate);
+        }
+
+        let clk = self.clock_freq as u64;
+        let target = 16 * baudrate as u64;
+        if target > clk {
+            return Err(ConfigError::InvalidBaudrate);
+        }
+
+        // error 为 |clk * num - target * den|，实际误差为 error / (16 * den)
+        let mut best: Option<(u64, u64, u64)> = None;
+        for den in 1..=BRM_MAX {
+            let num = (target * den + clk / 2) / clk;
+            if num == 0 {
+                continue;
+            }
+
+            let error = (clk * num).abs_diff(target * den);
+            let better = best.is_none_or(|(_, d, e)| {
+                (error as u128) * (d as u128) < (e as u128) * (den as u128)
+            });
+            if better {
+                best = Some((num, den, error));
+            }
+            if error == 0 {
+                break;
+            }
+        }
+
+        match best {
+            Some((num, den, error)) if baud_error_ok(error, target * den) => {
+                Ok((num as u32 - 1, den as u32 - 1))
+            }
+            _ => Err(ConfigError::InvalidBaudrate),
+        }
+    }
+
+    /// 在当前 UCR2 的基础上计算应用配置后的值，未设置的项保持不变
+    fn format_for(&self, config: &Config) -> u32 {
+        let mut ucr2 = self.registers().ucr2.extract();
+
+        if let Some(bits) = config.data_bits {
+            ucr2.modify(UCR2::WS.val((bits == DataBits::Eight) as u32));
+        }
+
+        if let Some(stop_bits) = config.stop_bits {
+            ucr2.modify(UCR2::STPB.val((stop_bits == StopBits::Two) as u32));
+        }
+
+        if let Some(parity) = config.parity {
+            let field = match parity {
+                Parity::Even => UCR2::PREN::SET + UCR2::PROE::CLEAR,
+                Parity::Odd => UCR2::PREN::SET + UCR2::PROE::SET,
+                _ => UCR2::PREN::CLEAR + UCR2::PROE::CLEAR,
+            };
+            ucr2.modify(field);
+        }
+
+        ucr2.get() & UCR2_FORMAT
+    }
+
+    /// 等待发送 FIFO 和移位寄存器都为空
+    fn wait_tx_idle(&self) -> bool {
+        for _ in 0..BUSY_SPIN_LIMIT {
+            if self.registers().usr2.is_set(USR2::TXDC) {
+                return true;
+            }
+            core::hint::spin_loop();
+        }
+        false
+    }
+
+    /// 初始化 i.MX UART
+    fn init(&self) {
+        let regs = self.registers();
+
+        // 关闭中断，保留 UARTEN
+        regs.ucr1.write(UCR1::UARTEN::SET);
+
+        // i.MX6 之后必须设置 RXDMUXSEL
+        regs.ucr3.modify(UCR3::RXDMUXSEL::SET);
+
+        // 参考时钟不分频，接收 FIFO 有 1 个字节即触发
+        regs.ufcr
+            .write(UFCR::RFDIV::Div1 + UFCR::RXTL.val(1) + UFCR::TXTL.val(TX_TRIGGER));
+
+        // 清除残留状态
+        regs.usr1.set(regs.usr1.get());
+        regs.usr2.set(regs.usr2.get());
+
+        // 保留帧格式，开启收发和接收老化定时器，忽略 RTS
+        let format = regs.ucr2.get() & UCR2_FORMAT;
+        regs.ucr2.set(
+            format
+                | (UCR2::SRST::SET
+                    + UCR2::RXEN::SET
+                    + UCR2::TXEN::SET
+                    + UCR2::ATEN::SET
+                    + UCR2::IRTS::SET)
+                    .value,
+        );
+    }
+}
+
+impl InterfaceRaw for ImxUart {
+    type IrqHandler = ImxUartIrqHandler;
+
+    type Sender = crate::Sender;
+
+    type Reciever = crate::Reciever;
+
+    fn base_addr(&self) -> usize {
+        self.base.0.as_ptr() as usize
+    }
+
+    fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        self.capabilities().validate(config)?;
+        let divisors = config
+            .baudrate
+            .map(|baudrate| self.divisors_for(baudrate))
+            .transpose()?;
+        let format = self.format_for(config);
+
+        if !self.wait_tx_idle() {
+            return Err(ConfigError::Timeout);
+        }
+
+        let regs = self.registers();
+
+        // 修改波特率和帧格式前关闭收发，SRST 保持为 1 避免复位
+        let original_ucr2 = regs.ucr2.get() | UCR2::SRST::SET.value;
+        let enable = UCR2::RXEN::SET.value | UCR2::TXEN::SET.value;
+        regs.ucr2.set(original_ucr2 & !enable);
+
+        // 除数按不分频的参考时钟计算，UBIR 必须先于 UBMR 写入
+        if let Some((ubir, ubmr)) = divisors {
+            regs.ufcr.modify(UFCR::RFDIV::Div1);
+            regs.ubir.set(ubir);
+            regs.ubmr.set(ubmr);
+        }
+
+        // 写入帧格式并恢复原来的收发使能状态
+        regs.ucr2.set((original_ucr2 & !UCR2_FORMAT) | format);
+
+        Ok(())
+    }
+
+    fn baudrate(&self) -> u32 {
+        let regs = self.registers();
+        let ubir = regs.ubir.get() as u64 & 0xFFFF;
+        let ubmr = regs.ubmr.get() as u64 & 0xFFFF;
+
+        (self.ref_clk() as u64 * (ubir + 1) / (16 * (ubmr + 1))) as u32
+    }
+
+    fn data_bits(&self) -> DataBits {
+        if self.registers().ucr2.is_set(UCR2::WS) {
+            DataBits::Eight
+        } else {
+            DataBits::Seven
+        }
+    }
+
+    fn stop_bits(&self) -> StopBits {
+        if self.registers().ucr2.is_set(UCR2::STPB) {
+            StopBits::Two
+        } else {
+            StopBits::One
+        }
+    }
+
+    fn parity(&self) -> Parity {
+        let ucr2 = self.registers().ucr2.extract();
+        if !ucr2.is_set(UCR2::PREN) {
+            Parity::None
+        } else if ucr2.is_set(UCR2::PROE) {
+            Parity::Odd
+        } else {
+            Parity::Even
+        }
+    }
+
+    fn clock_freq(&self) -> Option<NonZeroU32> {
+        self.clock_freq.try_into().ok()
+    }
+
+    fn open(&mut self) {
+        self.init()
+    }
+
+    fn close(&mut self) {
+        let regs = self.registers();
+        regs.ucr1.set(0);
+        regs.ucr2
+            .modify(UCR2::RXEN::CLEAR + UCR2::TXEN::CLEAR + UCR2::ATEN::CLEAR);
+    }
+
+    fn enable_loopback(&mut self) {
+        self.registers().uts.modify(UTS::LOOP::SET);
+    }
+
+    fn disable_loopback(&mut self) {
+        self.registers().uts.modify(UTS::LOOP::CLEAR);
+    }
+
+    fn is_loopback_enabled(&self) -> bool {
+        self.registers().uts.is_set(UTS::LOOP)
+    }
+
+    fn set_irq_mask(&mut self, mask: InterruptMask) {
+        let rx = mask.contains(InterruptMask::RX_AVAILABLE) as u32;
+        let tx = mask.contains(InterruptMask::TX_EMPTY) as u32;
+
+        self.registers()
+            .ucr1
+            .modify(UCR1::RRDYEN.val(rx) + UCR1::TRDYEN.val(tx));
+    }
+
+    fn get_irq_mask(&self) -> InterruptMask {
+        let ucr1 = self.registers().ucr1.extract();
+        let mut mask = InterruptMask::empty();
+
+        if ucr1.is_set(UCR1::RRDYEN) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if ucr1.is_set(UCR1::TRDYEN) {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        mask
+    }
+
+    fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+        self.irq.take()
+    }
+
+    fn take_tx(&mut self) -> Option<Self::Sender> {
+        self.tx.take().map(crate::Sender::ImxUartSender)
+    }
+
+    fn take_rx(&mut self) -> Option<Self::Reciever> {
+        self.rx.take().map(crate::Reciever::ImxUartReciever)
+    }
+
+    fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+        let tx = match tx {
+            crate::Sender::ImxUartSender(s) => s,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    tx.external_base().unwrap_or(0), // 不匹配的发送器类型
+                ));
+            }
+        };
+
+        if self.base != tx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                tx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.tx = Some(tx);
+        Ok(())
+    }
+
+    fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+        let rx = match rx {
+            crate::Reciever::ImxUartReciever(r) => r,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    rx.external_base().unwrap_or(0), // 不匹配的接收器类型
+                ));
+            }
+        };
+
+        if self.base != rx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                rx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.rx = Some(rx);
+        Ok(())
+    }
+}
+
+impl Caps for ImxUart {
+    fn capabilities(&self) -> Capabilities {
+        let (min_baudrate, max_baudrate) =
+            Capabilities::baudrate_range(self.clock_freq, 16, BRM_MAX as u32);
+
+        Capabilities {
+            fifo_depth: FIFO_DEPTH,
+            min_baudrate,
+            max_baudrate,
+            data_bits: &[DataBits::Seven, DataBits::Eight],
+            stop_bits: ALL_STOP_BITS,
+            parity: &[Parity::None, Parity::Even, Parity::Odd],
+            features: Features::LOOPBACK,
+        }
+    }
+}
+
+/// RXTL 为接收触发字节数；TXTL 的最小值为 2，发送 FIFO 少于 TXTL 个字节时触发，
+/// 即剩余字节数不超过 TXTL - 1
+impl FifoThreshold for ImxUart {
+    fn fifo_triggers(&self) -> FifoTriggers {
+        let ufcr = self.registers().ufcr.extract();
+        FifoTriggers {
+            rx: Some(ufcr.read(UFCR::RXTL) as u16),
+            tx: Some((ufcr.read(UFCR::TXTL) as u16).saturating_sub(1)),
+            rx_timeout: None,
+        }
+    }
+
+    fn set_fifo_thresholds(&mut self, req: &FifoThresholds) -> Result<FifoTriggers, FifoError> {
+        let req = req.resolve(FIFO_DEPTH, &self.fifo_triggers())?;
+        let regs = self.registers();
+
+        if let Some(rx) = req.rx {
+            regs.ufcr
+                .modify(UFCR::RXTL.val(rx.clamp(1, FIFO_DEPTH) as u32));
+        }
+        if let Some(tx) = req.tx {
+            let txtl = (tx + 1).clamp(2, FIFO_DEPTH);
+            regs.ufcr.modify(UFCR::TXTL.val(txtl as u32));
+        }
+
+        Ok(self.fifo_triggers())
+    }
+}
+
+/// 发送 FIFO 只能通过 UCR2.SRST 软复位清空，复位同时清空接收 FIFO，
+/// 并把 UBIR/UBMR/UTS 恢复为默认值，因此先保存再写回
+impl Flush for ImxUart {
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError> {
+        let regs = self.registers();
+
+        if queue.tx() {
+            let ubir = regs.ubir.get();
+            let ubmr = regs.ubmr.get();
+            let uts = regs.uts.get();
+
+            // SRST 低有效，复位完成后由硬件置 1
+            regs.ucr2.modify(UCR2::SRST::CLEAR);
+            for _ in 0..BUSY_SPIN_LIMIT {
+                if regs.ucr2.is_set(UCR2::SRST) {
+                    break;
+                }
+                core::hint::spin_loop();
+            }
+
+            regs.ubir.set(ubir);
+            regs.ubmr.set(ubmr);
+            regs.uts.set(uts);
+        }
+
+        if queue.rx() {
+            for _ in 0..FIFO_DEPTH {
+                if !regs.usr2.is_set(USR2::RDR) {
+                    break;
+                }
+                regs.urxd.get();
+            }
+            regs.usr1.set(regs.usr1.get());
+            regs.usr2.set(regs.usr2.get());
+        }
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), FlushError> {
+        if self.wait_tx_idle() {
+            Ok(())
+        } else {
+            Err(FlushError::Timeout)
+        }
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// i.MX UART 挂起时保存的寄存器上下文
+#[derive(Debug, Clone, Copy)]
+pub struct ImxUartContext {
+    ucr1: u32,
+    ucr2: u32,
+    ucr3: u32,
+    ucr4: u32,
+    ufcr: u32,
+    uesc: u32,
+    utim: u32,
+    ubir: u32,
+    ubmr: u32,
+    onems: u32,
+    uts: u32,
+}
+
+impl Suspend for ImxUart {
+    type Context = ImxUartContext;
+
+    fn save_context(&self) -> Self::Context {
+        let regs = self.registers();
+        ImxUartContext {
+            ucr1: regs.ucr1.get(),
+            ucr2: regs.ucr2.get(),
+            ucr3: regs.ucr3.get(),
+            ucr4: regs.ucr4.get(),
+            ufcr: regs.ufcr.get(),
+            uesc: regs.uesc.get(),
+            utim: regs.utim.get(),
+            ubir: regs.ubir.get(),
+            ubmr: regs.ubmr.get(),
+            onems: regs.onems.get(),
+            uts: regs.uts.get(),
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        let regs = self.registers();
+
+        // 恢复期间保持中断关闭
+        regs.ucr1.set(ctx.ucr1 & UCR1::UARTEN::SET.value);
+
+        regs.ufcr.set(ctx.ufcr);
+        regs.uesc.set(ctx.uesc);
+        regs.utim.set(ctx.utim);
+        regs.ubir.set(ctx.ubir);
+        regs.ubmr.set(ctx.ubmr);
+        regs.onems.set(ctx.onems);
+        regs.uts.set(ctx.uts);
+        regs.ucr3.set(ctx.ucr3);
+        regs.ucr4.set(ctx.ucr4);
+        regs.ucr2.set(ctx.ucr2 | UCR2::SRST::SET.value);
+
+        // 清除上电过程中残留的状态，再恢复中断使能
+        regs.usr1.set(regs.usr1.get());
+        regs.usr2.set(regs.usr2.get());
+        regs.ucr1.set(ctx.ucr1);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Reg(NonNull<ImxUartRegisters>);
+
+unsafe impl Send for Reg {}
+
+impl Reg {
+    fn registers(&self) -> &ImxUartRegisters {
+        unsafe { self.0.as_ref() }
+    }
+}
+
+pub struct ImxUartSender {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    tx_pause: TxPause,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for ImxUartSender {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl TSender for ImxUartSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        RawSender::write_byte(self, byte)
+    }
+}
+
+impl RawSender for ImxUartSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return false;
+        };
+        if self.base.registers().uts.is_set(UTS::TXFULL) {
+            return false;
+        }
+
+        self.base.registers().utxd.write(UTXD::DATA.val(byte as _));
+        if let Some(stats) = self.stats {
+            stats.record_tx(1);
+        }
+
+        true
+    }
+}
+
+pub struct ImxUartReciever {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for ImxUartReciever {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl ImxUartReciever {
+    fn read_raw(&mut self) -> Option<Result<u8, TransferError>> {
+        let regs = self.base.registers();
+        if !regs.usr2.is_set(USR2::RDR) {
+            return None;
+        }
+
+        // URXD 同时给出数据和该字节的错误状态
+        let urxd = regs.urxd.extract();
+        let data = urxd.read(URXD::DATA) as u8;
+
+        if !urxd.is_set(URXD::ERR) {
+            return Some(Ok(data));
+        }
+
+        let result = if urxd.is_set(URXD::BRK) {
+            Err(TransferError::Break)
+        } else if urxd.is_set(URXD::PRERR) {
+            Err(TransferError::Parity)
+        } else if urxd.is_set(URXD::FRMERR) {
+            Err(TransferError::Framing)
+        } else {
+            // 溢出标志需要在 USR2 中写 1 清除
+            regs.usr2.write(USR2::ORE::SET);
+            Err(TransferError::Overrun(data))
+        };
+
+        Some(result)
+    }
+}
+
+impl RawReciever for ImxUartReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        let result = self.read_raw()?;
+        if let Some(stats) = self.stats {
+            stats.record_rx(&result);
+        }
+        Some(result)
+    }
+}
+
+pub struct ImxUartIrqHandler {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+unsafe impl Sync for ImxUartIrqHandler {}
+
+impl TIrqHandler for ImxUartIrqHandler {
+    fn clean_interrupt_status(&self) -> InterruptMask {
+        let regs = self.base.registers();
+        let ucr1 = regs.ucr1.extract();
+        let usr1 = regs.usr1.extract();
+        let mut mask = InterruptMask::empty();
+
+        // 老化定时器超时表示 FIFO 中还有未达到触发级别的数据
+        let rx = usr1.is_set(USR1::RRDY) || usr1.is_set(USR1::AGTIM);
+        let tx = usr1.is_set(USR1::TRDY);
+
+        if rx && ucr1.is_set(UCR1::RRDYEN) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if tx && ucr1.is_set(UCR1::TRDYEN) {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        if let Some(stats) = self.stats {
+            if usr1.is_set(USR1::RRDY) {
+                stats.record_irq(IrqCause::Rx);
+            }
+            if usr1.is_set(USR1::AGTIM) {
+                stats.record_irq(IrqCause::RxTimeout);
+            }
+            if mask.contains(InterruptMask::TX_EMPTY) {
+                stats.record_irq(IrqCause::Tx);
+            }
+            if usr1.is_set(USR1::FRAMERR) || usr1.is_set(USR1::PARITYERR) {
+                stats.record_irq(IrqCause::LineStatus);
+            }
+        }
+
+        // RRDY/TRDY 随 FIFO 状态自动清除，只需清除 AGTIM 和错误标志
+        regs.usr1
+            .write(USR1::AGTIM::SET + USR1::FRAMERR::SET + USR1::PARITYERR::SET);
+
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fake_mmio::FakeMmio, FifoLevel};
+
+    fn uart(regs: &FakeMmio<46>, clock_freq: u32) -> ImxUart {
+        // 发送完成，配置时无需等待
+        regs.set(0x98, USR2::TXDC::SET.value);
+        ImxUart::new(regs.base(), clock_freq)
+    }
+
+    #[test]
+    fn brm_search_finds_exact_ratio() {
+        let regs = FakeMmio::<46>::new();
+        let mut uart = uart(&regs, 80_000_000);
+
+        // 16 * 115200 / 80 MHz = 72 / 3125
+        uart.set_config(&Config::new().baudrate(115200)).unwrap();
+        assert_eq!((regs.get(0xa4), regs.get(0xa8)), (71, 3124));
+        assert_eq!(uart.baudrate(), 115200);
+    }
+
+    #[test]
+    fn brm_search_keeps_precision_for_large_modulus() {
+        // 约分后模数超出范围，逐次减半会得到 4000010
+        let regs = FakeMmio::<46>::new();
+        let mut uart = uart(&regs, 66_666_667);
+        uart.set_config(&Config::new().baudrate(4_000_000)).unwrap();
+        assert_eq!((regs.get(0xa4), regs.get(0xa8)), (23, 24));
+        assert_eq!(uart.baudrate(), 4_000_000);
+    }
+
+    #[test]
+    fn set_config_restores_rx_tx_enable() {
+        let regs = FakeMmio::<46>::new();
+        let mut uart = uart(&regs, 80_000_000);
+        let enable = UCR2::RXEN::SET.value | UCR2::TXEN::SET.value;
+        regs.set(0x84, enable | UCR2::WS::SET.value);
+
+        uart.set_config(&Config::new().parity(Parity::Odd)).unwrap();
+        assert_eq!(
+            regs.get(0x84),
+            UCR2_FORMAT & !UCR2::STPB::SET.value | enable | UCR2::SRST::SET.value
+        );
+        assert_eq!(uart.parity(), Parity::Odd);
+    }
+
+    #[test]
+    fn overrun_is_reported_and_cleared() {
+        let regs = FakeMmio::<46>::new();
+        let uart = uart(&regs, 80_000_000);
+        let mut rx = uart.rx.unwrap();
+
+        regs.set(0x98, USR2::RDR::SET.value);
+        regs.set(
+            0x00,
+            (URXD::CHARRDY::SET + URXD::ERR::SET + URXD::OVRRUN::SET + URXD::DATA.val(7)).value,
+        );
+        assert_eq!(
+            RawReciever::read_byte(&mut rx),
+            Some(Err(TransferError::Overrun(7)))
+        );
+        assert_eq!(regs.get(0x98), USR2::ORE::SET.value);
+    }
+
+    #[test]
+    fn aging_irq_reports_rx_and_is_acked() {
+        let regs = FakeMmio::<46>::new();
+        let mut uart = uart(&regs, 80_000_000);
+        let irq = uart.irq_handler().unwrap();
+
+        uart.set_irq_mask(InterruptMask::RX_AVAILABLE);
+        regs.set(0x94, USR1::AGTIM::SET.value | USR1::TRDY::SET.value);
+        assert_eq!(
+            irq.clean_interrupt_status().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+        assert_eq!(
+            regs.get(0x94),
+            (USR1::AGTIM::SET + USR1::FRAMERR::SET + USR1::PARITYERR::SET).value
+        );
+    }
+
+    #[test]
+    fn tx_trigger_has_minimum_of_two() {
+        let regs = FakeMmio::<46>::new();
+        let mut uart = uart(&regs, 80_000_000);
+
+        let req = FifoThresholds::new()
+            .rx(FifoLevel::Bytes(8))
+            .tx(FifoLevel::Bytes(0));
+        let triggers = uart.set_fifo_thresholds(&req).unwrap();
+        assert_eq!(
+            regs.get(0x90),
+            (UFCR::RXTL.val(8) + UFCR::TXTL.val(2)).value
+        );
+        assert_eq!((triggers.rx, triggers.tx), (Some(8), Some(1)));
+    }
+}
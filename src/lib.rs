@@ -5,6 +5,16 @@
 //! 本库提供统一的串口驱动接口，支持多种硬件平台：
 //! - ARM PL011 UART
 //! - NS16550/16450 UART（IO Port 和 MMIO 版本）
+//! - SiFive UART
+//! - Xilinx AXI UART Lite 和 Cadence UART（Zynq/ZynqMP）
+//! - NXP i.MX UART 和 LPUART
+//! - Broadcom BCM2835 mini-UART
+//! - Samsung Exynos UART 及 Apple S5L UART
+//! - Amlogic Meson UART
+//! - Renesas SCIF
+//! - virtio-console（virtio-mmio 传输）
+//! - RISC-V SBI 控制台
+//! - ARM semihosting 和 AArch64 DCC 调试控制台
 //!
 //! ## 特性
 //!
@@ -25,6 +35,48 @@
 //! - 支持 IO Port（x86_64）和 MMIO（通用）两种访问方式
 //! - 支持 16 字节 FIFO 缓冲
 //!
+//! ### SiFive UART
+//! - RISC-V HiFive 系列及 QEMU sifive 机器
+//! - 固定 8 位数据、无校验，8 字节 FIFO
+//!
+//! ### Xilinx UART
+//! - AXI UART Lite：格式在综合时固定，16 字节 FIFO
+//! - Cadence UART（`xlnx,xuartps`）：CD/BDIV 两级分频，64 字节 FIFO，接收超时中断
+//!
+//! ### NXP UART
+//! - i.MX UART（`fsl,imx-uart`）：UBIR/UBMR 分数分频，32 字节 FIFO
+//! - LPUART（`fsl,imx7ulp-lpuart`）：OSR/SBR 组合搜索，FIFO 深度由硬件参数决定
+//!
+//! ### BCM2835 mini-UART
+//! - 树莓派 AUX 外设中的精简 16550，8 字节 FIFO，无校验
+//! - 波特率跟随 VPU 核心时钟，可选管理 AUX 使能位
+//!
+//! ### Samsung UART
+//! - S5PV210/Exynos（`samsung,exynos4210-uart`）：FIFO 深度按端口不同，UINTP/UINTM 管理中断
+//! - Apple S5L（`apple,s5l-uart`）：16 字节 FIFO，中断使能和状态位于 UCON/UTRSTAT
+//! - 整数加 1/16 小数分频
+//!
+//! ### Amlogic Meson UART
+//! - S905、A311D 等 SoC 的 AO/EE UART，64 字节 FIFO
+//! - 支持旧版 CONTROL 分频和 REG5 新分频（外设时钟或晶振）
+//!
+//! ### Renesas SCIF
+//! - R-Car 等 SoC，16 字节 FIFO，可配置收发触发级别
+//! - 在 CKS 预分频和 SCBRR 中搜索波特率，RTS/CTS 通过 SCSPTR 控制
+//!
+//! ### virtio-console
+//! - QEMU、Firecracker 虚拟机控制台，支持 legacy 和 modern virtio-mmio
+//! - 收发队列通过 `dma-api` 分配，寄存器访问可替换为模拟设备
+//!
+//! ### RISC-V SBI 控制台
+//! - 启动早期通过 OpenSBI 等固件输出，优先使用 DBCN 扩展，否则回退到 legacy putchar/getchar
+//! - ecall 可替换为模拟固件
+//!
+//! ### ARM 调试控制台
+//! - semihosting：`SYS_WRITEC`/`SYS_WRITE0`/`SYS_READC`，适用于 FVP、QEMU 和 JTAG 调试
+//! - DCC：通过 MDCCSR_EL0/DBGDTRTX_EL0/DBGDTRRX_EL0 与调试器交换字符
+//! - 陷入指令和系统寄存器访问可替换为模拟实现
+//!
 //! ## 快速开始
 //!
 //! ```rust
@@ -56,9 +108,50 @@
 //! uart.open().unwrap();
 //! ```
 
+extern crate alloc;
+
+use alloc::boxed::Box;
+
 // 导入核心模块
+pub mod bcm2835aux;
+pub mod cadence;
+pub mod caps;
+pub mod claim;
+pub mod cmdline;
+pub mod dcc;
+#[cfg(test)]
+mod fake_mmio;
+pub mod fifo;
+pub mod flush;
+pub mod imx;
+pub mod lpuart;
+pub mod meson;
 pub mod ns16550;
 pub mod pl011;
+pub mod pm;
+pub mod reconfig;
+pub mod samsung;
+pub mod sbi;
+pub mod scif;
+pub mod semihosting;
+pub mod sifive;
+pub mod stats;
+pub mod typestate;
+pub mod uartlite;
+pub mod virtio;
+
+pub use caps::{Capabilities, Caps, Features};
+pub use claim::{Claim, ClaimError, Region};
+pub use fifo::{
+    FifoError, FifoLevel, FifoThreshold, FifoThresholds, FifoTriggers, WithFifoThresholds,
+};
+pub use flush::{Flush, FlushError, FlushQueue};
+pub use pm::Suspend;
+pub use reconfig::{ConfigWhen, ReconfigError, Reconfigure, TxPause};
+pub use stats::{Stats, StatsSnapshot};
+pub use typestate::{StateError, Uart};
+
+use stats::AttachStats;
 
 use enum_dispatch::enum_dispatch;
 // 重新导出 rdif-serial 的所有类型
@@ -70,6 +163,21 @@ pub enum Sender {
     Ns16550Sender(ns16550::Ns16550Sender<ns16550::Port>),
     Ns16550MmioSender(ns16550::Ns16550Sender<ns16550::Mmio>),
     Pl011Sender(pl011::Pl011Sender),
+    SifiveSender(sifive::SifiveSender),
+    UartLiteSender(uartlite::UartLiteSender),
+    CadenceSender(cadence::CadenceSender),
+    ImxUartSender(imx::ImxUartSender),
+    LpuartSender(lpuart::LpuartSender),
+    MiniUartSender(bcm2835aux::MiniUartSender),
+    SamsungUartSender(samsung::SamsungUartSender),
+    MesonUartSender(meson::MesonUartSender),
+    ScifSender(scif::ScifSender),
+    VirtioConsoleSender(virtio::VirtioConsoleSender),
+    SbiConsoleSender(sbi::SbiConsoleSender),
+    SemihostingSender(semihosting::SemihostingSender),
+    DccSender(dcc::DccSender),
+    /// 树外驱动的发送端
+    External(Box<dyn ExternalSender>),
 }
 
 #[enum_dispatch(Sender)]
@@ -87,6 +195,53 @@ trait RawSender {
     }
 }
 
+/// 树外驱动的发送端
+///
+/// 外部驱动的 `InterfaceRaw::Sender` 同样使用 [`Sender`]，`take_tx` 返回
+/// [`Sender::External`]，`set_tx` 通过 [`Sender::into_external`] 取回并校验基地址。
+/// 内置驱动仍走枚举分发，不经过虚调用。
+pub trait ExternalSender: TSender {
+    /// 所属设备的基地址，供 `set_tx` 校验
+    fn base_addr(&self) -> usize;
+}
+
+impl RawSender for Box<dyn ExternalSender> {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        TSender::write_byte(&mut **self, byte)
+    }
+
+    fn write_bytes(&mut self, buffer: &[u8]) -> usize {
+        TSender::write_bytes(&mut **self, buffer)
+    }
+}
+
+/// 外部驱动自行统计，挂接时忽略
+impl AttachStats for Box<dyn ExternalSender> {
+    fn attach_stats(&mut self, _stats: &'static Stats) {}
+}
+
+impl Sender {
+    pub fn external(sender: impl ExternalSender) -> Self {
+        Self::External(Box::new(sender))
+    }
+
+    /// 外部驱动发送端所属设备的基地址，内置驱动返回 `None`
+    pub fn external_base(&self) -> Option<usize> {
+        match self {
+            Self::External(sender) => Some(sender.base_addr()),
+            _ => None,
+        }
+    }
+
+    /// 取出外部驱动的发送端，其他变体原样返回
+    pub fn into_external(self) -> Result<Box<dyn ExternalSender>, Self> {
+        match self {
+            Self::External(sender) => Ok(sender),
+            other => Err(other),
+        }
+    }
+}
+
 impl TSender for Sender {
     fn write_byte(&mut self, byte: u8) -> bool {
         RawSender::write_byte(self, byte)
@@ -103,6 +258,64 @@ pub enum Reciever {
     Ns16550Reciever(ns16550::Ns16550Reciever<ns16550::Port>),
     Ns16550MmioReciever(ns16550::Ns16550Reciever<ns16550::Mmio>),
     Pl011Reciever(pl011::Pl011Reciever),
+    SifiveReciever(sifive::SifiveReciever),
+    UartLiteReciever(uartlite::UartLiteReciever),
+    CadenceReciever(cadence::CadenceReciever),
+    ImxUartReciever(imx::ImxUartReciever),
+    LpuartReciever(lpuart::LpuartReciever),
+    MiniUartReciever(bcm2835aux::MiniUartReciever),
+    SamsungUartReciever(samsung::SamsungUartReciever),
+    MesonUartReciever(meson::MesonUartReciever),
+    ScifReciever(scif::ScifReciever),
+    VirtioConsoleReciever(virtio::VirtioConsoleReciever),
+    SbiConsoleReciever(sbi::SbiConsoleReciever),
+    SemihostingReciever(semihosting::SemihostingReciever),
+    DccReciever(dcc::DccReciever),
+    /// 树外驱动的接收端
+    External(Box<dyn ExternalReciever>),
+}
+
+/// 树外驱动的接收端，用法与 [`ExternalSender`] 相同
+pub trait ExternalReciever: TReciever {
+    /// 所属设备的基地址，供 `set_rx` 校验
+    fn base_addr(&self) -> usize;
+}
+
+impl RawReciever for Box<dyn ExternalReciever> {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        TReciever::read_byte(&mut **self)
+    }
+
+    fn read_bytes(&mut self, bytes: &mut [u8]) -> Result<usize, TransBytesError> {
+        TReciever::read_bytes(&mut **self, bytes)
+    }
+}
+
+/// 外部驱动自行统计，挂接时忽略
+impl AttachStats for Box<dyn ExternalReciever> {
+    fn attach_stats(&mut self, _stats: &'static Stats) {}
+}
+
+impl Reciever {
+    pub fn external(reciever: impl ExternalReciever) -> Self {
+        Self::External(Box::new(reciever))
+    }
+
+    /// 外部驱动接收端所属设备的基地址，内置驱动返回 `None`
+    pub fn external_base(&self) -> Option<usize> {
+        match self {
+            Self::External(reciever) => Some(reciever.base_addr()),
+            _ => None,
+        }
+    }
+
+    /// 取出外部驱动的接收端，其他变体原样返回
+    pub fn into_external(self) -> Result<Box<dyn ExternalReciever>, Self> {
+        match self {
+            Self::External(reciever) => Ok(reciever),
+            other => Err(other),
+        }
+    }
 }
 
 impl TReciever for Reciever {
@@ -140,3 +353,47 @@ trait RawReciever {
         Ok(read_count)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake_mmio::FakeMmio;
+
+    struct Ext {
+        base: usize,
+        written: usize,
+    }
+
+    impl TSender for Ext {
+        fn write_byte(&mut self, _byte: u8) -> bool {
+            self.written += 1;
+            true
+        }
+    }
+
+    impl ExternalSender for Ext {
+        fn base_addr(&self) -> usize {
+            self.base
+        }
+    }
+
+    #[test]
+    fn external_sender_dispatches_and_reports_base() {
+        let mut tx = Sender::external(Ext {
+            base: 0x1234_0000,
+            written: 0,
+        });
+        assert_eq!(TSender::write_bytes(&mut tx, b"abc"), 3);
+        assert_eq!(tx.external_base(), Some(0x1234_0000));
+
+        // 内置驱动拒绝外部发送端时报告其基地址
+        let regs = FakeMmio::<{ 0x1000 / 4 }>::new();
+        let mut uart = pl011::Pl011::new(regs.base(), 24_000_000);
+        let err = uart.set_tx(tx).err().unwrap();
+        let base = regs.base().as_ptr() as usize;
+        assert_eq!(
+            alloc::format!("{err}"),
+            alloc::format!("{}", SetBackError::new(base, 0x1234_0000))
+        );
+    }
+}
@@ -0,0 +1,941 @@
+//! NXP LPUART 驱动 (`fsl,imx7ulp-lpuart` / `fsl,imx8qxp-lpuart` / `fsl,imx93-lpuart`)
+//!
+//! 用于 i.MX7ULP、i.MX8QXP、i.MX93 等 SoC 的 32 位 LPUART。
+//! 波特率由过采样倍数 OSR 和模数 SBR 共同决定：`baud = clk / ((OSR + 1) * SBR)`，
+//! FIFO 深度由硬件参数决定，运行时从 FIFO 寄存器读取。
+
+use core::{num::NonZeroU32, ptr::NonNull};
+
+use alloc::sync::Arc;
+
+use rdif_serial::{BSerial, InterfaceRaw, SerialDyn, SetBackError, TIrqHandler, TSender};
+use tock_registers::{
+    interfaces::*, register_bitfields, register_structs, registers::*, LocalRegisterCopy,
+};
+
+use crate::{
+    caps::{baud_error_ok, ALL_STOP_BITS},
+    claim::{self, Claim, ClaimError, Region},
+    fifo::{FifoError, FifoThreshold, FifoThresholds, FifoTriggers},
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, IrqCause, Stats},
+    Capabilities, Caps, Config, ConfigError, DataBits, Features, InterruptMask, Parity,
+    RawReciever, RawSender, StopBits, Suspend, TransferError,
+};
+
+register_bitfields! [
+    u32,
+
+    /// Baud Rate Register
+    BAUD [
+        SBR OFFSET(0) NUMBITS(13) [],
+        SBNS OFFSET(13) NUMBITS(1) [],
+        BOTHEDGE OFFSET(17) NUMBITS(1) [],
+        RDMAE OFFSET(21) NUMBITS(1) [],
+        TDMAE OFFSET(23) NUMBITS(1) [],
+        OSR OFFSET(24) NUMBITS(5) []
+    ],
+
+    /// Status Register
+    STAT [
+        PF OFFSET(16) NUMBITS(1) [],
+        FE OFFSET(17) NUMBITS(1) [],
+        NF OFFSET(18) NUMBITS(1) [],
+        OR OFFSET(19) NUMBITS(1) [],
+        IDLE OFFSET(20) NUMBITS(1) [],
+        RDRF OFFSET(21) NUMBITS(1) [],
+        TC OFFSET(22) NUMBITS(1) [],
+        TDRE OFFSET(23) NUMBITS(1) [],
+        RAF OFFSET(24) NUMBITS(1) [],
+        RXEDGIF OFFSET(30) NUMBITS(1) [],
+        LBKDIF OFFSET(31) NUMBITS(1) []
+    ],
+
+    /// Control Register
+    CTRL [
+        PT OFFSET(0) NUMBITS(1) [],
+        PE OFFSET(1) NUMBITS(1) [],
+        ILT OFFSET(2) NUMBITS(1) [],
+        M OFFSET(4) NUMBITS(1) [],
+        RSRC OFFSET(5) NUMBITS(1) [],
+        LOOPS OFFSET(7) NUMBITS(1) [],
+        IDLECFG OFFSET(8) NUMBITS(3) [],
+        M7 OFFSET(11) NUMBITS(1) [],
+        RE OFFSET(18) NUMBITS(1) [],
+        TE OFFSET(19) NUMBITS(1) [],
+        ILIE OFFSET(20) NUMBITS(1) [],
+        RIE OFFSET(21) NUMBITS(1) [],
+        TCIE OFFSET(22) NUMBITS(1) [],
+        TIE OFFSET(23) NUMBITS(1) []
+    ],
+
+    /// Data Register
+    DATA [
+        DATA OFFSET(0) NUMBITS(10) [],
+        IDLINE OFFSET(11) NUMBITS(1) [],
+        RXEMPT OFFSET(12) NUMBITS(1) [],
+        FRETSC OFFSET(13) NUMBITS(1) [],
+        PARITYE OFFSET(14) NUMBITS(1) [],
+        NOISY OFFSET(15) NUMBITS(1) []
+    ],
+
+    /// FIFO Register
+    FIFO [
+        RXFIFOSIZE OFFSET(0) NUMBITS(3) [],
+        RXFE OFFSET(3) NUMBITS(1) [],
+        TXFIFOSIZE OFFSET(4) NUMBITS(3) [],
+        TXFE OFFSET(7) NUMBITS(1) [],
+        RXIDEN OFFSET(10) NUMBITS(3) [],
+        RXFLUSH OFFSET(14) NUMBITS(1) [],
+        TXFLUSH OFFSET(15) NUMBITS(1) [],
+        RXUF OFFSET(16) NUMBITS(1) [],
+        TXOF OFFSET(17) NUMBITS(1) [],
+        RXEMPT OFFSET(22) NUMBITS(1) [],
+        TXEMPT OFFSET(23) NUMBITS(1) []
+    ],
+
+    /// Watermark Register
+    WATER [
+        TXWATER OFFSET(0) NUMBITS(8) [],
+        TXCOUNT OFFSET(8) NUMBITS(8) [],
+        RXWATER OFFSET(16) NUMBITS(8) [],
+        RXCOUNT OFFSET(24) NUMBITS(8) []
+    ]
+];
+
+register_structs! {
+    pub LpuartRegisters {
+        (0x00 => verid: ReadOnly<u32>),                       // 版本寄存器
+        (0x04 => param: ReadOnly<u32>),                       // 参数寄存器
+        (0x08 => global: ReadWrite<u32>),                     // 全局寄存器
+        (0x0c => pincfg: ReadWrite<u32>),                     // 引脚配置寄存器
+        (0x10 => baud: ReadWrite<u32, BAUD::Register>),       // 波特率寄存器
+        (0x14 => stat: ReadWrite<u32, STAT::Register>),       // 状态寄存器（标志位写 1 清除）
+        (0x18 => ctrl: ReadWrite<u32, CTRL::Register>),       // 控制寄存器
+        (0x1c => data: ReadWrite<u32, DATA::Register>),       // 数据寄存器
+        (0x20 => r#match: ReadWrite<u32>),                    // 地址匹配寄存器
+        (0x24 => modir: ReadWrite<u32>),                      // 调制解调器 IrDA 寄存器
+        (0x28 => fifo: ReadWrite<u32, FIFO::Register>),       // FIFO 寄存器
+        (0x2c => water: ReadWrite<u32, WATER::Register>),     // 水位寄存器
+        (0x30 => @END),
+    }
+}
+
+// SAFETY: 寄存器访问均为单次 32 位读写
+unsafe impl Sync for LpuartRegisters {}
+
+/// 等待发送完成的最大轮询次数
+const BUSY_SPIN_LIMIT: usize = 1_000_000;
+
+/// 过采样倍数（OSR + 1）的取值范围
+const OSR_MIN: u32 = 4;
+const OSR_MAX: u32 = 32;
+
+/// SBR 的最大值
+const SBR_MAX: u32 = 0x1FFF;
+
+/// STAT 中写 1 清除的标志位
+const STAT_W1C: u32 = STAT::PF::SET.value
+    | STAT::FE::SET.value
+    | STAT::NF::SET.value
+    | STAT::OR::SET.value
+    | STAT::IDLE::SET.value
+    | STAT::RXEDGIF::SET.value
+    | STAT::LBKDIF::SET.value;
+
+/// CTRL 中与帧格式相关的位：PT、PE、M、M7
+const CTRL_FORMAT: u32 =
+    CTRL::PT::SET.value | CTRL::PE::SET.value | CTRL::M::SET.value | CTRL::M7::SET.value;
+
+/// FIFO 大小字段到深度的换算：0 表示 1 字节，其余为 2^(n+1)
+fn fifo_size(field: u32) -> u16 {
+    if field == 0 {
+        1
+    } else {
+        1 << (field + 1)
+    }
+}
+
+/// NXP LPUART 驱动结构体
+pub struct Lpuart {
+    base: Reg,
+    clock_freq: u32,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    tx_pause: TxPause,
+    tx: Option<LpuartSender>,
+    rx: Option<LpuartReciever>,
+    irq: Option<LpuartIrqHandler>,
+    /// 通过 `try_new` 系列创建时持有的区域占用，与各句柄共享
+    claim: Option<Arc<Claim>>,
+}
+
+impl Lpuart {
+    /// 不占用寄存器区域，需要防止同一设备被重复创建时使用 [`Self::try_new`]
+    pub fn new(base: NonNull<u8>, clock_freq: u32) -> Self {
+        Self::with_claim(base, clock_freq, None)
+    }
+
+    pub fn new_boxed(base: NonNull<u8>, clock_freq: u32) -> BSerial {
+        SerialDyn::new_boxed(Self::new(base, clock_freq))
+    }
+
+    /// 占用寄存器区域后创建实例，区域与已有实例重叠时返回错误
+    pub fn try_new(base: NonNull<u8>, clock_freq: u32) -> Result<Self, ClaimError> {
+        let claim = claim::claim(Region::mmio(base, size_of::<LpuartRegisters>()))?;
+        Ok(Self::with_claim(base, clock_freq, Some(claim)))
+    }
+
+    pub fn try_new_boxed(base: NonNull<u8>, clock_freq: u32) -> Result<BSerial, ClaimError> {
+        Ok(SerialDyn::new_boxed(Self::try_new(base, clock_freq)?))
+    }
+
+    /// 通过 `try_new` 系列创建时持有的寄存器区域占用
+    pub fn claim(&self) -> Option<&Claim> {
+        self.claim.as_deref()
+    }
+
+    fn with_claim(base: NonNull<u8>, clock_freq: u32, claim: Option<Arc<Claim>>) -> Self {
+        let base = Reg(base.cast());
+        let tx_pause = TxPause::default();
+
+        Self {
+            base,
+            clock_freq,
+            tx_pause: tx_pause.clone(),
+            tx: Some(LpuartSender {
+                base,
+                stats: None,
+                tx_pause: tx_pause.clone(),
+                _claim: claim.clone(),
+            }),
+            rx: Some(LpuartReciever {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            irq: Some(LpuartIrqHandler {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            claim,
+        }
+    }
+
+    fn registers(&self) -> &LpuartRegisters {
+        self.base.registers()
+    }
+
+    /// 挂接统计计数器，只对尚未取出的收发和中断句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+        if let Some(irq) = self.irq.as_mut() {
+            irq.stats = Some(stats);
+        }
+    }
+
+    /// 硬件 FIFO 深度（收发中较小的一个）
+    pub fn fifo_depth(&self) -> u16 {
+        let fifo = self.registers().fifo.extract();
+        fifo_size(fifo.read(FIFO::TXFIFOSIZE)).min(fifo_size(fifo.read(FIFO::RXFIFOSIZE)))
+    }
+
+    /// 在全部过采样倍数中搜索误差最小的 (OSR, SBR) 组合
+    ///
+    /// 误差相同时优先选择较大的过采样倍数，接收采样更可靠。
+    fn divisors_for(&self, baudrate: u32) -> Result<(u32, u32), ConfigError> {
+        if baudrate == 0 || self.clock_freq == 0 {
+            return Err(ConfigError::InvalidBaudrate);
+        }
+
+        let clk = self.clock_freq as u64;
+        let baud = baudrate as u64;
+        let mut best: Option<(u32, u32, u64)> = None;
+
+        for osr in OSR_MIN..=OSR_MAX {
+            let div = baud * osr as u64;
+            let sbr = ((clk + div / 2) / div).clamp(1, SBR_MAX as u64);
+
+            let actual = clk / (osr as u64 * sbr);
+            let error = actual.abs_diff(baud);
+            if best.is_none_or(|(_, _, e)| error <= e) {
+                best = Some((osr, sbr as u32, error));
+            }
+        }
+
+        match best {
+            Some((osr, sbr, error)) if baud_error_ok(error, baud) => Ok((osr, sbr)),
+            _ => Err(ConfigError::InvalidBaudrate),
+        }
+    }
+
+    /// 在当前 CTRL 的基础上计算应用配置后的帧格式位
+    ///
+    /// LPUART 的数据位数包含校验位：8 位数据加校验需要 9 位帧（M），
+    /// 7 位数据无校验使用 7 位帧（M7）。
+    fn format_for(&self, config: &Config) -> u32 {
+        let data_bits = config.data_bits.unwrap_or_else(|| self.data_bits());
+        let parity = config.parity.unwrap_or_else(|| self.parity());
+        let mut ctrl = LocalRegisterCopy::<u32, CTRL::Register>::new(0);
+
+        match parity {
+            Parity::Even => ctrl.modify(CTRL::PE::SET + CTRL::PT::CLEAR),
+            Parity::Odd => ctrl.modify(CTRL::PE::SET + CTRL::PT::SET),
+            _ => {}
+        }
+
+        let with_parity = ctrl.is_set(CTRL::PE);
+        match (data_bits, with_parity) {
+            (DataBits::Eight, true) => ctrl.modify(CTRL::M::SET),
+            (DataBits::Seven, false) => ctrl.modify(CTRL::M7::SET),
+            _ => {}
+        }
+
+        ctrl.get()
+    }
+
+    /// 等待发送 FIFO 和移位寄存器都为空
+    fn wait_tx_idle(&self) -> bool {
+        for _ in 0..BUSY_SPIN_LIMIT {
+            if self.registers().stat.is_set(STAT::TC) {
+                return true;
+            }
+            core::hint::spin_loop();
+        }
+        false
+    }
+
+    /// 初始化 LPUART
+    fn init(&self) {
+        let regs = self.registers();
+
+        // 关闭收发和全部中断，保留帧格式和回环设置
+        let keep = CTRL_FORMAT | CTRL::LOOPS::SET.value | CTRL::RSRC::SET.value;
+        regs.ctrl.set(regs.ctrl.get() & keep);
+
+        // 开启并清空 FIFO，接收空闲 1 个字符即触发 RDRF
+        regs.fifo.write(
+            FIFO::TXFE::SET
+                + FIFO::RXFE::SET
+                + FIFO::TXFLUSH::SET
+                + FIFO::RXFLUSH::SET
+                + FIFO::RXIDEN.val(1),
+        );
+        regs.water
+            .write(WATER::TXWATER.val(0) + WATER::RXWATER.val(0));
+
+        // 清除残留标志
+        regs.stat.set(STAT_W1C);
+
+        // 空闲检测从停止位之后开始计数，空闲 1 个字符即置位 IDLE
+        regs.ctrl
+            .modify(CTRL::ILT::SET + CTRL::IDLECFG.val(0) + CTRL::TE::SET + CTRL::RE::SET);
+    }
+}
+
+impl InterfaceRaw for Lpuart {
+    type IrqHandler = LpuartIrqHandler;
+
+    type Sender = crate::Sender;
+
+    type Reciever = crate::Reciever;
+
+    fn base_addr(&self) -> usize {
+        self.base.0.as_ptr() as usize
+    }
+
+    fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        self.capabilities().validate(config)?;
+        let divisors = config
+            .baudrate
+            .map(|baudrate| self.divisors_for(baudrate))
+            .transpose()?;
+        let format = self.format_for(config);
+
+        if !self.wait_tx_idle() {
+            return Err(ConfigError::Timeout);
+        }
+
+        let regs = self.registers();
+
+        // 修改 BAUD 和帧格式前必须关闭收发
+        let original_ctrl = regs.ctrl.extract();
+        regs.ctrl.modify(CTRL::TE::CLEAR + CTRL::RE::CLEAR);
+
+        let mut baud = regs.baud.extract();
+        if let Some((osr, sbr)) = divisors {
+            // 过采样倍数低于 8 时必须使用双沿采样
+            baud.modify(
+                BAUD::OSR.val(osr - 1) + BAUD::SBR.val(sbr) + BAUD::BOTHEDGE.val((osr < 8) as u32),
+            );
+        }
+        if let Some(stop_bits) = config.stop_bits {
+            baud.modify(BAUD::SBNS.val((stop_bits == StopBits::Two) as u32));
+        }
+        regs.baud.set(baud.get());
+
+        // 写入帧格式并恢复原来的收发使能状态
+        regs.ctrl.set((original_ctrl.get() & !CTRL_FORMAT) | format);
+
+        Ok(())
+    }
+
+    fn baudrate(&self) -> u32 {
+        let baud = self.registers().baud.extract();
+        let osr = baud.read(BAUD::OSR) + 1;
+        let sbr = baud.read(BAUD::SBR);
+        if sbr == 0 {
+            return 0;
+        }
+        self.clock_freq / (osr * sbr)
+    }
+
+    fn data_bits(&self) -> DataBits {
+        let ctrl = self.registers().ctrl.extract();
+        if ctrl.is_set(CTRL::M) || (!ctrl.is_set(CTRL::PE) && !ctrl.is_set(CTRL::M7)) {
+            DataBits::Eight
+        } else {
+            DataBits::Seven
+        }
+    }
+
+    fn stop_bits(&self) -> StopBits {
+        if self.registers().baud.is_set(BAUD::SBNS) {
+            StopBits::Two
+        } else {
+            StopBits::One
+        }
+    }
+
+    fn parity(&self) -> Parity {
+        let ctrl = self.registers().ctrl.extract();
+        if !ctrl.is_set(CTRL::PE) {
+            Parity::None
+        } else if ctrl.is_set(CTRL::PT) {
+            Parity::Odd
+        } else {
+            Parity::Even
+        }
+    }
+
+    fn clock_freq(&self) -> Option<NonZeroU32> {
+        self.clock_freq.try_into().ok()
+    }
+
+    fn open(&mut self) {
+        self.init()
+    }
+
+    fn close(&mut self) {
+        self.registers().ctrl.modify(
+            CTRL::TE::CLEAR
+                + CTRL::RE::CLEAR
+                + CTRL::TIE::CLEAR
+                + CTRL::TCIE::CLEAR
+                + CTRL::RIE::CLEAR
+                + CTRL::ILIE::CLEAR,
+        );
+    }
+
+    fn enable_loopback(&mut self) {
+        self.registers()
+            .ctrl
+            .modify(CTRL::LOOPS::SET + CTRL::RSRC::CLEAR);
+    }
+
+    fn disable_loopback(&mut self) {
+        self.registers().ctrl.modify(CTRL::LOOPS::CLEAR);
+    }
+
+    fn is_loopback_enabled(&self) -> bool {
+        let ctrl = self.registers().ctrl.extract();
+        ctrl.is_set(CTRL::LOOPS) && !ctrl.is_set(CTRL::RSRC)
+    }
+
+    fn set_irq_mask(&mut self, mask: InterruptMask) {
+        let rx = mask.contains(InterruptMask::RX_AVAILABLE) as u32;
+        let tx = mask.contains(InterruptMask::TX_EMPTY) as u32;
+
+        // 空闲线路中断用于取走未达到水位的剩余数据
+        self.registers()
+            .ctrl
+            .modify(CTRL::RIE.val(rx) + CTRL::ILIE.val(rx) + CTRL::TIE.val(tx));
+    }
+
+    fn get_irq_mask(&self) -> InterruptMask {
+        let ctrl = self.registers().ctrl.extract();
+        let mut mask = InterruptMask::empty();
+
+        if ctrl.is_set(CTRL::RIE) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if ctrl.is_set(CTRL::TIE) {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        mask
+    }
+
+    fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+        self.irq.take()
+    }
+
+    fn take_tx(&mut self) -> Option<Self::Sender> {
+        self.tx.take().map(crate::Sender::LpuartSender)
+    }
+
+    fn take_rx(&mut self) -> Option<Self::Reciever> {
+        self.rx.take().map(crate::Reciever::LpuartReciever)
+    }
+
+    fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+        let tx = match tx {
+            crate::Sender::LpuartSender(s) => s,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    tx.external_base().unwrap_or(0), // 不匹配的发送器类型
+                ));
+            }
+        };
+
+        if self.base != tx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                tx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.tx = Some(tx);
+        Ok(())
+    }
+
+    fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+        let rx = match rx {
+            crate::Reciever::LpuartReciever(r) => r,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    rx.external_base().unwrap_or(0), // 不匹配的接收器类型
+                ));
+            }
+        };
+
+        if self.base != rx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                rx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.rx = Some(rx);
+        Ok(())
+    }
+}
+
+impl Caps for Lpuart {
+    fn capabilities(&self) -> Capabilities {
+        let (min_baudrate, _) = Capabilities::baudrate_range(self.clock_freq, OSR_MAX, SBR_MAX);
+        let (_, max_baudrate) = Capabilities::baudrate_range(self.clock_freq, OSR_MIN, 1);
+
+        Capabilities {
+            fifo_depth: self.fifo_depth(),
+            min_baudrate,
+            max_baudrate,
+            data_bits: &[DataBits::Seven, DataBits::Eight],
+            stop_bits: ALL_STOP_BITS,
+            parity: &[Parity::None, Parity::Even, Parity::Odd],
+            features: Features::LOOPBACK,
+        }
+    }
+}
+
+/// 接收 FIFO 字节数超过 RXWATER 时触发，发送 FIFO 字节数不超过 TXWATER 时触发
+impl FifoThreshold for Lpuart {
+    fn fifo_triggers(&self) -> FifoTriggers {
+        let water = self.registers().water.extract();
+        FifoTriggers {
+            rx: Some(water.read(WATER::RXWATER) as u16 + 1),
+            tx: Some(water.read(WATER::TXWATER) as u16),
+            rx_timeout: None,
+        }
+    }
+
+    fn set_fifo_thresholds(&mut self, req: &FifoThresholds) -> Result<FifoTriggers, FifoError> {
+        let depth = self.fifo_depth();
+        let req = req.resolve(depth, &self.fifo_triggers())?;
+        let regs = self.registers();
+
+        if let Some(rx) = req.rx {
+            let rxwater = rx.clamp(1, depth) - 1;
+            regs.water.modify(WATER::RXWATER.val(rxwater as u32));
+        }
+        if let Some(tx) = req.tx {
+            let txwater = tx.min(depth.saturating_sub(1));
+            regs.water.modify(WATER::TXWATER.val(txwater as u32));
+        }
+
+        Ok(self.fifo_triggers())
+    }
+}
+
+impl Flush for Lpuart {
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError> {
+        let regs = self.registers();
+
+        // 清空位写 1 生效并自动清零
+        let flush = match queue {
+            FlushQueue::Rx => FIFO::RXFLUSH::SET,
+            FlushQueue::Tx => FIFO::TXFLUSH::SET,
+            FlushQueue::Both => FIFO::RXFLUSH::SET + FIFO::TXFLUSH::SET,
+        };
+        regs.fifo.modify(flush);
+
+        if queue.rx() {
+            // 溢出标志不清除时接收器保持停止
+            regs.stat
+                .write(STAT::OR::SET + STAT::NF::SET + STAT::FE::SET + STAT::PF::SET);
+        }
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), FlushError> {
+        if self.wait_tx_idle() {
+            Ok(())
+        } else {
+            Err(FlushError::Timeout)
+        }
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// LPUART 挂起时保存的寄存器上下文
+#[derive(Debug, Clone, Copy)]
+pub struct LpuartContext {
+    baud: u32,
+    ctrl: u32,
+    fifo: u32,
+    water: u32,
+    modir: u32,
+}
+
+impl Suspend for Lpuart {
+    type Context = LpuartContext;
+
+    fn save_context(&self) -> Self::Context {
+        let regs = self.registers();
+        LpuartContext {
+            baud: regs.baud.get(),
+            ctrl: regs.ctrl.get(),
+            fifo: regs.fifo.get(),
+            water: regs.water.get(),
+            modir: regs.modir.get(),
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        let regs = self.registers();
+
+        // BAUD、FIFO 和 MODIR 只能在收发关闭时修改
+        regs.ctrl.set(0);
+
+        regs.baud.set(ctx.baud);
+        regs.modir.set(ctx.modir);
+        regs.fifo.set(
+            (ctx.fifo & (FIFO::TXFE::SET + FIFO::RXFE::SET + FIFO::RXIDEN.val(0b111)).value)
+                | (FIFO::TXFLUSH::SET + FIFO::RXFLUSH::SET).value,
+        );
+        regs.water.set(ctx.water);
+
+        // 清除上电过程中残留的标志，最后恢复收发和中断使能
+        regs.stat.set(STAT_W1C);
+        regs.ctrl.set(ctx.ctrl);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Reg(NonNull<LpuartRegisters>);
+
+unsafe impl Send for Reg {}
+
+impl Reg {
+    fn registers(&self) -> &LpuartRegisters {
+        unsafe { self.0.as_ref() }
+    }
+}
+
+pub struct LpuartSender {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    tx_pause: TxPause,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for LpuartSender {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl TSender for LpuartSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        RawSender::write_byte(self, byte)
+    }
+}
+
+impl RawSender for LpuartSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return false;
+        };
+        let regs = self.base.registers();
+        let depth = fifo_size(regs.fifo.read(FIFO::TXFIFOSIZE)) as u32;
+        if regs.water.read(WATER::TXCOUNT) >= depth {
+            return false;
+        }
+
+        regs.data.write(DATA::DATA.val(byte as _));
+        if let Some(stats) = self.stats {
+            stats.record_tx(1);
+        }
+
+        true
+    }
+}
+
+pub struct LpuartReciever {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for LpuartReciever {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl LpuartReciever {
+    fn read_raw(&mut self) -> Option<Result<u8, TransferError>> {
+        let regs = self.base.registers();
+        if regs.water.read(WATER::RXCOUNT) == 0 {
+            return None;
+        }
+
+        // 溢出后接收器停止工作，必须清除 OR 才能继续接收
+        let overrun = regs.stat.is_set(STAT::OR);
+
+        // DATA 同时给出数据和该字节的错误状态
+        let data = regs.data.extract();
+        let byte = data.read(DATA::DATA) as u8;
+
+        let result = if data.is_set(DATA::PARITYE) {
+            Err(TransferError::Parity)
+        } else if data.is_set(DATA::FRETSC) {
+            // 数据全 0 的帧错误即为 break
+            if byte == 0 {
+                Err(TransferError::Break)
+            } else {
+                Err(TransferError::Framing)
+            }
+        } else if overrun {
+            Err(TransferError::Overrun(byte))
+        } else {
+            Ok(byte)
+        };
+
+        // 只清除本字节对应的标志，之后字节的错误留给下一次读取
+        let mut errors = LocalRegisterCopy::<u32, STAT::Register>::new(0);
+        errors.modify(
+            STAT::OR.val(overrun as u32)
+                + STAT::PF.val(data.is_set(DATA::PARITYE) as u32)
+                + STAT::FE.val(data.is_set(DATA::FRETSC) as u32)
+                + STAT::NF.val(data.is_set(DATA::NOISY) as u32),
+        );
+        if errors.get() != 0 {
+            regs.stat.set(errors.get());
+        }
+
+        Some(result)
+    }
+}
+
+impl RawReciever for LpuartReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        let result = self.read_raw()?;
+        if let Some(stats) = self.stats {
+            stats.record_rx(&result);
+        }
+        Some(result)
+    }
+}
+
+pub struct LpuartIrqHandler {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+unsafe impl Sync for LpuartIrqHandler {}
+
+impl TIrqHandler for LpuartIrqHandler {
+    fn clean_interrupt_status(&self) -> InterruptMask {
+        let regs = self.base.registers();
+        let stat = regs.stat.extract();
+        let ctrl = regs.ctrl.extract();
+        let mut mask = InterruptMask::empty();
+
+        let rx = stat.is_set(STAT::RDRF) && ctrl.is_set(CTRL::RIE);
+        let idle = stat.is_set(STAT::IDLE) && ctrl.is_set(CTRL::ILIE);
+        let tx = stat.is_set(STAT::TDRE) && ctrl.is_set(CTRL::TIE);
+
+        if rx || idle {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if tx {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        if let Some(stats) = self.stats {
+            let causes = [
+                (rx, IrqCause::Rx),
+                (idle, IrqCause::RxTimeout),
+                (tx, IrqCause::Tx),
+                (stat.is_set(STAT::OR), IrqCause::LineStatus),
+                (stat.is_set(STAT::FE), IrqCause::LineStatus),
+                (stat.is_set(STAT::PF), IrqCause::LineStatus),
+            ];
+            for (pending, cause) in causes {
+                if pending {
+                    stats.record_irq(cause);
+                }
+            }
+        }
+
+        // RDRF/TDRE 随 FIFO 状态自动清除；错误标志留给 read_byte 处理
+        if stat.is_set(STAT::IDLE) {
+            regs.stat.write(STAT::IDLE::SET);
+        }
+
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake_mmio::FakeMmio;
+
+    fn uart(regs: &FakeMmio<12>) -> Lpuart {
+        // 发送完成，配置时无需等待
+        regs.set(0x14, STAT::TC::SET.value);
+        Lpuart::new(regs.base(), 24_000_000)
+    }
+
+    #[test]
+    fn osr_sbr_search_picks_closest() {
+        let regs = FakeMmio::<12>::new();
+        let mut uart = uart(&regs);
+
+        // 24 MHz / (26 * 8) = 115384，误差 0.16%
+        uart.set_config(&Config::new().baudrate(115200)).unwrap();
+        let baud = regs.get(0x10);
+        assert_eq!((baud >> 24, baud & 0x1FFF), (25, 8));
+        assert_eq!(baud & BAUD::BOTHEDGE::SET.value, 0);
+        assert_eq!(uart.baudrate(), 115384);
+
+        // 只有 6 倍过采样能精确得到 4 MHz，需要双沿采样
+        uart.set_config(&Config::new().baudrate(4_000_000)).unwrap();
+        let baud = regs.get(0x10);
+        assert_eq!((baud >> 24, baud & 0x1FFF), (5, 1));
+        assert_ne!(baud & BAUD::BOTHEDGE::SET.value, 0);
+        assert_eq!(uart.baudrate(), 4_000_000);
+    }
+
+    #[test]
+    fn format_bits_include_parity() {
+        let regs = FakeMmio::<12>::new();
+        let mut uart = uart(&regs);
+        regs.set(0x18, CTRL::TE::SET.value | CTRL::RE::SET.value);
+
+        uart.set_config(&Config::new().parity(Parity::Odd)).unwrap();
+        // 8 位数据加校验使用 9 位帧，收发使能保持不变
+        assert_eq!(
+            regs.get(0x18),
+            CTRL_FORMAT & !CTRL::M7::SET.value | CTRL::TE::SET.value | CTRL::RE::SET.value
+        );
+        assert_eq!(uart.data_bits(), DataBits::Eight);
+        assert_eq!(uart.parity(), Parity::Odd);
+    }
+
+    #[test]
+    fn read_clears_only_reported_flags() {
+        let regs = FakeMmio::<12>::new();
+        let uart = uart(&regs);
+        let mut rx = uart.rx.unwrap();
+
+        regs.set(0x2c, WATER::RXCOUNT.val(2).value);
+        regs.set(
+            0x14,
+            STAT::OR::SET.value | STAT::PF::SET.value | STAT::FE::SET.value,
+        );
+        regs.set(0x1c, DATA::PARITYE::SET.value | 0x41);
+        assert_eq!(
+            RawReciever::read_byte(&mut rx),
+            Some(Err(TransferError::Parity))
+        );
+        // 帧错误属于后面的字节，不能在这里清除
+        assert_eq!(regs.get(0x14), STAT::OR::SET.value | STAT::PF::SET.value);
+
+        regs.set(0x14, 0);
+        regs.set(0x1c, DATA::FRETSC::SET.value);
+        assert_eq!(
+            RawReciever::read_byte(&mut rx),
+            Some(Err(TransferError::Break))
+        );
+        assert_eq!(regs.get(0x14), STAT::FE::SET.value);
+    }
+
+    #[test]
+    fn fifo_size_fields_decode_depth() {
+        let regs = FakeMmio::<12>::new();
+        let mut uart = uart(&regs);
+        let mut tx = uart.tx.take().unwrap();
+
+        // 发送 16 字节，接收 8 字节
+        regs.set(
+            0x28,
+            FIFO::TXFIFOSIZE.val(3).value | FIFO::RXFIFOSIZE.val(2).value,
+        );
+        assert_eq!(uart.fifo_depth(), 8);
+
+        regs.set(0x2c, WATER::TXCOUNT.val(15).value);
+        assert!(RawSender::write_byte(&mut tx, b'a'));
+        regs.set(0x2c, WATER::TXCOUNT.val(16).value);
+        assert!(!RawSender::write_byte(&mut tx, b'b'));
+    }
+
+    #[test]
+    fn idle_irq_reports_rx_and_is_acked() {
+        let regs = FakeMmio::<12>::new();
+        let mut uart = uart(&regs);
+        let irq = uart.irq_handler().unwrap();
+
+        uart.set_irq_mask(InterruptMask::RX_AVAILABLE);
+        regs.set(0x14, STAT::IDLE::SET.value | STAT::TDRE::SET.value);
+        assert_eq!(
+            irq.clean_interrupt_status().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+        assert_eq!(regs.get(0x14), STAT::IDLE::SET.value);
+    }
+}
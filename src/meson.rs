@@ -0,0 +1,900 @@
+//! Amlogic Meson UART 驱动 (`amlogic,meson-ao-uart` / `amlogic,meson-gx-uart` 等)
+//!
+//! 用于 S905、A311D 等 SoC 的 AO/EE UART。寄存器为 WFIFO/RFIFO/CONTROL/STATUS/MISC/REG5，
+//! 收发各有 64 字节 FIFO，不支持回环。波特率有两种编程方式：
+//!
+//! - 旧版：分频值位于 CONTROL[11:0]，`baud = clk / (4 * (N + 1))`
+//! - 新版：分频值位于 REG5，时钟源可选外设时钟（÷4）或 24 MHz 晶振（÷3，G12A 之后可 ÷2）
+//!
+//! 驱动按构造时给定的 [`MesonBaudSource`] 编程，`clock_freq` 为该时钟源的频率。
+
+use core::{num::NonZeroU32, ptr::NonNull};
+
+use alloc::sync::Arc;
+
+use rdif_serial::{BSerial, InterfaceRaw, SerialDyn, SetBackError, TIrqHandler, TSender};
+use tock_registers::{interfaces::*, register_bitfields, register_structs, registers::*};
+
+use crate::{
+    caps::{ALL_DATA_BITS, ALL_STOP_BITS},
+    claim::{self, Claim, ClaimError, Region},
+    fifo::{FifoError, FifoThreshold, FifoThresholds, FifoTriggers},
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, IrqCause, Stats},
+    Capabilities, Caps, Config, ConfigError, DataBits, Features, InterruptMask, Parity,
+    RawReciever, RawSender, StopBits, Suspend, TransferError,
+};
+
+register_bitfields! [
+    u32,
+
+    /// Write FIFO
+    WFIFO [
+        DATA OFFSET(0) NUMBITS(8) []
+    ],
+
+    /// Read FIFO
+    RFIFO [
+        DATA OFFSET(0) NUMBITS(8) []
+    ],
+
+    /// Control Register
+    CONTROL [
+        OLD_BAUD OFFSET(0) NUMBITS(12) [],
+        TX_EN OFFSET(12) NUMBITS(1) [],
+        RX_EN OFFSET(13) NUMBITS(1) [],
+        TWO_WIRE_EN OFFSET(15) NUMBITS(1) [],
+        STOP_BITS OFFSET(16) NUMBITS(2) [
+            One = 0b00,
+            Two = 0b01
+        ],
+        PARITY_ODD OFFSET(18) NUMBITS(1) [],
+        PARITY_EN OFFSET(19) NUMBITS(1) [],
+        DATA_LEN OFFSET(20) NUMBITS(2) [
+            EightBit = 0b00,
+            SevenBit = 0b01,
+            SixBit = 0b10,
+            FiveBit = 0b11
+        ],
+        TX_RST OFFSET(22) NUMBITS(1) [],
+        RX_RST OFFSET(23) NUMBITS(1) [],
+        CLEAR_ERR OFFSET(24) NUMBITS(1) [],
+        RX_INT_EN OFFSET(27) NUMBITS(1) [],
+        TX_INT_EN OFFSET(28) NUMBITS(1) []
+    ],
+
+    /// Status Register
+    STATUS [
+        RX_COUNT OFFSET(0) NUMBITS(7) [],
+        TX_COUNT OFFSET(8) NUMBITS(7) [],
+        PARITY_ERR OFFSET(16) NUMBITS(1) [],
+        FRAME_ERR OFFSET(17) NUMBITS(1) [],
+        TX_FIFO_WERR OFFSET(18) NUMBITS(1) [],
+        RX_EMPTY OFFSET(20) NUMBITS(1) [],
+        TX_FULL OFFSET(21) NUMBITS(1) [],
+        TX_EMPTY OFFSET(22) NUMBITS(1) [],
+        XMIT_BUSY OFFSET(25) NUMBITS(1) []
+    ],
+
+    /// Misc Register
+    MISC [
+        RECV_IRQ_CNT OFFSET(0) NUMBITS(8) [],
+        XMIT_IRQ_CNT OFFSET(8) NUMBITS(8) []
+    ],
+
+    /// Baud Rate Register
+    REG5 [
+        BAUD OFFSET(0) NUMBITS(23) [],
+        BAUD_USE OFFSET(23) NUMBITS(1) [],
+        XTAL OFFSET(24) NUMBITS(1) [],
+        XTAL_DIV2 OFFSET(27) NUMBITS(1) []
+    ]
+];
+
+register_structs! {
+    pub MesonUartRegisters {
+        (0x00 => wfifo: WriteOnly<u32, WFIFO::Register>),     // 发送 FIFO
+        (0x04 => rfifo: ReadOnly<u32, RFIFO::Register>),      // 接收 FIFO
+        (0x08 => control: ReadWrite<u32, CONTROL::Register>), // 控制寄存器
+        (0x0c => status: ReadOnly<u32, STATUS::Register>),    // 状态寄存器
+        (0x10 => misc: ReadWrite<u32, MISC::Register>),       // 中断阈值寄存器
+        (0x14 => reg5: ReadWrite<u32, REG5::Register>),       // 波特率寄存器
+        (0x18 => @END),
+    }
+}
+
+// SAFETY: 寄存器访问均为单次 32 位读写
+unsafe impl Sync for MesonUartRegisters {}
+
+/// 收发 FIFO 深度
+const FIFO_DEPTH: u16 = 64;
+
+/// 等待发送完成的最大轮询次数
+const BUSY_SPIN_LIMIT: usize = 1_000_000;
+
+/// 发送 FIFO 低于该字节数时触发发送中断的默认值
+const TX_TRIGGER: u32 = FIFO_DEPTH as u32 / 2;
+
+/// Meson UART 波特率时钟源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MesonBaudSource {
+    /// 旧版分频（CONTROL[11:0]），外设时钟 ÷4
+    Legacy,
+    /// REG5 分频，外设时钟（clk81/PLL）÷4
+    Pll,
+    /// REG5 分频，24 MHz 晶振 ÷3
+    Xtal,
+    /// REG5 分频，24 MHz 晶振 ÷2（G12A 及之后）
+    XtalDiv2,
+}
+
+impl MesonBaudSource {
+    /// 分频之前的固定预分频系数
+    const fn prescaler(self) -> u32 {
+        match self {
+            Self::Legacy | Self::Pll => 4,
+            Self::Xtal => 3,
+            Self::XtalDiv2 => 2,
+        }
+    }
+
+    /// 分频值的最大值（加 1 后）
+    const fn max_divisor(self) -> u32 {
+        match self {
+            Self::Legacy => 1 << 12,
+            _ => 1 << 23,
+        }
+    }
+}
+
+/// Amlogic Meson UART 驱动结构体
+pub struct MesonUart {
+    base: Reg,
+    clock_freq: u32,
+    source: MesonBaudSource,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    tx_pause: TxPause,
+    tx: Option<MesonUartSender>,
+    rx: Option<MesonUartReciever>,
+    irq: Option<MesonUartIrqHandler>,
+    /// 通过 `try_new` 系列创建时持有的区域占用，与各句柄共享
+    claim: Option<Arc<Claim>>,
+}
+
+impl MesonUart {
+    /// `clock_freq` 为 `source` 对应时钟源的频率
+    ///
+    /// 不占用寄存器区域，需要防止同一设备被重复创建时使用 [`Self::try_new`]
+    pub fn new(base: NonNull<u8>, clock_freq: u32, source: MesonBaudSource) -> Self {
+        Self::with_claim(base, clock_freq, source, None)
+    }
+
+    pub fn new_boxed(base: NonNull<u8>, clock_freq: u32, source: MesonBaudSource) -> BSerial {
+        SerialDyn::new_boxed(Self::new(base, clock_freq, source))
+    }
+
+    /// 占用寄存器区域后创建实例，区域与已有实例重叠时返回错误
+    pub fn try_new(
+        base: NonNull<u8>,
+        clock_freq: u32,
+        source: MesonBaudSource,
+    ) -> Result<Self, ClaimError> {
+        let claim = claim::claim(Region::mmio(base, size_of::<MesonUartRegisters>()))?;
+        Ok(Self::with_claim(base, clock_freq, source, Some(claim)))
+    }
+
+    pub fn try_new_boxed(
+        base: NonNull<u8>,
+        clock_freq: u32,
+        source: MesonBaudSource,
+    ) -> Result<BSerial, ClaimError> {
+        Ok(SerialDyn::new_boxed(Self::try_new(
+            base, clock_freq, source,
+        )?))
+    }
+
+    /// 通过 `try_new` 系列创建时持有的寄存器区域占用
+    pub fn claim(&self) -> Option<&Claim> {
+        self.claim.as_deref()
+    }
+
+    fn with_claim(
+        base: NonNull<u8>,
+        clock_freq: u32,
+        source: MesonBaudSource,
+        claim: Option<Arc<Claim>>,
+    ) -> Self {
+        let base = Reg(base.cast());
+        let tx_pause = TxPause::default();
+
+        Self {
+            base,
+            clock_freq,
+            source,
+            tx_pause: tx_pause.clone(),
+            tx: Some(MesonUartSender {
+                base,
+                stats: None,
+                tx_pause: tx_pause.clone(),
+                _claim: claim.clone(),
+            }),
+            rx: Some(MesonUartReciever {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            irq: Some(MesonUartIrqHandler {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            claim,
+        }
+    }
+
+    fn registers(&self) -> &MesonUartRegisters {
+        self.base.registers()
+    }
+
+    /// 挂接统计计数器，只对尚未取出的收发和中断句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+        if let Some(irq) = self.irq.as_mut() {
+            irq.stats = Some(stats);
+        }
+    }
+
+    /// 计算分频值：N = clk / (prescaler * baud) - 1
+    fn divisor_for(&self, baudrate: u32) -> Result<u32, ConfigError> {
+        if baudrate == 0 || self.clock_freq == 0 {
+            return Err(ConfigError::InvalidBaudrate);
+        }
+
+        let step = self.source.prescaler() as u64 * baudrate as u64;
+        let div = (self.clock_freq as u64 + step / 2) / step;
+        if div == 0 || div > self.source.max_divisor() as u64 {
+            return Err(ConfigError::InvalidBaudrate);
+        }
+        Ok(div as u32 - 1)
+    }
+
+    /// 按时钟源写入分频值
+    fn apply_divisor(&self, div: u32) {
+        let regs = self.registers();
+        match self.source {
+            MesonBaudSource::Legacy => {
+                regs.reg5.modify(REG5::BAUD_USE::CLEAR);
+                regs.control.modify(CONTROL::OLD_BAUD.val(div));
+            }
+            MesonBaudSource::Pll => {
+                regs.reg5.write(REG5::BAUD.val(div) + REG5::BAUD_USE::SET);
+            }
+            MesonBaudSource::Xtal => {
+                regs.reg5
+                    .write(REG5::BAUD.val(div) + REG5::BAUD_USE::SET + REG5::XTAL::SET);
+            }
+            MesonBaudSource::XtalDiv2 => {
+                regs.reg5.write(
+                    REG5::BAUD.val(div)
+                        + REG5::BAUD_USE::SET
+                        + REG5::XTAL::SET
+                        + REG5::XTAL_DIV2::SET,
+                );
+            }
+        }
+    }
+
+    /// 等待发送 FIFO 和移位寄存器都为空
+    fn wait_tx_idle(&self) -> bool {
+        for _ in 0..BUSY_SPIN_LIMIT {
+            let status = self.registers().status.extract();
+            if status.is_set(STATUS::TX_EMPTY) && !status.is_set(STATUS::XMIT_BUSY) {
+                return true;
+            }
+            core::hint::spin_loop();
+        }
+        false
+    }
+
+    /// 复位指定方向的 FIFO，复位接收方向时同时清除错误标志
+    fn reset_fifos(&self, queue: FlushQueue) {
+        let regs = self.registers();
+        let (set, clear) = match queue {
+            FlushQueue::Rx => (
+                CONTROL::RX_RST::SET + CONTROL::CLEAR_ERR::SET,
+                CONTROL::RX_RST::CLEAR + CONTROL::CLEAR_ERR::CLEAR,
+            ),
+            FlushQueue::Tx => (CONTROL::TX_RST::SET, CONTROL::TX_RST::CLEAR),
+            FlushQueue::Both => (
+                CONTROL::TX_RST::SET + CONTROL::RX_RST::SET + CONTROL::CLEAR_ERR::SET,
+                CONTROL::TX_RST::CLEAR + CONTROL::RX_RST::CLEAR + CONTROL::CLEAR_ERR::CLEAR,
+            ),
+        };
+        regs.control.modify(set);
+        regs.control.modify(clear);
+    }
+
+    /// 初始化 Meson UART
+    fn init(&self) {
+        let regs = self.registers();
+
+        // 关闭中断后复位 FIFO
+        regs.control
+            .modify(CONTROL::RX_INT_EN::CLEAR + CONTROL::TX_INT_EN::CLEAR);
+        self.reset_fifos(FlushQueue::Both);
+
+        // 接收 FIFO 有 1 个字节即触发，发送 FIFO 低于一半时触发
+        regs.misc
+            .write(MISC::RECV_IRQ_CNT.val(1) + MISC::XMIT_IRQ_CNT.val(TX_TRIGGER));
+
+        // 仅使用 TX/RX 两线，忽略 CTS
+        regs.control
+            .modify(CONTROL::TX_EN::SET + CONTROL::RX_EN::SET + CONTROL::TWO_WIRE_EN::SET);
+    }
+}
+
+impl InterfaceRaw for MesonUart {
+    type IrqHandler = MesonUartIrqHandler;
+
+    type Sender = crate::Sender;
+
+    type Reciever = crate::Reciever;
+
+    fn base_addr(&self) -> usize {
+        self.base.0.as_ptr() as usize
+    }
+
+    fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        self.capabilities().validate(config)?;
+        let div = config
+            .baudrate
+            .map(|baudrate| self.divisor_for(baudrate))
+            .transpose()?;
+
+        let mut control = self.registers().control.extract();
+        if let Some(bits) = config.data_bits {
+            let field = match bits {
+                DataBits::Five => CONTROL::DATA_LEN::FiveBit,
+                DataBits::Six => CONTROL::DATA_LEN::SixBit,
+                DataBits::Seven => CONTROL::DATA_LEN::SevenBit,
+                DataBits::Eight => CONTROL::DATA_LEN::EightBit,
+            };
+            control.modify(field);
+        }
+        if let Some(stop_bits) = config.stop_bits {
+            let field = match stop_bits {
+                StopBits::One => CONTROL::STOP_BITS::One,
+                StopBits::Two => CONTROL::STOP_BITS::Two,
+            };
+            control.modify(field);
+        }
+        if let Some(parity) = config.parity {
+            let field = match parity {
+                Parity::Even => CONTROL::PARITY_EN::SET + CONTROL::PARITY_ODD::CLEAR,
+                Parity::Odd => CONTROL::PARITY_EN::SET + CONTROL::PARITY_ODD::SET,
+                _ => CONTROL::PARITY_EN::CLEAR + CONTROL::PARITY_ODD::CLEAR,
+            };
+            control.modify(field);
+        }
+
+        if !self.wait_tx_idle() {
+            return Err(ConfigError::Timeout);
+        }
+
+        self.registers().control.set(control.get());
+        if let Some(div) = div {
+            self.apply_divisor(div);
+        }
+
+        Ok(())
+    }
+
+    fn baudrate(&self) -> u32 {
+        let regs = self.registers();
+        let reg5 = regs.reg5.extract();
+
+        let div = if reg5.is_set(REG5::BAUD_USE) {
+            reg5.read(REG5::BAUD)
+        } else {
+            regs.control.read(CONTROL::OLD_BAUD)
+        };
+        self.clock_freq / (self.source.prescaler() * (div + 1))
+    }
+
+    fn data_bits(&self) -> DataBits {
+        match self.registers().control.read_as_enum(CONTROL::DATA_LEN) {
+            Some(CONTROL::DATA_LEN::Value::FiveBit) => DataBits::Five,
+            Some(CONTROL::DATA_LEN::Value::SixBit) => DataBits::Six,
+            Some(CONTROL::DATA_LEN::Value::SevenBit) => DataBits::Seven,
+            Some(CONTROL::DATA_LEN::Value::EightBit) | None => DataBits::Eight,
+        }
+    }
+
+    fn stop_bits(&self) -> StopBits {
+        match self.registers().control.read_as_enum(CONTROL::STOP_BITS) {
+            Some(CONTROL::STOP_BITS::Value::Two) => StopBits::Two,
+            _ => StopBits::One,
+        }
+    }
+
+    fn parity(&self) -> Parity {
+        let control = self.registers().control.extract();
+        if !control.is_set(CONTROL::PARITY_EN) {
+            Parity::None
+        } else if control.is_set(CONTROL::PARITY_ODD) {
+            Parity::Odd
+        } else {
+            Parity::Even
+        }
+    }
+
+    fn clock_freq(&self) -> Option<NonZeroU32> {
+        self.clock_freq.try_into().ok()
+    }
+
+    fn open(&mut self) {
+        self.init()
+    }
+
+    fn close(&mut self) {
+        self.registers().control.modify(
+            CONTROL::RX_INT_EN::CLEAR
+                + CONTROL::TX_INT_EN::CLEAR
+                + CONTROL::TX_EN::CLEAR
+                + CONTROL::RX_EN::CLEAR,
+        );
+    }
+
+    // Meson UART 不支持回环
+    fn enable_loopback(&mut self) {}
+
+    fn disable_loopback(&mut self) {}
+
+    fn is_loopback_enabled(&self) -> bool {
+        false
+    }
+
+    fn set_irq_mask(&mut self, mask: InterruptMask) {
+        let rx = mask.contains(InterruptMask::RX_AVAILABLE) as u32;
+        let tx = mask.contains(InterruptMask::TX_EMPTY) as u32;
+
+        self.registers()
+            .control
+            .modify(CONTROL::RX_INT_EN.val(rx) + CONTROL::TX_INT_EN.val(tx));
+    }
+
+    fn get_irq_mask(&self) -> InterruptMask {
+        let control = self.registers().control.extract();
+        let mut mask = InterruptMask::empty();
+
+        if control.is_set(CONTROL::RX_INT_EN) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if control.is_set(CONTROL::TX_INT_EN) {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        mask
+    }
+
+    fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+        self.irq.take()
+    }
+
+    fn take_tx(&mut self) -> Option<Self::Sender> {
+        self.tx.take().map(crate::Sender::MesonUartSender)
+    }
+
+    fn take_rx(&mut self) -> Option<Self::Reciever> {
+        self.rx.take().map(crate::Reciever::MesonUartReciever)
+    }
+
+    fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+        let tx = match tx {
+            crate::Sender::MesonUartSender(s) => s,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    tx.external_base().unwrap_or(0), // 不匹配的发送器类型
+                ));
+            }
+        };
+
+        if self.base != tx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                tx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.tx = Some(tx);
+        Ok(())
+    }
+
+    fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+        let rx = match rx {
+            crate::Reciever::MesonUartReciever(r) => r,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    rx.external_base().unwrap_or(0), // 不匹配的接收器类型
+                ));
+            }
+        };
+
+        if self.base != rx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                rx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.rx = Some(rx);
+        Ok(())
+    }
+}
+
+impl Caps for MesonUart {
+    fn capabilities(&self) -> Capabilities {
+        let (min_baudrate, max_baudrate) = Capabilities::baudrate_range(
+            self.clock_freq,
+            self.source.prescaler(),
+            self.source.max_divisor(),
+        );
+
+        Capabilities {
+            fifo_depth: FIFO_DEPTH,
+            min_baudrate,
+            max_baudrate,
+            data_bits: ALL_DATA_BITS,
+            stop_bits: ALL_STOP_BITS,
+            parity: &[Parity::None, Parity::Even, Parity::Odd],
+            features: Features::empty(),
+        }
+    }
+}
+
+/// 接收 FIFO 达到 RECV_IRQ_CNT 时触发，发送 FIFO 少于 XMIT_IRQ_CNT 时触发，
+/// 即剩余字节数不超过 XMIT_IRQ_CNT - 1
+impl FifoThreshold for MesonUart {
+    fn fifo_triggers(&self) -> FifoTriggers {
+        let misc = self.registers().misc.extract();
+        FifoTriggers {
+            rx: Some(misc.read(MISC::RECV_IRQ_CNT) as u16),
+            tx: Some((misc.read(MISC::XMIT_IRQ_CNT) as u16).saturating_sub(1)),
+            rx_timeout: None,
+        }
+    }
+
+    fn set_fifo_thresholds(&mut self, req: &FifoThresholds) -> Result<FifoTriggers, FifoError> {
+        let req = req.resolve(FIFO_DEPTH, &self.fifo_triggers())?;
+        let regs = self.registers();
+
+        if let Some(rx) = req.rx {
+            regs.misc
+                .modify(MISC::RECV_IRQ_CNT.val(rx.clamp(1, FIFO_DEPTH) as u32));
+        }
+        if let Some(tx) = req.tx {
+            let cnt = (tx + 1).min(FIFO_DEPTH);
+            regs.misc.modify(MISC::XMIT_IRQ_CNT.val(cnt as u32));
+        }
+
+        Ok(self.fifo_triggers())
+    }
+}
+
+impl Flush for MesonUart {
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError> {
+        self.reset_fifos(queue);
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), FlushError> {
+        if self.wait_tx_idle() {
+            Ok(())
+        } else {
+            Err(FlushError::Timeout)
+        }
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// Meson UART 挂起时保存的寄存器上下文
+#[derive(Debug, Clone, Copy)]
+pub struct MesonUartContext {
+    control: u32,
+    misc: u32,
+    reg5: u32,
+}
+
+impl Suspend for MesonUart {
+    type Context = MesonUartContext;
+
+    fn save_context(&self) -> Self::Context {
+        let regs = self.registers();
+        MesonUartContext {
+            control: regs.control.get(),
+            misc: regs.misc.get(),
+            reg5: regs.reg5.get(),
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        let regs = self.registers();
+        let irq_en = (CONTROL::RX_INT_EN::SET + CONTROL::TX_INT_EN::SET).mask();
+
+        // 恢复期间保持中断关闭，复位位只在 reset_fifos 中短暂置位
+        regs.control.set(ctx.control & !irq_en);
+        self.reset_fifos(FlushQueue::Both);
+
+        regs.misc.set(ctx.misc);
+        regs.reg5.set(ctx.reg5);
+        regs.control.set(ctx.control);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Reg(NonNull<MesonUartRegisters>);
+
+unsafe impl Send for Reg {}
+
+impl Reg {
+    fn registers(&self) -> &MesonUartRegisters {
+        unsafe { self.0.as_ref() }
+    }
+}
+
+pub struct MesonUartSender {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    tx_pause: TxPause,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for MesonUartSender {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl TSender for MesonUartSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        RawSender::write_byte(self, byte)
+    }
+}
+
+impl RawSender for MesonUartSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return false;
+        };
+        if self.base.registers().status.is_set(STATUS::TX_FULL) {
+            return false;
+        }
+
+        self.base
+            .registers()
+            .wfifo
+            .write(WFIFO::DATA.val(byte as _));
+        if let Some(stats) = self.stats {
+            stats.record_tx(1);
+        }
+
+        true
+    }
+}
+
+pub struct MesonUartReciever {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for MesonUartReciever {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl MesonUartReciever {
+    fn read_raw(&mut self) -> Option<Result<u8, TransferError>> {
+        let regs = self.base.registers();
+        let status = regs.status.extract();
+        if status.is_set(STATUS::RX_EMPTY) {
+            return None;
+        }
+
+        let data = regs.rfifo.read(RFIFO::DATA) as u8;
+
+        // 错误标志不跟随字节，一直保持到写 CLEAR_ERR；与 Linux 驱动一致，
+        // FIFO 写错误计为溢出
+        let result = if status.is_set(STATUS::TX_FIFO_WERR) {
+            Err(TransferError::Overrun(data))
+        } else if status.is_set(STATUS::FRAME_ERR) {
+            Err(TransferError::Framing)
+        } else if status.is_set(STATUS::PARITY_ERR) {
+            Err(TransferError::Parity)
+        } else {
+            return Some(Ok(data));
+        };
+
+        regs.control.modify(CONTROL::CLEAR_ERR::SET);
+        regs.control.modify(CONTROL::CLEAR_ERR::CLEAR);
+
+        Some(result)
+    }
+}
+
+impl RawReciever for MesonUartReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        let result = self.read_raw()?;
+        if let Some(stats) = self.stats {
+            stats.record_rx(&result);
+        }
+        Some(result)
+    }
+}
+
+pub struct MesonUartIrqHandler {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+unsafe impl Sync for MesonUartIrqHandler {}
+
+impl TIrqHandler for MesonUartIrqHandler {
+    /// 硬件没有中断状态寄存器，按 FIFO 状态推断中断原因；
+    /// 中断随 FIFO 水位自动撤销，无需清除。
+    fn clean_interrupt_status(&self) -> InterruptMask {
+        let regs = self.base.registers();
+        let control = regs.control.extract();
+        let status = regs.status.extract();
+        let mut mask = InterruptMask::empty();
+
+        if control.is_set(CONTROL::RX_INT_EN) && !status.is_set(STATUS::RX_EMPTY) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        let tx_trigger = regs.misc.read(MISC::XMIT_IRQ_CNT);
+        if control.is_set(CONTROL::TX_INT_EN) && status.read(STATUS::TX_COUNT) < tx_trigger {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        if let Some(stats) = self.stats {
+            if mask.contains(InterruptMask::RX_AVAILABLE) {
+                stats.record_irq(IrqCause::Rx);
+            }
+            if mask.contains(InterruptMask::TX_EMPTY) {
+                stats.record_irq(IrqCause::Tx);
+            }
+            if status.is_set(STATUS::FRAME_ERR) || status.is_set(STATUS::PARITY_ERR) {
+                stats.record_irq(IrqCause::LineStatus);
+            }
+        }
+
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fake_mmio::FakeMmio, FifoLevel};
+
+    fn uart(regs: &FakeMmio<6>, clock_freq: u32, source: MesonBaudSource) -> MesonUart {
+        // 发送空闲，配置时无需等待
+        regs.set(0x0c, STATUS::TX_EMPTY::SET.value);
+        MesonUart::new(regs.base(), clock_freq, source)
+    }
+
+    #[test]
+    fn xtal_divisor_goes_to_reg5() {
+        let regs = FakeMmio::<6>::new();
+        let mut uart = uart(&regs, 24_000_000, MesonBaudSource::Xtal);
+
+        // 24 MHz / (3 * 115200) = 69.4，取整为 69
+        uart.set_config(&Config::new().baudrate(115200)).unwrap();
+        assert_eq!(
+            regs.get(0x14),
+            (REG5::BAUD.val(68) + REG5::BAUD_USE::SET + REG5::XTAL::SET).value
+        );
+        assert_eq!(uart.baudrate(), 24_000_000 / (3 * 69));
+    }
+
+    #[test]
+    fn legacy_divisor_is_limited_to_12_bits() {
+        let regs = FakeMmio::<6>::new();
+        let mut uart = uart(&regs, 24_000_000, MesonBaudSource::Legacy);
+        regs.set(0x14, REG5::BAUD_USE::SET.value);
+
+        // 24 MHz / (4 * 115200) = 52.1
+        uart.set_config(&Config::new().baudrate(115200)).unwrap();
+        assert_eq!(regs.get(0x08) & 0xFFF, 51);
+        assert_eq!(regs.get(0x14), 0);
+    }
+
+    #[test]
+    fn large_divisor_needs_reg5() {
+        // 200 MHz / (4 * 9600) = 5208，只有 REG5 放得下
+        let regs = FakeMmio::<6>::new();
+        let mut legacy = uart(&regs, 200_000_000, MesonBaudSource::Legacy);
+        let mut pll = MesonUart::new(regs.base(), 200_000_000, MesonBaudSource::Pll);
+        assert_eq!(
+            legacy.set_config(&Config::new().baudrate(9600)),
+            Err(ConfigError::InvalidBaudrate)
+        );
+        pll.set_config(&Config::new().baudrate(9600)).unwrap();
+        assert_eq!(
+            regs.get(0x14),
+            (REG5::BAUD.val(5207) + REG5::BAUD_USE::SET).value
+        );
+    }
+
+    #[test]
+    fn sticky_error_is_reported_and_cleared() {
+        let regs = FakeMmio::<6>::new();
+        let uart = uart(&regs, 24_000_000, MesonBaudSource::Xtal);
+        let mut rx = uart.rx.unwrap();
+
+        regs.set(0x0c, STATUS::FRAME_ERR::SET.value);
+        regs.set(0x04, 0x11);
+        assert_eq!(
+            RawReciever::read_byte(&mut rx),
+            Some(Err(TransferError::Framing))
+        );
+        // CLEAR_ERR 写 1 后立即撤销
+        assert_eq!(regs.get(0x08) & CONTROL::CLEAR_ERR::SET.value, 0);
+
+        regs.set(0x0c, STATUS::RX_EMPTY::SET.value);
+        assert_eq!(RawReciever::read_byte(&mut rx), None);
+    }
+
+    #[test]
+    fn irq_follows_fifo_counts() {
+        let regs = FakeMmio::<6>::new();
+        let mut uart = uart(&regs, 24_000_000, MesonBaudSource::Xtal);
+        let irq = uart.irq_handler().unwrap();
+
+        uart.set_irq_mask(InterruptMask::RX_AVAILABLE | InterruptMask::TX_EMPTY);
+        regs.set(0x10, MISC::XMIT_IRQ_CNT.val(32).value);
+
+        regs.set(0x0c, STATUS::TX_COUNT.val(40).value);
+        assert_eq!(
+            irq.clean_interrupt_status().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+        regs.set(
+            0x0c,
+            (STATUS::TX_COUNT.val(10) + STATUS::RX_EMPTY::SET).value,
+        );
+        assert_eq!(
+            irq.clean_interrupt_status().bits(),
+            InterruptMask::TX_EMPTY.bits()
+        );
+    }
+
+    #[test]
+    fn tx_trigger_counts_below_threshold() {
+        let regs = FakeMmio::<6>::new();
+        let mut uart = uart(&regs, 24_000_000, MesonBaudSource::Xtal);
+
+        let req = FifoThresholds::new()
+            .rx(FifoLevel::Bytes(8))
+            .tx(FifoLevel::Bytes(15));
+        let triggers = uart.set_fifo_thresholds(&req).unwrap();
+        assert_eq!(
+            regs.get(0x10),
+            (MISC::RECV_IRQ_CNT.val(8) + MISC::XMIT_IRQ_CNT.val(16)).value
+        );
+        assert_eq!((triggers.rx, triggers.tx), (Some(8), Some(15)));
+    }
+}
@@ -7,7 +7,10 @@ use rdif_serial::{BSerial, SerialDyn};
 use crate::ns16550::{Ns16550IrqHandler, Ns16550Reciever, Ns16550Sender};
 
 use super::{Kind, Ns16550};
-use core::ptr::NonNull;
+use crate::claim::{self, Claim, ClaimError, Region};
+use crate::reconfig::TxPause;
+use alloc::sync::Arc;
+use core::{ptr::NonNull, sync::atomic::AtomicU8};
 
 #[derive(Clone)]
 pub struct Mmio {
@@ -36,29 +39,79 @@ impl Kind for Mmio {
 }
 
 impl Ns16550<Mmio> {
+    /// 不占用寄存器区域，需要防止同一设备被重复创建时使用 [`Self::try_new_mmio`]
     pub fn new_mmio(base: NonNull<u8>, clock_freq: u32, reg_width: usize) -> Ns16550<Mmio> {
+        Self::with_claim(base, clock_freq, reg_width, None)
+    }
+
+    pub fn new_mmio_boxed(base: NonNull<u8>, clock_freq: u32, reg_width: usize) -> BSerial {
+        SerialDyn::new_boxed(Ns16550::new_mmio(base, clock_freq, reg_width))
+    }
+
+    /// 占用 8 个寄存器的区域后创建实例，区域与已有实例重叠时返回错误
+    pub fn try_new_mmio(
+        base: NonNull<u8>,
+        clock_freq: u32,
+        reg_width: usize,
+    ) -> Result<Ns16550<Mmio>, ClaimError> {
+        let claim = claim::claim(Region::mmio(base, 8 * reg_width))?;
+        Ok(Self::with_claim(base, clock_freq, reg_width, Some(claim)))
+    }
+
+    pub fn try_new_mmio_boxed(
+        base: NonNull<u8>,
+        clock_freq: u32,
+        reg_width: usize,
+    ) -> Result<BSerial, ClaimError> {
+        Ok(SerialDyn::new_boxed(Ns16550::try_new_mmio(
+            base, clock_freq, reg_width,
+        )?))
+    }
+
+    fn with_claim(
+        base: NonNull<u8>,
+        clock_freq: u32,
+        reg_width: usize,
+        claim: Option<Arc<Claim>>,
+    ) -> Ns16550<Mmio> {
         let base = Mmio {
             base: base.as_ptr() as usize,
             width: reg_width,
         };
 
+        let pending_lsr = Arc::new(AtomicU8::new(0));
+        let tx_pause = TxPause::default();
+
         Ns16550 {
             base: base.clone(),
             clock_freq,
-            irq: Some(Ns16550IrqHandler { base: base.clone() }),
+            variant: None,
+            fcr: super::FifoControlFlags::empty(),
+            shadow: None,
+            pending_lsr: pending_lsr.clone(),
+            irq: Some(Ns16550IrqHandler {
+                base: base.clone(),
+                pending_lsr: pending_lsr.clone(),
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            tx_pause: tx_pause.clone(),
             tx: Some(crate::Sender::Ns16550MmioSender(Ns16550Sender {
                 base: base.clone(),
+                stats: None,
+                tx_pause,
+                _claim: claim.clone(),
             })),
             rx: Some(crate::Reciever::Ns16550MmioReciever(Ns16550Reciever {
                 base,
+                pending_lsr,
+                stats: None,
+                _claim: claim.clone(),
             })),
+            claim,
         }
     }
 
-    pub fn new_mmio_boxed(base: NonNull<u8>, clock_freq: u32, reg_width: usize) -> BSerial {
-        SerialDyn::new_boxed(Ns16550::new_mmio(base, clock_freq, reg_width))
-    }
-
     pub fn take_tx(&mut self) -> Option<crate::Sender> {
         self.tx.take()
     }
@@ -66,4 +119,22 @@ impl Ns16550<Mmio> {
     pub fn take_rx(&mut self) -> Option<crate::Reciever> {
         self.rx.take()
     }
+
+    /// 取出静态类型的收发端，读写完全单态化，不经过枚举分发
+    ///
+    /// 任一端已被取出时返回 `None`，另一端留在驱动中。收发端可以用 `.into()` 转换为
+    /// [`crate::Sender`]/[`crate::Reciever`] 后通过 `set_tx`/`set_rx` 归还。
+    pub fn split(&mut self) -> Option<(Ns16550Sender<Mmio>, Ns16550Reciever<Mmio>)> {
+        match (self.tx.take(), self.rx.take()) {
+            (
+                Some(crate::Sender::Ns16550MmioSender(tx)),
+                Some(crate::Reciever::Ns16550MmioReciever(rx)),
+            ) => Some((tx, rx)),
+            (tx, rx) => {
+                self.tx = tx;
+                self.rx = rx;
+                None
+            }
+        }
+    }
 }
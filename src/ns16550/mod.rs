@@ -7,10 +7,12 @@
 // 公共寄存器定义
 mod registers;
 
-use bitflags::Flags;
+use alloc::sync::Arc;
+use bitflags::{bitflags, Flags};
+use core::sync::atomic::{AtomicU8, Ordering};
 use rdif_serial::{
     Config, ConfigError, DataBits, InterfaceRaw, InterruptMask, Parity, SetBackError, StopBits,
-    TIrqHandler, TSender, TransferError,
+    TIrqHandler, TReciever, TSender, TransferError,
 };
 use registers::*;
 
@@ -22,8 +24,23 @@ mod mmio;
 pub use mmio::*;
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 pub use pio::*;
+pub use registers::{LineStatusFlags, ModemStatusFlags};
+
+use crate::{
+    caps::{ALL_DATA_BITS, ALL_PARITY, ALL_STOP_BITS},
+    claim::Claim,
+    fifo::{select_level, FifoError, FifoThreshold, FifoThresholds, FifoTriggers},
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, IrqCause, Stats},
+    Capabilities, Caps, Features, RawReciever, RawSender, Suspend,
+};
+
+/// 等待发送器空闲的最大轮询次数
+const TX_IDLE_SPIN_LIMIT: usize = 100_000;
 
-use crate::{RawReciever, RawSender};
+/// 单次中断处理中读取 IIR 的最大次数，防止异常硬件导致死循环
+const IRQ_LOOP_LIMIT: usize = 16;
 
 pub trait Kind: Clone + Send + Sync + 'static {
     fn read_reg(&self, reg: u8) -> u8;
@@ -40,12 +57,61 @@ pub trait Kind: Clone + Send + Sync + 'static {
     }
 }
 
+/// NS16550 兼容芯片型号
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ns16550Variant {
+    /// 8250/16450，无 FIFO
+    Ns16450,
+    /// 早期 16550，FIFO 不可用
+    Ns16550,
+    /// 16550A，16 字节 FIFO
+    Ns16550A,
+    /// 16750，64 字节 FIFO，支持自动流控
+    Ns16750,
+}
+
+impl Ns16550Variant {
+    /// FIFO 深度（字节），无可用 FIFO 时为 1
+    pub fn fifo_depth(&self) -> u16 {
+        match self {
+            Ns16550Variant::Ns16450 | Ns16550Variant::Ns16550 => 1,
+            Ns16550Variant::Ns16550A => 16,
+            Ns16550Variant::Ns16750 => 64,
+        }
+    }
+
+    pub fn has_fifo(&self) -> bool {
+        self.fifo_depth() > 1
+    }
+}
+
+/// 驱动写入过的寄存器值
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Shadow {
+    /// 不含 DLAB 的 LCR
+    lcr: LineControlFlags,
+    mcr: ModemControlFlags,
+    ier: InterruptEnableFlags,
+    divisor: u16,
+}
+
 pub struct Ns16550<T: Kind> {
     pub(crate) base: T,
     pub(crate) clock_freq: u32,
+    pub(crate) variant: Option<Ns16550Variant>,
+    /// FCR 只写，保存最近一次写入的持久位（不含清空位）
+    pub(crate) fcr: FifoControlFlags,
+    /// LCR/MCR/IER 和除数的影子副本，首次写入或 `open` 时从硬件同步
+    pub(crate) shadow: Option<Shadow>,
+    /// 中断处理清除、尚未由接收端上报的线路错误，与收发句柄共享
+    pub(crate) pending_lsr: Arc<AtomicU8>,
     pub(crate) irq: Option<Ns16550IrqHandler<T>>,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    pub(crate) tx_pause: TxPause,
     pub(crate) tx: Option<crate::Sender>,
     pub(crate) rx: Option<crate::Reciever>,
+    /// 通过 `try_new` 系列创建时持有的区域占用，与各句柄共享
+    pub(crate) claim: Option<Arc<Claim>>,
 }
 
 impl<T: Kind> InterfaceRaw for Ns16550<T> {
@@ -58,35 +124,30 @@ impl<T: Kind> InterfaceRaw for Ns16550<T> {
     }
 
     fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
-        // 配置波特率
-        if let Some(baudrate) = config.baudrate {
-            self.set_baudrate_internal(baudrate)?;
-        }
-
-        // 配置数据位
-        if let Some(data_bits) = config.data_bits {
-            self.set_data_bits_internal(data_bits)?;
+        // 先整体校验并计算出所有寄存器值，校验失败时不写任何寄存器
+        self.capabilities().validate(config)?;
+        let divisor = config
+            .baudrate
+            .map(|baudrate| self.divisor_for(baudrate))
+            .transpose()?;
+
+        let current = self.shadow_mut().lcr;
+        let lcr = Self::line_control_for(current, config);
+
+        // 一次性应用：DLAB 置位 → 写除数 → 单次写入最终 LCR（同时清除 DLAB）
+        if let Some(divisor) = divisor {
+            self.write_flags(UART_LCR, current | LineControlFlags::DIVISOR_LATCH_ACCESS);
+            self.write_reg_u8(UART_DLL, (divisor & 0xFF) as u8);
+            self.write_reg_u8(UART_DLH, (divisor >> 8) as u8);
+            self.shadow_mut().divisor = divisor;
         }
+        self.write_lcr(lcr);
 
-        // 配置停止位
-        if let Some(stop_bits) = config.stop_bits {
-            self.set_stop_bits_internal(stop_bits)?;
-        }
-
-        // 配置奇偶校验
-        if let Some(parity) = config.parity {
-            self.set_parity_internal(parity)?;
-        }
         Ok(())
     }
 
     fn baudrate(&self) -> u32 {
-        // 只读方式获取波特率，通过读取 DLL 和 DLH
-        // 注意：如果 DLAB 未设置，读取的可能不是除数值
-        let dll = self.read_reg_u8(UART_DLL) as u16;
-        let dlh = self.read_reg_u8(UART_DLH) as u16;
-        let divisor = dll | (dlh << 8);
-
+        let divisor = self.current().divisor;
         if divisor == 0 {
             return 0;
         }
@@ -95,7 +156,7 @@ impl<T: Kind> InterfaceRaw for Ns16550<T> {
     }
 
     fn data_bits(&self) -> DataBits {
-        let lcr: LineControlFlags = self.read_flags(UART_LCR);
+        let lcr = self.current().lcr;
         let wlen = lcr & LineControlFlags::WORD_LENGTH_MASK;
         if wlen == LineControlFlags::WORD_LENGTH_5 {
             DataBits::Five
@@ -109,7 +170,7 @@ impl<T: Kind> InterfaceRaw for Ns16550<T> {
     }
 
     fn stop_bits(&self) -> StopBits {
-        let lcr: LineControlFlags = self.read_flags(UART_LCR);
+        let lcr = self.current().lcr;
         if lcr.contains(LineControlFlags::STOP_BITS) {
             StopBits::Two
         } else {
@@ -118,7 +179,7 @@ impl<T: Kind> InterfaceRaw for Ns16550<T> {
     }
 
     fn parity(&self) -> Parity {
-        let lcr: LineControlFlags = self.read_flags(UART_LCR);
+        let lcr = self.current().lcr;
 
         if !lcr.contains(LineControlFlags::PARITY_ENABLE) {
             Parity::None
@@ -149,29 +210,30 @@ impl<T: Kind> InterfaceRaw for Ns16550<T> {
 
     fn close(&mut self) {
         // 禁用所有中断
-        self.write_flags(UART_IER, InterruptEnableFlags::empty());
+        self.write_ier(InterruptEnableFlags::empty());
 
         // 禁用 DTR 和 RTS
-        let mut mcr: ModemControlFlags = self.read_flags(UART_MCR);
+        let mut mcr = self.shadow_mut().mcr;
         mcr.remove(ModemControlFlags::DATA_TERMINAL_READY | ModemControlFlags::REQUEST_TO_SEND);
-        self.write_flags(UART_MCR, mcr);
+        self.write_mcr(mcr);
     }
 
     fn enable_loopback(&mut self) {
-        let mut mcr: ModemControlFlags = self.read_flags(UART_MCR);
+        let mut mcr = self.shadow_mut().mcr;
         mcr.insert(ModemControlFlags::LOOPBACK_ENABLE);
-        self.write_flags(UART_MCR, mcr);
+        self.write_mcr(mcr);
     }
 
     fn disable_loopback(&mut self) {
-        let mut mcr: ModemControlFlags = self.read_flags(UART_MCR);
+        let mut mcr = self.shadow_mut().mcr;
         mcr.remove(ModemControlFlags::LOOPBACK_ENABLE);
-        self.write_flags(UART_MCR, mcr);
+        self.write_mcr(mcr);
     }
 
     fn is_loopback_enabled(&self) -> bool {
-        let mcr: ModemControlFlags = self.read_flags(UART_MCR);
-        mcr.contains(ModemControlFlags::LOOPBACK_ENABLE)
+        self.current()
+            .mcr
+            .contains(ModemControlFlags::LOOPBACK_ENABLE)
     }
 
     fn set_irq_mask(&mut self, mask: InterruptMask) {
@@ -185,11 +247,11 @@ impl<T: Kind> InterfaceRaw for Ns16550<T> {
             ier.insert(InterruptEnableFlags::TRANSMITTER_HOLDING_EMPTY);
         }
 
-        self.write_flags(UART_IER, ier);
+        self.write_ier(ier);
     }
 
     fn get_irq_mask(&self) -> InterruptMask {
-        let ier: InterruptEnableFlags = self.read_flags(UART_IER);
+        let ier = self.current().ier;
         let mut mask = InterruptMask::empty();
 
         if ier.contains(InterruptEnableFlags::RECEIVED_DATA_AVAILABLE) {
@@ -233,7 +295,8 @@ impl<T: Kind> InterfaceRaw for Ns16550<T> {
                 }
             }
             _ => {
-                return Err(SetBackError::new(want, 0)); // 不匹配的类型
+                return Err(SetBackError::new(want, tx.external_base().unwrap_or(0)));
+                // 不匹配的类型
             }
         }
         self.tx = Some(tx);
@@ -257,7 +320,8 @@ impl<T: Kind> InterfaceRaw for Ns16550<T> {
                 }
             }
             _ => {
-                return Err(SetBackError::new(want, 0)); // 不匹配的类型
+                return Err(SetBackError::new(want, rx.external_base().unwrap_or(0)));
+                // 不匹配的类型
             }
         }
         self.rx = Some(rx);
@@ -266,6 +330,11 @@ impl<T: Kind> InterfaceRaw for Ns16550<T> {
 }
 
 impl<T: Kind> Ns16550<T> {
+    /// 通过 `try_new_*` 创建时持有的寄存器区域占用
+    pub fn claim(&self) -> Option<&Claim> {
+        self.claim.as_deref()
+    }
+
     // 基础 u8 寄存器访问（用于除数寄存器等特殊场景）
     fn read_reg_u8(&self, reg: u8) -> u8 {
         self.base.read_reg(reg)
@@ -284,167 +353,476 @@ impl<T: Kind> Ns16550<T> {
         self.base.write_reg(reg, val.bits());
     }
 
-    /// 检查是否为 16550+（支持 FIFO）
-    pub fn is_16550_plus(&self) -> bool {
-        // 通过读取 IIR 寄存器的 FIFO 位来判断
-        // IIR 的位7-6在 16550+ 中会显示 FIFO 启用状态
-        let fifo: InterruptIdentificationFlags = self.read_flags(UART_IIR);
-        fifo.contains(InterruptIdentificationFlags::FIFO_ENABLE_MASK)
+    /// 挂接统计计数器，只对尚未取出的收发和中断句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+        if let Some(irq) = self.irq.as_mut() {
+            irq.stats = Some(stats);
+        }
     }
 
-    /// 设置波特率
-    fn set_baudrate_internal(&mut self, baudrate: u32) -> Result<(), ConfigError> {
-        if baudrate == 0 || self.clock_freq == 0 {
-            return Err(ConfigError::InvalidBaudrate);
+    /// 写 FCR 并记录其中的持久位
+    fn write_fcr(&mut self, fcr: FifoControlFlags) {
+        self.write_flags(UART_FCR, fcr);
+        self.fcr = fcr
+            - (FifoControlFlags::CLEAR_RECEIVER_FIFO | FifoControlFlags::CLEAR_TRANSMITTER_FIFO);
+    }
+
+    /// 从硬件读取影子寄存器的当前值
+    ///
+    /// 除数在 DLAB=1 时读取，IER 在 DLAB=0 时读取，结束后恢复原来的 LCR。
+    fn read_hw_shadow(&self) -> Shadow {
+        let raw: LineControlFlags = self.read_flags(UART_LCR);
+        let lcr = raw - LineControlFlags::DIVISOR_LATCH_ACCESS;
+
+        self.base
+            .write_flags(UART_LCR, lcr | LineControlFlags::DIVISOR_LATCH_ACCESS);
+        let dll = self.read_reg_u8(UART_DLL) as u16;
+        let dlh = self.read_reg_u8(UART_DLH) as u16;
+        self.base.write_flags(UART_LCR, lcr);
+        let ier = self.read_flags(UART_IER);
+        let mcr = self.read_flags(UART_MCR);
+        if raw != lcr {
+            self.base.write_flags(UART_LCR, raw);
         }
 
-        let divisor = self.clock_freq / (16 * baudrate);
-        if divisor == 0 || divisor > 0xFFFF {
-            return Err(ConfigError::InvalidBaudrate);
+        Shadow {
+            lcr,
+            mcr,
+            ier,
+            divisor: dll | (dlh << 8),
         }
+    }
+
+    /// 影子寄存器的值，尚未同步时直接读取硬件
+    fn current(&self) -> Shadow {
+        self.shadow.unwrap_or_else(|| self.read_hw_shadow())
+    }
 
-        // 保存原始 LCR
-        let mut lcr: LineControlFlags = self.read_flags(UART_LCR);
+    fn shadow_mut(&mut self) -> &mut Shadow {
+        let shadow = match self.shadow {
+            Some(shadow) => shadow,
+            None => self.read_hw_shadow(),
+        };
+        self.shadow.insert(shadow)
+    }
 
-        // 设置 DLAB 以访问波特率除数寄存器
-        lcr.insert(LineControlFlags::DIVISOR_LATCH_ACCESS);
+    /// 写 LCR（不含 DLAB）并更新影子
+    fn write_lcr(&mut self, lcr: LineControlFlags) {
         self.write_flags(UART_LCR, lcr);
+        self.shadow_mut().lcr = lcr;
+    }
 
-        // 设置除数（使用 u8 方法，因为这是原始数据写入）
-        self.write_reg_u8(UART_DLL, (divisor & 0xFF) as u8);
-        self.write_reg_u8(UART_DLH, ((divisor >> 8) & 0xFF) as u8);
+    fn write_mcr(&mut self, mcr: ModemControlFlags) {
+        self.write_flags(UART_MCR, mcr);
+        self.shadow_mut().mcr = mcr;
+    }
 
-        // 清除 DLAB 位，恢复正常访问
-        lcr.remove(LineControlFlags::DIVISOR_LATCH_ACCESS);
-        self.write_flags(UART_LCR, lcr);
+    fn write_ier(&mut self, ier: InterruptEnableFlags) {
+        self.write_flags(UART_IER, ier);
+        self.shadow_mut().ier = ier;
+    }
 
-        Ok(())
+    /// 芯片型号
+    ///
+    /// `open` 时会探测型号；探测前不读寄存器（读 IIR 会应答挂起的 THRI），
+    /// 根据驱动写入的 FCR 推断，未启用 FIFO 时按 16450 处理。
+    pub fn variant(&self) -> Ns16550Variant {
+        self.variant.unwrap_or(if self.is_fifo_enabled() {
+            Ns16550Variant::Ns16550A
+        } else {
+            Ns16550Variant::Ns16450
+        })
     }
 
-    /// 设置数据位
-    fn set_data_bits_internal(&mut self, bits: DataBits) -> Result<(), ConfigError> {
-        let wlen = match bits {
-            DataBits::Five => LineControlFlags::WORD_LENGTH_5,
-            DataBits::Six => LineControlFlags::WORD_LENGTH_6,
-            DataBits::Seven => LineControlFlags::WORD_LENGTH_7,
-            DataBits::Eight => LineControlFlags::WORD_LENGTH_8,
+    /// 探测芯片型号，参考 Linux 8250 驱动的 autoconfig 流程
+    ///
+    /// 探测会启用 FIFO（16750 上同时启用 64 字节模式），不清空 FIFO 内容，
+    /// 保留之前设置的接收触发级别。
+    fn detect_variant(&mut self) -> Ns16550Variant {
+        let lcr = self.shadow_mut().lcr;
+        let enable =
+            FifoControlFlags::ENABLE_FIFO | (self.fcr & FifoControlFlags::TRIGGER_LEVEL_MASK);
+
+        // 写入 FCR 启用 FIFO，IIR 的 bit 6-7 反映 FIFO 是否可用
+        self.write_fcr(enable);
+        let iir: InterruptIdentificationFlags = self.read_flags(UART_IIR);
+
+        let variant = match (iir & InterruptIdentificationFlags::FIFO_ENABLE_MASK).bits() >> 6 {
+            0 => Ns16550Variant::Ns16450,
+            3 => {
+                // 16750 的 64 字节 FIFO 使能位只有在 DLAB=1 时才能写入
+                let fcr = enable | FifoControlFlags::FIFO_64_BYTES;
+                self.write_flags(UART_LCR, LineControlFlags::empty());
+                self.write_fcr(fcr);
+                let iir1: InterruptIdentificationFlags = self.read_flags(UART_IIR);
+                self.write_flags(UART_LCR, LineControlFlags::DIVISOR_LATCH_ACCESS);
+                self.write_fcr(fcr);
+                let iir2: InterruptIdentificationFlags = self.read_flags(UART_IIR);
+
+                if !iir1.contains(InterruptIdentificationFlags::FIFO_64_BYTES)
+                    && iir2.contains(InterruptIdentificationFlags::FIFO_64_BYTES)
+                {
+                    Ns16550Variant::Ns16750
+                } else {
+                    self.write_fcr(enable);
+                    Ns16550Variant::Ns16550A
+                }
+            }
+            _ => Ns16550Variant::Ns16550,
         };
 
-        let mut lcr: LineControlFlags = self.read_flags(UART_LCR);
-        // 清除旧的数据位设置，然后设置新的
-        lcr.remove(LineControlFlags::WORD_LENGTH_MASK);
-        lcr.insert(wlen);
-        self.write_flags(UART_LCR, lcr);
+        if !variant.has_fifo() {
+            self.write_fcr(FifoControlFlags::empty());
+        }
+        self.write_lcr(lcr);
 
-        Ok(())
+        variant
     }
 
-    /// 设置停止位
-    fn set_stop_bits_internal(&mut self, bits: StopBits) -> Result<(), ConfigError> {
-        let mut lcr: LineControlFlags = self.read_flags(UART_LCR);
-        match bits {
-            StopBits::One => lcr.remove(LineControlFlags::STOP_BITS),
-            StopBits::Two => lcr.insert(LineControlFlags::STOP_BITS),
+    /// 检查是否为 16550+（支持 FIFO），规则同 [`Self::variant`]
+    pub fn is_16550_plus(&self) -> bool {
+        self.variant().has_fifo()
+    }
+
+    /// 计算波特率对应的除数（四舍五入）
+    fn divisor_for(&self, baudrate: u32) -> Result<u16, ConfigError> {
+        if baudrate == 0 || self.clock_freq == 0 {
+            return Err(ConfigError::InvalidBaudrate);
         }
-        self.write_flags(UART_LCR, lcr);
-        Ok(())
+
+        let divisor = (self.clock_freq as u64 + 8 * baudrate as u64) / (16 * baudrate as u64);
+        if divisor == 0 || divisor > 0xFFFF {
+            return Err(ConfigError::InvalidBaudrate);
+        }
+
+        Ok(divisor as u16)
     }
 
-    /// 设置奇偶校验
-    fn set_parity_internal(&mut self, parity: Parity) -> Result<(), ConfigError> {
-        let mut lcr: LineControlFlags = self.read_flags(UART_LCR);
+    /// 在当前 LCR 的基础上计算应用配置后的 LCR，未设置的项保持不变
+    fn line_control_for(mut lcr: LineControlFlags, config: &Config) -> LineControlFlags {
+        // 数据位
+        if let Some(bits) = config.data_bits {
+            let wlen = match bits {
+                DataBits::Five => LineControlFlags::WORD_LENGTH_5,
+                DataBits::Six => LineControlFlags::WORD_LENGTH_6,
+                DataBits::Seven => LineControlFlags::WORD_LENGTH_7,
+                DataBits::Eight => LineControlFlags::WORD_LENGTH_8,
+            };
+            lcr.remove(LineControlFlags::WORD_LENGTH_MASK);
+            lcr.insert(wlen);
+        }
 
-        // 先清除所有校验相关位
-        lcr.remove(
-            LineControlFlags::PARITY_ENABLE
-                | LineControlFlags::EVEN_PARITY
-                | LineControlFlags::STICK_PARITY,
-        );
+        // 停止位
+        match config.stop_bits {
+            Some(StopBits::One) => lcr.remove(LineControlFlags::STOP_BITS),
+            Some(StopBits::Two) => lcr.insert(LineControlFlags::STOP_BITS),
+            None => {}
+        }
 
-        // 根据校验类型设置相应位
-        match parity {
-            Parity::None => {
-                // 已经清除，无需额外操作
-            }
-            Parity::Odd => {
-                lcr.insert(LineControlFlags::PARITY_ENABLE);
-            }
-            Parity::Even => {
-                lcr.insert(LineControlFlags::PARITY_ENABLE | LineControlFlags::EVEN_PARITY);
-            }
-            Parity::Mark => {
-                lcr.insert(LineControlFlags::PARITY_ENABLE | LineControlFlags::STICK_PARITY);
-            }
-            Parity::Space => {
-                lcr.insert(
-                    LineControlFlags::PARITY_ENABLE
-                        | LineControlFlags::EVEN_PARITY
-                        | LineControlFlags::STICK_PARITY,
-                );
+        // 奇偶校验：先清除所有校验相关位，再根据校验类型设置
+        if let Some(parity) = config.parity {
+            lcr.remove(
+                LineControlFlags::PARITY_ENABLE
+                    | LineControlFlags::EVEN_PARITY
+                    | LineControlFlags::STICK_PARITY,
+            );
+            match parity {
+                Parity::None => {}
+                Parity::Odd => {
+                    lcr.insert(LineControlFlags::PARITY_ENABLE);
+                }
+                Parity::Even => {
+                    lcr.insert(LineControlFlags::PARITY_ENABLE | LineControlFlags::EVEN_PARITY);
+                }
+                Parity::Mark => {
+                    lcr.insert(LineControlFlags::PARITY_ENABLE | LineControlFlags::STICK_PARITY);
+                }
+                Parity::Space => {
+                    lcr.insert(
+                        LineControlFlags::PARITY_ENABLE
+                            | LineControlFlags::EVEN_PARITY
+                            | LineControlFlags::STICK_PARITY,
+                    );
+                }
             }
         }
 
-        self.write_flags(UART_LCR, lcr);
-        Ok(())
+        lcr
     }
 
     /// 启用或禁用 FIFO
     pub fn enable_fifo(&mut self, enable: bool) {
-        if enable && self.is_16550_plus() {
+        // 探测前型号未知，16450 会忽略 FCR 写入，直接写入即可
+        if enable && self.variant.is_none_or(|v| v.has_fifo()) {
             let mut fcr = FifoControlFlags::ENABLE_FIFO;
             fcr.insert(FifoControlFlags::CLEAR_RECEIVER_FIFO);
             fcr.insert(FifoControlFlags::CLEAR_TRANSMITTER_FIFO);
             fcr.insert(FifoControlFlags::TRIGGER_1_BYTE);
-            self.write_flags(UART_FCR, fcr);
+            self.write_fcr(fcr);
         } else {
-            self.write_flags(UART_FCR, FifoControlFlags::empty());
+            self.write_fcr(FifoControlFlags::empty());
         }
     }
 
-    /// 设置 FIFO 触发级别
-    pub fn set_fifo_trigger_level(&mut self, level: u8) {
-        if !self.is_16550_plus() {
-            return;
+    /// 等待发送保持寄存器和移位寄存器都为空（LSR.TEMT）
+    fn wait_tx_idle(&self) -> bool {
+        for _ in 0..TX_IDLE_SPIN_LIMIT {
+            let lsr: LineStatusFlags = self.read_flags(UART_LSR);
+            if lsr.contains(LineStatusFlags::TRANSMITTER_EMPTY) {
+                return true;
+            }
+            core::hint::spin_loop();
         }
-
-        let trigger_value = match level {
-            0..=3 => FifoControlFlags::TRIGGER_1_BYTE,
-            4..=7 => FifoControlFlags::TRIGGER_4_BYTES,
-            8..=11 => FifoControlFlags::TRIGGER_8_BYTES,
-            _ => FifoControlFlags::TRIGGER_14_BYTES,
-        };
-
-        // 读取当前 FCR 设置，清除触发级别位，然后设置新的触发级别
-        let mut fcr: FifoControlFlags = self.read_flags(UART_FCR);
-        fcr.remove(FifoControlFlags::TRIGGER_LEVEL_MASK);
-        fcr.insert(trigger_value);
-        self.write_flags(UART_FCR, fcr);
+        false
     }
 
     /// 初始化 UART
     fn init(&mut self) {
+        // 重新同步影子寄存器，固件或其他软件可能改过配置
+        self.shadow = Some(self.read_hw_shadow());
+
         // 禁用所有中断
-        self.write_flags(UART_IER, InterruptEnableFlags::empty());
+        self.write_ier(InterruptEnableFlags::empty());
+
+        // 等待发送器空闲后再探测型号，切换 FIFO 使能会丢弃未发出的数据
+        self.wait_tx_idle();
+        self.variant = Some(self.detect_variant());
 
         // 确保传输器启用（设置 DTR 和 RTS）
-        let mut mcr: ModemControlFlags = self.read_flags(UART_MCR);
+        let mut mcr = self.shadow_mut().mcr;
         mcr.insert(ModemControlFlags::DATA_TERMINAL_READY | ModemControlFlags::REQUEST_TO_SEND);
-        self.write_flags(UART_MCR, mcr);
+        self.write_mcr(mcr);
     }
 
     /// 检查 FIFO 是否启用
+    ///
+    /// FCR 只写，返回驱动最近一次写入的状态。
     pub fn is_fifo_enabled(&self) -> bool {
-        if !self.is_16550_plus() {
-            return false;
+        self.fcr.contains(FifoControlFlags::ENABLE_FIFO)
+    }
+}
+
+impl<T: Kind> Caps for Ns16550<T> {
+    fn capabilities(&self) -> Capabilities {
+        let variant = self.variant();
+        let (min_baudrate, max_baudrate) =
+            Capabilities::baudrate_range(self.clock_freq, 16, 0xFFFF);
+
+        let mut features = Features::LOOPBACK;
+        if variant.has_fifo() {
+            features |= Features::DMA;
+        }
+        if variant == Ns16550Variant::Ns16750 {
+            features |= Features::HW_FLOW_CONTROL;
+        }
+
+        Capabilities {
+            fifo_depth: self.fifo_variant().fifo_depth(),
+            min_baudrate,
+            max_baudrate,
+            data_bits: ALL_DATA_BITS,
+            stop_bits: ALL_STOP_BITS,
+            parity: ALL_PARITY,
+            features,
+        }
+    }
+}
+
+/// 16550A 的接收触发档位，对应 FCR bit 6-7
+const RX_TRIGGER_LEVELS: [u16; 4] = [1, 4, 8, 14];
+
+/// 16750 启用 64 字节 FIFO 后的接收触发档位
+const RX_TRIGGER_LEVELS_64: [u16; 4] = [1, 16, 32, 56];
+
+const RX_TRIGGER_FLAGS: [FifoControlFlags; 4] = [
+    FifoControlFlags::TRIGGER_1_BYTE,
+    FifoControlFlags::TRIGGER_4_BYTES,
+    FifoControlFlags::TRIGGER_8_BYTES,
+    FifoControlFlags::TRIGGER_14_BYTES,
+];
+
+impl<T: Kind> Ns16550<T> {
+    /// FIFO 相关请求按此型号校验：探测前按 16550A 处理，`open` 时再按实际型号应用
+    fn fifo_variant(&self) -> Ns16550Variant {
+        self.variant.unwrap_or(Ns16550Variant::Ns16550A)
+    }
+
+    /// 当前 FIFO 模式下的接收触发档位，无 FIFO 时为 `None`
+    fn rx_trigger_levels(&self) -> Option<&'static [u16; 4]> {
+        let variant = self.fifo_variant();
+        if !variant.has_fifo() {
+            None
+        } else if variant == Ns16550Variant::Ns16750
+            && self.fcr.contains(FifoControlFlags::FIFO_64_BYTES)
+        {
+            Some(&RX_TRIGGER_LEVELS_64)
+        } else {
+            Some(&RX_TRIGGER_LEVELS)
+        }
+    }
+}
+
+/// 16550A/16750 只有接收触发级别可配置，发送中断固定在 FIFO 空时触发，
+/// 接收超时固定为 4 个字符时间
+impl<T: Kind> FifoThreshold for Ns16550<T> {
+    fn fifo_triggers(&self) -> FifoTriggers {
+        let rx = self.rx_trigger_levels().map(|levels| {
+            let index = (self.fcr & FifoControlFlags::TRIGGER_LEVEL_MASK).bits() >> 6;
+            levels[index as usize]
+        });
+
+        FifoTriggers {
+            rx,
+            tx: None,
+            rx_timeout: None,
         }
-        // 通过检查 IIR 的 FIFO 位来判断
-        let iir: InterruptIdentificationFlags = self.read_flags(UART_IIR);
-        iir.contains(InterruptIdentificationFlags::FIFO_ENABLE_MASK)
+    }
+
+    fn set_fifo_thresholds(&mut self, req: &FifoThresholds) -> Result<FifoTriggers, FifoError> {
+        let req = req.resolve(self.fifo_variant().fifo_depth(), &self.fifo_triggers())?;
+
+        if let (Some(rx), Some(levels)) = (req.rx, self.rx_trigger_levels()) {
+            let level = select_level(levels, rx);
+            let index = levels.iter().position(|&l| l == level).unwrap_or(0);
+
+            // FCR 只写，在影子上替换触发级别位，清空位不会被重复写入；
+            // 探测前 FIFO 未启用，硬件忽略触发级别，由 `open` 从影子带入
+            let mut fcr = self.fcr;
+            fcr.remove(FifoControlFlags::TRIGGER_LEVEL_MASK);
+            fcr.insert(RX_TRIGGER_FLAGS[index]);
+            self.write_fcr(fcr);
+        }
+
+        Ok(self.fifo_triggers())
+    }
+}
+
+/// 启用 FIFO 时通过 FCR 的清空位丢弃数据；无 FIFO 时逐字节读空接收缓冲，
+/// 发送保持寄存器中至多一个字节，无需清空
+impl<T: Kind> Flush for Ns16550<T> {
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError> {
+        if self.is_fifo_enabled() {
+            let mut fcr = self.fcr;
+            if queue.rx() {
+                fcr.insert(FifoControlFlags::CLEAR_RECEIVER_FIFO);
+            }
+            if queue.tx() {
+                fcr.insert(FifoControlFlags::CLEAR_TRANSMITTER_FIFO);
+            }
+            self.write_fcr(fcr);
+        } else if queue.rx() {
+            for _ in 0..TX_IDLE_SPIN_LIMIT {
+                let lsr: LineStatusFlags = self.read_flags(UART_LSR);
+                if !lsr.contains(LineStatusFlags::DATA_READY) {
+                    break;
+                }
+                self.read_reg_u8(UART_RBR);
+            }
+        }
+
+        if queue.rx() {
+            // 清空 FIFO 不影响 LSR 中的错误位，读取一次将其清除
+            let _: LineStatusFlags = self.read_flags(UART_LSR);
+            self.pending_lsr.store(0, Ordering::Release);
+        }
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), FlushError> {
+        if self.wait_tx_idle() {
+            Ok(())
+        } else {
+            Err(FlushError::Timeout)
+        }
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// NS16550 挂起时保存的寄存器上下文
+#[derive(Debug, Clone, Copy)]
+pub struct Ns16550Context {
+    divisor: u16,
+    lcr: u8,
+    fcr: u8,
+    ier: u8,
+    mcr: u8,
+    scr: u8,
+}
+
+impl<T: Kind> Suspend for Ns16550<T> {
+    type Context = Ns16550Context;
+
+    fn save_context(&self) -> Self::Context {
+        let shadow = self.current();
+        Ns16550Context {
+            divisor: shadow.divisor,
+            lcr: shadow.lcr.bits(),
+            fcr: self.fcr.bits(),
+            ier: shadow.ier.bits(),
+            mcr: shadow.mcr.bits(),
+            scr: self.read_reg_u8(UART_SCR),
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        // 恢复期间屏蔽中断
+        self.write_flags(UART_IER, InterruptEnableFlags::empty());
+
+        // DLAB=1 时写除数；16750 的 64 字节 FIFO 位也只能在 DLAB=1 时写入
+        let lcr = LineControlFlags::from_bits_retain(ctx.lcr);
+        self.write_flags(UART_LCR, lcr | LineControlFlags::DIVISOR_LATCH_ACCESS);
+        self.write_reg_u8(UART_DLL, (ctx.divisor & 0xFF) as u8);
+        self.write_reg_u8(UART_DLH, (ctx.divisor >> 8) as u8);
+        let fcr = FifoControlFlags::from_bits_retain(ctx.fcr);
+        if fcr.contains(FifoControlFlags::ENABLE_FIFO) {
+            // 同时清空 FIFO，丢弃上电过程中的残留数据
+            self.write_fcr(
+                fcr | FifoControlFlags::CLEAR_RECEIVER_FIFO
+                    | FifoControlFlags::CLEAR_TRANSMITTER_FIFO,
+            );
+        } else {
+            self.write_fcr(FifoControlFlags::empty());
+        }
+
+        // 单次写入 LCR，同时清除 DLAB
+        self.write_flags(UART_LCR, lcr);
+
+        self.write_reg_u8(UART_MCR, ctx.mcr);
+        self.write_reg_u8(UART_SCR, ctx.scr);
+
+        // 最后恢复中断使能
+        self.write_reg_u8(UART_IER, ctx.ier);
+
+        // 恢复后的硬件状态就是上下文中的值
+        self.shadow = Some(Shadow {
+            lcr,
+            mcr: ModemControlFlags::from_bits_retain(ctx.mcr),
+            ier: InterruptEnableFlags::from_bits_retain(ctx.ier),
+            divisor: ctx.divisor,
+        });
     }
 }
 
 pub struct Ns16550Sender<T: Kind> {
     pub(crate) base: T,
+    pub(crate) stats: Option<&'static Stats>,
+    pub(crate) tx_pause: TxPause,
+    pub(crate) _claim: Option<Arc<Claim>>,
+}
+
+impl<T: Kind> AttachStats for Ns16550Sender<T> {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
 }
 
 impl<T: Kind> TSender for Ns16550Sender<T> {
@@ -455,11 +833,39 @@ impl<T: Kind> TSender for Ns16550Sender<T> {
 
 pub struct Ns16550Reciever<T: Kind> {
     pub(crate) base: T,
+    /// 中断处理读取 LSR 时清除的错误位，留给下一次读取上报
+    pub(crate) pending_lsr: Arc<AtomicU8>,
+    pub(crate) stats: Option<&'static Stats>,
+    pub(crate) _claim: Option<Arc<Claim>>,
+}
+
+impl<T: Kind> AttachStats for Ns16550Reciever<T> {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl<T: Kind> TReciever for Ns16550Reciever<T> {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        RawReciever::read_byte(self)
+    }
 }
 
 impl<T: Kind> RawReciever for Ns16550Reciever<T> {
     fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        let result = self.read_raw()?;
+        if let Some(stats) = self.stats {
+            stats.record_rx(&result);
+        }
+        Some(result)
+    }
+}
+
+impl<T: Kind> Ns16550Reciever<T> {
+    fn read_raw(&self) -> Option<Result<u8, TransferError>> {
         let lsr: LineStatusFlags = self.base.read_flags(UART_LSR);
+        let lsr =
+            lsr | LineStatusFlags::from_bits_retain(self.pending_lsr.swap(0, Ordering::AcqRel));
 
         // 按优先级检查错误（从高到低）
         if lsr.contains(LineStatusFlags::OVERRUN_ERROR) {
@@ -490,49 +896,531 @@ impl<T: Kind> RawReciever for Ns16550Reciever<T> {
     }
 }
 
-pub struct Ns16550IrqHandler<T: Kind> {
-    pub(crate) base: T,
+bitflags! {
+    /// 一次中断处理中出现过的中断源
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Ns16550IrqCauses: u8 {
+        /// 接收线路状态（RLSI），已通过读取 LSR 应答
+        const LINE_STATUS = 0x01;
+        /// 接收数据可用（RDI），读取数据后才会清除
+        const RX_DATA = 0x02;
+        /// 字符超时（CTI），读取数据后才会清除
+        const RX_TIMEOUT = 0x04;
+        /// 发送保持寄存器空（THRI），已通过读取 IIR 应答
+        const TX_EMPTY = 0x08;
+        /// 调制解调器状态（MSI），已通过读取 MSR 应答
+        const MODEM_STATUS = 0x10;
+    }
 }
 
-impl<T: Kind> TIrqHandler for Ns16550IrqHandler<T> {
-    fn clean_interrupt_status(&self) -> InterruptMask {
-        let iir: InterruptIdentificationFlags = self.base.read_flags(UART_IIR);
-        let mut mask = InterruptMask::empty();
+/// 中断处理结果，包含应答时读到的 LSR/MSR
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ns16550IrqStatus {
+    pub causes: Ns16550IrqCauses,
+    /// 处理 RLSI 时读到的 LSR，多次读取的结果按位合并
+    pub lsr: Option<LineStatusFlags>,
+    /// 处理 MSI 时读到的 MSR，多次读取的结果按位合并
+    pub msr: Option<ModemStatusFlags>,
+}
 
-        // 检查是否有中断挂起
-        if iir.contains(InterruptIdentificationFlags::NO_INTERRUPT_PENDING) {
-            return mask;
+impl Ns16550IrqStatus {
+    /// 转换为通用中断掩码
+    pub fn mask(&self) -> InterruptMask {
+        let mut mask = InterruptMask::empty();
+        if self.causes.intersects(
+            Ns16550IrqCauses::LINE_STATUS
+                | Ns16550IrqCauses::RX_DATA
+                | Ns16550IrqCauses::RX_TIMEOUT,
+        ) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if self.causes.contains(Ns16550IrqCauses::TX_EMPTY) {
+            mask |= InterruptMask::TX_EMPTY;
         }
+        mask
+    }
+}
+
+pub struct Ns16550IrqHandler<T: Kind> {
+    pub(crate) base: T,
+    pub(crate) pending_lsr: Arc<AtomicU8>,
+    pub(crate) stats: Option<&'static Stats>,
+    pub(crate) _claim: Option<Arc<Claim>>,
+}
 
-        // 获取中断ID（需要提取bit 1-3）
-        let interrupt_id = iir & InterruptIdentificationFlags::INTERRUPT_ID_MASK;
+impl<T: Kind> Ns16550IrqHandler<T> {
+    /// 循环读取 IIR 直到没有挂起的中断，逐个应答中断源
+    ///
+    /// 接收数据只能由接收端读走，遇到 RDI/CTI 时停止循环；
+    /// 优先级更低的中断源会在数据读空后再次触发中断。
+    /// RLSI 读取 LSR 时清除的错误位会转交接收端，下一次读取时照常上报。
+    pub fn handle_irq(&self) -> Ns16550IrqStatus {
+        let mut status = Ns16550IrqStatus::default();
+
+        for _ in 0..IRQ_LOOP_LIMIT {
+            let iir: InterruptIdentificationFlags = self.base.read_flags(UART_IIR);
+            if iir.contains(InterruptIdentificationFlags::NO_INTERRUPT_PENDING) {
+                break;
+            }
 
-        // 使用精确匹配而不是 contains
-        if interrupt_id == InterruptIdentificationFlags::RECEIVER_LINE_STATUS
-            || interrupt_id == InterruptIdentificationFlags::RECEIVED_DATA_AVAILABLE
-            || interrupt_id == InterruptIdentificationFlags::CHARACTER_TIMEOUT
-        {
-            // 接收数据可用中断或字符超时中断
-            mask |= InterruptMask::RX_AVAILABLE;
-        } else if interrupt_id == InterruptIdentificationFlags::TRANSMITTER_HOLDING_EMPTY {
-            // 发送保持寄存器空中断
-            mask |= InterruptMask::TX_EMPTY;
-        } else if interrupt_id == InterruptIdentificationFlags::MODEM_STATUS {
-            // Modem 状态中断
+            let interrupt_id = iir & InterruptIdentificationFlags::INTERRUPT_ID_MASK;
+            let (cause, stop) =
+                if interrupt_id == InterruptIdentificationFlags::RECEIVER_LINE_STATUS {
+                    let lsr: LineStatusFlags = self.base.read_flags(UART_LSR);
+                    self.pending_lsr
+                        .fetch_or((lsr & LineStatusFlags::ERROR_MASK).bits(), Ordering::AcqRel);
+                    status.lsr = Some(status.lsr.map_or(lsr, |old| old | lsr));
+                    (Ns16550IrqCauses::LINE_STATUS, false)
+                } else if interrupt_id == InterruptIdentificationFlags::RECEIVED_DATA_AVAILABLE {
+                    (Ns16550IrqCauses::RX_DATA, true)
+                } else if interrupt_id == InterruptIdentificationFlags::CHARACTER_TIMEOUT {
+                    (Ns16550IrqCauses::RX_TIMEOUT, true)
+                } else if interrupt_id == InterruptIdentificationFlags::TRANSMITTER_HOLDING_EMPTY {
+                    // 读取 IIR 已清除 THRI
+                    (Ns16550IrqCauses::TX_EMPTY, false)
+                } else if interrupt_id == InterruptIdentificationFlags::MODEM_STATUS {
+                    let msr: ModemStatusFlags = self.base.read_flags(UART_MSR);
+                    status.msr = Some(status.msr.map_or(msr, |old| old | msr));
+                    (Ns16550IrqCauses::MODEM_STATUS, false)
+                } else {
+                    // 未知中断源（如 DesignWare 的忙检测）无法在这里应答
+                    break;
+                };
+
+            status.causes |= cause;
+            if let Some(stats) = self.stats {
+                stats.record_irq(match cause {
+                    Ns16550IrqCauses::LINE_STATUS => IrqCause::LineStatus,
+                    Ns16550IrqCauses::RX_DATA => IrqCause::Rx,
+                    Ns16550IrqCauses::RX_TIMEOUT => IrqCause::RxTimeout,
+                    Ns16550IrqCauses::TX_EMPTY => IrqCause::Tx,
+                    _ => IrqCause::ModemStatus,
+                });
+            }
+            if stop {
+                break;
+            }
         }
 
-        mask
+        status
+    }
+}
+
+impl<T: Kind> TIrqHandler for Ns16550IrqHandler<T> {
+    fn clean_interrupt_status(&self) -> InterruptMask {
+        self.handle_irq().mask()
     }
 }
 
 impl<T: Kind> RawSender for Ns16550Sender<T> {
     fn write_byte(&mut self, byte: u8) -> bool {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return false;
+        };
         let lsr: LineStatusFlags = self.base.read_flags(UART_LSR);
         if lsr.contains(LineStatusFlags::TRANSMITTER_HOLDING_EMPTY) {
             self.base.write_reg(UART_THR, byte);
+            if let Some(stats) = self.stats {
+                stats.record_tx(1);
+            }
             true
         } else {
             false
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::{collections::VecDeque, sync::Mutex};
+
+    use super::*;
+    use crate::{ConfigWhen, FifoLevel, ReconfigError, Reconfigure, WithFifoThresholds};
+
+    #[derive(Default)]
+    struct FakeState {
+        ier: u8,
+        lcr: u8,
+        mcr: u8,
+        dll: u8,
+        dlh: u8,
+        /// 最近一次写入 FCR 的值
+        fcr: u8,
+        lsr_errors: u8,
+        rx: VecDeque<u8>,
+        thri: bool,
+        msr_delta: u8,
+        /// 模拟 16550A：启用 FIFO 后 IIR 的 bit 6-7 置位
+        has_fifo: bool,
+    }
+
+    /// 按 16550 的优先级和清除规则模拟 IIR/LSR/MSR，以及 DLAB 的寄存器切换
+    #[derive(Clone, Default)]
+    struct FakeUart(Arc<Mutex<FakeState>>);
+
+    impl Kind for FakeUart {
+        fn read_reg(&self, reg: u8) -> u8 {
+            let mut s = self.0.lock().unwrap();
+            let dlab = s.lcr & UART_LCR_DLAB != 0;
+            match reg {
+                UART_DLL if dlab => s.dll,
+                UART_DLH if dlab => s.dlh,
+                UART_RBR => s.rx.pop_front().unwrap_or(0),
+                UART_IER => s.ier,
+                UART_LCR => s.lcr,
+                UART_MCR => s.mcr,
+                UART_IIR => {
+                    let fifo = if s.has_fifo && s.fcr & UART_FCR_ENABLE_FIFO != 0 {
+                        UART_IIR_FIFO_ENABLE
+                    } else {
+                        0
+                    };
+                    let id = if s.lsr_errors != 0 && s.ier & UART_IER_RLSI != 0 {
+                        UART_IIR_RLSI
+                    } else if !s.rx.is_empty() && s.ier & UART_IER_RDI != 0 {
+                        UART_IIR_RDI
+                    } else if s.thri && s.ier & UART_IER_THRI != 0 {
+                        s.thri = false;
+                        UART_IIR_THRI
+                    } else if s.msr_delta != 0 && s.ier & UART_IER_MSI != 0 {
+                        UART_IIR_MSI
+                    } else {
+                        UART_IIR_NO_INT
+                    };
+                    id | fifo
+                }
+                UART_LSR => {
+                    let dr = if s.rx.is_empty() { 0 } else { UART_LSR_DR };
+                    core::mem::take(&mut s.lsr_errors) | dr | UART_LSR_THRE | UART_LSR_TEMT
+                }
+                UART_MSR => core::mem::take(&mut s.msr_delta),
+                _ => 0,
+            }
+        }
+
+        fn write_reg(&self, reg: u8, val: u8) {
+            let mut s = self.0.lock().unwrap();
+            let dlab = s.lcr & UART_LCR_DLAB != 0;
+            match reg {
+                UART_DLL if dlab => s.dll = val,
+                UART_DLH if dlab => s.dlh = val,
+                UART_IER => s.ier = val,
+                UART_FCR => {
+                    if val & UART_FCR_CLEAR_RCVR != 0 {
+                        s.rx.clear();
+                    }
+                    s.fcr = val;
+                }
+                UART_LCR => s.lcr = val,
+                UART_MCR => s.mcr = val,
+                _ => {}
+            }
+        }
+
+        fn get_base(&self) -> usize {
+            0
+        }
+    }
+
+    fn handles(uart: &FakeUart) -> (Ns16550IrqHandler<FakeUart>, Ns16550Reciever<FakeUart>) {
+        let pending_lsr = Arc::new(AtomicU8::new(0));
+        (
+            Ns16550IrqHandler {
+                base: uart.clone(),
+                pending_lsr: pending_lsr.clone(),
+                stats: None,
+                _claim: None,
+            },
+            Ns16550Reciever {
+                base: uart.clone(),
+                pending_lsr,
+                stats: None,
+                _claim: None,
+            },
+        )
+    }
+
+    #[test]
+    fn irq_services_all_causes() {
+        let uart = FakeUart::default();
+        {
+            let mut s = uart.0.lock().unwrap();
+            s.ier = UART_IER_RDI | UART_IER_RLSI | UART_IER_THRI | UART_IER_MSI;
+            s.lsr_errors = UART_LSR_OE;
+            s.thri = true;
+            s.msr_delta = UART_MSR_DCTS;
+        }
+        let (irq, _) = handles(&uart);
+
+        let status = irq.handle_irq();
+        assert_eq!(
+            status.causes,
+            Ns16550IrqCauses::LINE_STATUS
+                | Ns16550IrqCauses::TX_EMPTY
+                | Ns16550IrqCauses::MODEM_STATUS
+        );
+        assert!(status.lsr.unwrap().contains(LineStatusFlags::OVERRUN_ERROR));
+        assert_eq!(status.msr, Some(ModemStatusFlags::DELTA_CLEAR_TO_SEND));
+        assert_eq!(
+            status.mask().bits(),
+            (InterruptMask::RX_AVAILABLE | InterruptMask::TX_EMPTY).bits()
+        );
+
+        // 所有中断源都已应答
+        assert_eq!(uart.read_reg(UART_IIR), UART_IIR_NO_INT);
+    }
+
+    #[test]
+    fn irq_leaves_rx_data_for_reciever() {
+        let uart = FakeUart::default();
+        {
+            let mut s = uart.0.lock().unwrap();
+            s.ier = UART_IER_RDI | UART_IER_RLSI;
+            s.lsr_errors = UART_LSR_PE;
+            s.rx.extend([0x55, 0xaa]);
+        }
+        let (irq, mut rx) = handles(&uart);
+
+        let status = irq.handle_irq();
+        assert_eq!(
+            status.causes,
+            Ns16550IrqCauses::LINE_STATUS | Ns16550IrqCauses::RX_DATA
+        );
+        assert_eq!(uart.0.lock().unwrap().rx.len(), 2);
+
+        // 中断处理清除的校验错误仍由接收端上报
+        assert_eq!(
+            TReciever::read_byte(&mut rx),
+            Some(Err(TransferError::Parity))
+        );
+        assert_eq!(TReciever::read_byte(&mut rx), Some(Ok(0xaa)));
+        assert_eq!(TReciever::read_byte(&mut rx), None);
+        assert_eq!(uart.read_reg(UART_IIR), UART_IIR_NO_INT);
+    }
+
+    fn uart(fake: &FakeUart) -> Ns16550<FakeUart> {
+        Ns16550 {
+            base: fake.clone(),
+            clock_freq: 1_843_200,
+            variant: None,
+            fcr: FifoControlFlags::empty(),
+            shadow: None,
+            pending_lsr: Arc::new(AtomicU8::new(0)),
+            irq: None,
+            tx_pause: TxPause::default(),
+            tx: None,
+            rx: None,
+            claim: None,
+        }
+    }
+
+    #[test]
+    fn getters_use_programmed_values() {
+        let fake = FakeUart::default();
+        {
+            // 固件留下的配置：9600 8N1，IER 中有数据
+            let mut s = fake.0.lock().unwrap();
+            s.lcr = UART_LCR_WLEN8;
+            s.dll = 12;
+            s.ier = UART_IER_RDI;
+        }
+        let mut uart = uart(&fake);
+
+        // 未同步时通过 DLAB 切换读取除数
+        assert_eq!(uart.baudrate(), 9600);
+        assert_eq!(fake.0.lock().unwrap().lcr, UART_LCR_WLEN8);
+
+        let config = Config::new().baudrate(115200).parity(Parity::Even);
+        uart.set_config(&config).unwrap();
+        assert_eq!(uart.baudrate(), 115200);
+        assert_eq!(uart.parity(), Parity::Even);
+        assert_eq!(
+            uart.get_irq_mask().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+
+        // 读回影子，而不是把 RBR/IER 当作除数
+        fake.0.lock().unwrap().rx.push_back(0xff);
+        assert_eq!(uart.baudrate(), 115200);
+    }
+
+    #[test]
+    fn fifo_trigger_uses_fcr_shadow() {
+        let fake = FakeUart::default();
+        let mut uart = uart(&fake);
+        uart.variant = Some(Ns16550Variant::Ns16550A);
+
+        uart.write_fcr(FifoControlFlags::ENABLE_FIFO | FifoControlFlags::CLEAR_RECEIVER_FIFO);
+        assert!(uart.is_fifo_enabled());
+
+        // 在影子上修改触发级别，清空位不会被重复写入
+        let applied = uart
+            .set_fifo_thresholds(&FifoThresholds::new().rx(FifoLevel::Bytes(10)))
+            .unwrap();
+        assert_eq!(applied.rx, Some(8));
+        assert_eq!(
+            fake.0.lock().unwrap().fcr,
+            (FifoControlFlags::ENABLE_FIFO | FifoControlFlags::TRIGGER_8_BYTES).bits()
+        );
+
+        // 只有接收触发级别可配置
+        assert_eq!(
+            uart.set_fifo_thresholds(&FifoThresholds::new().tx(FifoLevel::Bytes(4))),
+            Err(FifoError::TxUnsupported)
+        );
+
+        // 16750 的 64 字节模式使用另一组档位
+        uart.variant = Some(Ns16550Variant::Ns16750);
+        uart.write_fcr(FifoControlFlags::ENABLE_FIFO | FifoControlFlags::FIFO_64_BYTES);
+        let applied = uart
+            .set_fifo_thresholds(&FifoThresholds::new().rx(FifoLevel::Fraction(1, 2)))
+            .unwrap();
+        assert_eq!(applied.rx, Some(32));
+    }
+
+    #[test]
+    fn thresholds_set_before_open_survive_probe() {
+        let fake = FakeUart::default();
+        fake.0.lock().unwrap().has_fifo = true;
+        let mut uart = uart(&fake);
+
+        // 探测前按 16550A 校验，请求不会被当作 16450 拒绝
+        assert_eq!(uart.capabilities().fifo_depth, 16);
+        let applied = uart
+            .set_fifo_thresholds(&FifoThresholds::new().rx(FifoLevel::Bytes(8)))
+            .unwrap();
+        assert_eq!(applied.rx, Some(8));
+
+        uart.open();
+        assert_eq!(uart.variant(), Ns16550Variant::Ns16550A);
+        assert_eq!(uart.fifo_triggers().rx, Some(8));
+        assert_eq!(
+            fake.0.lock().unwrap().fcr & FifoControlFlags::TRIGGER_LEVEL_MASK.bits(),
+            FifoControlFlags::TRIGGER_8_BYTES.bits()
+        );
+    }
+
+    #[test]
+    fn wrapper_accepts_unopened_ns16550() {
+        let fake = FakeUart::default();
+        fake.0.lock().unwrap().has_fifo = true;
+        let req = FifoThresholds::new().rx(FifoLevel::Bytes(14));
+        let mut wrapped = WithFifoThresholds::new(uart(&fake), req).unwrap();
+
+        wrapped.open();
+        assert_eq!(wrapped.inner().fifo_triggers().rx, Some(14));
+    }
+
+    #[test]
+    fn flush_discards_rx_and_pending_errors() {
+        let fake = FakeUart::default();
+        let mut uart = uart(&fake);
+        uart.variant = Some(Ns16550Variant::Ns16550A);
+        uart.write_fcr(FifoControlFlags::ENABLE_FIFO);
+
+        fake.0.lock().unwrap().rx.extend([1, 2, 3]);
+        uart.pending_lsr.store(UART_LSR_PE, Ordering::Release);
+        uart.flush(FlushQueue::Rx).unwrap();
+        assert!(fake.0.lock().unwrap().rx.is_empty());
+        assert_eq!(uart.pending_lsr.load(Ordering::Acquire), 0);
+        assert_eq!(uart.fcr, FifoControlFlags::ENABLE_FIFO);
+
+        // 无 FIFO 时逐字节读空
+        uart.write_fcr(FifoControlFlags::empty());
+        fake.0.lock().unwrap().rx.extend([4, 5]);
+        uart.flush(FlushQueue::Both).unwrap();
+        assert!(fake.0.lock().unwrap().rx.is_empty());
+
+        assert_eq!(uart.drain(), Ok(()));
+    }
+
+    /// 以堆内存作寄存器的 MMIO 驱动，收发端齐全且发送空闲
+    fn mmio_uart(regs: &mut [u8; 8]) -> Ns16550<Mmio> {
+        regs[UART_LSR as usize] = UART_LSR_THRE | UART_LSR_TEMT;
+        let base = core::ptr::NonNull::new(regs.as_mut_ptr()).unwrap();
+        Ns16550::new_mmio(base, 1_843_200, 1)
+    }
+
+    #[test]
+    fn reconfigure_flush_discards_rx_after_applying() {
+        let mut regs = std::boxed::Box::new([0u8; 8]);
+        let mut uart = mmio_uart(&mut regs);
+        uart.pending_lsr.store(UART_LSR_PE, Ordering::Release);
+
+        // 校验失败时不丢弃数据
+        let bad = Config::new().baudrate(0);
+        assert!(matches!(
+            uart.set_config_when(&bad, ConfigWhen::Flush),
+            Err(ReconfigError::Config(_))
+        ));
+        assert_eq!(uart.pending_lsr.load(Ordering::Acquire), UART_LSR_PE);
+
+        let config = Config::new().baudrate(115200);
+        uart.set_config_when(&config, ConfigWhen::Drain).unwrap();
+        assert_eq!(uart.pending_lsr.load(Ordering::Acquire), UART_LSR_PE);
+        assert_eq!(uart.baudrate(), 115200);
+
+        uart.set_config_when(&config, ConfigWhen::Flush).unwrap();
+        assert_eq!(uart.pending_lsr.load(Ordering::Acquire), 0);
+    }
+
+    #[test]
+    fn reconfigure_pauses_taken_sender() {
+        let mut regs = std::boxed::Box::new([0u8; 8]);
+        let mut uart = mmio_uart(&mut regs);
+        uart.set_config(&Config::new().baudrate(115200)).unwrap();
+
+        // 与 SerialDyn 相同，收发端都已取出
+        let mut tx = uart.take_tx().unwrap();
+        let _rx = uart.take_rx().unwrap();
+        let config = Config::new().baudrate(9600);
+        uart.set_config_when(&config, ConfigWhen::Flush).unwrap();
+        assert_eq!(uart.baudrate(), 9600);
+
+        // 暂停期间写入失败，恢复后照常写入
+        uart.tx_pause.pause();
+        assert!(!TSender::write_byte(&mut tx, b'a'));
+        uart.tx_pause.resume();
+        assert!(!uart.tx_pause.is_paused());
+        assert!(TSender::write_byte(&mut tx, b'b'));
+        assert_eq!(regs[UART_THR as usize], b'b');
+    }
+
+    #[test]
+    fn split_returns_typed_halves() {
+        let mut regs = std::boxed::Box::new([0u8; 8]);
+        regs[UART_RBR as usize] = 0x41;
+        regs[UART_LSR as usize] = UART_LSR_DR;
+        let base = core::ptr::NonNull::new(regs.as_mut_ptr()).unwrap();
+        let mut uart = Ns16550::new_mmio(base, 1_843_200, 1);
+
+        let (tx, mut rx) = uart.split().unwrap();
+        assert_eq!(TReciever::read_byte(&mut rx), Some(Ok(0x41)));
+        assert!(uart.split().is_none());
+
+        // 转换为枚举后归还，枚举与静态类型之间可以互相转换
+        uart.set_tx(tx.into()).unwrap();
+        uart.set_rx(rx.into()).unwrap();
+        let tx: Ns16550Sender<Mmio> = uart.take_tx().unwrap().try_into().ok().unwrap();
+        uart.set_tx(tx.into()).unwrap();
+        assert!(uart.split().is_some());
+    }
+
+    #[test]
+    fn validation_before_open_keeps_pending_thri() {
+        let fake = FakeUart::default();
+        let mut uart = uart(&fake);
+        {
+            let mut s = fake.0.lock().unwrap();
+            s.ier = UART_IER_THRI;
+            s.thri = true;
+        }
+
+        // 探测前校验配置不读 IIR，挂起的发送中断仍留给中断处理
+        assert_eq!(uart.variant(), Ns16550Variant::Ns16450);
+        uart.set_config(&Config::new().baudrate(115200)).unwrap();
+        assert!(fake.0.lock().unwrap().thri);
+    }
+}
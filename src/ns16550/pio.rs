@@ -2,7 +2,12 @@
 //!
 //! 仅在 x86_64 架构下编译，使用 x86_64 crate 进行端口 I/O
 
+use alloc::sync::Arc;
+use core::sync::atomic::AtomicU8;
+
 use super::{Kind, Ns16550, Ns16550IrqHandler, Ns16550Reciever, Ns16550Sender};
+use crate::claim::{self, Claim, ClaimError, Region};
+use crate::reconfig::TxPause;
 
 /// NS16550 IO Port 版本驱动
 #[derive(Clone, Debug)]
@@ -31,24 +36,67 @@ impl Ns16550<Port> {
     ///
     /// * `port` - 串口基地址 (如 COM1 为 0x3F8)
     /// * `clock_freq` - UART 时钟频率，通常为 1.8432 MHz
+    ///
+    /// 不占用端口区域，需要防止同一设备被重复创建时使用 [`Self::try_new_port`]
     pub fn new_port(port: u16, clock_freq: u32) -> Ns16550<Port> {
+        Self::with_claim(port, clock_freq, None)
+    }
+
+    pub fn new_port_boxed(port: u16, clock_freq: u32) -> rdif_serial::BSerial {
+        rdif_serial::SerialDyn::new_boxed(Ns16550::new_port(port, clock_freq))
+    }
+
+    /// 占用 8 个端口后创建实例，区域与已有实例重叠时返回错误
+    pub fn try_new_port(port: u16, clock_freq: u32) -> Result<Ns16550<Port>, ClaimError> {
+        let claim = claim::claim(Region::port(port, 8))?;
+        Ok(Self::with_claim(port, clock_freq, Some(claim)))
+    }
+
+    pub fn try_new_port_boxed(
+        port: u16,
+        clock_freq: u32,
+    ) -> Result<rdif_serial::BSerial, ClaimError> {
+        Ok(rdif_serial::SerialDyn::new_boxed(Ns16550::try_new_port(
+            port, clock_freq,
+        )?))
+    }
+
+    fn with_claim(port: u16, clock_freq: u32, claim: Option<Arc<Claim>>) -> Ns16550<Port> {
         let base = Port { port };
 
+        let pending_lsr = Arc::new(AtomicU8::new(0));
+        let tx_pause = TxPause::default();
+
         Ns16550 {
             base: base.clone(),
             clock_freq,
-            irq: Some(Ns16550IrqHandler { base: base.clone() }),
+            variant: None,
+            fcr: super::FifoControlFlags::empty(),
+            shadow: None,
+            pending_lsr: pending_lsr.clone(),
+            irq: Some(Ns16550IrqHandler {
+                base: base.clone(),
+                pending_lsr: pending_lsr.clone(),
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            tx_pause: tx_pause.clone(),
             tx: Some(crate::Sender::Ns16550Sender(Ns16550Sender {
                 base: base.clone(),
+                stats: None,
+                tx_pause,
+                _claim: claim.clone(),
+            })),
+            rx: Some(crate::Reciever::Ns16550Reciever(Ns16550Reciever {
+                base,
+                pending_lsr,
+                stats: None,
+                _claim: claim.clone(),
             })),
-            rx: Some(crate::Reciever::Ns16550Reciever(Ns16550Reciever { base })),
+            claim,
         }
     }
 
-    pub fn new_port_boxed(port: u16, clock_freq: u32) -> rdif_serial::BSerial {
-        rdif_serial::SerialDyn::new_boxed(Ns16550::new_port(port, clock_freq))
-    }
-
     pub fn take_tx(&mut self) -> Option<crate::Sender> {
         self.tx.take()
     }
@@ -56,4 +104,22 @@ impl Ns16550<Port> {
     pub fn take_rx(&mut self) -> Option<crate::Reciever> {
         self.rx.take()
     }
+
+    /// 取出静态类型的收发端，读写完全单态化，不经过枚举分发
+    ///
+    /// 任一端已被取出时返回 `None`，另一端留在驱动中。收发端可以用 `.into()` 转换为
+    /// [`crate::Sender`]/[`crate::Reciever`] 后通过 `set_tx`/`set_rx` 归还。
+    pub fn split(&mut self) -> Option<(Ns16550Sender<Port>, Ns16550Reciever<Port>)> {
+        match (self.tx.take(), self.rx.take()) {
+            (
+                Some(crate::Sender::Ns16550Sender(tx)),
+                Some(crate::Reciever::Ns16550Reciever(rx)),
+            ) => Some((tx, rx)),
+            (tx, rx) => {
+                self.tx = tx;
+                self.rx = rx;
+                None
+            }
+        }
+    }
 }
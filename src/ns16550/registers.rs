@@ -65,6 +65,10 @@ bitflags! {
         /// 调制解调器信号状态改变中断
         const MODEM_STATUS = 0x00;
 
+        /// 64字节FIFO指示 (16750)
+        /// 64字节FIFO模式生效时置1
+        const FIFO_64_BYTES = 0x20;
+
         /// FIFO使能位掩码
         /// bit 6-7，表示FIFO功能状态
         const FIFO_ENABLE_MASK = 0xC0;
@@ -92,6 +96,10 @@ bitflags! {
         /// 置1时选择DMA模式0，清0时选择模式1
         const DMA_MODE_SELECT = 0x08;
 
+        /// 64字节FIFO使能 (16750)
+        /// 仅在LCR.DLAB=1时可写
+        const FIFO_64_BYTES = 0x20;
+
         /// FIFO触发级别掩码
         /// bit 6-7，设置FIFO触发中断的阈值
         const TRIGGER_LEVEL_MASK = 0xC0;
@@ -186,6 +194,10 @@ bitflags! {
         /// 置1时启用内部环回，用于自测试
         const LOOPBACK_ENABLE = 0x10;
 
+        /// 自动流控使能 (16750)
+        /// 置1时由硬件根据CTS/RTS自动控制收发
+        const AUTO_FLOW_CONTROL = 0x20;
+
         /// 调制解调器控制掩码
         /// bit 0-3，调制解调器控制信号掩码
         const MODEM_CONTROL_MASK = 0x0F;
@@ -349,12 +361,14 @@ pub const UART_IIR_THRI: u8 = 0x02; // Transmitter Holding Register Empty Interr
 pub const UART_IIR_MSI: u8 = 0x00; // Modem Status Interrupt
 pub const UART_IIR_FIFO_ENABLE: u8 = 0xC0; // FIFO Enable bits
 pub const UART_IIR_FIFO_MASK: u8 = 0xC0; // FIFO bits mask
+pub const UART_IIR_64BYTE_FIFO: u8 = 0x20; // 16750 64 byte FIFO enabled
 
 // FCR (FIFO Control Register) 位定义
 pub const UART_FCR_ENABLE_FIFO: u8 = 0x01; // Enable FIFO
 pub const UART_FCR_CLEAR_RCVR: u8 = 0x02; // Clear receiver FIFO
 pub const UART_FCR_CLEAR_XMIT: u8 = 0x04; // Clear transmitter FIFO
 pub const UART_FCR_DMA_SELECT: u8 = 0x08; // DMA mode select
+pub const UART_FCR7_64BYTE: u8 = 0x20; // 16750 64 byte FIFO enable (DLAB=1)
 pub const UART_FCR_TRIGGER_MASK: u8 = 0xC0; // Trigger level mask
 pub const UART_FCR_TRIGGER_1: u8 = 0x00; // 1 byte trigger
 pub const UART_FCR_TRIGGER_4: u8 = 0x40; // 4 byte trigger
@@ -379,6 +393,7 @@ pub const UART_MCR_RTS: u8 = 0x02; // Request to Send
 pub const UART_MCR_OUT1: u8 = 0x04; // Out 1
 pub const UART_MCR_OUT2: u8 = 0x08; // Out 2
 pub const UART_MCR_LOOP: u8 = 0x10; // Enable loopback test mode
+pub const UART_MCR_AFE: u8 = 0x20; // Enable auto-RTS/CTS (16750)
 
 // LSR (Line Status Register) 位定义
 pub const UART_LSR_DR: u8 = 0x01; // Data ready
@@ -1,13 +1,24 @@
 use core::{num::NonZeroU32, ptr::NonNull};
 
+use alloc::sync::Arc;
+
 use rdif_serial::{
-    BSerial, InterfaceRaw, SerialDyn, SetBackError, TIrqHandler, TSender, TransBytesError,
-    TransferError,
+    BSerial, InterfaceRaw, SerialDyn, SetBackError, TIrqHandler, TReciever, TSender,
+    TransBytesError, TransferError,
+};
+use tock_registers::{
+    interfaces::*, register_bitfields, register_structs, registers::*, LocalRegisterCopy,
 };
-use tock_registers::{interfaces::*, register_bitfields, register_structs, registers::*};
 
 use crate::{
-    Config, ConfigError, DataBits, InterruptMask, Parity, RawReciever, RawSender, StopBits,
+    caps::{ALL_DATA_BITS, ALL_PARITY, ALL_STOP_BITS},
+    claim::{self, Claim, ClaimError, Region},
+    fifo::{select_level, FifoError, FifoThreshold, FifoThresholds, FifoTriggers},
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, IrqCause, Stats},
+    Capabilities, Caps, Config, ConfigError, DataBits, Features, InterruptMask, Parity,
+    RawReciever, RawSender, StopBits, Suspend,
 };
 
 register_bitfields! [
@@ -133,6 +144,8 @@ register_structs! {
         (0x044 => uarticr: WriteOnly<u32, UARTIS::Register>),       // 中断清除寄存器
         (0x048 => uartdmacr: ReadWrite<u32, UARTDMACR::Register>),  // DMA控制寄存器
         (0x04c => _reserved3),                                      // 保留
+        (0xfe0 => uartperiphid: [ReadOnly<u32>; 4]),                // 外设标识寄存器 0-3
+        (0xff0 => uartpcellid: [ReadOnly<u32>; 4]),                 // PrimeCell 标识寄存器 0-3
         (0x1000 => @END),
     }
 }
@@ -140,13 +153,20 @@ register_structs! {
 // SAFETY: PL011 寄存器访问是原子的，硬件保证了内存映射寄存器的线程安全
 unsafe impl Sync for Pl011Registers {}
 
+/// 等待 BUSY 清零的最大轮询次数
+const BUSY_SPIN_LIMIT: usize = 1_000_000;
+
 /// PL011 UART 驱动结构体
 pub struct Pl011 {
     base: Reg,
     clock_freq: u32,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    tx_pause: TxPause,
     tx: Option<Pl011Sender>,
     rx: Option<Pl011Reciever>,
     irq: Option<Pl011IrqHandler>,
+    /// 通过 `try_new` 系列创建时持有的区域占用，与各句柄共享
+    claim: Option<Arc<Claim>>,
 }
 
 impl Pl011 {
@@ -160,22 +180,58 @@ impl Pl011 {
         Self::new(base, clock_freq)
     }
 
+    /// 不占用寄存器区域，需要防止同一设备被重复创建时使用 [`Self::try_new`]
     pub fn new(base: NonNull<u8>, clock_freq: u32) -> Self {
+        Self::with_claim(base, clock_freq, None)
+    }
+
+    pub fn new_boxed(base: NonNull<u8>, clock_freq: u32) -> BSerial {
+        SerialDyn::new_boxed(Self::new(base, clock_freq))
+    }
+
+    /// 占用寄存器区域后创建实例，区域与已有实例重叠时返回错误
+    pub fn try_new(base: NonNull<u8>, clock_freq: u32) -> Result<Self, ClaimError> {
+        let claim = claim::claim(Region::mmio(base, size_of::<Pl011Registers>()))?;
+        Ok(Self::with_claim(base, clock_freq, Some(claim)))
+    }
+
+    pub fn try_new_boxed(base: NonNull<u8>, clock_freq: u32) -> Result<BSerial, ClaimError> {
+        Ok(SerialDyn::new_boxed(Self::try_new(base, clock_freq)?))
+    }
+
+    /// 通过 `try_new` 系列创建时持有的寄存器区域占用
+    pub fn claim(&self) -> Option<&Claim> {
+        self.claim.as_deref()
+    }
+
+    fn with_claim(base: NonNull<u8>, clock_freq: u32, claim: Option<Arc<Claim>>) -> Self {
         let base = Reg(base.cast());
+        let tx_pause = TxPause::default();
 
         Self {
             base,
             clock_freq,
-            tx: Some(Pl011Sender { base }),
-            rx: Some(Pl011Reciever { base }),
-            irq: Some(Pl011IrqHandler { base }),
+            tx_pause: tx_pause.clone(),
+            tx: Some(Pl011Sender {
+                base,
+                stats: None,
+                tx_pause: tx_pause.clone(),
+                _claim: claim.clone(),
+            }),
+            rx: Some(Pl011Reciever {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            irq: Some(Pl011IrqHandler {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            claim,
         }
     }
 
-    pub fn new_boxed(base: NonNull<u8>, clock_freq: u32) -> BSerial {
-        SerialDyn::new_boxed(Self::new(base, clock_freq))
-    }
-
     fn registers(&self) -> &Pl011Registers {
         unsafe { &*self.base.0.as_ptr() }
     }
@@ -205,84 +261,90 @@ impl Pl011 {
     }
 
     // 内部私有方法，用于配置
-    fn set_baudrate_internal(&self, baudrate: u32) -> Result<(), ConfigError> {
+    /// 计算波特率对应的 (IBRD, FBRD)
+    fn divisors_for(&self, baudrate: u32) -> Result<(u32, u32), ConfigError> {
         // PL011 波特率计算公式：
         // BAUDDIV = (FUARTCLK / (16 * Baud rate))
         // IBRD = integer(BAUDDIV)
         // FBRD = integer((BAUDDIV - IBRD) * 64 + 0.5)
-
-        let bauddiv = self.clock_freq / (16 * baudrate);
-        let remainder = self.clock_freq % (16 * baudrate);
-        let fbrd = (remainder * 64 + (16 * baudrate / 2)) / (16 * baudrate);
-
-        if bauddiv == 0 || bauddiv > 0xFFFF {
+        // 合并为以 1/64 为单位的除数：DIV = (4 * FUARTCLK + Baud / 2) / Baud
+        if baudrate == 0 {
             return Err(ConfigError::InvalidBaudrate);
         }
 
-        self.registers()
-            .uartibrd
-            .write(UARTIBRD::BAUD_DIVINT.val(bauddiv));
-        self.registers()
-            .uartfbrd
-            .write(UARTFBRD::BAUD_DIVFRAC.val(fbrd));
-
-        Ok(())
-    }
+        let div = (4 * self.clock_freq as u64 + baudrate as u64 / 2) / baudrate as u64;
+        let ibrd = (div >> 6) as u32;
+        let fbrd = (div & 0x3F) as u32;
 
-    fn set_data_bits_internal(&self, bits: DataBits) -> Result<(), ConfigError> {
-        let wlen = match bits {
-            DataBits::Five => UARTLCR_H::WLEN::FiveBit,
-            DataBits::Six => UARTLCR_H::WLEN::SixBit,
-            DataBits::Seven => UARTLCR_H::WLEN::SevenBit,
-            DataBits::Eight => UARTLCR_H::WLEN::EightBit,
-        };
+        if ibrd == 0 || ibrd > 0xFFFF {
+            return Err(ConfigError::InvalidBaudrate);
+        }
 
-        self.registers().uartlcr_h.modify(wlen);
-        Ok(())
+        Ok((ibrd, fbrd))
     }
 
-    fn set_stop_bits_internal(&self, bits: StopBits) -> Result<(), ConfigError> {
-        match bits {
-            StopBits::One => self.registers().uartlcr_h.modify(UARTLCR_H::STP2::CLEAR),
-            StopBits::Two => self.registers().uartlcr_h.modify(UARTLCR_H::STP2::SET),
+    /// 在当前 LCR_H 的基础上计算应用配置后的值，未设置的项保持不变
+    fn line_control_for(&self, config: &Config) -> LocalRegisterCopy<u32, UARTLCR_H::Register> {
+        let mut lcr_h = self.registers().uartlcr_h.extract();
+
+        if let Some(bits) = config.data_bits {
+            let wlen = match bits {
+                DataBits::Five => UARTLCR_H::WLEN::FiveBit,
+                DataBits::Six => UARTLCR_H::WLEN::SixBit,
+                DataBits::Seven => UARTLCR_H::WLEN::SevenBit,
+                DataBits::Eight => UARTLCR_H::WLEN::EightBit,
+            };
+            lcr_h.modify(wlen);
         }
 
-        Ok(())
-    }
+        match config.stop_bits {
+            Some(StopBits::One) => lcr_h.modify(UARTLCR_H::STP2::CLEAR),
+            Some(StopBits::Two) => lcr_h.modify(UARTLCR_H::STP2::SET),
+            None => {}
+        }
 
-    fn set_parity_internal(&self, parity: Parity) -> Result<(), ConfigError> {
-        match parity {
-            Parity::None => {
+        if let Some(parity) = config.parity {
+            let field = match parity {
                 // PEN = 0, 无奇偶校验
-                self.registers().uartlcr_h.modify(UARTLCR_H::PEN::CLEAR);
-            }
-            Parity::Odd => {
+                Parity::None => {
+                    UARTLCR_H::PEN::CLEAR + UARTLCR_H::EPS::CLEAR + UARTLCR_H::SPS::CLEAR
+                }
                 // PEN = 1, EPS = 0 (奇校验), SPS = 0
-                self.registers()
-                    .uartlcr_h
-                    .modify(UARTLCR_H::PEN::SET + UARTLCR_H::EPS::CLEAR + UARTLCR_H::SPS::CLEAR);
-            }
-            Parity::Even => {
+                Parity::Odd => UARTLCR_H::PEN::SET + UARTLCR_H::EPS::CLEAR + UARTLCR_H::SPS::CLEAR,
                 // PEN = 1, EPS = 1 (偶校验), SPS = 0
-                self.registers()
-                    .uartlcr_h
-                    .modify(UARTLCR_H::PEN::SET + UARTLCR_H::EPS::SET + UARTLCR_H::SPS::CLEAR);
-            }
-            Parity::Mark => {
+                Parity::Even => UARTLCR_H::PEN::SET + UARTLCR_H::EPS::SET + UARTLCR_H::SPS::CLEAR,
                 // PEN = 1, SPS = 1, EPS = 0 (奇校验)
-                self.registers()
-                    .uartlcr_h
-                    .modify(UARTLCR_H::PEN::SET + UARTLCR_H::EPS::CLEAR + UARTLCR_H::SPS::SET);
-            }
-            Parity::Space => {
+                Parity::Mark => UARTLCR_H::PEN::SET + UARTLCR_H::EPS::CLEAR + UARTLCR_H::SPS::SET,
                 // PEN = 1, EPS = 1 (偶校验), SPS = 1
-                self.registers()
-                    .uartlcr_h
-                    .modify(UARTLCR_H::PEN::SET + UARTLCR_H::EPS::SET + UARTLCR_H::SPS::SET);
+                Parity::Space => UARTLCR_H::PEN::SET + UARTLCR_H::EPS::SET + UARTLCR_H::SPS::SET,
+            };
+            lcr_h.modify(field);
+        }
+
+        lcr_h
+    }
+
+    /// 等待发送 FIFO 为空且最后一个字符移出（BUSY=0 且 TXFE=1），超时返回 false
+    fn wait_idle(&self) -> bool {
+        for _ in 0..BUSY_SPIN_LIMIT {
+            let fr = self.registers().uartfr.extract();
+            if !fr.is_set(UARTFR::BUSY) && fr.is_set(UARTFR::TXFE) {
+                return true;
             }
+            core::hint::spin_loop();
         }
+        false
+    }
 
-        Ok(())
+    /// 等待当前字符移出（BUSY=0），超时返回 false
+    fn wait_not_busy(&self) -> bool {
+        for _ in 0..BUSY_SPIN_LIMIT {
+            if !self.registers().uartfr.is_set(UARTFR::BUSY) {
+                return true;
+            }
+            core::hint::spin_loop();
+        }
+        false
     }
 
     /// 初始化 PL011 UART
@@ -308,8 +370,7 @@ impl Pl011 {
             let lcr_h = self.registers().uartlcr_h.get();
             log::debug!("UART IFLS: 0x{:02x}, LCR_H: 0x{:02x}", ifls, lcr_h);
             log::debug!("  FIFO enabled: {}", lcr_h & (1 << 4) != 0);
-            log::debug!("  RX trigger level: 1/8");
-            log::debug!("  TX trigger level: 1/2");
+            log::debug!("  FIFO triggers: {:?}", self.fifo_triggers());
         }
         self.registers().uartimsc.set(0); // 禁用所有中断
                                           // 启用 UART
@@ -318,6 +379,19 @@ impl Pl011 {
             .modify(UARTCR::UARTEN::SET + UARTCR::TXE::SET + UARTCR::RXE::SET);
     }
 
+    /// 挂接统计计数器，只对尚未取出的收发和中断句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+        if let Some(irq) = self.irq.as_mut() {
+            irq.stats = Some(stats);
+        }
+    }
+
     pub fn task_tx(&mut self) -> Option<crate::Sender> {
         self.tx.take().map(crate::Sender::Pl011Sender)
     }
@@ -325,6 +399,21 @@ impl Pl011 {
     pub fn task_rx(&mut self) -> Option<crate::Reciever> {
         self.rx.take().map(crate::Reciever::Pl011Reciever)
     }
+
+    /// 取出静态类型的收发端，读写完全单态化，不经过枚举分发
+    ///
+    /// 任一端已被取出时返回 `None`，另一端留在驱动中。收发端可以用 `.into()` 转换为
+    /// [`crate::Sender`]/[`crate::Reciever`] 后通过 `set_tx`/`set_rx` 归还。
+    pub fn split(&mut self) -> Option<(Pl011Sender, Pl011Reciever)> {
+        match (self.tx.take(), self.rx.take()) {
+            (Some(tx), Some(rx)) => Some((tx, rx)),
+            (tx, rx) => {
+                self.tx = tx;
+                self.rx = rx;
+                None
+            }
+        }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -340,6 +429,15 @@ impl Reg {
 
 pub struct Pl011Sender {
     base: Reg,
+    stats: Option<&'static Stats>,
+    tx_pause: TxPause,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for Pl011Sender {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
 }
 
 impl TSender for Pl011Sender {
@@ -350,11 +448,17 @@ impl TSender for Pl011Sender {
 
 impl RawSender for Pl011Sender {
     fn write_byte(&mut self, byte: u8) -> bool {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return false;
+        };
         if self.base.registers().uartfr.is_set(UARTFR::TXFF) {
             return false;
         }
 
         self.base.registers().uartdr.set(byte as _);
+        if let Some(stats) = self.stats {
+            stats.record_tx(1);
+        }
 
         true
     }
@@ -362,10 +466,18 @@ impl RawSender for Pl011Sender {
 
 pub struct Pl011Reciever {
     base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
 }
 
-impl RawReciever for Pl011Reciever {
-    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+impl AttachStats for Pl011Reciever {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl Pl011Reciever {
+    fn read_raw(&self) -> Option<Result<u8, TransferError>> {
         if self.base.registers().uartfr.is_set(UARTFR::RXFE) {
             return None;
         }
@@ -391,12 +503,32 @@ impl RawReciever for Pl011Reciever {
 
         Some(Ok(data))
     }
+}
+
+impl TReciever for Pl011Reciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        RawReciever::read_byte(self)
+    }
+
+    fn read_bytes(&mut self, bytes: &mut [u8]) -> Result<usize, TransBytesError> {
+        RawReciever::read_bytes(self, bytes)
+    }
+}
+
+impl RawReciever for Pl011Reciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        let result = self.read_raw()?;
+        if let Some(stats) = self.stats {
+            stats.record_rx(&result);
+        }
+        Some(result)
+    }
 
     fn read_bytes(&mut self, bytes: &mut [u8]) -> Result<usize, TransBytesError> {
         let mut count = 0;
         let mut overrun_data = None;
         for byte in bytes.iter_mut() {
-            match self.read_byte() {
+            match RawReciever::read_byte(self) {
                 Some(Ok(b)) => {
                     *byte = b;
                 }
@@ -430,6 +562,8 @@ impl RawReciever for Pl011Reciever {
 
 pub struct Pl011IrqHandler {
     base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
 }
 
 unsafe impl Sync for Pl011IrqHandler {}
@@ -439,13 +573,35 @@ impl TIrqHandler for Pl011IrqHandler {
         let mis = self.base.registers().uartmis.extract();
         let mut mask = InterruptMask::empty();
 
-        if mis.is_set(UARTIS::RX) {
+        // 接收超时表示 FIFO 中还有不足触发级别的数据，同样按接收可用上报
+        if mis.is_set(UARTIS::RX) || mis.is_set(UARTIS::RT) {
             mask |= InterruptMask::RX_AVAILABLE;
         }
         if mis.is_set(UARTIS::TX) {
             mask |= InterruptMask::TX_EMPTY;
         }
 
+        if let Some(stats) = self.stats {
+            let causes = [
+                (UARTIS::RX, IrqCause::Rx),
+                (UARTIS::TX, IrqCause::Tx),
+                (UARTIS::RT, IrqCause::RxTimeout),
+                (UARTIS::OE, IrqCause::LineStatus),
+                (UARTIS::BE, IrqCause::LineStatus),
+                (UARTIS::PE, IrqCause::LineStatus),
+                (UARTIS::FE, IrqCause::LineStatus),
+                (UARTIS::RIM, IrqCause::ModemStatus),
+                (UARTIS::CTSM, IrqCause::ModemStatus),
+                (UARTIS::DCDM, IrqCause::ModemStatus),
+                (UARTIS::DSRM, IrqCause::ModemStatus),
+            ];
+            for (field, cause) in causes {
+                if mis.is_set(field) {
+                    stats.record_irq(cause);
+                }
+            }
+        }
+
         self.base.registers().uarticr.set(mis.get());
 
         mask
@@ -462,40 +618,48 @@ impl InterfaceRaw for Pl011 {
     fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
         use tock_registers::interfaces::Readable;
 
-        // 根据ARM文档的建议配置流程：
-        // 1. 禁用UART
-        let original_enable = self.registers().uartcr.is_set(UARTCR::UARTEN); // 保存原始使能状态
-        self.registers().uartcr.modify(UARTCR::UARTEN::CLEAR); // 禁用UART
+        // 0. 在禁用 UART 之前整体校验，避免只应用了部分设置
+        self.capabilities().validate(config)?;
 
-        // 2. 等待当前字符传输完成
-        while self.registers().uartfr.is_set(UARTFR::BUSY) {
-            core::hint::spin_loop();
-        }
+        // 1. 计算出所有寄存器值，失败时不触碰硬件
+        let divisors = config
+            .baudrate
+            .map(|baudrate| self.divisors_for(baudrate))
+            .transpose()?;
+        let lcr_h = self.line_control_for(config);
 
-        // 3. 刷新发送FIFO（通过设置FEN=0）
+        // 保存原始控制寄存器，用于恢复
+        let original_cr = self.registers().uartcr.get();
+        let original_lcr_h = self.registers().uartlcr_h.get();
+
+        // 根据ARM文档的建议配置流程：
+        // 2. 禁用UART
+        self.registers().uartcr.modify(UARTCR::UARTEN::CLEAR);
+
+        // 3. 刷新发送FIFO（通过设置FEN=0），未发出的字节被丢弃。FIFO 中有数据时
+        //    BUSY 一直置位，必须先清空；需要保留已排队数据时使用 `ConfigWhen::Drain` 先排空
         self.registers().uartlcr_h.modify(UARTLCR_H::FEN::CLEAR);
 
-        // 4. 配置各项参数
-        if let Some(baudrate) = config.baudrate {
-            self.set_baudrate_internal(baudrate)?;
-        }
-        if let Some(data_bits) = config.data_bits {
-            self.set_data_bits_internal(data_bits)?;
-        }
-        if let Some(stop_bits) = config.stop_bits {
-            self.set_stop_bits_internal(stop_bits)?;
-        }
-        if let Some(parity) = config.parity {
-            self.set_parity_internal(parity)?;
+        // 4. 等待当前字符传输完成，超时则恢复原状态
+        if !self.wait_not_busy() {
+            self.registers().uartlcr_h.set(original_lcr_h);
+            self.registers().uartcr.set(original_cr);
+            return Err(ConfigError::Timeout);
         }
 
-        // 5. 重新启用FIFO
-        self.registers().uartlcr_h.modify(UARTLCR_H::FEN::SET);
-
-        // 6. 恢复UART使能状态
-        if original_enable {
-            self.registers().uartcr.modify(UARTCR::UARTEN::SET); // 重新启用UART
+        // 5. 写入 IBRD/FBRD，随后写 LCR_H 使除数生效（lcr_h 基于原值计算，FEN 随之恢复）
+        if let Some((ibrd, fbrd)) = divisors {
+            self.registers()
+                .uartibrd
+                .write(UARTIBRD::BAUD_DIVINT.val(ibrd));
+            self.registers()
+                .uartfbrd
+                .write(UARTFBRD::BAUD_DIVFRAC.val(fbrd));
         }
+        self.registers().uartlcr_h.set(lcr_h.get());
+
+        // 6. 恢复控制寄存器（包括 UARTEN）
+        self.registers().uartcr.set(original_cr);
 
         Ok(())
     }
@@ -582,7 +746,8 @@ impl InterfaceRaw for Pl011 {
     fn set_irq_mask(&mut self, mask: InterruptMask) {
         let mut imsc = 0;
         if mask.contains(InterruptMask::RX_AVAILABLE) {
-            imsc += UARTIS::RX::SET.value;
+            // 不足 RX 触发级别的尾部数据只会触发接收超时中断
+            imsc += (UARTIS::RX::SET + UARTIS::RT::SET).value;
         }
         if mask.contains(InterruptMask::TX_EMPTY) {
             imsc += UARTIS::TX::SET.value;
@@ -595,7 +760,8 @@ impl InterfaceRaw for Pl011 {
         let imsc = self.registers().uartimsc.extract();
         let mut mask = InterruptMask::empty();
 
-        if imsc.is_set(UARTIS::RX) {
+        // 只开了接收超时同样能收到数据
+        if imsc.is_set(UARTIS::RX) || imsc.is_set(UARTIS::RT) {
             mask |= InterruptMask::RX_AVAILABLE;
         }
         if imsc.is_set(UARTIS::TX) {
@@ -627,7 +793,7 @@ impl InterfaceRaw for Pl011 {
             _ => {
                 return Err(SetBackError::new(
                     self.base.0.as_ptr() as _,
-                    0, // 不匹配的发送器类型
+                    tx.external_base().unwrap_or(0), // 不匹配的发送器类型
                 ));
             }
         };
@@ -649,7 +815,7 @@ impl InterfaceRaw for Pl011 {
             _ => {
                 return Err(SetBackError::new(
                     self.base.0.as_ptr() as _,
-                    0, // 不匹配的接收器类型
+                    rx.external_base().unwrap_or(0), // 不匹配的接收器类型
                 ));
             }
         };
@@ -664,8 +830,143 @@ impl InterfaceRaw for Pl011 {
     }
 }
 
+/// PL011 没有单独清空 FIFO 的控制位，只能通过切换 FEN 同时清空收发 FIFO：
+/// 清空发送方向时接收 FIFO 中尚未读取的数据一并丢弃；只清空接收方向时逐字节读空。
+///
+/// 手册要求改写 LCR_H 前先禁用 UART，清空发送方向会短暂清除 UARTEN，
+/// 等待当前字符移出后恢复原来的 CR。
+impl Flush for Pl011 {
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError> {
+        let regs = self.registers();
+
+        if queue.tx() && regs.uartlcr_h.is_set(UARTLCR_H::FEN) {
+            let original_cr = regs.uartcr.get();
+            regs.uartcr.modify(UARTCR::UARTEN::CLEAR);
+            // FIFO 中有数据时 BUSY 一直置位，先清空再等待
+            regs.uartlcr_h.modify(UARTLCR_H::FEN::CLEAR);
+            let idle = self.wait_not_busy();
+            regs.uartlcr_h.modify(UARTLCR_H::FEN::SET);
+            regs.uartcr.set(original_cr);
+            if !idle {
+                return Err(FlushError::Timeout);
+            }
+        }
+
+        if queue.rx() {
+            for _ in 0..self.fifo_depth() {
+                if regs.uartfr.is_set(UARTFR::RXFE) {
+                    break;
+                }
+                regs.uartdr.get();
+            }
+            // 写 ECR 清除残留的错误标志
+            regs.uartrsr_ecr.set(0);
+        }
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), FlushError> {
+        if self.wait_idle() {
+            Ok(())
+        } else {
+            Err(FlushError::Timeout)
+        }
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// PL011 挂起时保存的寄存器上下文
+#[derive(Debug, Clone, Copy)]
+pub struct Pl011Context {
+    ibrd: u32,
+    fbrd: u32,
+    lcr_h: u32,
+    cr: u32,
+    ifls: u32,
+    imsc: u32,
+    dmacr: u32,
+    ilpr: u32,
+}
+
+impl Suspend for Pl011 {
+    type Context = Pl011Context;
+
+    fn save_context(&self) -> Self::Context {
+        let regs = self.registers();
+        Pl011Context {
+            ibrd: regs.uartibrd.get(),
+            fbrd: regs.uartfbrd.get(),
+            lcr_h: regs.uartlcr_h.get(),
+            cr: regs.uartcr.get(),
+            ifls: regs.uartifls.get(),
+            imsc: regs.uartimsc.get(),
+            dmacr: regs.uartdmacr.get(),
+            ilpr: regs.uartilpr.get(),
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        let regs = self.registers();
+
+        // 恢复期间保持 UART 禁用、中断屏蔽
+        regs.uartcr.set(ctx.cr & !UARTCR::UARTEN::SET.value);
+        regs.uartimsc.set(0);
+
+        // 除数在写 LCR_H 时才生效，必须先写 IBRD/FBRD
+        regs.uartibrd.set(ctx.ibrd);
+        regs.uartfbrd.set(ctx.fbrd);
+        regs.uartlcr_h.set(ctx.lcr_h);
+        regs.uartilpr.set(ctx.ilpr);
+        regs.uartifls.set(ctx.ifls);
+        regs.uartdmacr.set(ctx.dmacr);
+
+        // 清除上电过程中残留的中断，再恢复中断屏蔽
+        regs.uarticr.set(0x7FF);
+        regs.uartimsc.set(ctx.imsc);
+
+        // 最后恢复 UARTEN
+        regs.uartcr.set(ctx.cr);
+    }
+}
+
+impl Caps for Pl011 {
+    fn capabilities(&self) -> Capabilities {
+        // BAUDDIV = IBRD + FBRD/64，IBRD 取值 1..=0xFFFF
+        let (min_baudrate, max_baudrate) =
+            Capabilities::baudrate_range(self.clock_freq, 16, 0xFFFF);
+
+        Capabilities {
+            fifo_depth: self.fifo_depth(),
+            min_baudrate,
+            max_baudrate,
+            data_bits: ALL_DATA_BITS,
+            stop_bits: ALL_STOP_BITS,
+            parity: ALL_PARITY,
+            features: Features::HW_FLOW_CONTROL
+                | Features::DMA
+                | Features::IRDA
+                | Features::LOOPBACK,
+        }
+    }
+}
+
 // 额外的便利方法，用于 FIFO 和流控制
 impl Pl011 {
+    /// FIFO 深度
+    ///
+    /// r1p5（UARTPeriphID2 修订号 >= 3）起 FIFO 从 16 字节加深到 32 字节。
+    pub fn fifo_depth(&self) -> u16 {
+        let revision = (self.registers().uartperiphid[2].get() >> 4) & 0xF;
+        if revision >= 3 {
+            32
+        } else {
+            16
+        }
+    }
+
     /// 启用或禁用 FIFO
     pub fn enable_fifo(&self, enable: bool) {
         if enable {
@@ -675,35 +976,161 @@ impl Pl011 {
         }
     }
 
-    /// 设置 FIFO 触发级别
-    pub fn set_fifo_trigger_level(&self, rx_level: u8, tx_level: u8) {
-        // PL011 FIFO 触发级别：
-        // 0b000: 1/8 full
-        // 0b001: 1/4 full
-        // 0b010: 1/2 full
-        // 0b011: 3/4 full
-        // 0b100: 7/8 full
+    /// IFLS 各档位对应的字节数：1/8、1/4、1/2、3/4、7/8
+    fn trigger_levels(&self) -> [u16; 5] {
+        let depth = self.fifo_depth();
+        [2, 4, 8, 12, 14].map(|sixteenths| depth * sixteenths / 16)
+    }
+}
 
-        let rx_iflsel = match rx_level {
-            0..=2 => 0b000,  // 1/8
-            3..=4 => 0b001,  // 1/4
-            5..=8 => 0b010,  // 1/2
-            9..=12 => 0b011, // 3/4
-            _ => 0b100,      // 7/8
-        };
+/// 接收在 FIFO 达到、发送在 FIFO 降到所选档位时触发，接收超时固定为 32 个位时间
+impl FifoThreshold for Pl011 {
+    fn fifo_triggers(&self) -> FifoTriggers {
+        let levels = self.trigger_levels();
+        let ifls = self.registers().uartifls.extract();
+        let level = |sel: u32| levels[(sel as usize).min(levels.len() - 1)];
+
+        FifoTriggers {
+            rx: Some(level(ifls.read(UARTIFLS::RXIFLSEL))),
+            tx: Some(level(ifls.read(UARTIFLS::TXIFLSEL))),
+            rx_timeout: None,
+        }
+    }
 
-        let tx_iflsel = match tx_level {
-            0..=2 => 0b000,  // 1/8
-            3..=4 => 0b001,  // 1/4
-            5..=8 => 0b010,  // 1/2
-            9..=12 => 0b011, // 3/4
-            _ => 0b100,      // 7/8
+    fn set_fifo_thresholds(&mut self, req: &FifoThresholds) -> Result<FifoTriggers, FifoError> {
+        let req = req.resolve(self.fifo_depth(), &self.fifo_triggers())?;
+        let levels = self.trigger_levels();
+        let sel = |want: u16| {
+            let level = select_level(&levels, want);
+            levels.iter().position(|&l| l == level).unwrap_or(0) as u32
         };
 
-        self.registers()
-            .uartifls
-            .write(UARTIFLS::RXIFLSEL.val(rx_iflsel) + UARTIFLS::TXIFLSEL.val(tx_iflsel));
+        let regs = self.registers();
+        if let Some(rx) = req.rx {
+            regs.uartifls.modify(UARTIFLS::RXIFLSEL.val(sel(rx)));
+        }
+        if let Some(tx) = req.tx {
+            regs.uartifls.modify(UARTIFLS::TXIFLSEL.val(sel(tx)));
+        }
+
+        Ok(self.fifo_triggers())
     }
 }
 
 // ModemStatus 现在在 lib.rs 中定义，这里只是导出
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake_mmio::FakeMmio;
+
+    fn uart(regs: &FakeMmio<1024>) -> Pl011 {
+        Pl011::new(regs.base(), 24_000_000)
+    }
+
+    #[test]
+    fn rx_timeout_counts_as_rx_interrupt() {
+        let regs = FakeMmio::<1024>::new();
+        let mut uart = uart(&regs);
+        let irq = uart.irq_handler().unwrap();
+
+        uart.set_irq_mask(InterruptMask::RX_AVAILABLE);
+        assert_eq!(regs.get(0x38), (UARTIS::RX::SET + UARTIS::RT::SET).value);
+
+        regs.set(0x38, UARTIS::RT::SET.value);
+        assert_eq!(
+            uart.get_irq_mask().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+
+        // FIFO 未达到触发级别，只有接收超时挂起
+        regs.set(0x40, UARTIS::RT::SET.value);
+        assert_eq!(
+            irq.clean_interrupt_status().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+        assert_eq!(regs.get(0x44), UARTIS::RT::SET.value);
+    }
+
+    #[test]
+    fn set_config_discards_queued_tx_before_waiting_busy() {
+        extern crate std;
+        use core::sync::atomic::{AtomicBool, Ordering};
+
+        let regs = FakeMmio::<1024>::new();
+        let mut uart = uart(&regs);
+        let enabled = (UARTCR::UARTEN::SET + UARTCR::TXE::SET + UARTCR::RXE::SET).value;
+        let lcr_h = (UARTLCR_H::FEN::SET + UARTLCR_H::WLEN::EightBit).value;
+        regs.set(0x30, enabled);
+        regs.set(0x2c, lcr_h);
+        // 发送 FIFO 中还有数据
+        regs.set(0x18, UARTFR::BUSY::SET.value);
+
+        // 模拟硬件：FIFO 清空后 BUSY 才会清零
+        let done = AtomicBool::new(false);
+        let result = std::thread::scope(|s| {
+            let hw = regs;
+            let done = &done;
+            s.spawn(move || {
+                while !done.load(Ordering::Acquire) {
+                    if hw.get(0x2c) & UARTLCR_H::FEN::SET.value == 0 {
+                        hw.set(0x18, 0);
+                        break;
+                    }
+                    std::thread::yield_now();
+                }
+            });
+            let result = uart.set_config(&Config::new().baudrate(115200));
+            done.store(true, Ordering::Release);
+            result
+        });
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(regs.get(0x24), 13);
+        assert_eq!(regs.get(0x2c), lcr_h);
+        assert_eq!(regs.get(0x30), enabled);
+    }
+
+    #[test]
+    fn tx_flush_toggles_fen_with_uart_disabled() {
+        extern crate std;
+        use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+        let regs = FakeMmio::<1024>::new();
+        let mut uart = uart(&regs);
+        let enabled = (UARTCR::UARTEN::SET + UARTCR::TXE::SET + UARTCR::RXE::SET).value;
+        let lcr_h = (UARTLCR_H::FEN::SET + UARTLCR_H::WLEN::EightBit).value;
+        regs.set(0x30, enabled);
+        regs.set(0x2c, lcr_h);
+        regs.set(0x18, (UARTFR::BUSY::SET + UARTFR::RXFE::SET).value);
+
+        // 模拟硬件：FEN 清零时记录 CR，FIFO 清空后 BUSY 才会清零
+        let done = AtomicBool::new(false);
+        let cr_at_toggle = AtomicU32::new(u32::MAX);
+        let result = std::thread::scope(|s| {
+            let hw = regs;
+            let (done, cr_at_toggle) = (&done, &cr_at_toggle);
+            s.spawn(move || {
+                while !done.load(Ordering::Acquire) {
+                    if hw.get(0x2c) & UARTLCR_H::FEN::SET.value == 0 {
+                        cr_at_toggle.store(hw.get(0x30), Ordering::Release);
+                        hw.set(0x18, UARTFR::RXFE::SET.value);
+                        break;
+                    }
+                    std::thread::yield_now();
+                }
+            });
+            let result = uart.flush(FlushQueue::Tx);
+            done.store(true, Ordering::Release);
+            result
+        });
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            cr_at_toggle.load(Ordering::Acquire) & UARTCR::UARTEN::SET.value,
+            0
+        );
+        assert_eq!(regs.get(0x2c), lcr_h);
+        assert_eq!(regs.get(0x30), enabled);
+    }
+}
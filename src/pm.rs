@@ -0,0 +1,38 @@
+//! 挂起/恢复支持
+//!
+//! 电源域关闭后 UART 的所有寄存器都会丢失。驱动在挂起前通过 [`Suspend::suspend`]
+//! 保存寄存器上下文，上电后通过 [`Suspend::resume`] 按硬件要求的顺序重新写回。
+
+use rdif_serial::{InterfaceRaw, InterruptMask};
+
+/// 寄存器上下文保存与恢复
+pub trait Suspend: InterfaceRaw {
+    /// 可恢复的寄存器上下文
+    type Context: Clone + Send + 'static;
+
+    /// 读取当前寄存器状态
+    fn save_context(&self) -> Self::Context;
+
+    /// 按正确顺序写回寄存器状态
+    fn restore_context(&mut self, ctx: &Self::Context);
+
+    /// 挂起前调用，返回需要在恢复时使用的上下文
+    ///
+    /// `wakeup` 为 true 时仅保留接收中断，使 RX 活动可以作为唤醒源；
+    /// 否则屏蔽全部中断。
+    fn suspend(&mut self, wakeup: bool) -> Self::Context {
+        let ctx = self.save_context();
+        let mask = if wakeup {
+            InterruptMask::RX_AVAILABLE
+        } else {
+            InterruptMask::empty()
+        };
+        self.set_irq_mask(mask);
+        ctx
+    }
+
+    /// 上电后调用，恢复挂起前的配置
+    fn resume(&mut self, ctx: &Self::Context) {
+        self.restore_context(ctx);
+    }
+}
@@ -0,0 +1,156 @@
+//! 运行中重新配置
+//!
+//! `InterfaceRaw::set_config` 立即改写寄存器：发送 FIFO 中尚未发出的字节会以新波特率
+//! 发出，PL011 等需要先禁用 UART 的设备还会直接丢弃它们。[`Reconfigure`] 对应
+//! termios `tcsetattr` 的三种时机，在改写前用 [`Flush`] 排空发送方向，必要时丢弃接收数据。
+//!
+//! 收发端被取出后仍可能并发写入，排空后新写入的字节会赶上切换。驱动与发送端共享一个
+//! [`TxPause`]，重新配置期间发送端的写入直接返回失败，调用方按 FIFO 满处理重试即可。
+//! 没有提供 [`Flush::tx_pause`] 的驱动只有在收发端都已归还时才允许重新配置，否则返回
+//! [`ReconfigError::HalvesTaken`]。
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use rdif_serial::{Config, ConfigError, InterfaceRaw};
+
+use crate::{Caps, Flush, FlushError, FlushQueue};
+
+/// 新配置生效的时机
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConfigWhen {
+    /// 立即生效（`TCSANOW`）
+    #[default]
+    Now,
+    /// 等待已写入的数据全部发出后生效（`TCSADRAIN`）
+    Drain,
+    /// 排空发送方向后生效，并丢弃尚未读取的接收数据（`TCSAFLUSH`）
+    Flush,
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconfigError {
+    #[error("sender or reciever has not been returned")]
+    HalvesTaken,
+    #[error("invalid configuration: {0:?}")]
+    Config(ConfigError),
+}
+
+impl From<ConfigError> for ReconfigError {
+    fn from(e: ConfigError) -> Self {
+        Self::Config(e)
+    }
+}
+
+/// 驱动与发送端共享的写入暂停标志
+#[derive(Clone, Default)]
+pub struct TxPause(Arc<PauseState>);
+
+#[derive(Default)]
+struct PauseState {
+    paused: AtomicBool,
+    /// 已通过检查、正在写寄存器的发送端数量
+    writers: AtomicUsize,
+}
+
+/// 发送端写入期间持有，释放后重新配置才能继续
+pub struct Writing<'a>(&'a PauseState);
+
+impl Drop for Writing<'_> {
+    fn drop(&mut self) {
+        self.0.writers.fetch_sub(1, Ordering::Release);
+    }
+}
+
+impl TxPause {
+    /// 发送端写入前调用，暂停期间返回 `None`
+    pub fn enter(&self) -> Option<Writing<'_>> {
+        let state = &*self.0;
+        // 先登记再检查，与 pause 先置位再等待配对，两边至少有一方能看到对方
+        state.writers.fetch_add(1, Ordering::SeqCst);
+        let writing = Writing(state);
+        if state.paused.load(Ordering::SeqCst) {
+            return None;
+        }
+        Some(writing)
+    }
+
+    /// 置位暂停标志，并等待已经开始的写入完成
+    pub(crate) fn pause(&self) {
+        self.0.paused.store(true, Ordering::SeqCst);
+        while self.0.writers.load(Ordering::SeqCst) != 0 {
+            core::hint::spin_loop();
+        }
+    }
+
+    pub(crate) fn resume(&self) {
+        self.0.paused.store(false, Ordering::Release);
+    }
+
+    /// 是否处于暂停状态
+    pub fn is_paused(&self) -> bool {
+        self.0.paused.load(Ordering::Acquire)
+    }
+}
+
+/// 收发端是否都在驱动中，取出检查后原样放回
+pub(crate) fn halves_home<D: InterfaceRaw + ?Sized>(dev: &mut D) -> bool {
+    let tx = dev.take_tx();
+    let rx = dev.take_rx();
+    let home = tx.is_some() && rx.is_some();
+    // 取出的是驱动自己的收发端，放回不会失败
+    if let Some(tx) = tx {
+        let _ = dev.set_tx(tx);
+    }
+    if let Some(rx) = rx {
+        let _ = dev.set_rx(rx);
+    }
+    home
+}
+
+/// 按指定时机应用配置
+///
+/// 为所有同时实现 [`Flush`] 的驱动自动实现。
+pub trait Reconfigure: InterfaceRaw + Caps + Flush {
+    /// 先整体校验配置，失败时不排空也不丢弃任何数据
+    ///
+    /// 从暂停发送端到新配置生效期间，已取出的发送端写入失败。
+    /// 排空超时返回 `ReconfigError::Config(ConfigError::Timeout)`，此时配置保持不变。
+    /// `Flush` 模式在新配置生效之后才丢弃接收数据，切换过程中按旧波特率收到的乱码一并清除。
+    fn set_config_when(&mut self, config: &Config, when: ConfigWhen) -> Result<(), ReconfigError> {
+        self.capabilities().validate(config)?;
+        let pause = self.tx_pause().cloned();
+        if pause.is_none() && !halves_home(self) {
+            return Err(ReconfigError::HalvesTaken);
+        }
+
+        if let Some(pause) = &pause {
+            pause.pause();
+        }
+        let result = apply(self, config, when);
+        if let Some(pause) = &pause {
+            pause.resume();
+        }
+        result
+    }
+}
+
+impl<T: InterfaceRaw + Caps + Flush> Reconfigure for T {}
+
+fn apply<D: Reconfigure + ?Sized>(
+    dev: &mut D,
+    config: &Config,
+    when: ConfigWhen,
+) -> Result<(), ReconfigError> {
+    if when != ConfigWhen::Now {
+        dev.drain().map_err(|_: FlushError| ConfigError::Timeout)?;
+    }
+
+    dev.set_config(config)?;
+
+    if when == ConfigWhen::Flush {
+        // 只清空接收方向，不会返回 TxUnsupported
+        let _ = dev.flush(FlushQueue::Rx);
+    }
+    Ok(())
+}
@@ -0,0 +1,1124 @@
+//! Samsung UART 驱动 (`samsung,s5pv210-uart` / `samsung,exynos4210-uart` / `apple,s5l-uart`)
+//!
+//! 覆盖 S5PV210/Exynos 系列及其在 Apple Silicon 上的 S5L 衍生版本。两者共用
+//! ULCON/UCON/UFCON/UTRSTAT/UFSTAT/UBRDIV/UFRACVAL 寄存器布局，差异在于：
+//!
+//! - Exynos 的 FIFO 深度按端口不同（例如 Exynos4210 为 256/64/16/16），
+//!   UFSTAT 计数字段宽 8 位；中断由独立的 UINTP/UINTM 管理。
+//! - Apple S5L 的 FIFO 固定 16 字节，沿用 S3C2410 的 UFSTAT 布局；
+//!   中断使能位位于 UCON，状态位位于 UTRSTAT（写 1 清除）。
+//!
+//! 波特率由整数和 1/16 小数分频组成：`baud = clk / (16 * (UBRDIV + 1) + UFRACVAL)`。
+
+use core::{num::NonZeroU32, ptr::NonNull};
+
+use alloc::sync::Arc;
+
+use rdif_serial::{BSerial, InterfaceRaw, SerialDyn, SetBackError, TIrqHandler, TSender};
+use tock_registers::{
+    fields::FieldValue, interfaces::*, register_bitfields, register_structs, registers::*,
+    LocalRegisterCopy,
+};
+
+use crate::{
+    caps::{ALL_DATA_BITS, ALL_PARITY, ALL_STOP_BITS},
+    claim::{self, Claim, ClaimError, Region},
+    fifo::{FifoError, FifoThreshold, FifoThresholds, FifoTriggers},
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, IrqCause, Stats},
+    Capabilities, Caps, Config, ConfigError, DataBits, Features, InterruptMask, Parity,
+    RawReciever, RawSender, StopBits, Suspend, TransferError,
+};
+
+register_bitfields! [
+    u32,
+
+    /// Line Control Register
+    ULCON [
+        WORD_LEN OFFSET(0) NUMBITS(2) [
+            FiveBit = 0b00,
+            SixBit = 0b01,
+            SevenBit = 0b10,
+            EightBit = 0b11
+        ],
+        STOP OFFSET(2) NUMBITS(1) [],
+        PARITY OFFSET(3) NUMBITS(3) [
+            None = 0b000,
+            Odd = 0b100,
+            Even = 0b101,
+            Mark = 0b110,
+            Space = 0b111
+        ],
+        IR_MODE OFFSET(6) NUMBITS(1) []
+    ],
+
+    /// Control Register
+    UCON [
+        RX_MODE OFFSET(0) NUMBITS(2) [
+            Disabled = 0b00,
+            IrqOrPolling = 0b01
+        ],
+        TX_MODE OFFSET(2) NUMBITS(2) [
+            Disabled = 0b00,
+            IrqOrPolling = 0b01
+        ],
+        SEND_BREAK OFFSET(4) NUMBITS(1) [],
+        LOOPBACK OFFSET(5) NUMBITS(1) [],
+        RX_ERR_IRQ OFFSET(6) NUMBITS(1) [],
+        RX_TIMEOUT OFFSET(7) NUMBITS(1) [],
+        RX_LEVEL_IRQ OFFSET(8) NUMBITS(1) [],
+        TX_LEVEL_IRQ OFFSET(9) NUMBITS(1) [],
+        // Apple S5L 的中断使能位，与 Exynos 的 TX_LEVEL_IRQ 重叠
+        S5L_RXTO_ENA OFFSET(9) NUMBITS(1) [],
+        S5L_RXTHRESH_ENA OFFSET(12) NUMBITS(1) [],
+        S5L_TXTHRESH_ENA OFFSET(13) NUMBITS(1) []
+    ],
+
+    /// FIFO Control Register
+    UFCON [
+        FIFO_EN OFFSET(0) NUMBITS(1) [],
+        RX_RESET OFFSET(1) NUMBITS(1) [],
+        TX_RESET OFFSET(2) NUMBITS(1) [],
+        RX_TRIGGER OFFSET(4) NUMBITS(3) [],
+        TX_TRIGGER OFFSET(8) NUMBITS(3) []
+    ],
+
+    /// Modem Control Register
+    UMCON [
+        RTS OFFSET(0) NUMBITS(1) [],
+        AFC OFFSET(4) NUMBITS(1) []
+    ],
+
+    /// Tx/Rx Status Register
+    UTRSTAT [
+        RX_READY OFFSET(0) NUMBITS(1) [],
+        TX_BUF_EMPTY OFFSET(1) NUMBITS(1) [],
+        TX_EMPTY OFFSET(2) NUMBITS(1) [],
+        // Apple S5L 的中断状态位，写 1 清除
+        S5L_RXTO_LEGACY OFFSET(3) NUMBITS(1) [],
+        S5L_RXTHRESH OFFSET(4) NUMBITS(1) [],
+        S5L_TXTHRESH OFFSET(5) NUMBITS(1) [],
+        S5L_RXTO OFFSET(9) NUMBITS(1) []
+    ],
+
+    /// Error Status Register（读取后清除）
+    UERSTAT [
+        OVERRUN OFFSET(0) NUMBITS(1) [],
+        PARITY OFFSET(1) NUMBITS(1) [],
+        FRAME OFFSET(2) NUMBITS(1) [],
+        BREAK OFFSET(3) NUMBITS(1) []
+    ],
+
+    /// FIFO Status Register（S5PV210/Exynos 布局）
+    UFSTAT [
+        RX_COUNT OFFSET(0) NUMBITS(8) [],
+        RX_FULL OFFSET(8) NUMBITS(1) [],
+        RX_ERROR OFFSET(9) NUMBITS(1) [],
+        TX_COUNT OFFSET(16) NUMBITS(8) [],
+        TX_FULL OFFSET(24) NUMBITS(1) []
+    ],
+
+    /// FIFO Status Register（S3C2410/Apple S5L 布局）
+    UFSTAT_S5L [
+        RX_COUNT OFFSET(0) NUMBITS(4) [],
+        TX_COUNT OFFSET(4) NUMBITS(4) [],
+        RX_FULL OFFSET(8) NUMBITS(1) [],
+        TX_FULL OFFSET(9) NUMBITS(1) []
+    ],
+
+    /// Transmit Buffer Register
+    UTXH [
+        DATA OFFSET(0) NUMBITS(8) []
+    ],
+
+    /// Receive Buffer Register
+    URXH [
+        DATA OFFSET(0) NUMBITS(8) []
+    ],
+
+    /// Baud Rate Divisor Register
+    UBRDIV [
+        DIV OFFSET(0) NUMBITS(16) []
+    ],
+
+    /// Divisor Fractional Value Register
+    UFRACVAL [
+        FRAC OFFSET(0) NUMBITS(4) []
+    ],
+
+    /// Interrupt Pending / Source Pending / Mask Register（仅 Exynos）
+    UINT [
+        RXD OFFSET(0) NUMBITS(1) [],
+        ERROR OFFSET(1) NUMBITS(1) [],
+        TXD OFFSET(2) NUMBITS(1) [],
+        MODEM OFFSET(3) NUMBITS(1) []
+    ]
+];
+
+register_structs! {
+    pub SamsungUartRegisters {
+        (0x00 => ulcon: ReadWrite<u32, ULCON::Register>),     // 线路控制寄存器
+        (0x04 => ucon: ReadWrite<u32, UCON::Register>),       // 控制寄存器
+        (0x08 => ufcon: ReadWrite<u32, UFCON::Register>),     // FIFO 控制寄存器
+        (0x0c => umcon: ReadWrite<u32, UMCON::Register>),     // 调制解调器控制寄存器
+        (0x10 => utrstat: ReadWrite<u32, UTRSTAT::Register>), // 收发状态寄存器
+        (0x14 => uerstat: ReadOnly<u32, UERSTAT::Register>),  // 错误状态寄存器
+        (0x18 => ufstat: ReadOnly<u32>),                      // FIFO 状态寄存器，布局随变体不同
+        (0x1c => umstat: ReadOnly<u32>),                      // 调制解调器状态寄存器
+        (0x20 => utxh: WriteOnly<u32, UTXH::Register>),       // 发送缓冲寄存器
+        (0x24 => urxh: ReadOnly<u32, URXH::Register>),        // 接收缓冲寄存器
+        (0x28 => ubrdiv: ReadWrite<u32, UBRDIV::Register>),   // 波特率整数分频
+        (0x2c => ufracval: ReadWrite<u32, UFRACVAL::Register>), // 波特率小数分频
+        (0x30 => uintp: ReadWrite<u32, UINT::Register>),      // 中断挂起寄存器（写 1 清除）
+        (0x34 => uintsp: ReadWrite<u32, UINT::Register>),     // 中断源挂起寄存器
+        (0x38 => uintm: ReadWrite<u32, UINT::Register>),      // 中断屏蔽寄存器
+        (0x3c => @END),
+    }
+}
+
+// SAFETY: 寄存器访问均为单次 32 位读写
+unsafe impl Sync for SamsungUartRegisters {}
+
+/// 等待发送完成的最大轮询次数
+const BUSY_SPIN_LIMIT: usize = 1_000_000;
+
+/// UBRDIV 的最大值（加 1 后）
+const UBRDIV_MAX: u32 = 0x1_0000;
+
+/// Samsung UART 寄存器变体
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SamsungVariant {
+    /// S5PV210 / Exynos：UINTP/UINTM 中断，8 位 FIFO 计数
+    Exynos,
+    /// Apple S5L：UCON/UTRSTAT 中断，S3C2410 风格 FIFO 计数，FIFO 固定 16 字节
+    AppleS5l,
+}
+
+impl SamsungVariant {
+    /// 设备树未给出 `samsung,uart-fifosize` 时按端口号选择的 FIFO 深度
+    pub const fn fifo_depth(self, port: usize) -> u16 {
+        match self {
+            Self::Exynos => match port {
+                0 => 256,
+                1 => 64,
+                _ => 16,
+            },
+            Self::AppleS5l => 16,
+        }
+    }
+
+    fn rx_pending(self, ufstat: u32) -> bool {
+        match self {
+            Self::Exynos => {
+                let ufstat = LocalRegisterCopy::<u32, UFSTAT::Register>::new(ufstat);
+                ufstat.read(UFSTAT::RX_COUNT) > 0 || ufstat.is_set(UFSTAT::RX_FULL)
+            }
+            Self::AppleS5l => {
+                let ufstat = LocalRegisterCopy::<u32, UFSTAT_S5L::Register>::new(ufstat);
+                ufstat.read(UFSTAT_S5L::RX_COUNT) > 0 || ufstat.is_set(UFSTAT_S5L::RX_FULL)
+            }
+        }
+    }
+
+    fn tx_full(self, ufstat: u32) -> bool {
+        match self {
+            Self::Exynos => {
+                LocalRegisterCopy::<u32, UFSTAT::Register>::new(ufstat).is_set(UFSTAT::TX_FULL)
+            }
+            Self::AppleS5l => LocalRegisterCopy::<u32, UFSTAT_S5L::Register>::new(ufstat)
+                .is_set(UFSTAT_S5L::TX_FULL),
+        }
+    }
+}
+
+/// Samsung UART 驱动结构体
+pub struct SamsungUart {
+    base: Reg,
+    clock_freq: u32,
+    variant: SamsungVariant,
+    fifo_depth: u16,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    tx_pause: TxPause,
+    tx: Option<SamsungUartSender>,
+    rx: Option<SamsungUartReciever>,
+    irq: Option<SamsungUartIrqHandler>,
+    /// 通过 `try_new` 系列创建时持有的区域占用，与各句柄共享
+    claim: Option<Arc<Claim>>,
+}
+
+impl SamsungUart {
+    /// `fifo_depth` 取自设备树 `samsung,uart-fifosize`，或使用 [`SamsungVariant::fifo_depth`]
+    ///
+    /// 不占用寄存器区域，需要防止同一设备被重复创建时使用 [`Self::try_new`]
+    pub fn new(
+        base: NonNull<u8>,
+        clock_freq: u32,
+        variant: SamsungVariant,
+        fifo_depth: u16,
+    ) -> Self {
+        Self::with_claim(base, clock_freq, variant, fifo_depth, None)
+    }
+
+    pub fn new_boxed(
+        base: NonNull<u8>,
+        clock_freq: u32,
+        variant: SamsungVariant,
+        fifo_depth: u16,
+    ) -> BSerial {
+        SerialDyn::new_boxed(Self::new(base, clock_freq, variant, fifo_depth))
+    }
+
+    /// 占用寄存器区域后创建实例，区域与已有实例重叠时返回错误
+    pub fn try_new(
+        base: NonNull<u8>,
+        clock_freq: u32,
+        variant: SamsungVariant,
+        fifo_depth: u16,
+    ) -> Result<Self, ClaimError> {
+        let claim = claim::claim(Region::mmio(base, size_of::<SamsungUartRegisters>()))?;
+        Ok(Self::with_claim(
+            base,
+            clock_freq,
+            variant,
+            fifo_depth,
+            Some(claim),
+        ))
+    }
+
+    pub fn try_new_boxed(
+        base: NonNull<u8>,
+        clock_freq: u32,
+        variant: SamsungVariant,
+        fifo_depth: u16,
+    ) -> Result<BSerial, ClaimError> {
+        Ok(SerialDyn::new_boxed(Self::try_new(
+            base, clock_freq, variant, fifo_depth,
+        )?))
+    }
+
+    /// 通过 `try_new` 系列创建时持有的寄存器区域占用
+    pub fn claim(&self) -> Option<&Claim> {
+        self.claim.as_deref()
+    }
+
+    fn with_claim(
+        base: NonNull<u8>,
+        clock_freq: u32,
+        variant: SamsungVariant,
+        fifo_depth: u16,
+        claim: Option<Arc<Claim>>,
+    ) -> Self {
+        let base = Reg(base.cast());
+        let tx_pause = TxPause::default();
+
+        Self {
+            base,
+            clock_freq,
+            variant,
+            fifo_depth,
+            tx_pause: tx_pause.clone(),
+            tx: Some(SamsungUartSender {
+                base,
+                variant,
+                stats: None,
+                tx_pause: tx_pause.clone(),
+                _claim: claim.clone(),
+            }),
+            rx: Some(SamsungUartReciever {
+                base,
+                variant,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            irq: Some(SamsungUartIrqHandler {
+                base,
+                variant,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            claim,
+        }
+    }
+
+    fn registers(&self) -> &SamsungUartRegisters {
+        self.base.registers()
+    }
+
+    /// 挂接统计计数器，只对尚未取出的收发和中断句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+        if let Some(irq) = self.irq.as_mut() {
+            irq.stats = Some(stats);
+        }
+    }
+
+    /// 计算 (UBRDIV, UFRACVAL)：clk / baud 四舍五入到 1/16 后拆分为整数和小数部分
+    fn divisors_for(&self, baudrate: u32) -> Result<(u32, u32), ConfigError> {
+        if baudrate == 0 || self.clock_freq == 0 {
+            return Err(ConfigError::InvalidBaudrate);
+        }
+
+        let sixteenths = (self.clock_freq as u64 + baudrate as u64 / 2) / baudrate as u64;
+        let div = sixteenths / 16;
+        if div == 0 || div > UBRDIV_MAX as u64 {
+            return Err(ConfigError::InvalidBaudrate);
+        }
+
+        Ok((div as u32 - 1, (sixteenths % 16) as u32))
+    }
+
+    /// 在当前 ULCON 的基础上计算应用配置后的值，未设置的项保持不变
+    fn format_for(&self, config: &Config) -> LocalRegisterCopy<u32, ULCON::Register> {
+        let mut ulcon = self.registers().ulcon.extract();
+
+        if let Some(bits) = config.data_bits {
+            let field = match bits {
+                DataBits::Five => ULCON::WORD_LEN::FiveBit,
+                DataBits::Six => ULCON::WORD_LEN::SixBit,
+                DataBits::Seven => ULCON::WORD_LEN::SevenBit,
+                DataBits::Eight => ULCON::WORD_LEN::EightBit,
+            };
+            ulcon.modify(field);
+        }
+
+        if let Some(stop_bits) = config.stop_bits {
+            ulcon.modify(ULCON::STOP.val((stop_bits == StopBits::Two) as u32));
+        }
+
+        if let Some(parity) = config.parity {
+            let field = match parity {
+                Parity::None => ULCON::PARITY::None,
+                Parity::Odd => ULCON::PARITY::Odd,
+                Parity::Even => ULCON::PARITY::Even,
+                Parity::Mark => ULCON::PARITY::Mark,
+                Parity::Space => ULCON::PARITY::Space,
+            };
+            ulcon.modify(field);
+        }
+
+        ulcon.modify(ULCON::IR_MODE::CLEAR);
+        ulcon
+    }
+
+    /// 等待发送 FIFO 和移位寄存器都为空
+    fn wait_tx_idle(&self) -> bool {
+        for _ in 0..BUSY_SPIN_LIMIT {
+            if self.registers().utrstat.is_set(UTRSTAT::TX_EMPTY) {
+                return true;
+            }
+            core::hint::spin_loop();
+        }
+        false
+    }
+
+    /// 屏蔽并清除所有中断
+    fn mask_all_irqs(&self) {
+        let regs = self.registers();
+        match self.variant {
+            SamsungVariant::Exynos => {
+                regs.uintm
+                    .write(UINT::RXD::SET + UINT::ERROR::SET + UINT::TXD::SET + UINT::MODEM::SET);
+                regs.uintp.set(regs.uintp.get());
+            }
+            SamsungVariant::AppleS5l => {
+                regs.ucon.modify(
+                    UCON::S5L_RXTO_ENA::CLEAR
+                        + UCON::S5L_RXTHRESH_ENA::CLEAR
+                        + UCON::S5L_TXTHRESH_ENA::CLEAR,
+                );
+                regs.utrstat.write(s5l_irq_flags());
+            }
+        }
+    }
+
+    /// 初始化 Samsung UART
+    fn init(&self) {
+        let regs = self.registers();
+
+        self.mask_all_irqs();
+
+        // 复位并启用 FIFO，触发级别取最低档
+        regs.ufcon
+            .write(UFCON::FIFO_EN::SET + UFCON::RX_RESET::SET + UFCON::TX_RESET::SET);
+
+        regs.umcon.write(UMCON::RTS::SET);
+
+        // 收发均使用中断/轮询模式，关闭回环和 break
+        let mut ucon = UCON::RX_MODE::IrqOrPolling
+            + UCON::TX_MODE::IrqOrPolling
+            + UCON::SEND_BREAK::CLEAR
+            + UCON::LOOPBACK::CLEAR;
+        if self.variant == SamsungVariant::Exynos {
+            // 电平触发，并在 FIFO 未达到触发级别时由接收超时补报
+            ucon += UCON::RX_TIMEOUT::SET + UCON::RX_LEVEL_IRQ::SET + UCON::TX_LEVEL_IRQ::SET;
+        }
+        regs.ucon.modify(ucon);
+    }
+}
+
+/// Apple S5L UTRSTAT 中所有写 1 清除的中断状态位
+fn s5l_irq_flags() -> FieldValue<u32, UTRSTAT::Register> {
+    UTRSTAT::S5L_RXTO_LEGACY::SET
+        + UTRSTAT::S5L_RXTHRESH::SET
+        + UTRSTAT::S5L_TXTHRESH::SET
+        + UTRSTAT::S5L_RXTO::SET
+}
+
+impl InterfaceRaw for SamsungUart {
+    type IrqHandler = SamsungUartIrqHandler;
+
+    type Sender = crate::Sender;
+
+    type Reciever = crate::Reciever;
+
+    fn base_addr(&self) -> usize {
+        self.base.0.as_ptr() as usize
+    }
+
+    fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        self.capabilities().validate(config)?;
+        let divisors = config
+            .baudrate
+            .map(|baudrate| self.divisors_for(baudrate))
+            .transpose()?;
+        let ulcon = self.format_for(config);
+
+        if !self.wait_tx_idle() {
+            return Err(ConfigError::Timeout);
+        }
+
+        let regs = self.registers();
+
+        if let Some((div, frac)) = divisors {
+            regs.ubrdiv.write(UBRDIV::DIV.val(div));
+            regs.ufracval.write(UFRACVAL::FRAC.val(frac));
+        }
+        regs.ulcon.set(ulcon.get());
+
+        Ok(())
+    }
+
+    fn baudrate(&self) -> u32 {
+        let regs = self.registers();
+        let div = regs.ubrdiv.read(UBRDIV::DIV);
+        let frac = regs.ufracval.read(UFRACVAL::FRAC);
+
+        self.clock_freq / (16 * (div + 1) + frac)
+    }
+
+    fn data_bits(&self) -> DataBits {
+        match self.registers().ulcon.read_as_enum(ULCON::WORD_LEN) {
+            Some(ULCON::WORD_LEN::Value::FiveBit) => DataBits::Five,
+            Some(ULCON::WORD_LEN::Value::SixBit) => DataBits::Six,
+            Some(ULCON::WORD_LEN::Value::SevenBit) => DataBits::Seven,
+            Some(ULCON::WORD_LEN::Value::EightBit) | None => DataBits::Eight,
+        }
+    }
+
+    fn stop_bits(&self) -> StopBits {
+        if self.registers().ulcon.is_set(ULCON::STOP) {
+            StopBits::Two
+        } else {
+            StopBits::One
+        }
+    }
+
+    fn parity(&self) -> Parity {
+        match self.registers().ulcon.read_as_enum(ULCON::PARITY) {
+            Some(ULCON::PARITY::Value::Odd) => Parity::Odd,
+            Some(ULCON::PARITY::Value::Even) => Parity::Even,
+            Some(ULCON::PARITY::Value::Mark) => Parity::Mark,
+            Some(ULCON::PARITY::Value::Space) => Parity::Space,
+            // 0b0xx 均表示无校验
+            _ => Parity::None,
+        }
+    }
+
+    fn clock_freq(&self) -> Option<NonZeroU32> {
+        self.clock_freq.try_into().ok()
+    }
+
+    fn open(&mut self) {
+        self.init()
+    }
+
+    fn close(&mut self) {
+        self.mask_all_irqs();
+        self.registers()
+            .ucon
+            .modify(UCON::RX_MODE::Disabled + UCON::TX_MODE::Disabled);
+    }
+
+    fn enable_loopback(&mut self) {
+        self.registers().ucon.modify(UCON::LOOPBACK::SET);
+    }
+
+    fn disable_loopback(&mut self) {
+        self.registers().ucon.modify(UCON::LOOPBACK::CLEAR);
+    }
+
+    fn is_loopback_enabled(&self) -> bool {
+        self.registers().ucon.is_set(UCON::LOOPBACK)
+    }
+
+    fn set_irq_mask(&mut self, mask: InterruptMask) {
+        let rx = mask.contains(InterruptMask::RX_AVAILABLE) as u32;
+        let tx = mask.contains(InterruptMask::TX_EMPTY) as u32;
+        let regs = self.registers();
+
+        match self.variant {
+            SamsungVariant::Exynos => {
+                // UINTM 中置位表示屏蔽，错误和调制解调器中断始终屏蔽
+                regs.uintm.write(
+                    UINT::RXD.val(rx ^ 1)
+                        + UINT::ERROR::SET
+                        + UINT::TXD.val(tx ^ 1)
+                        + UINT::MODEM::SET,
+                );
+            }
+            SamsungVariant::AppleS5l => {
+                regs.ucon.modify(
+                    UCON::S5L_RXTO_ENA.val(rx)
+                        + UCON::S5L_RXTHRESH_ENA.val(rx)
+                        + UCON::S5L_TXTHRESH_ENA.val(tx),
+                );
+            }
+        }
+    }
+
+    fn get_irq_mask(&self) -> InterruptMask {
+        let regs = self.registers();
+        let (rx, tx) = match self.variant {
+            SamsungVariant::Exynos => {
+                let uintm = regs.uintm.extract();
+                (!uintm.is_set(UINT::RXD), !uintm.is_set(UINT::TXD))
+            }
+            SamsungVariant::AppleS5l => {
+                let ucon = regs.ucon.extract();
+                (
+                    ucon.is_set(UCON::S5L_RXTHRESH_ENA),
+                    ucon.is_set(UCON::S5L_TXTHRESH_ENA),
+                )
+            }
+        };
+
+        let mut mask = InterruptMask::empty();
+        if rx {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if tx {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        mask
+    }
+
+    fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+        self.irq.take()
+    }
+
+    fn take_tx(&mut self) -> Option<Self::Sender> {
+        self.tx.take().map(crate::Sender::SamsungUartSender)
+    }
+
+    fn take_rx(&mut self) -> Option<Self::Reciever> {
+        self.rx.take().map(crate::Reciever::SamsungUartReciever)
+    }
+
+    fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+        let tx = match tx {
+            crate::Sender::SamsungUartSender(s) => s,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    tx.external_base().unwrap_or(0), // 不匹配的发送器类型
+                ));
+            }
+        };
+
+        if self.base != tx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                tx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.tx = Some(tx);
+        Ok(())
+    }
+
+    fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+        let rx = match rx {
+            crate::Reciever::SamsungUartReciever(r) => r,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    rx.external_base().unwrap_or(0), // 不匹配的接收器类型
+                ));
+            }
+        };
+
+        if self.base != rx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                rx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.rx = Some(rx);
+        Ok(())
+    }
+}
+
+impl Caps for SamsungUart {
+    fn capabilities(&self) -> Capabilities {
+        let (min_baudrate, max_baudrate) =
+            Capabilities::baudrate_range(self.clock_freq, 16, UBRDIV_MAX);
+
+        Capabilities {
+            fifo_depth: self.fifo_depth,
+            min_baudrate,
+            max_baudrate,
+            data_bits: ALL_DATA_BITS,
+            stop_bits: ALL_STOP_BITS,
+            parity: ALL_PARITY,
+            features: Features::HW_FLOW_CONTROL | Features::LOOPBACK,
+        }
+    }
+}
+
+/// Exynos 的触发级别以 FIFO 深度的 1/8 为步长：接收为 (n + 1) 步，发送为 n 步。
+/// Apple S5L 的 UFCON 编码不同，触发级别报告为不可配置。
+impl FifoThreshold for SamsungUart {
+    fn fifo_triggers(&self) -> FifoTriggers {
+        if self.variant != SamsungVariant::Exynos {
+            return FifoTriggers::default();
+        }
+
+        let step = self.fifo_depth / 8;
+        let ufcon = self.registers().ufcon.extract();
+        FifoTriggers {
+            rx: Some(step * (ufcon.read(UFCON::RX_TRIGGER) as u16 + 1)),
+            tx: Some(step * ufcon.read(UFCON::TX_TRIGGER) as u16),
+            rx_timeout: None,
+        }
+    }
+
+    fn set_fifo_thresholds(&mut self, req: &FifoThresholds) -> Result<FifoTriggers, FifoError> {
+        let req = req.resolve(self.fifo_depth, &self.fifo_triggers())?;
+        let step = (self.fifo_depth / 8).max(1);
+        let regs = self.registers();
+
+        // UFCON 的复位位写 1 生效，修改时不能带上
+        let mut ufcon = regs.ufcon.extract();
+        ufcon.modify(UFCON::RX_RESET::CLEAR + UFCON::TX_RESET::CLEAR);
+        if let Some(rx) = req.rx {
+            ufcon.modify(UFCON::RX_TRIGGER.val((rx / step).clamp(1, 8) as u32 - 1));
+        }
+        if let Some(tx) = req.tx {
+            ufcon.modify(UFCON::TX_TRIGGER.val((tx / step).min(7) as u32));
+        }
+        regs.ufcon.set(ufcon.get());
+
+        Ok(self.fifo_triggers())
+    }
+}
+
+impl Flush for SamsungUart {
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError> {
+        let regs = self.registers();
+
+        // 复位位由硬件自动清零
+        let reset = match queue {
+            FlushQueue::Rx => UFCON::RX_RESET::SET,
+            FlushQueue::Tx => UFCON::TX_RESET::SET,
+            FlushQueue::Both => UFCON::RX_RESET::SET + UFCON::TX_RESET::SET,
+        };
+        regs.ufcon.modify(reset);
+
+        if queue.rx() {
+            // UERSTAT 读取即清除
+            regs.uerstat.get();
+        }
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), FlushError> {
+        if self.wait_tx_idle() {
+            Ok(())
+        } else {
+            Err(FlushError::Timeout)
+        }
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// Samsung UART 挂起时保存的寄存器上下文
+#[derive(Debug, Clone, Copy)]
+pub struct SamsungUartContext {
+    ulcon: u32,
+    ucon: u32,
+    ufcon: u32,
+    umcon: u32,
+    ubrdiv: u32,
+    ufracval: u32,
+    uintm: u32,
+}
+
+impl Suspend for SamsungUart {
+    type Context = SamsungUartContext;
+
+    fn save_context(&self) -> Self::Context {
+        let regs = self.registers();
+        SamsungUartContext {
+            ulcon: regs.ulcon.get(),
+            ucon: regs.ucon.get(),
+            ufcon: regs.ufcon.get(),
+            umcon: regs.umcon.get(),
+            ubrdiv: regs.ubrdiv.get(),
+            ufracval: regs.ufracval.get(),
+            uintm: match self.variant {
+                SamsungVariant::Exynos => regs.uintm.get(),
+                SamsungVariant::AppleS5l => 0,
+            },
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        let regs = self.registers();
+
+        // 恢复期间保持中断关闭
+        self.mask_all_irqs();
+
+        regs.ubrdiv.set(ctx.ubrdiv);
+        regs.ufracval.set(ctx.ufracval);
+        regs.ulcon.set(ctx.ulcon);
+        regs.umcon.set(ctx.umcon);
+        regs.ufcon
+            .set(ctx.ufcon | (UFCON::RX_RESET::SET + UFCON::TX_RESET::SET).value);
+
+        // Apple S5L 的中断使能位于 UCON，需先清除上电残留的状态
+        match self.variant {
+            SamsungVariant::Exynos => {
+                regs.ucon.set(ctx.ucon);
+                regs.uintp.set(regs.uintp.get());
+                regs.uintm.set(ctx.uintm);
+            }
+            SamsungVariant::AppleS5l => {
+                regs.utrstat.write(s5l_irq_flags());
+                regs.ucon.set(ctx.ucon);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Reg(NonNull<SamsungUartRegisters>);
+
+unsafe impl Send for Reg {}
+
+impl Reg {
+    fn registers(&self) -> &SamsungUartRegisters {
+        unsafe { self.0.as_ref() }
+    }
+}
+
+pub struct SamsungUartSender {
+    base: Reg,
+    variant: SamsungVariant,
+    stats: Option<&'static Stats>,
+    tx_pause: TxPause,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for SamsungUartSender {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl TSender for SamsungUartSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        RawSender::write_byte(self, byte)
+    }
+}
+
+impl RawSender for SamsungUartSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return false;
+        };
+        let regs = self.base.registers();
+        if self.variant.tx_full(regs.ufstat.get()) {
+            return false;
+        }
+
+        regs.utxh.write(UTXH::DATA.val(byte as _));
+        if let Some(stats) = self.stats {
+            stats.record_tx(1);
+        }
+
+        true
+    }
+}
+
+pub struct SamsungUartReciever {
+    base: Reg,
+    variant: SamsungVariant,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for SamsungUartReciever {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl SamsungUartReciever {
+    fn read_raw(&mut self) -> Option<Result<u8, TransferError>> {
+        let regs = self.base.registers();
+        if !self.variant.rx_pending(regs.ufstat.get()) {
+            return None;
+        }
+
+        // UERSTAT 对应 FIFO 头部的字节，必须在读取 URXH 之前读出
+        let err = regs.uerstat.extract();
+        let data = regs.urxh.read(URXH::DATA) as u8;
+
+        let result = if err.is_set(UERSTAT::BREAK) {
+            Err(TransferError::Break)
+        } else if err.is_set(UERSTAT::PARITY) {
+            Err(TransferError::Parity)
+        } else if err.is_set(UERSTAT::FRAME) {
+            Err(TransferError::Framing)
+        } else if err.is_set(UERSTAT::OVERRUN) {
+            Err(TransferError::Overrun(data))
+        } else {
+            Ok(data)
+        };
+
+        Some(result)
+    }
+}
+
+impl RawReciever for SamsungUartReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        let result = self.read_raw()?;
+        if let Some(stats) = self.stats {
+            stats.record_rx(&result);
+        }
+        Some(result)
+    }
+}
+
+pub struct SamsungUartIrqHandler {
+    base: Reg,
+    variant: SamsungVariant,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+unsafe impl Sync for SamsungUartIrqHandler {}
+
+impl SamsungUartIrqHandler {
+    fn clean_exynos(&self) -> InterruptMask {
+        let regs = self.base.registers();
+        let pending = regs.uintp.extract();
+        let mut mask = InterruptMask::empty();
+
+        // 接收超时同样通过 RXD 上报
+        if pending.is_set(UINT::RXD) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if pending.is_set(UINT::TXD) {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        if let Some(stats) = self.stats {
+            if pending.is_set(UINT::RXD) {
+                stats.record_irq(IrqCause::Rx);
+            }
+            if pending.is_set(UINT::TXD) {
+                stats.record_irq(IrqCause::Tx);
+            }
+            if pending.is_set(UINT::ERROR) {
+                stats.record_irq(IrqCause::LineStatus);
+            }
+            if pending.is_set(UINT::MODEM) {
+                stats.record_irq(IrqCause::ModemStatus);
+            }
+        }
+
+        // 写 1 清除已处理的挂起位
+        regs.uintp.set(pending.get());
+
+        mask
+    }
+
+    fn clean_s5l(&self) -> InterruptMask {
+        let regs = self.base.registers();
+        let ucon = regs.ucon.extract();
+        let utrstat = regs.utrstat.extract();
+        let mut mask = InterruptMask::empty();
+
+        let rx_thresh = utrstat.is_set(UTRSTAT::S5L_RXTHRESH);
+        let rx_timeout =
+            utrstat.is_set(UTRSTAT::S5L_RXTO) || utrstat.is_set(UTRSTAT::S5L_RXTO_LEGACY);
+        let tx = utrstat.is_set(UTRSTAT::S5L_TXTHRESH);
+
+        if (rx_thresh || rx_timeout) && ucon.is_set(UCON::S5L_RXTHRESH_ENA) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if tx && ucon.is_set(UCON::S5L_TXTHRESH_ENA) {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        if let Some(stats) = self.stats {
+            if rx_thresh {
+                stats.record_irq(IrqCause::Rx);
+            }
+            if rx_timeout {
+                stats.record_irq(IrqCause::RxTimeout);
+            }
+            if mask.contains(InterruptMask::TX_EMPTY) {
+                stats.record_irq(IrqCause::Tx);
+            }
+        }
+
+        regs.utrstat.write(s5l_irq_flags());
+
+        mask
+    }
+}
+
+impl TIrqHandler for SamsungUartIrqHandler {
+    fn clean_interrupt_status(&self) -> InterruptMask {
+        match self.variant {
+            SamsungVariant::Exynos => self.clean_exynos(),
+            SamsungVariant::AppleS5l => self.clean_s5l(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fake_mmio::FakeMmio, FifoLevel};
+
+    fn uart(regs: &FakeMmio<15>, variant: SamsungVariant) -> SamsungUart {
+        // 发送完成，配置时无需等待
+        regs.set(0x10, UTRSTAT::TX_EMPTY::SET.value);
+        SamsungUart::new(regs.base(), 100_000_000, variant, variant.fifo_depth(1))
+    }
+
+    #[test]
+    fn divisor_splits_sixteenths() {
+        let regs = FakeMmio::<15>::new();
+        let mut uart = uart(&regs, SamsungVariant::Exynos);
+
+        // 100 MHz / 115200 = 868.06 个 1/16 位时间 -> 54 + 4/16
+        uart.set_config(&Config::new().baudrate(115200)).unwrap();
+        assert_eq!((regs.get(0x28), regs.get(0x2c)), (53, 4));
+        assert_eq!(uart.baudrate(), 100_000_000 / 868);
+
+        uart.set_config(
+            &Config::new()
+                .data_bits(DataBits::Seven)
+                .parity(Parity::Even),
+        )
+        .unwrap();
+        assert_eq!(
+            regs.get(0x00),
+            (ULCON::WORD_LEN::SevenBit + ULCON::PARITY::Even).value
+        );
+    }
+
+    #[test]
+    fn ufstat_layout_follows_variant() {
+        let regs = FakeMmio::<15>::new();
+        let mut exynos = uart(&regs, SamsungVariant::Exynos);
+        let mut s5l = SamsungUart::new(regs.base(), 100_000_000, SamsungVariant::AppleS5l, 16);
+        let mut exynos_rx = exynos.rx.take().unwrap();
+        let mut s5l_rx = s5l.rx.take().unwrap();
+        let mut exynos_tx = exynos.tx.take().unwrap();
+        let mut s5l_tx = s5l.tx.take().unwrap();
+
+        // S5L 布局下是 1 个待发送字节，Exynos 布局下是 16 个待接收字节
+        regs.set(0x18, UFSTAT_S5L::TX_COUNT.val(1).value);
+        regs.set(0x24, 0x61);
+        assert_eq!(RawReciever::read_byte(&mut s5l_rx), None);
+        assert_eq!(RawReciever::read_byte(&mut exynos_rx), Some(Ok(0x61)));
+
+        regs.set(0x18, UFSTAT_S5L::TX_FULL::SET.value);
+        assert!(!RawSender::write_byte(&mut s5l_tx, b'a'));
+        assert!(RawSender::write_byte(&mut exynos_tx, b'a'));
+        regs.set(0x18, UFSTAT::TX_FULL::SET.value);
+        assert!(!RawSender::write_byte(&mut exynos_tx, b'b'));
+    }
+
+    #[test]
+    fn exynos_irq_acks_uintp() {
+        let regs = FakeMmio::<15>::new();
+        let mut uart = uart(&regs, SamsungVariant::Exynos);
+        let irq = uart.irq_handler().unwrap();
+
+        uart.set_irq_mask(InterruptMask::RX_AVAILABLE);
+        assert_eq!(
+            regs.get(0x38),
+            (UINT::ERROR::SET + UINT::TXD::SET + UINT::MODEM::SET).value
+        );
+        assert_eq!(
+            uart.get_irq_mask().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+
+        regs.set(0x30, (UINT::RXD::SET + UINT::ERROR::SET).value);
+        assert_eq!(
+            irq.clean_interrupt_status().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+        assert_eq!(regs.get(0x30), (UINT::RXD::SET + UINT::ERROR::SET).value);
+    }
+
+    #[test]
+    fn s5l_rx_timeout_reports_rx_and_is_acked() {
+        let regs = FakeMmio::<15>::new();
+        let mut uart = SamsungUart::new(regs.base(), 100_000_000, SamsungVariant::AppleS5l, 16);
+        let irq = uart.irq_handler().unwrap();
+
+        uart.set_irq_mask(InterruptMask::RX_AVAILABLE);
+        regs.set(0x10, UTRSTAT::S5L_RXTO::SET.value);
+        assert_eq!(
+            irq.clean_interrupt_status().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+        assert_eq!(regs.get(0x10), s5l_irq_flags().value);
+    }
+
+    #[test]
+    fn exynos_triggers_use_eighth_steps() {
+        let regs = FakeMmio::<15>::new();
+        let mut uart = uart(&regs, SamsungVariant::Exynos);
+        regs.set(0x08, UFCON::FIFO_EN::SET.value | UFCON::RX_RESET::SET.value);
+
+        let req = FifoThresholds::new()
+            .rx(FifoLevel::Bytes(16))
+            .tx(FifoLevel::Bytes(24));
+        let triggers = uart.set_fifo_thresholds(&req).unwrap();
+        assert_eq!(
+            regs.get(0x08),
+            (UFCON::FIFO_EN::SET + UFCON::RX_TRIGGER.val(1) + UFCON::TX_TRIGGER.val(3)).value
+        );
+        assert_eq!((triggers.rx, triggers.tx), (Some(16), Some(24)));
+    }
+}
@@ -0,0 +1,803 @@
+//! RISC-V SBI 控制台
+//!
+//! 在还不知道 UART 地址的启动早期，通过固件（OpenSBI 等）提供的控制台输出。
+//! 固件报告 Debug Console 扩展（DBCN）时使用 `sbi_debug_console_write/read`，
+//! 否则回退到 legacy 的 `console_putchar/getchar`。
+//!
+//! ecall 经过 [`SbiCall`] 抽象，主机测试中可以替换为模拟固件。
+//! 固件控制台没有波特率、帧格式和中断：`set_config` 只记录波特率，帧格式固定为 8N1。
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use rdif_serial::{
+    BSerial, InterfaceRaw, SerialDyn, SetBackError, TIrqHandler, TReciever, TSender,
+};
+
+use crate::{
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, Stats},
+    Capabilities, Caps, Config, ConfigError, DataBits, Features, FifoThreshold, InterruptMask,
+    Parity, RawReciever, RawSender, StopBits, Suspend, TransferError,
+};
+
+/// Base 扩展
+const BASE_EID: usize = 0x10;
+const BASE_GET_SPEC_VERSION: usize = 0;
+const BASE_PROBE_EXTENSION: usize = 3;
+
+/// Debug Console 扩展（"DBCN"）
+const DBCN_EID: usize = 0x4442_434e;
+const DBCN_WRITE: usize = 0;
+const DBCN_READ: usize = 1;
+const DBCN_WRITE_BYTE: usize = 2;
+
+/// legacy 控制台扩展
+const LEGACY_PUTCHAR_EID: usize = 0x01;
+const LEGACY_GETCHAR_EID: usize = 0x02;
+
+/// DBCN 传递的缓冲区必须物理连续，按页拆分
+const PAGE_SIZE: usize = 4096;
+
+/// 接收缓冲区大小，DBCN 每次最多读取这么多字节
+const RX_BUF_SIZE: usize = 16;
+
+/// 清空接收方向时最多向固件读取的次数
+const RX_DISCARD_LIMIT: usize = 256;
+
+/// SBI 调用返回值
+///
+/// legacy 扩展只返回 a0，其值位于 `error` 中。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SbiRet {
+    pub error: isize,
+    pub value: usize,
+}
+
+/// SBI ecall 接口
+pub trait SbiCall: Send + Sync + 'static {
+    /// 以 `eid`/`fid` 发起调用，`args` 依次放入 a0-a2
+    fn ecall(&self, eid: usize, fid: usize, args: [usize; 3]) -> SbiRet;
+}
+
+/// 通过 `ecall` 指令陷入固件
+#[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+pub struct Ecall;
+
+#[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+impl SbiCall for Ecall {
+    fn ecall(&self, eid: usize, fid: usize, args: [usize; 3]) -> SbiRet {
+        let error: usize;
+        let value: usize;
+        unsafe {
+            core::arch::asm!(
+                "ecall",
+                inlateout("a0") args[0] => error,
+                inlateout("a1") args[1] => value,
+                in("a2") args[2],
+                in("a6") fid,
+                in("a7") eid,
+                options(nostack),
+            );
+        }
+        SbiRet {
+            error: error as isize,
+            value,
+        }
+    }
+}
+
+/// 探测错误
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SbiError {
+    #[error("Firmware provides no console extension")]
+    NoConsole,
+}
+
+/// 使用的 SBI 控制台接口
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SbiConsoleMode {
+    /// Debug Console 扩展（SBI v2.0）
+    DebugConsole,
+    /// legacy `console_putchar/getchar`
+    Legacy,
+}
+
+/// 收发端共享的固件句柄
+#[derive(Clone)]
+struct Sbi {
+    call: Arc<dyn SbiCall>,
+    mode: SbiConsoleMode,
+    virt_to_phys: fn(usize) -> u64,
+    /// 置位时接收端在下次读取前丢弃已缓存的数据
+    rx_discard: Arc<AtomicBool>,
+}
+
+impl Sbi {
+    fn addr(&self) -> usize {
+        Arc::as_ptr(&self.call) as *const u8 as usize
+    }
+
+    /// 把缓冲区地址拆成 DBCN 的 (base_lo, base_hi)
+    fn phys_args(&self, ptr: *const u8) -> (usize, usize) {
+        let phys = (self.virt_to_phys)(ptr as usize);
+        (
+            phys as usize,
+            phys.checked_shr(usize::BITS).unwrap_or(0) as usize,
+        )
+    }
+
+    fn debug_console_write(&self, bytes: &[u8]) -> Option<usize> {
+        let (lo, hi) = self.phys_args(bytes.as_ptr());
+        let ret = self.call.ecall(DBCN_EID, DBCN_WRITE, [bytes.len(), lo, hi]);
+        (ret.error == 0).then_some(ret.value)
+    }
+
+    fn debug_console_read(&self, buf: &mut [u8]) -> Option<usize> {
+        let (lo, hi) = self.phys_args(buf.as_ptr());
+        let ret = self.call.ecall(DBCN_EID, DBCN_READ, [buf.len(), lo, hi]);
+        (ret.error == 0).then_some(ret.value.min(buf.len()))
+    }
+}
+
+impl PartialEq for Sbi {
+    fn eq(&self, other: &Self) -> bool {
+        self.addr() == other.addr()
+    }
+}
+
+/// SBI 控制台驱动结构体
+pub struct SbiConsole {
+    sbi: Sbi,
+    baudrate: u32,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    tx_pause: TxPause,
+    tx: Option<SbiConsoleSender>,
+    rx: Option<SbiConsoleReciever>,
+    irq: Option<SbiConsoleIrqHandler>,
+}
+
+impl SbiConsole {
+    /// 通过 `ecall` 探测固件控制台
+    ///
+    /// `virt_to_phys` 把缓冲区虚拟地址转换为物理地址，供 DBCN 调用使用；
+    /// 恒等映射时可传入 `|va| va as u64`。
+    #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+    pub fn new(virt_to_phys: fn(usize) -> u64) -> Result<Self, SbiError> {
+        Self::with_sbi(Arc::new(Ecall), virt_to_phys)
+    }
+
+    #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+    pub fn new_boxed(virt_to_phys: fn(usize) -> u64) -> Result<BSerial, SbiError> {
+        Ok(SerialDyn::new_boxed(Self::new(virt_to_phys)?))
+    }
+
+    /// 使用自定义 ecall 实现创建实例
+    pub fn with_sbi(
+        call: Arc<dyn SbiCall>,
+        virt_to_phys: fn(usize) -> u64,
+    ) -> Result<Self, SbiError> {
+        let mode = Self::detect(call.as_ref())?;
+        let sbi = Sbi {
+            call,
+            mode,
+            virt_to_phys,
+            rx_discard: Arc::new(AtomicBool::new(false)),
+        };
+        let tx_pause = TxPause::default();
+
+        Ok(Self {
+            tx_pause: tx_pause.clone(),
+            tx: Some(SbiConsoleSender {
+                sbi: sbi.clone(),
+                stats: None,
+                tx_pause: tx_pause.clone(),
+            }),
+            rx: Some(SbiConsoleReciever {
+                sbi: sbi.clone(),
+                buf: RxBuf([0; RX_BUF_SIZE]),
+                head: 0,
+                len: 0,
+                stats: None,
+            }),
+            irq: Some(SbiConsoleIrqHandler),
+            sbi,
+            baudrate: 115200,
+        })
+    }
+
+    pub fn with_sbi_boxed(
+        call: Arc<dyn SbiCall>,
+        virt_to_phys: fn(usize) -> u64,
+    ) -> Result<BSerial, SbiError> {
+        Ok(SerialDyn::new_boxed(Self::with_sbi(call, virt_to_phys)?))
+    }
+
+    /// SBI v0.1 没有 Base 扩展，只能假定 legacy 控制台存在
+    fn detect(call: &dyn SbiCall) -> Result<SbiConsoleMode, SbiError> {
+        let probe = |eid: usize| {
+            let ret = call.ecall(BASE_EID, BASE_PROBE_EXTENSION, [eid, 0, 0]);
+            ret.error == 0 && ret.value != 0
+        };
+
+        if call.ecall(BASE_EID, BASE_GET_SPEC_VERSION, [0; 3]).error != 0 {
+            return Ok(SbiConsoleMode::Legacy);
+        }
+        if probe(DBCN_EID) {
+            return Ok(SbiConsoleMode::DebugConsole);
+        }
+        if probe(LEGACY_PUTCHAR_EID) {
+            return Ok(SbiConsoleMode::Legacy);
+        }
+
+        Err(SbiError::NoConsole)
+    }
+
+    /// 挂接统计计数器，只对尚未取出的收发句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+    }
+
+    /// 当前使用的固件接口
+    pub fn mode(&self) -> SbiConsoleMode {
+        self.sbi.mode
+    }
+}
+
+impl InterfaceRaw for SbiConsole {
+    type IrqHandler = SbiConsoleIrqHandler;
+
+    type Sender = crate::Sender;
+
+    type Reciever = crate::Reciever;
+
+    fn base_addr(&self) -> usize {
+        self.sbi.addr()
+    }
+
+    fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        self.capabilities().validate(config)?;
+        if let Some(baudrate) = config.baudrate {
+            self.baudrate = baudrate;
+        }
+        Ok(())
+    }
+
+    /// 固件控制台的波特率由固件决定，返回最近一次配置的值
+    fn baudrate(&self) -> u32 {
+        self.baudrate
+    }
+
+    fn data_bits(&self) -> DataBits {
+        DataBits::Eight
+    }
+
+    fn stop_bits(&self) -> StopBits {
+        StopBits::One
+    }
+
+    fn parity(&self) -> Parity {
+        Parity::None
+    }
+
+    fn clock_freq(&self) -> Option<core::num::NonZeroU32> {
+        None
+    }
+
+    // 固件控制台始终可用
+    fn open(&mut self) {}
+
+    fn close(&mut self) {}
+
+    fn enable_loopback(&mut self) {}
+
+    fn disable_loopback(&mut self) {}
+
+    fn is_loopback_enabled(&self) -> bool {
+        false
+    }
+
+    // SBI 控制台没有中断，只能轮询
+    fn set_irq_mask(&mut self, _mask: InterruptMask) {}
+
+    fn get_irq_mask(&self) -> InterruptMask {
+        InterruptMask::empty()
+    }
+
+    fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+        self.irq.take()
+    }
+
+    fn take_tx(&mut self) -> Option<Self::Sender> {
+        self.tx.take().map(crate::Sender::SbiConsoleSender)
+    }
+
+    fn take_rx(&mut self) -> Option<Self::Reciever> {
+        self.rx.take().map(crate::Reciever::SbiConsoleReciever)
+    }
+
+    fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+        let tx = match tx {
+            crate::Sender::SbiConsoleSender(s) => s,
+            _ => {
+                return Err(SetBackError::new(
+                    self.sbi.addr(),
+                    tx.external_base().unwrap_or(0), // 不匹配的发送器类型
+                ));
+            }
+        };
+
+        if self.sbi != tx.sbi {
+            return Err(SetBackError::new(self.sbi.addr(), tx.sbi.addr()));
+        }
+
+        self.tx = Some(tx);
+        Ok(())
+    }
+
+    fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+        let rx = match rx {
+            crate::Reciever::SbiConsoleReciever(r) => r,
+            _ => {
+                return Err(SetBackError::new(
+                    self.sbi.addr(),
+                    rx.external_base().unwrap_or(0), // 不匹配的接收器类型
+                ));
+            }
+        };
+
+        if self.sbi != rx.sbi {
+            return Err(SetBackError::new(self.sbi.addr(), rx.sbi.addr()));
+        }
+
+        self.rx = Some(rx);
+        Ok(())
+    }
+}
+
+impl Caps for SbiConsole {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            fifo_depth: match self.sbi.mode {
+                SbiConsoleMode::DebugConsole => RX_BUF_SIZE as u16,
+                SbiConsoleMode::Legacy => 1,
+            },
+            min_baudrate: 1,
+            max_baudrate: u32::MAX,
+            data_bits: &[DataBits::Eight],
+            stop_bits: &[StopBits::One],
+            parity: &[Parity::None],
+            features: Features::empty(),
+        }
+    }
+}
+
+impl FifoThreshold for SbiConsole {}
+
+/// 固件同步完成写入，发送方向没有待发数据；清空接收方向时丢弃接收端缓存
+/// 以及固件中尚未读取的输入
+impl Flush for SbiConsole {
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError> {
+        if !queue.rx() {
+            return Ok(());
+        }
+
+        self.sbi.rx_discard.store(true, Ordering::Release);
+        let mut buf = RxBuf([0; RX_BUF_SIZE]);
+        for _ in 0..RX_DISCARD_LIMIT {
+            let pending = match self.sbi.mode {
+                SbiConsoleMode::DebugConsole => {
+                    self.sbi.debug_console_read(&mut buf.0).unwrap_or(0) > 0
+                }
+                SbiConsoleMode::Legacy => {
+                    self.sbi.call.ecall(LEGACY_GETCHAR_EID, 0, [0; 3]).error >= 0
+                }
+            };
+            if !pending {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), FlushError> {
+        Ok(())
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// SBI 控制台挂起时保存的上下文，固件状态无需保存
+#[derive(Debug, Clone, Copy)]
+pub struct SbiConsoleContext {
+    baudrate: u32,
+}
+
+impl Suspend for SbiConsole {
+    type Context = SbiConsoleContext;
+
+    fn save_context(&self) -> Self::Context {
+        SbiConsoleContext {
+            baudrate: self.baudrate,
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        self.baudrate = ctx.baudrate;
+    }
+}
+
+pub struct SbiConsoleSender {
+    sbi: Sbi,
+    stats: Option<&'static Stats>,
+    tx_pause: TxPause,
+}
+
+impl AttachStats for SbiConsoleSender {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl SbiConsoleSender {
+    fn write_raw(&self, bytes: &[u8]) -> usize {
+        match self.sbi.mode {
+            SbiConsoleMode::DebugConsole => {
+                let mut written = 0;
+                while written < bytes.len() {
+                    let rest = &bytes[written..];
+                    let page_left = PAGE_SIZE - rest.as_ptr() as usize % PAGE_SIZE;
+                    let chunk = &rest[..rest.len().min(page_left)];
+
+                    // 固件可能只写出一部分，此时交给调用方重试
+                    let Some(n) = self.sbi.debug_console_write(chunk) else {
+                        break;
+                    };
+                    written += n;
+                    if n < chunk.len() {
+                        break;
+                    }
+                }
+                written
+            }
+            SbiConsoleMode::Legacy => {
+                for &byte in bytes {
+                    self.sbi
+                        .call
+                        .ecall(LEGACY_PUTCHAR_EID, 0, [byte as usize, 0, 0]);
+                }
+                bytes.len()
+            }
+        }
+    }
+}
+
+impl TSender for SbiConsoleSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        RawSender::write_byte(self, byte)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> usize {
+        RawSender::write_bytes(self, bytes)
+    }
+}
+
+impl RawSender for SbiConsoleSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return false;
+        };
+        let ok = match self.sbi.mode {
+            SbiConsoleMode::DebugConsole => {
+                let ret = self
+                    .sbi
+                    .call
+                    .ecall(DBCN_EID, DBCN_WRITE_BYTE, [byte as usize, 0, 0]);
+                ret.error == 0
+            }
+            SbiConsoleMode::Legacy => self.write_raw(&[byte]) == 1,
+        };
+        if ok {
+            if let Some(stats) = self.stats {
+                stats.record_tx(1);
+            }
+        }
+        ok
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> usize {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return 0;
+        };
+        let written = self.write_raw(bytes);
+        if let Some(stats) = self.stats {
+            stats.record_tx(written);
+        }
+        written
+    }
+}
+
+/// 对齐到自身大小，保证不跨页
+#[repr(align(16))]
+struct RxBuf([u8; RX_BUF_SIZE]);
+
+pub struct SbiConsoleReciever {
+    sbi: Sbi,
+    buf: RxBuf,
+    head: usize,
+    len: usize,
+    stats: Option<&'static Stats>,
+}
+
+impl AttachStats for SbiConsoleReciever {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl SbiConsoleReciever {
+    fn read_raw(&mut self) -> Option<Result<u8, TransferError>> {
+        if self.sbi.rx_discard.swap(false, Ordering::AcqRel) {
+            self.head = self.len;
+        }
+
+        match self.sbi.mode {
+            SbiConsoleMode::DebugConsole => {
+                if self.head == self.len {
+                    self.len = self.sbi.debug_console_read(&mut self.buf.0)?;
+                    self.head = 0;
+                }
+                if self.head == self.len {
+                    return None;
+                }
+                let byte = self.buf.0[self.head];
+                self.head += 1;
+                Some(Ok(byte))
+            }
+            SbiConsoleMode::Legacy => {
+                // 没有数据时返回 -1
+                let ret = self.sbi.call.ecall(LEGACY_GETCHAR_EID, 0, [0; 3]);
+                (ret.error >= 0).then_some(Ok(ret.error as u8))
+            }
+        }
+    }
+}
+
+impl TReciever for SbiConsoleReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        RawReciever::read_byte(self)
+    }
+}
+
+impl RawReciever for SbiConsoleReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        let result = self.read_raw()?;
+        if let Some(stats) = self.stats {
+            stats.record_rx(&result);
+        }
+        Some(result)
+    }
+}
+
+/// SBI 控制台没有中断，处理函数总是返回空
+pub struct SbiConsoleIrqHandler;
+
+impl TIrqHandler for SbiConsoleIrqHandler {
+    fn clean_interrupt_status(&self) -> InterruptMask {
+        InterruptMask::empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::{collections::VecDeque, sync::Mutex, vec, vec::Vec};
+
+    use super::{
+        SbiCall, SbiConsole, SbiConsoleMode, SbiError, SbiRet, BASE_EID, BASE_GET_SPEC_VERSION,
+        BASE_PROBE_EXTENSION, DBCN_EID, DBCN_READ, DBCN_WRITE, DBCN_WRITE_BYTE, LEGACY_GETCHAR_EID,
+        LEGACY_PUTCHAR_EID, PAGE_SIZE,
+    };
+    use crate::{Flush, FlushQueue};
+    use alloc::sync::Arc;
+    use rdif_serial::{InterfaceRaw, TReciever, TSender};
+
+    const NOT_SUPPORTED: isize = -2;
+
+    #[derive(Default)]
+    struct FakeState {
+        /// SBI v0.1 固件没有 Base 扩展
+        base: bool,
+        dbcn: bool,
+        legacy: bool,
+        /// DBCN 单次写入的上限
+        write_limit: Option<usize>,
+        output: Vec<u8>,
+        input: VecDeque<u8>,
+        /// 每次 DBCN 写入的 (物理地址, 长度)
+        writes: Vec<(usize, usize)>,
+    }
+
+    /// 模拟固件，物理地址与主机地址相同
+    struct FakeSbi(Mutex<FakeState>);
+
+    impl FakeSbi {
+        fn new(base: bool, dbcn: bool, legacy: bool) -> Arc<Self> {
+            Arc::new(Self(Mutex::new(FakeState {
+                base,
+                dbcn,
+                legacy,
+                ..Default::default()
+            })))
+        }
+
+        fn output(&self) -> Vec<u8> {
+            self.0.lock().unwrap().output.clone()
+        }
+    }
+
+    impl SbiCall for FakeSbi {
+        fn ecall(&self, eid: usize, fid: usize, args: [usize; 3]) -> SbiRet {
+            let mut state = self.0.lock().unwrap();
+            let ok = |value| SbiRet { error: 0, value };
+            let unsupported = SbiRet {
+                error: NOT_SUPPORTED,
+                value: 0,
+            };
+
+            match (eid, fid) {
+                (BASE_EID, _) if !state.base => unsupported,
+                (BASE_EID, BASE_GET_SPEC_VERSION) => ok(2 << 24),
+                (BASE_EID, BASE_PROBE_EXTENSION) => ok(match args[0] {
+                    DBCN_EID => state.dbcn as usize,
+                    LEGACY_PUTCHAR_EID | LEGACY_GETCHAR_EID => state.legacy as usize,
+                    _ => 0,
+                }),
+                (DBCN_EID, _) if !state.dbcn => unsupported,
+                (DBCN_EID, DBCN_WRITE) => {
+                    let len = state
+                        .write_limit
+                        .map_or(args[0], |limit| args[0].min(limit));
+                    let data = unsafe { core::slice::from_raw_parts(args[1] as *const u8, len) };
+                    state.output.extend_from_slice(data);
+                    state.writes.push((args[1], args[0]));
+                    ok(len)
+                }
+                (DBCN_EID, DBCN_READ) => {
+                    let len = args[0].min(state.input.len());
+                    for i in 0..len {
+                        let byte = state.input.pop_front().unwrap();
+                        unsafe { ((args[1] + i) as *mut u8).write(byte) };
+                    }
+                    ok(len)
+                }
+                (DBCN_EID, DBCN_WRITE_BYTE) => {
+                    state.output.push(args[0] as u8);
+                    ok(0)
+                }
+                (LEGACY_PUTCHAR_EID, _) => {
+                    state.output.push(args[0] as u8);
+                    SbiRet { error: 0, value: 0 }
+                }
+                (LEGACY_GETCHAR_EID, _) => SbiRet {
+                    error: state.input.pop_front().map_or(-1, |b| b as isize),
+                    value: 0,
+                },
+                _ => unsupported,
+            }
+        }
+    }
+
+    fn identity(va: usize) -> u64 {
+        va as u64
+    }
+
+    #[test]
+    fn detect_mode() {
+        let console = SbiConsole::with_sbi(FakeSbi::new(true, true, true), identity).unwrap();
+        assert_eq!(console.mode(), SbiConsoleMode::DebugConsole);
+
+        let console = SbiConsole::with_sbi(FakeSbi::new(true, false, true), identity).unwrap();
+        assert_eq!(console.mode(), SbiConsoleMode::Legacy);
+
+        // SBI v0.1 无法探测，假定 legacy 可用
+        let console = SbiConsole::with_sbi(FakeSbi::new(false, false, true), identity).unwrap();
+        assert_eq!(console.mode(), SbiConsoleMode::Legacy);
+
+        assert_eq!(
+            SbiConsole::with_sbi(FakeSbi::new(true, false, false), identity).err(),
+            Some(SbiError::NoConsole)
+        );
+    }
+
+    #[test]
+    fn debug_console_roundtrip() {
+        let sbi = FakeSbi::new(true, true, false);
+        let mut console = SbiConsole::with_sbi(sbi.clone(), identity).unwrap();
+        let mut tx = console.take_tx().unwrap();
+        let mut rx = console.take_rx().unwrap();
+
+        assert_eq!(tx.write_bytes(b"hello"), 5);
+        assert!(tx.write_byte(b'!'));
+        assert_eq!(sbi.output(), b"hello!");
+
+        assert!(rx.read_byte().is_none());
+        sbi.0.lock().unwrap().input.extend(b"hi");
+        assert_eq!(rx.read_byte(), Some(Ok(b'h')));
+        assert_eq!(rx.read_byte(), Some(Ok(b'i')));
+        assert!(rx.read_byte().is_none());
+    }
+
+    #[test]
+    fn debug_console_write_splits_pages() {
+        let sbi = FakeSbi::new(true, true, false);
+        let mut console = SbiConsole::with_sbi(sbi.clone(), identity).unwrap();
+        let mut tx = console.take_tx().unwrap();
+
+        let buf = vec![0x55u8; 3 * PAGE_SIZE];
+        let start = PAGE_SIZE - buf.as_ptr() as usize % PAGE_SIZE + PAGE_SIZE - 10;
+        let data = &buf[start..start + 20];
+        assert_eq!(tx.write_bytes(data), 20);
+
+        let writes = sbi.0.lock().unwrap().writes.clone();
+        assert_eq!(writes.len(), 2);
+        for (addr, len) in writes {
+            assert_eq!(addr / PAGE_SIZE, (addr + len - 1) / PAGE_SIZE);
+        }
+        assert_eq!(sbi.output(), data);
+    }
+
+    #[test]
+    fn debug_console_partial_write() {
+        let sbi = FakeSbi::new(true, true, false);
+        sbi.0.lock().unwrap().write_limit = Some(3);
+        let mut console = SbiConsole::with_sbi(sbi.clone(), identity).unwrap();
+        let mut tx = console.take_tx().unwrap();
+
+        assert_eq!(tx.write_bytes(b"abcdef"), 3);
+        assert_eq!(sbi.output(), b"abc");
+    }
+
+    #[test]
+    fn legacy_roundtrip() {
+        let sbi = FakeSbi::new(true, false, true);
+        let mut console = SbiConsole::with_sbi(sbi.clone(), identity).unwrap();
+        let mut tx = console.take_tx().unwrap();
+        let mut rx = console.take_rx().unwrap();
+
+        assert_eq!(tx.write_bytes(b"legacy"), 6);
+        assert_eq!(sbi.output(), b"legacy");
+
+        assert!(rx.read_byte().is_none());
+        sbi.0.lock().unwrap().input.push_back(0xff);
+        assert_eq!(rx.read_byte(), Some(Ok(0xff)));
+    }
+
+    #[test]
+    fn flush_discards_buffered_input() {
+        let sbi = FakeSbi::new(true, true, false);
+        let mut console = SbiConsole::with_sbi(sbi.clone(), identity).unwrap();
+        let mut rx = console.take_rx().unwrap();
+
+        // 接收端已缓存 "abc" 中剩余的两个字节，固件中还有 "def"
+        sbi.0.lock().unwrap().input.extend(b"abc");
+        assert_eq!(rx.read_byte(), Some(Ok(b'a')));
+        sbi.0.lock().unwrap().input.extend(b"def");
+
+        console.flush(FlushQueue::Rx).unwrap();
+        assert!(sbi.0.lock().unwrap().input.is_empty());
+        assert!(rx.read_byte().is_none());
+
+        sbi.0.lock().unwrap().input.extend(b"g");
+        assert_eq!(rx.read_byte(), Some(Ok(b'g')));
+    }
+}
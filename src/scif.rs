@@ -0,0 +1,984 @@
+//! Renesas SCIF 驱动 (`renesas,rcar-gen3-scif` / `renesas,scif`)
+//!
+//! 用于 R-Car 等 SoC。寄存器为 SCSMR/SCBRR/SCSCR/SCFTDR/SCFSR/SCFRDR/SCFCR/SCFDR/SCSPTR/SCLSR，
+//! 收发各有 16 字节 FIFO。波特率由 SCSMR.CKS 的预分频和 8 位 SCBRR 组成：
+//! `baud = clk / (32 * 4^CKS * (SCBRR + 1))`。部分型号额外的 BRG 分频器未使用。
+//!
+//! SCFSR/SCLSR 的状态位需要先读到 1 再写 0 清除；接收错误在 `read_byte` 中随字节
+//! 一起上报并清除。
+
+use core::{num::NonZeroU32, ptr::NonNull};
+
+use alloc::sync::Arc;
+
+use rdif_serial::{BSerial, InterfaceRaw, SerialDyn, SetBackError, TIrqHandler, TSender};
+use tock_registers::{interfaces::*, register_bitfields, register_structs, registers::*};
+
+use crate::{
+    caps::ALL_STOP_BITS,
+    claim::{self, Claim, ClaimError, Region},
+    fifo::{select_level, FifoError, FifoThreshold, FifoThresholds, FifoTriggers},
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, IrqCause, Stats},
+    Capabilities, Caps, Config, ConfigError, DataBits, Features, InterruptMask, Parity,
+    RawReciever, RawSender, StopBits, Suspend, TransferError,
+};
+
+register_bitfields! [
+    u16,
+
+    /// Serial Mode Register
+    SCSMR [
+        CKS OFFSET(0) NUMBITS(2) [],
+        STOP OFFSET(3) NUMBITS(1) [],
+        ODD OFFSET(4) NUMBITS(1) [],
+        PE OFFSET(5) NUMBITS(1) [],
+        CHR OFFSET(6) NUMBITS(1) [],
+        CA OFFSET(7) NUMBITS(1) []
+    ],
+
+    /// Serial Control Register
+    SCSCR [
+        CKE OFFSET(0) NUMBITS(2) [],
+        REIE OFFSET(3) NUMBITS(1) [],
+        RE OFFSET(4) NUMBITS(1) [],
+        TE OFFSET(5) NUMBITS(1) [],
+        RIE OFFSET(6) NUMBITS(1) [],
+        TIE OFFSET(7) NUMBITS(1) []
+    ],
+
+    /// FIFO Status Register（读 1 后写 0 清除）
+    SCFSR [
+        DR OFFSET(0) NUMBITS(1) [],
+        RDF OFFSET(1) NUMBITS(1) [],
+        PER OFFSET(2) NUMBITS(1) [],
+        FER OFFSET(3) NUMBITS(1) [],
+        BRK OFFSET(4) NUMBITS(1) [],
+        TDFE OFFSET(5) NUMBITS(1) [],
+        TEND OFFSET(6) NUMBITS(1) [],
+        ER OFFSET(7) NUMBITS(1) []
+    ],
+
+    /// FIFO Control Register
+    SCFCR [
+        LOOP OFFSET(0) NUMBITS(1) [],
+        RFRST OFFSET(1) NUMBITS(1) [],
+        TFRST OFFSET(2) NUMBITS(1) [],
+        MCE OFFSET(3) NUMBITS(1) [],
+        TTRG OFFSET(4) NUMBITS(2) [
+            Eight = 0b00,
+            Four = 0b01,
+            Two = 0b10,
+            Zero = 0b11
+        ],
+        RTRG OFFSET(6) NUMBITS(2) [
+            One = 0b00,
+            Four = 0b01,
+            Eight = 0b10,
+            Fourteen = 0b11
+        ]
+    ],
+
+    /// FIFO Data Count Register
+    SCFDR [
+        RX_COUNT OFFSET(0) NUMBITS(5) [],
+        TX_COUNT OFFSET(8) NUMBITS(5) []
+    ],
+
+    /// Serial Port Register
+    SCSPTR [
+        SPB2DT OFFSET(0) NUMBITS(1) [],
+        SPB2IO OFFSET(1) NUMBITS(1) [],
+        SCKDT OFFSET(2) NUMBITS(1) [],
+        SCKIO OFFSET(3) NUMBITS(1) [],
+        CTSDT OFFSET(4) NUMBITS(1) [],
+        CTSIO OFFSET(5) NUMBITS(1) [],
+        RTSDT OFFSET(6) NUMBITS(1) [],
+        RTSIO OFFSET(7) NUMBITS(1) []
+    ],
+
+    /// Line Status Register（读 1 后写 0 清除）
+    SCLSR [
+        ORER OFFSET(0) NUMBITS(1) []
+    ]
+];
+
+register_structs! {
+    pub ScifRegisters {
+        (0x00 => scsmr: ReadWrite<u16, SCSMR::Register>),     // 串行模式寄存器
+        (0x02 => _reserved0),
+        (0x04 => scbrr: ReadWrite<u8>),                       // 波特率寄存器
+        (0x05 => _reserved1),
+        (0x08 => scscr: ReadWrite<u16, SCSCR::Register>),     // 串行控制寄存器
+        (0x0a => _reserved2),
+        (0x0c => scftdr: WriteOnly<u8>),                      // 发送 FIFO 数据寄存器
+        (0x0d => _reserved3),
+        (0x10 => scfsr: ReadWrite<u16, SCFSR::Register>),     // FIFO 状态寄存器
+        (0x12 => _reserved4),
+        (0x14 => scfrdr: ReadOnly<u8>),                       // 接收 FIFO 数据寄存器
+        (0x15 => _reserved5),
+        (0x18 => scfcr: ReadWrite<u16, SCFCR::Register>),     // FIFO 控制寄存器
+        (0x1a => _reserved6),
+        (0x1c => scfdr: ReadOnly<u16, SCFDR::Register>),      // FIFO 数据计数寄存器
+        (0x1e => _reserved7),
+        (0x20 => scsptr: ReadWrite<u16, SCSPTR::Register>),   // 串行端口寄存器
+        (0x22 => _reserved8),
+        (0x24 => sclsr: ReadWrite<u16, SCLSR::Register>),     // 线路状态寄存器
+        (0x26 => @END),
+    }
+}
+
+// SAFETY: 寄存器访问均为单次读写
+unsafe impl Sync for ScifRegisters {}
+
+/// 收发 FIFO 深度
+const FIFO_DEPTH: u16 = 16;
+
+/// 等待发送完成的最大轮询次数
+const BUSY_SPIN_LIMIT: usize = 1_000_000;
+
+/// SCBRR 的最大值（加 1 后）
+const BRR_MAX: u32 = 0x100;
+
+/// CKS = 3 时的总预分频系数
+const MAX_PRESCALER: u32 = 32 << 6;
+
+/// 接收 FIFO 触发级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RxTrigger {
+    One,
+    Four,
+    Eight,
+    Fourteen,
+}
+
+/// 发送 FIFO 触发级别，FIFO 中剩余字节数不超过该值时触发
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxTrigger {
+    Zero,
+    Two,
+    Four,
+    Eight,
+}
+
+impl RxTrigger {
+    const ALL: [Self; 4] = [Self::One, Self::Four, Self::Eight, Self::Fourteen];
+
+    /// 触发级别对应的字节数
+    pub const fn bytes(self) -> u16 {
+        match self {
+            Self::One => 1,
+            Self::Four => 4,
+            Self::Eight => 8,
+            Self::Fourteen => 14,
+        }
+    }
+}
+
+impl TxTrigger {
+    const ALL: [Self; 4] = [Self::Zero, Self::Two, Self::Four, Self::Eight];
+
+    /// 触发级别对应的字节数
+    pub const fn bytes(self) -> u16 {
+        match self {
+            Self::Zero => 0,
+            Self::Two => 2,
+            Self::Four => 4,
+            Self::Eight => 8,
+        }
+    }
+}
+
+/// Renesas SCIF 驱动结构体
+pub struct Scif {
+    base: Reg,
+    clock_freq: u32,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    tx_pause: TxPause,
+    tx: Option<ScifSender>,
+    rx: Option<ScifReciever>,
+    irq: Option<ScifIrqHandler>,
+    /// 通过 `try_new` 系列创建时持有的区域占用，与各句柄共享
+    claim: Option<Arc<Claim>>,
+}
+
+impl Scif {
+    /// 不占用寄存器区域，需要防止同一设备被重复创建时使用 [`Self::try_new`]
+    pub fn new(base: NonNull<u8>, clock_freq: u32) -> Self {
+        Self::with_claim(base, clock_freq, None)
+    }
+
+    pub fn new_boxed(base: NonNull<u8>, clock_freq: u32) -> BSerial {
+        SerialDyn::new_boxed(Self::new(base, clock_freq))
+    }
+
+    /// 占用寄存器区域后创建实例，区域与已有实例重叠时返回错误
+    pub fn try_new(base: NonNull<u8>, clock_freq: u32) -> Result<Self, ClaimError> {
+        let claim = claim::claim(Region::mmio(base, size_of::<ScifRegisters>()))?;
+        Ok(Self::with_claim(base, clock_freq, Some(claim)))
+    }
+
+    pub fn try_new_boxed(base: NonNull<u8>, clock_freq: u32) -> Result<BSerial, ClaimError> {
+        Ok(SerialDyn::new_boxed(Self::try_new(base, clock_freq)?))
+    }
+
+    /// 通过 `try_new` 系列创建时持有的寄存器区域占用
+    pub fn claim(&self) -> Option<&Claim> {
+        self.claim.as_deref()
+    }
+
+    fn with_claim(base: NonNull<u8>, clock_freq: u32, claim: Option<Arc<Claim>>) -> Self {
+        let base = Reg(base.cast());
+        let tx_pause = TxPause::default();
+
+        Self {
+            base,
+            clock_freq,
+            tx_pause: tx_pause.clone(),
+            tx: Some(ScifSender {
+                base,
+                stats: None,
+                tx_pause: tx_pause.clone(),
+                _claim: claim.clone(),
+            }),
+            rx: Some(ScifReciever {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            irq: Some(ScifIrqHandler {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            claim,
+        }
+    }
+
+    fn registers(&self) -> &ScifRegisters {
+        self.base.registers()
+    }
+
+    /// 挂接统计计数器，只对尚未取出的收发和中断句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+        if let Some(irq) = self.irq.as_mut() {
+            irq.stats = Some(stats);
+        }
+    }
+
+    /// 设置收发 FIFO 的中断触发级别
+    pub fn set_fifo_trigger(&mut self, rx: RxTrigger, tx: TxTrigger) {
+        let rtrg = match rx {
+            RxTrigger::One => SCFCR::RTRG::One,
+            RxTrigger::Four => SCFCR::RTRG::Four,
+            RxTrigger::Eight => SCFCR::RTRG::Eight,
+            RxTrigger::Fourteen => SCFCR::RTRG::Fourteen,
+        };
+        let ttrg = match tx {
+            TxTrigger::Zero => SCFCR::TTRG::Zero,
+            TxTrigger::Two => SCFCR::TTRG::Two,
+            TxTrigger::Four => SCFCR::TTRG::Four,
+            TxTrigger::Eight => SCFCR::TTRG::Eight,
+        };
+        self.registers().scfcr.modify(rtrg + ttrg);
+    }
+
+    /// 读取当前的收发触发级别
+    pub fn fifo_trigger(&self) -> (RxTrigger, TxTrigger) {
+        let scfcr = self.registers().scfcr.extract();
+        let rx = match scfcr.read_as_enum(SCFCR::RTRG) {
+            Some(SCFCR::RTRG::Value::Four) => RxTrigger::Four,
+            Some(SCFCR::RTRG::Value::Eight) => RxTrigger::Eight,
+            Some(SCFCR::RTRG::Value::Fourteen) => RxTrigger::Fourteen,
+            Some(SCFCR::RTRG::Value::One) | None => RxTrigger::One,
+        };
+        let tx = match scfcr.read_as_enum(SCFCR::TTRG) {
+            Some(SCFCR::TTRG::Value::Zero) => TxTrigger::Zero,
+            Some(SCFCR::TTRG::Value::Two) => TxTrigger::Two,
+            Some(SCFCR::TTRG::Value::Four) => TxTrigger::Four,
+            Some(SCFCR::TTRG::Value::Eight) | None => TxTrigger::Eight,
+        };
+        (rx, tx)
+    }
+
+    /// 开启或关闭 RTS/CTS 硬件流控（SCFCR.MCE）
+    ///
+    /// 关闭时 RTS 引脚改由 [`Scif::set_rts`] 通过 SCSPTR 控制。
+    pub fn set_hw_flow_control(&mut self, enable: bool) {
+        self.registers().scfcr.modify(SCFCR::MCE.val(enable as u16));
+    }
+
+    /// 通过 SCSPTR 驱动 RTS 引脚，`active` 为 true 时输出低电平（有效）
+    pub fn set_rts(&mut self, active: bool) {
+        self.registers()
+            .scsptr
+            .modify(SCSPTR::RTSIO::SET + SCSPTR::RTSDT.val(!active as u16));
+    }
+
+    /// 通过 SCSPTR 读取 CTS 引脚，低电平（有效）时返回 true
+    pub fn cts(&self) -> bool {
+        let regs = self.registers();
+        regs.scsptr.modify(SCSPTR::CTSIO::CLEAR);
+        !regs.scsptr.is_set(SCSPTR::CTSDT)
+    }
+
+    /// 计算 (CKS, SCBRR)，优先选择预分频最小、精度最高的组合
+    fn divisors_for(&self, baudrate: u32) -> Result<(u16, u8), ConfigError> {
+        if baudrate == 0 || self.clock_freq == 0 {
+            return Err(ConfigError::InvalidBaudrate);
+        }
+
+        for cks in 0..4u16 {
+            let step = (32u64 << (2 * cks)) * baudrate as u64;
+            let brr = (self.clock_freq as u64 + step / 2) / step;
+            if brr == 0 {
+                break;
+            }
+            if brr <= BRR_MAX as u64 {
+                return Ok((cks, (brr - 1) as u8));
+            }
+        }
+
+        Err(ConfigError::InvalidBaudrate)
+    }
+
+    /// 等待发送 FIFO 和移位寄存器都为空
+    fn wait_tx_idle(&self) -> bool {
+        for _ in 0..BUSY_SPIN_LIMIT {
+            if self.registers().scfsr.is_set(SCFSR::TEND) {
+                return true;
+            }
+            core::hint::spin_loop();
+        }
+        false
+    }
+
+    /// 清除残留的错误和接收状态
+    fn clear_status(&self) {
+        let regs = self.registers();
+        regs.scfsr.get();
+        regs.scfsr
+            .set(!(SCFSR::ER::SET + SCFSR::BRK::SET + SCFSR::DR::SET + SCFSR::RDF::SET).value);
+        regs.sclsr.get();
+        regs.sclsr.set(!SCLSR::ORER::SET.value);
+    }
+
+    /// 初始化 SCIF，按手册顺序：停止收发、复位 FIFO、清除状态、再开启收发
+    fn init(&self) {
+        let regs = self.registers();
+
+        regs.scscr.set(0);
+        regs.scfcr.write(SCFCR::RFRST::SET + SCFCR::TFRST::SET);
+        self.clear_status();
+
+        // 使用内部时钟，SCK 引脚不输出
+        regs.scscr.write(SCSCR::CKE.val(0));
+
+        // 接收 FIFO 有 1 个字节即触发，发送 FIFO 剩余不超过一半时触发
+        regs.scfcr.write(SCFCR::RTRG::One + SCFCR::TTRG::Eight);
+
+        regs.scscr.write(SCSCR::TE::SET + SCSCR::RE::SET);
+    }
+}
+
+impl InterfaceRaw for Scif {
+    type IrqHandler = ScifIrqHandler;
+
+    type Sender = crate::Sender;
+
+    type Reciever = crate::Reciever;
+
+    fn base_addr(&self) -> usize {
+        self.base.0.as_ptr() as usize
+    }
+
+    fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        self.capabilities().validate(config)?;
+        let divisors = config
+            .baudrate
+            .map(|baudrate| self.divisors_for(baudrate))
+            .transpose()?;
+
+        let mut smr = self.registers().scsmr.extract();
+        if let Some(bits) = config.data_bits {
+            smr.modify(SCSMR::CHR.val((bits == DataBits::Seven) as u16));
+        }
+        if let Some(stop_bits) = config.stop_bits {
+            smr.modify(SCSMR::STOP.val((stop_bits == StopBits::Two) as u16));
+        }
+        if let Some(parity) = config.parity {
+            let field = match parity {
+                Parity::Even => SCSMR::PE::SET + SCSMR::ODD::CLEAR,
+                Parity::Odd => SCSMR::PE::SET + SCSMR::ODD::SET,
+                _ => SCSMR::PE::CLEAR + SCSMR::ODD::CLEAR,
+            };
+            smr.modify(field);
+        }
+        if let Some((cks, _)) = divisors {
+            smr.modify(SCSMR::CKS.val(cks));
+        }
+        smr.modify(SCSMR::CA::CLEAR);
+
+        if !self.wait_tx_idle() {
+            return Err(ConfigError::Timeout);
+        }
+
+        // SCSMR/SCBRR 只能在 TE、RE 关闭时写入
+        let regs = self.registers();
+        let scr = regs.scscr.get();
+        regs.scscr.modify(SCSCR::TE::CLEAR + SCSCR::RE::CLEAR);
+
+        regs.scsmr.set(smr.get());
+        if let Some((_, brr)) = divisors {
+            regs.scbrr.set(brr);
+        }
+
+        regs.scscr.set(scr);
+
+        Ok(())
+    }
+
+    fn baudrate(&self) -> u32 {
+        let regs = self.registers();
+        let cks = regs.scsmr.read(SCSMR::CKS) as u32;
+        let brr = regs.scbrr.get() as u32;
+
+        self.clock_freq / ((32 << (2 * cks)) * (brr + 1))
+    }
+
+    fn data_bits(&self) -> DataBits {
+        if self.registers().scsmr.is_set(SCSMR::CHR) {
+            DataBits::Seven
+        } else {
+            DataBits::Eight
+        }
+    }
+
+    fn stop_bits(&self) -> StopBits {
+        if self.registers().scsmr.is_set(SCSMR::STOP) {
+            StopBits::Two
+        } else {
+            StopBits::One
+        }
+    }
+
+    fn parity(&self) -> Parity {
+        let smr = self.registers().scsmr.extract();
+        if !smr.is_set(SCSMR::PE) {
+            Parity::None
+        } else if smr.is_set(SCSMR::ODD) {
+            Parity::Odd
+        } else {
+            Parity::Even
+        }
+    }
+
+    fn clock_freq(&self) -> Option<NonZeroU32> {
+        self.clock_freq.try_into().ok()
+    }
+
+    fn open(&mut self) {
+        self.init()
+    }
+
+    fn close(&mut self) {
+        self.registers().scscr.set(0);
+    }
+
+    fn enable_loopback(&mut self) {
+        self.registers().scfcr.modify(SCFCR::LOOP::SET);
+    }
+
+    fn disable_loopback(&mut self) {
+        self.registers().scfcr.modify(SCFCR::LOOP::CLEAR);
+    }
+
+    fn is_loopback_enabled(&self) -> bool {
+        self.registers().scfcr.is_set(SCFCR::LOOP)
+    }
+
+    fn set_irq_mask(&mut self, mask: InterruptMask) {
+        let rx = mask.contains(InterruptMask::RX_AVAILABLE) as u16;
+        let tx = mask.contains(InterruptMask::TX_EMPTY) as u16;
+
+        self.registers()
+            .scscr
+            .modify(SCSCR::RIE.val(rx) + SCSCR::TIE.val(tx));
+    }
+
+    fn get_irq_mask(&self) -> InterruptMask {
+        let scr = self.registers().scscr.extract();
+        let mut mask = InterruptMask::empty();
+
+        if scr.is_set(SCSCR::RIE) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if scr.is_set(SCSCR::TIE) {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        mask
+    }
+
+    fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+        self.irq.take()
+    }
+
+    fn take_tx(&mut self) -> Option<Self::Sender> {
+        self.tx.take().map(crate::Sender::ScifSender)
+    }
+
+    fn take_rx(&mut self) -> Option<Self::Reciever> {
+        self.rx.take().map(crate::Reciever::ScifReciever)
+    }
+
+    fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+        let tx = match tx {
+            crate::Sender::ScifSender(s) => s,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    tx.external_base().unwrap_or(0), // 不匹配的发送器类型
+                ));
+            }
+        };
+
+        if self.base != tx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                tx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.tx = Some(tx);
+        Ok(())
+    }
+
+    fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+        let rx = match rx {
+            crate::Reciever::ScifReciever(r) => r,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    rx.external_base().unwrap_or(0), // 不匹配的接收器类型
+                ));
+            }
+        };
+
+        if self.base != rx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                rx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.rx = Some(rx);
+        Ok(())
+    }
+}
+
+impl Caps for Scif {
+    fn capabilities(&self) -> Capabilities {
+        let (min_baudrate, _) =
+            Capabilities::baudrate_range(self.clock_freq, MAX_PRESCALER, BRR_MAX);
+        let (_, max_baudrate) = Capabilities::baudrate_range(self.clock_freq, 32, BRR_MAX);
+
+        Capabilities {
+            fifo_depth: FIFO_DEPTH,
+            min_baudrate,
+            max_baudrate,
+            data_bits: &[DataBits::Seven, DataBits::Eight],
+            stop_bits: ALL_STOP_BITS,
+            parity: &[Parity::None, Parity::Even, Parity::Odd],
+            features: Features::HW_FLOW_CONTROL | Features::LOOPBACK,
+        }
+    }
+}
+
+impl FifoThreshold for Scif {
+    fn fifo_triggers(&self) -> FifoTriggers {
+        let (rx, tx) = self.fifo_trigger();
+        FifoTriggers {
+            rx: Some(rx.bytes()),
+            tx: Some(tx.bytes()),
+            rx_timeout: None,
+        }
+    }
+
+    fn set_fifo_thresholds(&mut self, req: &FifoThresholds) -> Result<FifoTriggers, FifoError> {
+        let req = req.resolve(FIFO_DEPTH, &self.fifo_triggers())?;
+        let (mut rx, mut tx) = self.fifo_trigger();
+
+        if let Some(want) = req.rx {
+            let level = select_level(&RxTrigger::ALL.map(RxTrigger::bytes), want);
+            rx = RxTrigger::ALL
+                .into_iter()
+                .find(|t| t.bytes() == level)
+                .unwrap_or(rx);
+        }
+        if let Some(want) = req.tx {
+            let level = select_level(&TxTrigger::ALL.map(TxTrigger::bytes), want);
+            tx = TxTrigger::ALL
+                .into_iter()
+                .find(|t| t.bytes() == level)
+                .unwrap_or(tx);
+        }
+        self.set_fifo_trigger(rx, tx);
+
+        Ok(self.fifo_triggers())
+    }
+}
+
+impl Flush for Scif {
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError> {
+        let regs = self.registers();
+
+        // 复位位不会自动清零，置位后需要再清除
+        let (set, clear) = match queue {
+            FlushQueue::Rx => (SCFCR::RFRST::SET, SCFCR::RFRST::CLEAR),
+            FlushQueue::Tx => (SCFCR::TFRST::SET, SCFCR::TFRST::CLEAR),
+            FlushQueue::Both => (
+                SCFCR::RFRST::SET + SCFCR::TFRST::SET,
+                SCFCR::RFRST::CLEAR + SCFCR::TFRST::CLEAR,
+            ),
+        };
+        regs.scfcr.modify(set);
+        regs.scfcr.modify(clear);
+
+        if queue.rx() {
+            self.clear_status();
+        }
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), FlushError> {
+        if self.wait_tx_idle() {
+            Ok(())
+        } else {
+            Err(FlushError::Timeout)
+        }
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// SCIF 挂起时保存的寄存器上下文
+#[derive(Debug, Clone, Copy)]
+pub struct ScifContext {
+    scsmr: u16,
+    scbrr: u8,
+    scscr: u16,
+    scfcr: u16,
+    scsptr: u16,
+}
+
+impl Suspend for Scif {
+    type Context = ScifContext;
+
+    fn save_context(&self) -> Self::Context {
+        let regs = self.registers();
+        ScifContext {
+            scsmr: regs.scsmr.get(),
+            scbrr: regs.scbrr.get(),
+            scscr: regs.scscr.get(),
+            scfcr: regs.scfcr.get(),
+            scsptr: regs.scsptr.get(),
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        let regs = self.registers();
+
+        // 与初始化相同的顺序，收发关闭时才能写入模式和波特率
+        regs.scscr.set(0);
+        regs.scfcr.write(SCFCR::RFRST::SET + SCFCR::TFRST::SET);
+        self.clear_status();
+
+        regs.scsmr.set(ctx.scsmr);
+        regs.scbrr.set(ctx.scbrr);
+        regs.scsptr.set(ctx.scsptr);
+        regs.scfcr
+            .set(ctx.scfcr & !(SCFCR::RFRST::SET + SCFCR::TFRST::SET).value);
+        regs.scscr.set(ctx.scscr);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Reg(NonNull<ScifRegisters>);
+
+unsafe impl Send for Reg {}
+
+impl Reg {
+    fn registers(&self) -> &ScifRegisters {
+        unsafe { self.0.as_ref() }
+    }
+}
+
+pub struct ScifSender {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    tx_pause: TxPause,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for ScifSender {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl TSender for ScifSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        RawSender::write_byte(self, byte)
+    }
+}
+
+impl RawSender for ScifSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return false;
+        };
+        let regs = self.base.registers();
+        if regs.scfdr.read(SCFDR::TX_COUNT) >= FIFO_DEPTH {
+            return false;
+        }
+
+        regs.scftdr.set(byte);
+
+        // 写入数据后清除 TDFE/TEND，剩余字节仍低于触发级别时硬件会重新置位
+        regs.scfsr.get();
+        regs.scfsr.set(!(SCFSR::TDFE::SET + SCFSR::TEND::SET).value);
+
+        if let Some(stats) = self.stats {
+            stats.record_tx(1);
+        }
+
+        true
+    }
+}
+
+pub struct ScifReciever {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for ScifReciever {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl ScifReciever {
+    fn read_raw(&mut self) -> Option<Result<u8, TransferError>> {
+        let regs = self.base.registers();
+        if regs.scfdr.read(SCFDR::RX_COUNT) == 0 {
+            return None;
+        }
+
+        // SCFSR 的 PER/FER 对应 SCFRDR 中即将读出的字节
+        let fsr = regs.scfsr.extract();
+        let data = regs.scfrdr.get();
+        let lsr = regs.sclsr.extract();
+
+        // 读到 1 的状态位写 0 清除，其余位写 1 保持不变
+        regs.scfsr
+            .set(!(SCFSR::ER::SET + SCFSR::BRK::SET + SCFSR::DR::SET + SCFSR::RDF::SET).value);
+
+        let result = if fsr.is_set(SCFSR::BRK) {
+            Err(TransferError::Break)
+        } else if fsr.is_set(SCFSR::FER) {
+            Err(TransferError::Framing)
+        } else if fsr.is_set(SCFSR::PER) {
+            Err(TransferError::Parity)
+        } else if lsr.is_set(SCLSR::ORER) {
+            regs.sclsr.set(!SCLSR::ORER::SET.value);
+            Err(TransferError::Overrun(data))
+        } else {
+            Ok(data)
+        };
+
+        Some(result)
+    }
+}
+
+impl RawReciever for ScifReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        let result = self.read_raw()?;
+        if let Some(stats) = self.stats {
+            stats.record_rx(&result);
+        }
+        Some(result)
+    }
+}
+
+pub struct ScifIrqHandler {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+unsafe impl Sync for ScifIrqHandler {}
+
+impl TIrqHandler for ScifIrqHandler {
+    /// 接收相关状态由 `read_byte` 在读出数据时清除；这里只清除 TDFE，
+    /// 发送 FIFO 仍低于触发级别时硬件会重新置位。
+    fn clean_interrupt_status(&self) -> InterruptMask {
+        let regs = self.base.registers();
+        let scr = regs.scscr.extract();
+        let fsr = regs.scfsr.extract();
+        let mut mask = InterruptMask::empty();
+
+        let rx = fsr.is_set(SCFSR::RDF) || fsr.is_set(SCFSR::DR);
+        let tx = fsr.is_set(SCFSR::TDFE);
+
+        if rx && scr.is_set(SCSCR::RIE) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if tx && scr.is_set(SCSCR::TIE) {
+            mask |= InterruptMask::TX_EMPTY;
+            regs.scfsr.set(!SCFSR::TDFE::SET.value);
+        }
+
+        if let Some(stats) = self.stats {
+            if fsr.is_set(SCFSR::RDF) {
+                stats.record_irq(IrqCause::Rx);
+            }
+            if fsr.is_set(SCFSR::DR) {
+                stats.record_irq(IrqCause::RxTimeout);
+            }
+            if mask.contains(InterruptMask::TX_EMPTY) {
+                stats.record_irq(IrqCause::Tx);
+            }
+            if fsr.is_set(SCFSR::ER) || fsr.is_set(SCFSR::BRK) {
+                stats.record_irq(IrqCause::LineStatus);
+            }
+        }
+
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fake_mmio::FakeMmio, FifoLevel};
+
+    /// 寄存器为 8/16 位，测试按小端取出所在 32 位字的低位
+    fn reg16(regs: &FakeMmio<10>, offset: usize) -> u16 {
+        regs.get(offset) as u16
+    }
+
+    fn uart(regs: &FakeMmio<10>) -> Scif {
+        // 发送完成，配置时无需等待
+        regs.set(0x10, SCFSR::TEND::SET.value as u32);
+        Scif::new(regs.base(), 66_666_667)
+    }
+
+    #[test]
+    fn smallest_prescaler_that_fits_brr() {
+        let regs = FakeMmio::<10>::new();
+        let mut uart = uart(&regs);
+        regs.set(0x08, (SCSCR::TE::SET + SCSCR::RE::SET).value as u32);
+
+        // 66.67 MHz / (32 * 115200) = 18.08
+        uart.set_config(&Config::new().baudrate(115200)).unwrap();
+        assert_eq!(reg16(&regs, 0x00) & 0b11, 0);
+        assert_eq!(regs.get(0x04) as u8, 17);
+        assert_eq!(uart.baudrate(), 115740);
+        // 收发使能恢复为原值
+        assert_eq!(reg16(&regs, 0x08), (SCSCR::TE::SET + SCSCR::RE::SET).value);
+
+        // CKS 0..2 下 SCBRR 都超过 8 位
+        uart.set_config(&Config::new().baudrate(300)).unwrap();
+        assert_eq!(reg16(&regs, 0x00) & 0b11, 3);
+        assert_eq!(regs.get(0x04) as u8, 108);
+        assert_eq!(uart.baudrate(), 298);
+
+        assert_eq!(
+            uart.set_config(&Config::new().baudrate(50)),
+            Err(ConfigError::InvalidBaudrate)
+        );
+    }
+
+    #[test]
+    fn read_clears_status_with_zero_writes() {
+        let regs = FakeMmio::<10>::new();
+        let uart = uart(&regs);
+        let mut rx = uart.rx.unwrap();
+        let keep = !(SCFSR::ER::SET + SCFSR::BRK::SET + SCFSR::DR::SET + SCFSR::RDF::SET).value;
+
+        regs.set(0x1c, SCFDR::RX_COUNT.val(1).value as u32);
+        regs.set(
+            0x10,
+            (SCFSR::FER::SET + SCFSR::ER::SET + SCFSR::DR::SET).value as u32,
+        );
+        regs.set(0x14, 0x3c);
+        assert_eq!(
+            RawReciever::read_byte(&mut rx),
+            Some(Err(TransferError::Framing))
+        );
+        assert_eq!(reg16(&regs, 0x10), keep);
+
+        regs.set(0x10, 0);
+        regs.set(0x24, SCLSR::ORER::SET.value as u32);
+        assert_eq!(
+            RawReciever::read_byte(&mut rx),
+            Some(Err(TransferError::Overrun(0x3c)))
+        );
+        assert_eq!(reg16(&regs, 0x24), !SCLSR::ORER::SET.value);
+    }
+
+    #[test]
+    fn tx_count_limits_writes() {
+        let regs = FakeMmio::<10>::new();
+        let uart = uart(&regs);
+        let mut tx = uart.tx.unwrap();
+
+        regs.set(0x1c, SCFDR::TX_COUNT.val(FIFO_DEPTH).value as u32);
+        assert!(!RawSender::write_byte(&mut tx, b'a'));
+        regs.set(0x1c, SCFDR::TX_COUNT.val(FIFO_DEPTH - 1).value as u32);
+        assert!(RawSender::write_byte(&mut tx, b'b'));
+        assert_eq!(regs.get(0x0c) as u8, b'b');
+    }
+
+    #[test]
+    fn irq_acks_only_tdfe() {
+        let regs = FakeMmio::<10>::new();
+        let mut uart = uart(&regs);
+        let irq = uart.irq_handler().unwrap();
+
+        uart.set_irq_mask(InterruptMask::RX_AVAILABLE | InterruptMask::TX_EMPTY);
+        regs.set(0x10, (SCFSR::TDFE::SET + SCFSR::DR::SET).value as u32);
+        assert_eq!(
+            irq.clean_interrupt_status().bits(),
+            (InterruptMask::RX_AVAILABLE | InterruptMask::TX_EMPTY).bits()
+        );
+        assert_eq!(reg16(&regs, 0x10), !SCFSR::TDFE::SET.value);
+    }
+
+    #[test]
+    fn thresholds_pick_lower_hardware_level() {
+        let regs = FakeMmio::<10>::new();
+        let mut uart = uart(&regs);
+
+        let req = FifoThresholds::new()
+            .rx(FifoLevel::Bytes(10))
+            .tx(FifoLevel::Bytes(3));
+        let triggers = uart.set_fifo_thresholds(&req).unwrap();
+        assert_eq!((triggers.rx, triggers.tx), (Some(8), Some(2)));
+        assert_eq!(
+            reg16(&regs, 0x18),
+            (SCFCR::RTRG::Eight + SCFCR::TTRG::Two).value
+        );
+    }
+}
@@ -0,0 +1,520 @@
+//! ARM semihosting 控制台
+//!
+//! 适用于只有 JTAG 的板子以及 FVP/QEMU（`-semihosting`）运行环境，通过调试器或模拟器
+//! 输出字符：`SYS_WRITEC` 写单个字符，`SYS_WRITE0` 写以 NUL 结尾的字符串。
+//!
+//! `SYS_READC` 会阻塞到宿主输入一个字符，不符合接收端非阻塞轮询的约定，
+//! 因此 `read_byte` 总是返回 `None`，需要输入时显式调用
+//! [`SemihostingReciever::read_blocking`]。
+//!
+//! 陷入调用经过 [`SemihostingCall`] 抽象，主机测试中可以替换为模拟宿主。
+//! 没有调试器连接时执行 semihosting 指令会触发异常，只应在确认宿主存在时使用。
+
+use alloc::sync::Arc;
+
+use rdif_serial::{InterfaceRaw, SetBackError, TIrqHandler, TReciever, TSender};
+
+use crate::{
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, Stats},
+    Capabilities, Caps, Config, ConfigError, DataBits, Features, FifoThreshold, InterruptMask,
+    Parity, RawReciever, RawSender, StopBits, Suspend, TransferError,
+};
+
+#[cfg(target_arch = "aarch64")]
+use rdif_serial::{BSerial, SerialDyn};
+
+/// semihosting 操作号
+const SYS_WRITEC: usize = 0x03;
+const SYS_WRITE0: usize = 0x04;
+const SYS_READC: usize = 0x07;
+
+/// `SYS_WRITE0` 使用的栈上缓冲区大小（含结尾 NUL）
+const WRITE0_BUF_SIZE: usize = 64;
+
+/// semihosting 陷入接口
+pub trait SemihostingCall: Send + Sync + 'static {
+    /// 执行操作 `op`，`param` 为参数块或数据的地址，返回宿主的结果
+    fn call(&self, op: usize, param: usize) -> usize;
+}
+
+/// AArch64 上通过 `HLT #0xF000` 陷入
+#[cfg(target_arch = "aarch64")]
+pub struct HltTrap;
+
+#[cfg(target_arch = "aarch64")]
+impl SemihostingCall for HltTrap {
+    fn call(&self, op: usize, param: usize) -> usize {
+        let ret: usize;
+        unsafe {
+            core::arch::asm!(
+                "hlt #0xf000",
+                inlateout("x0") op => ret,
+                in("x1") param,
+                options(nostack),
+            );
+        }
+        ret
+    }
+}
+
+#[derive(Clone)]
+struct Host(Arc<dyn SemihostingCall>);
+
+impl Host {
+    fn addr(&self) -> usize {
+        Arc::as_ptr(&self.0) as *const u8 as usize
+    }
+
+    fn call(&self, op: usize, param: usize) -> usize {
+        self.0.call(op, param)
+    }
+}
+
+impl PartialEq for Host {
+    fn eq(&self, other: &Self) -> bool {
+        self.addr() == other.addr()
+    }
+}
+
+/// semihosting 控制台驱动结构体
+pub struct Semihosting {
+    host: Host,
+    baudrate: u32,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    tx_pause: TxPause,
+    tx: Option<SemihostingSender>,
+    rx: Option<SemihostingReciever>,
+    irq: Option<SemihostingIrqHandler>,
+}
+
+impl Semihosting {
+    #[cfg(target_arch = "aarch64")]
+    pub fn new() -> Self {
+        Self::with_call(Arc::new(HltTrap))
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    pub fn new_boxed() -> BSerial {
+        SerialDyn::new_boxed(Self::new())
+    }
+
+    /// 使用自定义陷入实现创建实例
+    pub fn with_call(call: Arc<dyn SemihostingCall>) -> Self {
+        let host = Host(call);
+        let tx_pause = TxPause::default();
+
+        Self {
+            tx_pause: tx_pause.clone(),
+            tx: Some(SemihostingSender {
+                host: host.clone(),
+                stats: None,
+                tx_pause: tx_pause.clone(),
+            }),
+            rx: Some(SemihostingReciever {
+                host: host.clone(),
+                stats: None,
+            }),
+            irq: Some(SemihostingIrqHandler),
+            host,
+            baudrate: 115200,
+        }
+    }
+
+    /// 挂接统计计数器，只对尚未取出的收发句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+impl Default for Semihosting {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InterfaceRaw for Semihosting {
+    type IrqHandler = SemihostingIrqHandler;
+
+    type Sender = crate::Sender;
+
+    type Reciever = crate::Reciever;
+
+    fn base_addr(&self) -> usize {
+        self.host.addr()
+    }
+
+    fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        self.capabilities().validate(config)?;
+        if let Some(baudrate) = config.baudrate {
+            self.baudrate = baudrate;
+        }
+        Ok(())
+    }
+
+    /// semihosting 没有真实波特率，返回最近一次配置的值
+    fn baudrate(&self) -> u32 {
+        self.baudrate
+    }
+
+    fn data_bits(&self) -> DataBits {
+        DataBits::Eight
+    }
+
+    fn stop_bits(&self) -> StopBits {
+        StopBits::One
+    }
+
+    fn parity(&self) -> Parity {
+        Parity::None
+    }
+
+    fn clock_freq(&self) -> Option<core::num::NonZeroU32> {
+        None
+    }
+
+    fn open(&mut self) {}
+
+    fn close(&mut self) {}
+
+    fn enable_loopback(&mut self) {}
+
+    fn disable_loopback(&mut self) {}
+
+    fn is_loopback_enabled(&self) -> bool {
+        false
+    }
+
+    // semihosting 没有中断
+    fn set_irq_mask(&mut self, _mask: InterruptMask) {}
+
+    fn get_irq_mask(&self) -> InterruptMask {
+        InterruptMask::empty()
+    }
+
+    fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+        self.irq.take()
+    }
+
+    fn take_tx(&mut self) -> Option<Self::Sender> {
+        self.tx.take().map(crate::Sender::SemihostingSender)
+    }
+
+    fn take_rx(&mut self) -> Option<Self::Reciever> {
+        self.rx.take().map(crate::Reciever::SemihostingReciever)
+    }
+
+    fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+        let tx = match tx {
+            crate::Sender::SemihostingSender(s) => s,
+            _ => {
+                return Err(SetBackError::new(
+                    self.host.addr(),
+                    tx.external_base().unwrap_or(0), // 不匹配的发送器类型
+                ));
+            }
+        };
+
+        if self.host != tx.host {
+            return Err(SetBackError::new(self.host.addr(), tx.host.addr()));
+        }
+
+        self.tx = Some(tx);
+        Ok(())
+    }
+
+    fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+        let rx = match rx {
+            crate::Reciever::SemihostingReciever(r) => r,
+            _ => {
+                return Err(SetBackError::new(
+                    self.host.addr(),
+                    rx.external_base().unwrap_or(0), // 不匹配的接收器类型
+                ));
+            }
+        };
+
+        if self.host != rx.host {
+            return Err(SetBackError::new(self.host.addr(), rx.host.addr()));
+        }
+
+        self.rx = Some(rx);
+        Ok(())
+    }
+}
+
+impl Caps for Semihosting {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            fifo_depth: 1,
+            min_baudrate: 1,
+            max_baudrate: u32::MAX,
+            data_bits: &[DataBits::Eight],
+            stop_bits: &[StopBits::One],
+            parity: &[Parity::None],
+            features: Features::empty(),
+        }
+    }
+}
+
+impl FifoThreshold for Semihosting {}
+
+/// 每次调用都同步完成，驱动不缓存数据，也无法丢弃主机侧的输入
+impl Flush for Semihosting {
+    fn flush(&mut self, _queue: FlushQueue) -> Result<(), FlushError> {
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), FlushError> {
+        Ok(())
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// semihosting 挂起时保存的上下文
+#[derive(Debug, Clone, Copy)]
+pub struct SemihostingContext {
+    baudrate: u32,
+}
+
+impl Suspend for Semihosting {
+    type Context = SemihostingContext;
+
+    fn save_context(&self) -> Self::Context {
+        SemihostingContext {
+            baudrate: self.baudrate,
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        self.baudrate = ctx.baudrate;
+    }
+}
+
+pub struct SemihostingSender {
+    host: Host,
+    stats: Option<&'static Stats>,
+    tx_pause: TxPause,
+}
+
+impl AttachStats for SemihostingSender {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl SemihostingSender {
+    fn write_char(&self, byte: u8) {
+        self.host.call(SYS_WRITEC, &byte as *const u8 as usize);
+    }
+
+    /// 非 NUL 字节攒成字符串用 `SYS_WRITE0` 一次写出，减少陷入次数
+    fn write_raw(&self, bytes: &[u8]) {
+        let mut buf = [0u8; WRITE0_BUF_SIZE];
+        let mut len = 0;
+
+        let flush = |buf: &mut [u8; WRITE0_BUF_SIZE], len: &mut usize| {
+            if *len > 0 {
+                buf[*len] = 0;
+                self.host.call(SYS_WRITE0, buf.as_ptr() as usize);
+                *len = 0;
+            }
+        };
+
+        for &byte in bytes {
+            if byte == 0 {
+                flush(&mut buf, &mut len);
+                self.write_char(0);
+                continue;
+            }
+            buf[len] = byte;
+            len += 1;
+            if len == WRITE0_BUF_SIZE - 1 {
+                flush(&mut buf, &mut len);
+            }
+        }
+        flush(&mut buf, &mut len);
+    }
+}
+
+impl TSender for SemihostingSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        RawSender::write_byte(self, byte)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> usize {
+        RawSender::write_bytes(self, bytes)
+    }
+}
+
+impl RawSender for SemihostingSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return false;
+        };
+        self.write_char(byte);
+        if let Some(stats) = self.stats {
+            stats.record_tx(1);
+        }
+        true
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> usize {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return 0;
+        };
+        self.write_raw(bytes);
+        if let Some(stats) = self.stats {
+            stats.record_tx(bytes.len());
+        }
+        bytes.len()
+    }
+}
+
+/// 轮询读取总是返回 `None`，阻塞读取使用 [`Self::read_blocking`]
+pub struct SemihostingReciever {
+    host: Host,
+    stats: Option<&'static Stats>,
+}
+
+impl SemihostingReciever {
+    /// 通过 `SYS_READC` 读取一个字符，阻塞到宿主输入为止
+    pub fn read_blocking(&mut self) -> Result<u8, TransferError> {
+        let result = Ok(self.host.call(SYS_READC, 0) as u8);
+        if let Some(stats) = self.stats {
+            stats.record_rx(&result);
+        }
+        result
+    }
+}
+
+impl AttachStats for SemihostingReciever {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl TReciever for SemihostingReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        RawReciever::read_byte(self)
+    }
+}
+
+/// semihosting 无法查询是否有输入，轮询时按没有数据处理
+impl RawReciever for SemihostingReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        None
+    }
+}
+
+/// semihosting 没有中断，处理函数总是返回空
+pub struct SemihostingIrqHandler;
+
+impl TIrqHandler for SemihostingIrqHandler {
+    fn clean_interrupt_status(&self) -> InterruptMask {
+        InterruptMask::empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::{collections::VecDeque, ffi::CStr, sync::Mutex, vec::Vec};
+
+    use super::{
+        Semihosting, SemihostingCall, SemihostingReciever, SYS_READC, SYS_WRITE0, SYS_WRITEC,
+    };
+    use alloc::sync::Arc;
+    use rdif_serial::{InterfaceRaw, TReciever, TSender};
+
+    #[derive(Default)]
+    struct FakeState {
+        output: Vec<u8>,
+        input: VecDeque<u8>,
+        ops: Vec<usize>,
+    }
+
+    /// 模拟调试宿主，参数地址即主机地址
+    #[derive(Default)]
+    struct FakeHost(Mutex<FakeState>);
+
+    impl SemihostingCall for FakeHost {
+        fn call(&self, op: usize, param: usize) -> usize {
+            let mut state = self.0.lock().unwrap();
+            state.ops.push(op);
+            match op {
+                SYS_WRITEC => {
+                    let byte = unsafe { *(param as *const u8) };
+                    state.output.push(byte);
+                    0
+                }
+                SYS_WRITE0 => {
+                    let s = unsafe { CStr::from_ptr(param as *const _) };
+                    state.output.extend_from_slice(s.to_bytes());
+                    0
+                }
+                SYS_READC => state.input.pop_front().unwrap() as usize,
+                _ => usize::MAX,
+            }
+        }
+    }
+
+    #[test]
+    fn write_batches_strings() {
+        let host = Arc::new(FakeHost::default());
+        let mut console = Semihosting::with_call(host.clone());
+        let mut tx = console.take_tx().unwrap();
+
+        assert!(tx.write_byte(b'>'));
+        let data: Vec<u8> = (0..100).map(|i| b'a' + i % 26).collect();
+        assert_eq!(tx.write_bytes(&data), data.len());
+
+        let state = host.0.lock().unwrap();
+        let mut expected = Vec::from(*b">");
+        expected.extend_from_slice(&data);
+        assert_eq!(state.output, expected);
+        // 单字符加两段字符串
+        assert_eq!(state.ops, [SYS_WRITEC, SYS_WRITE0, SYS_WRITE0]);
+    }
+
+    #[test]
+    fn write_keeps_nul_bytes() {
+        let host = Arc::new(FakeHost::default());
+        let mut console = Semihosting::with_call(host.clone());
+        let mut tx = console.take_tx().unwrap();
+
+        assert_eq!(tx.write_bytes(b"a\0b"), 3);
+        let state = host.0.lock().unwrap();
+        assert_eq!(state.output, b"a\0b");
+        assert_eq!(state.ops, [SYS_WRITE0, SYS_WRITEC, SYS_WRITE0]);
+    }
+
+    #[test]
+    fn poll_never_blocks() {
+        let host = Arc::new(FakeHost::default());
+        host.0.lock().unwrap().input.extend(b"q");
+        let mut console = Semihosting::with_call(host.clone());
+        let mut rx = console.take_rx().unwrap();
+
+        // 轮询不陷入宿主，排空循环可以结束
+        assert_eq!(rx.read_byte(), None);
+        let mut buf = [0u8; 4];
+        assert_eq!(rx.read_bytes(&mut buf), Ok(0));
+        assert!(host.0.lock().unwrap().ops.is_empty());
+
+        let mut rx: SemihostingReciever = rx.try_into().ok().unwrap();
+        assert_eq!(rx.read_blocking(), Ok(b'q'));
+        assert_eq!(host.0.lock().unwrap().ops, [SYS_READC]);
+    }
+}
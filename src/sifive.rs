@@ -0,0 +1,674 @@
+//! SiFive UART 驱动 (`sifive,uart0`)
+//!
+//! 用于 HiFive 系列开发板和 QEMU `sifive_u`/`sifive_e` 机器。
+//! 该 UART 只支持 8 位数据、无校验，可选 1/2 位停止位，收发各有 8 字节 FIFO，
+//! 波特率由单个除数决定：`baud = clk / (div + 1)`。
+
+use core::{num::NonZeroU32, ptr::NonNull};
+
+use alloc::sync::Arc;
+
+use rdif_serial::{BSerial, InterfaceRaw, SerialDyn, SetBackError, TIrqHandler, TSender};
+use tock_registers::{
+    interfaces::*, register_bitfields, register_structs, registers::*, LocalRegisterCopy,
+};
+
+use crate::{
+    caps::ALL_STOP_BITS,
+    claim::{self, Claim, ClaimError, Region},
+    fifo::{FifoError, FifoThreshold, FifoThresholds, FifoTriggers},
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, IrqCause, Stats},
+    Capabilities, Caps, Config, ConfigError, DataBits, Features, InterruptMask, Parity,
+    RawReciever, RawSender, StopBits, Suspend, TransferError,
+};
+
+register_bitfields! [
+    u32,
+
+    /// Transmit Data Register
+    TXDATA [
+        DATA OFFSET(0) NUMBITS(8) [],
+        FULL OFFSET(31) NUMBITS(1) []
+    ],
+
+    /// Receive Data Register
+    RXDATA [
+        DATA OFFSET(0) NUMBITS(8) [],
+        EMPTY OFFSET(31) NUMBITS(1) []
+    ],
+
+    /// Transmit Control Register
+    TXCTRL [
+        TXEN OFFSET(0) NUMBITS(1) [],
+        NSTOP OFFSET(1) NUMBITS(1) [],
+        TXCNT OFFSET(16) NUMBITS(3) []
+    ],
+
+    /// Receive Control Register
+    RXCTRL [
+        RXEN OFFSET(0) NUMBITS(1) [],
+        RXCNT OFFSET(16) NUMBITS(3) []
+    ],
+
+    /// Interrupt Enable / Pending Register
+    IP [
+        TXWM OFFSET(0) NUMBITS(1) [],
+        RXWM OFFSET(1) NUMBITS(1) []
+    ],
+
+    /// Baud Rate Divisor Register
+    DIV [
+        DIV OFFSET(0) NUMBITS(16) []
+    ]
+];
+
+register_structs! {
+    pub SifiveRegisters {
+        (0x00 => txdata: ReadWrite<u32, TXDATA::Register>),   // 发送数据寄存器
+        (0x04 => rxdata: ReadOnly<u32, RXDATA::Register>),    // 接收数据寄存器（读取即出队）
+        (0x08 => txctrl: ReadWrite<u32, TXCTRL::Register>),   // 发送控制寄存器
+        (0x0c => rxctrl: ReadWrite<u32, RXCTRL::Register>),   // 接收控制寄存器
+        (0x10 => ie: ReadWrite<u32, IP::Register>),           // 中断使能寄存器
+        (0x14 => ip: ReadOnly<u32, IP::Register>),            // 中断挂起寄存器
+        (0x18 => div: ReadWrite<u32, DIV::Register>),         // 波特率除数寄存器
+        (0x1c => @END),
+    }
+}
+
+// SAFETY: 寄存器访问均为单次 32 位读写
+unsafe impl Sync for SifiveRegisters {}
+
+/// 收发 FIFO 深度
+const FIFO_DEPTH: u16 = 8;
+
+/// 等待发送 FIFO 清空的最大轮询次数
+const BUSY_SPIN_LIMIT: usize = 1_000_000;
+
+/// SiFive UART 驱动结构体
+pub struct Sifive {
+    base: Reg,
+    clock_freq: u32,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    tx_pause: TxPause,
+    tx: Option<SifiveSender>,
+    rx: Option<SifiveReciever>,
+    irq: Option<SifiveIrqHandler>,
+    /// 通过 `try_new` 系列创建时持有的区域占用，与各句柄共享
+    claim: Option<Arc<Claim>>,
+}
+
+impl Sifive {
+    /// 不占用寄存器区域，需要防止同一设备被重复创建时使用 [`Self::try_new`]
+    pub fn new(base: NonNull<u8>, clock_freq: u32) -> Self {
+        Self::with_claim(base, clock_freq, None)
+    }
+
+    pub fn new_boxed(base: NonNull<u8>, clock_freq: u32) -> BSerial {
+        SerialDyn::new_boxed(Self::new(base, clock_freq))
+    }
+
+    /// 占用寄存器区域后创建实例，区域与已有实例重叠时返回错误
+    pub fn try_new(base: NonNull<u8>, clock_freq: u32) -> Result<Self, ClaimError> {
+        let claim = claim::claim(Region::mmio(base, size_of::<SifiveRegisters>()))?;
+        Ok(Self::with_claim(base, clock_freq, Some(claim)))
+    }
+
+    pub fn try_new_boxed(base: NonNull<u8>, clock_freq: u32) -> Result<BSerial, ClaimError> {
+        Ok(SerialDyn::new_boxed(Self::try_new(base, clock_freq)?))
+    }
+
+    /// 通过 `try_new` 系列创建时持有的寄存器区域占用
+    pub fn claim(&self) -> Option<&Claim> {
+        self.claim.as_deref()
+    }
+
+    fn with_claim(base: NonNull<u8>, clock_freq: u32, claim: Option<Arc<Claim>>) -> Self {
+        let base = Reg(base.cast());
+        let tx_pause = TxPause::default();
+
+        Self {
+            base,
+            clock_freq,
+            tx_pause: tx_pause.clone(),
+            tx: Some(SifiveSender {
+                base,
+                stats: None,
+                tx_pause: tx_pause.clone(),
+                _claim: claim.clone(),
+            }),
+            rx: Some(SifiveReciever {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            irq: Some(SifiveIrqHandler {
+                base,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            claim,
+        }
+    }
+
+    fn registers(&self) -> &SifiveRegisters {
+        self.base.registers()
+    }
+
+    /// 挂接统计计数器，只对尚未取出的收发和中断句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+        if let Some(irq) = self.irq.as_mut() {
+            irq.stats = Some(stats);
+        }
+    }
+
+    /// 计算波特率对应的除数：div = ceil(clk / baud) - 1
+    fn divisor_for(&self, baudrate: u32) -> Result<u32, ConfigError> {
+        if baudrate == 0 || self.clock_freq == 0 {
+            return Err(ConfigError::InvalidBaudrate);
+        }
+
+        let div = self.clock_freq.div_ceil(baudrate) - 1;
+        if div > 0xFFFF {
+            return Err(ConfigError::InvalidBaudrate);
+        }
+        Ok(div)
+    }
+
+    /// 初始化 UART
+    fn init(&self) {
+        self.registers().ie.set(0);
+
+        // TXWM：发送 FIFO 中数据少于 1 个（即为空）时挂起
+        // RXWM：接收 FIFO 中数据多于 0 个时挂起
+        self.registers()
+            .txctrl
+            .modify(TXCTRL::TXEN::SET + TXCTRL::TXCNT.val(1));
+        self.registers()
+            .rxctrl
+            .modify(RXCTRL::RXEN::SET + RXCTRL::RXCNT.val(0));
+    }
+}
+
+impl InterfaceRaw for Sifive {
+    type IrqHandler = SifiveIrqHandler;
+
+    type Sender = crate::Sender;
+
+    type Reciever = crate::Reciever;
+
+    fn base_addr(&self) -> usize {
+        self.base.0.as_ptr() as usize
+    }
+
+    fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        // 数据位和校验由硬件固定，不支持的设置在此处被拒绝
+        self.capabilities().validate(config)?;
+        let div = config
+            .baudrate
+            .map(|baudrate| self.divisor_for(baudrate))
+            .transpose()?;
+
+        if let Some(div) = div {
+            self.registers().div.write(DIV::DIV.val(div));
+        }
+        match config.stop_bits {
+            Some(StopBits::One) => self.registers().txctrl.modify(TXCTRL::NSTOP::CLEAR),
+            Some(StopBits::Two) => self.registers().txctrl.modify(TXCTRL::NSTOP::SET),
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    fn baudrate(&self) -> u32 {
+        let div = self.registers().div.read(DIV::DIV);
+        self.clock_freq / (div + 1)
+    }
+
+    fn data_bits(&self) -> DataBits {
+        DataBits::Eight
+    }
+
+    fn stop_bits(&self) -> StopBits {
+        if self.registers().txctrl.is_set(TXCTRL::NSTOP) {
+            StopBits::Two
+        } else {
+            StopBits::One
+        }
+    }
+
+    fn parity(&self) -> Parity {
+        Parity::None
+    }
+
+    fn clock_freq(&self) -> Option<NonZeroU32> {
+        self.clock_freq.try_into().ok()
+    }
+
+    fn open(&mut self) {
+        self.init()
+    }
+
+    fn close(&mut self) {
+        self.registers().ie.set(0);
+        self.registers().txctrl.modify(TXCTRL::TXEN::CLEAR);
+        self.registers().rxctrl.modify(RXCTRL::RXEN::CLEAR);
+    }
+
+    // SiFive UART 没有回环模式
+    fn enable_loopback(&mut self) {}
+
+    fn disable_loopback(&mut self) {}
+
+    fn is_loopback_enabled(&self) -> bool {
+        false
+    }
+
+    fn set_irq_mask(&mut self, mask: InterruptMask) {
+        let mut ie = 0;
+        if mask.contains(InterruptMask::RX_AVAILABLE) {
+            ie |= IP::RXWM::SET.value;
+        }
+        if mask.contains(InterruptMask::TX_EMPTY) {
+            ie |= IP::TXWM::SET.value;
+        }
+        self.registers().ie.set(ie);
+    }
+
+    fn get_irq_mask(&self) -> InterruptMask {
+        let ie = self.registers().ie.extract();
+        let mut mask = InterruptMask::empty();
+
+        if ie.is_set(IP::RXWM) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if ie.is_set(IP::TXWM) {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        mask
+    }
+
+    fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+        self.irq.take()
+    }
+
+    fn take_tx(&mut self) -> Option<Self::Sender> {
+        self.tx.take().map(crate::Sender::SifiveSender)
+    }
+
+    fn take_rx(&mut self) -> Option<Self::Reciever> {
+        self.rx.take().map(crate::Reciever::SifiveReciever)
+    }
+
+    fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+        let tx = match tx {
+            crate::Sender::SifiveSender(s) => s,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    tx.external_base().unwrap_or(0), // 不匹配的发送器类型
+                ));
+            }
+        };
+
+        if self.base != tx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                tx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.tx = Some(tx);
+        Ok(())
+    }
+
+    fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+        let rx = match rx {
+            crate::Reciever::SifiveReciever(r) => r,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    rx.external_base().unwrap_or(0), // 不匹配的接收器类型
+                ));
+            }
+        };
+
+        if self.base != rx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                rx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.rx = Some(rx);
+        Ok(())
+    }
+}
+
+impl Caps for Sifive {
+    fn capabilities(&self) -> Capabilities {
+        let (min_baudrate, max_baudrate) =
+            Capabilities::baudrate_range(self.clock_freq, 1, 0x1_0000);
+
+        Capabilities {
+            fifo_depth: FIFO_DEPTH,
+            min_baudrate,
+            max_baudrate,
+            data_bits: &[DataBits::Eight],
+            stop_bits: ALL_STOP_BITS,
+            parity: &[Parity::None],
+            features: Features::empty(),
+        }
+    }
+}
+
+/// 接收 FIFO 字节数超过 RXCNT 时触发，发送 FIFO 字节数少于 TXCNT 时触发
+impl FifoThreshold for Sifive {
+    fn fifo_triggers(&self) -> FifoTriggers {
+        let regs = self.registers();
+        FifoTriggers {
+            rx: Some(regs.rxctrl.read(RXCTRL::RXCNT) as u16 + 1),
+            tx: Some((regs.txctrl.read(TXCTRL::TXCNT) as u16).saturating_sub(1)),
+            rx_timeout: None,
+        }
+    }
+
+    fn set_fifo_thresholds(&mut self, req: &FifoThresholds) -> Result<FifoTriggers, FifoError> {
+        let req = req.resolve(FIFO_DEPTH, &self.fifo_triggers())?;
+        let regs = self.registers();
+
+        // 两个计数字段都只有 3 位
+        if let Some(rx) = req.rx {
+            let rxcnt = rx.clamp(1, FIFO_DEPTH) - 1;
+            regs.rxctrl.modify(RXCTRL::RXCNT.val(rxcnt as u32));
+        }
+        if let Some(tx) = req.tx {
+            let txcnt = (tx + 1).min(FIFO_DEPTH - 1);
+            regs.txctrl.modify(TXCTRL::TXCNT.val(txcnt as u32));
+        }
+
+        Ok(self.fifo_triggers())
+    }
+}
+
+/// 硬件没有 FIFO 复位位，也没有移位寄存器状态：接收方向逐字节读空，
+/// 发送数据无法撤回；排空借助 TXCNT = 1 时的水位标志等到发送 FIFO 为空
+///
+/// 由于看不到移位寄存器，`drain` 返回时最后一个字节可能仍在发送，距离真正离开线路
+/// 最多还有一个字符时间（10~11 位）。之后立即改波特率或断电的调用方需要自行延时。
+impl Flush for Sifive {
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError> {
+        if queue.rx() {
+            // 每次读取 rxdata 都会出队一个字节，读到 EMPTY 为止
+            for _ in 0..FIFO_DEPTH {
+                if self.registers().rxdata.is_set(RXDATA::EMPTY) {
+                    break;
+                }
+            }
+        }
+        if queue.tx() {
+            return Err(FlushError::TxUnsupported);
+        }
+        Ok(())
+    }
+
+    /// 只等到发送 FIFO 为空，不包括移位寄存器中的最后一个字节
+    fn drain(&mut self) -> Result<(), FlushError> {
+        let regs = self.registers();
+        let txctrl = regs.txctrl.get();
+
+        // txwm 在发送 FIFO 字节数少于 TXCNT 时置位
+        regs.txctrl.modify(TXCTRL::TXCNT.val(1));
+        let mut drained = false;
+        for _ in 0..BUSY_SPIN_LIMIT {
+            if regs.ip.is_set(IP::TXWM) {
+                drained = true;
+                break;
+            }
+            core::hint::spin_loop();
+        }
+        regs.txctrl.set(txctrl);
+
+        if drained {
+            Ok(())
+        } else {
+            Err(FlushError::Timeout)
+        }
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// SiFive UART 挂起时保存的寄存器上下文
+#[derive(Debug, Clone, Copy)]
+pub struct SifiveContext {
+    txctrl: u32,
+    rxctrl: u32,
+    ie: u32,
+    div: u32,
+}
+
+impl Suspend for Sifive {
+    type Context = SifiveContext;
+
+    fn save_context(&self) -> Self::Context {
+        let regs = self.registers();
+        SifiveContext {
+            txctrl: regs.txctrl.get(),
+            rxctrl: regs.rxctrl.get(),
+            ie: regs.ie.get(),
+            div: regs.div.get(),
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        let regs = self.registers();
+
+        regs.ie.set(0);
+        regs.div.set(ctx.div);
+        regs.txctrl.set(ctx.txctrl);
+        regs.rxctrl.set(ctx.rxctrl);
+        regs.ie.set(ctx.ie);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Reg(NonNull<SifiveRegisters>);
+
+unsafe impl Send for Reg {}
+
+impl Reg {
+    fn registers(&self) -> &SifiveRegisters {
+        unsafe { self.0.as_ref() }
+    }
+}
+
+pub struct SifiveSender {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    tx_pause: TxPause,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for SifiveSender {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl TSender for SifiveSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        RawSender::write_byte(self, byte)
+    }
+}
+
+impl RawSender for SifiveSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return false;
+        };
+        if self.base.registers().txdata.is_set(TXDATA::FULL) {
+            return false;
+        }
+
+        self.base.registers().txdata.set(byte as _);
+        if let Some(stats) = self.stats {
+            stats.record_tx(1);
+        }
+
+        true
+    }
+}
+
+pub struct SifiveReciever {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for SifiveReciever {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl RawReciever for SifiveReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        // 读取 rxdata 会同时出队，必须一次读出 EMPTY 和 DATA
+        let rxdata = self.base.registers().rxdata.extract();
+        if rxdata.is_set(RXDATA::EMPTY) {
+            return None;
+        }
+
+        if let Some(stats) = self.stats {
+            stats.record_rx_byte();
+        }
+        Some(Ok(rxdata.read(RXDATA::DATA) as u8))
+    }
+}
+
+pub struct SifiveIrqHandler {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+unsafe impl Sync for SifiveIrqHandler {}
+
+impl TIrqHandler for SifiveIrqHandler {
+    /// 水位中断为电平触发，状态随 FIFO 变化自动清除；
+    /// 没有待发送数据时需要调用方关闭 `TX_EMPTY` 中断。
+    fn clean_interrupt_status(&self) -> InterruptMask {
+        let regs = self.base.registers();
+        let pending = regs.ip.get() & regs.ie.get();
+        let pending = LocalRegisterCopy::<u32, IP::Register>::new(pending);
+        let mut mask = InterruptMask::empty();
+
+        if pending.is_set(IP::RXWM) {
+            mask |= InterruptMask::RX_AVAILABLE;
+            if let Some(stats) = self.stats {
+                stats.record_irq(IrqCause::Rx);
+            }
+        }
+        if pending.is_set(IP::TXWM) {
+            mask |= InterruptMask::TX_EMPTY;
+            if let Some(stats) = self.stats {
+                stats.record_irq(IrqCause::Tx);
+            }
+        }
+
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fake_mmio::FakeMmio, FifoLevel};
+
+    fn uart(regs: &FakeMmio<8>) -> Sifive {
+        Sifive::new(regs.base(), 16_000_000)
+    }
+
+    #[test]
+    fn divisor_rounds_baudrate_down() {
+        let regs = FakeMmio::<8>::new();
+        let mut uart = uart(&regs);
+
+        // div = ceil(16000000 / 115200) - 1 = 138
+        uart.set_config(&Config::new().baudrate(115200)).unwrap();
+        assert_eq!(regs.get(0x18), 138);
+        assert_eq!(uart.baudrate(), 16_000_000 / 139);
+
+        // 除数超过 16 位
+        assert_eq!(
+            uart.set_config(&Config::new().baudrate(100)),
+            Err(ConfigError::InvalidBaudrate)
+        );
+        assert_eq!(
+            uart.set_config(&Config::new().parity(Parity::Even)),
+            Err(ConfigError::UnsupportedParity)
+        );
+        assert_eq!(regs.get(0x18), 138);
+
+        uart.set_config(&Config::new().stop_bits(StopBits::Two))
+            .unwrap();
+        assert_eq!(uart.stop_bits(), StopBits::Two);
+    }
+
+    #[test]
+    fn read_decodes_rxdata_and_counts() {
+        static STATS: Stats = Stats::new();
+
+        let regs = FakeMmio::<8>::new();
+        let mut uart = uart(&regs);
+        uart.attach_stats(&STATS);
+        let mut rx = uart.rx.take().unwrap();
+
+        regs.set(0x04, 0x41);
+        assert_eq!(RawReciever::read_byte(&mut rx), Some(Ok(0x41)));
+        regs.set(0x04, RXDATA::EMPTY::SET.value | 0x42);
+        assert_eq!(RawReciever::read_byte(&mut rx), None);
+        assert_eq!(STATS.snapshot().rx_bytes, 1);
+    }
+
+    #[test]
+    fn irq_reports_only_enabled_watermarks() {
+        let regs = FakeMmio::<8>::new();
+        let mut uart = uart(&regs);
+        let irq = uart.irq_handler().unwrap();
+
+        uart.set_irq_mask(InterruptMask::RX_AVAILABLE);
+        assert_eq!(regs.get(0x10), IP::RXWM::SET.value);
+        regs.set(0x14, IP::RXWM::SET.value | IP::TXWM::SET.value);
+        assert_eq!(
+            irq.clean_interrupt_status().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+    }
+
+    #[test]
+    fn fifo_counts_map_to_watermarks() {
+        let regs = FakeMmio::<8>::new();
+        let mut uart = uart(&regs);
+
+        let req = FifoThresholds::new()
+            .rx(FifoLevel::Bytes(4))
+            .tx(FifoLevel::Bytes(2));
+        let triggers = uart.set_fifo_thresholds(&req).unwrap();
+        assert_eq!((triggers.rx, triggers.tx), (Some(4), Some(2)));
+        assert_eq!(regs.get(0x0c) >> 16, 3);
+        assert_eq!(regs.get(0x08) >> 16, 3);
+    }
+}
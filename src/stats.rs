@@ -0,0 +1,282 @@
+//! 端口统计计数
+//!
+//! 计数器由收发和中断路径在运行时累加，全部使用原子变量，可以在其他核上随时读取快照。
+//! 统计是可选的：通过驱动的 `attach_stats` 挂接一个 `'static` 的 [`Stats`] 后才会计数。
+//!
+//! ```rust,ignore
+//! static UART0_STATS: Stats = Stats::new();
+//!
+//! let mut uart = Pl011::new(base, clock);
+//! uart.attach_stats(&UART0_STATS);
+//! // ...
+//! let snapshot = UART0_STATS.snapshot();
+//! ```
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use enum_dispatch::enum_dispatch;
+
+use crate::TransferError;
+
+/// 中断原因，用于按原因统计中断次数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrqCause {
+    /// 接收数据达到触发级别
+    Rx,
+    /// 发送 FIFO 低于触发级别
+    Tx,
+    /// 接收超时
+    RxTimeout,
+    /// 接收线路状态（错误）
+    LineStatus,
+    /// 调制解调器状态变化
+    ModemStatus,
+}
+
+/// 端口统计计数器
+#[derive(Debug, Default)]
+pub struct Stats {
+    tx_bytes: AtomicUsize,
+    rx_bytes: AtomicUsize,
+    overrun: AtomicUsize,
+    parity: AtomicUsize,
+    framing: AtomicUsize,
+    brk: AtomicUsize,
+    rx_dropped: AtomicUsize,
+    irq_rx: AtomicUsize,
+    irq_tx: AtomicUsize,
+    irq_rx_timeout: AtomicUsize,
+    irq_line_status: AtomicUsize,
+    irq_modem_status: AtomicUsize,
+}
+
+/// 某一时刻的统计值
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatsSnapshot {
+    pub tx_bytes: usize,
+    pub rx_bytes: usize,
+    pub overrun: usize,
+    pub parity: usize,
+    pub framing: usize,
+    pub brk: usize,
+    /// 软件缓冲区满时丢弃的接收数据
+    pub rx_dropped: usize,
+    pub irq_rx: usize,
+    pub irq_tx: usize,
+    pub irq_rx_timeout: usize,
+    pub irq_line_status: usize,
+    pub irq_modem_status: usize,
+}
+
+impl StatsSnapshot {
+    /// 接收错误总数
+    pub fn rx_errors(&self) -> usize {
+        self.overrun + self.parity + self.framing + self.brk
+    }
+
+    /// 中断总数
+    pub fn interrupts(&self) -> usize {
+        self.irq_rx
+            + self.irq_tx
+            + self.irq_rx_timeout
+            + self.irq_line_status
+            + self.irq_modem_status
+    }
+}
+
+impl Stats {
+    pub const fn new() -> Self {
+        Self {
+            tx_bytes: AtomicUsize::new(0),
+            rx_bytes: AtomicUsize::new(0),
+            overrun: AtomicUsize::new(0),
+            parity: AtomicUsize::new(0),
+            framing: AtomicUsize::new(0),
+            brk: AtomicUsize::new(0),
+            rx_dropped: AtomicUsize::new(0),
+            irq_rx: AtomicUsize::new(0),
+            irq_tx: AtomicUsize::new(0),
+            irq_rx_timeout: AtomicUsize::new(0),
+            irq_line_status: AtomicUsize::new(0),
+            irq_modem_status: AtomicUsize::new(0),
+        }
+    }
+
+    /// 读取当前统计值
+    pub fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            tx_bytes: self.tx_bytes.load(Ordering::Relaxed),
+            rx_bytes: self.rx_bytes.load(Ordering::Relaxed),
+            overrun: self.overrun.load(Ordering::Relaxed),
+            parity: self.parity.load(Ordering::Relaxed),
+            framing: self.framing.load(Ordering::Relaxed),
+            brk: self.brk.load(Ordering::Relaxed),
+            rx_dropped: self.rx_dropped.load(Ordering::Relaxed),
+            irq_rx: self.irq_rx.load(Ordering::Relaxed),
+            irq_tx: self.irq_tx.load(Ordering::Relaxed),
+            irq_rx_timeout: self.irq_rx_timeout.load(Ordering::Relaxed),
+            irq_line_status: self.irq_line_status.load(Ordering::Relaxed),
+            irq_modem_status: self.irq_modem_status.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 读取并清零，返回清零前的统计值
+    pub fn reset(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            tx_bytes: self.tx_bytes.swap(0, Ordering::Relaxed),
+            rx_bytes: self.rx_bytes.swap(0, Ordering::Relaxed),
+            overrun: self.overrun.swap(0, Ordering::Relaxed),
+            parity: self.parity.swap(0, Ordering::Relaxed),
+            framing: self.framing.swap(0, Ordering::Relaxed),
+            brk: self.brk.swap(0, Ordering::Relaxed),
+            rx_dropped: self.rx_dropped.swap(0, Ordering::Relaxed),
+            irq_rx: self.irq_rx.swap(0, Ordering::Relaxed),
+            irq_tx: self.irq_tx.swap(0, Ordering::Relaxed),
+            irq_rx_timeout: self.irq_rx_timeout.swap(0, Ordering::Relaxed),
+            irq_line_status: self.irq_line_status.swap(0, Ordering::Relaxed),
+            irq_modem_status: self.irq_modem_status.swap(0, Ordering::Relaxed),
+        }
+    }
+
+    /// 记录软件缓冲区满时丢弃的接收数据，供上层缓冲实现调用
+    pub fn record_rx_dropped(&self, count: usize) {
+        self.rx_dropped.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_tx(&self, count: usize) {
+        self.tx_bytes.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// 记录一个无错误的接收字节，供没有线路错误状态的硬件使用
+    pub(crate) fn record_rx_byte(&self) {
+        self.rx_bytes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录一次 `read_byte` 的结果
+    pub(crate) fn record_rx(&self, result: &Result<u8, TransferError>) {
+        let counter = match result {
+            Ok(_) => &self.rx_bytes,
+            Err(TransferError::Overrun(_)) => {
+                // 溢出时携带的数据本身有效
+                self.rx_bytes.fetch_add(1, Ordering::Relaxed);
+                &self.overrun
+            }
+            Err(TransferError::Parity) => &self.parity,
+            Err(TransferError::Framing) => &self.framing,
+            Err(TransferError::Break) => &self.brk,
+            Err(TransferError::Closed) => return,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_irq(&self, cause: IrqCause) {
+        let counter = match cause {
+            IrqCause::Rx => &self.irq_rx,
+            IrqCause::Tx => &self.irq_tx,
+            IrqCause::RxTimeout => &self.irq_rx_timeout,
+            IrqCause::LineStatus => &self.irq_line_status,
+            IrqCause::ModemStatus => &self.irq_modem_status,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// 为收发句柄挂接统计计数器
+#[enum_dispatch(Sender, Reciever)]
+pub(crate) trait AttachStats {
+    fn attach_stats(&mut self, stats: &'static Stats);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_stats_are_zero() {
+        assert_eq!(Stats::new().snapshot(), StatsSnapshot::default());
+    }
+
+    #[test]
+    fn snapshot_reports_each_counter() {
+        let stats = Stats::new();
+        stats.record_tx(5);
+        stats.record_tx(3);
+        stats.record_rx_byte();
+        stats.record_rx_dropped(4);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(
+            snapshot,
+            StatsSnapshot {
+                tx_bytes: 8,
+                rx_bytes: 1,
+                rx_dropped: 4,
+                ..Default::default()
+            }
+        );
+        // 快照不影响计数
+        assert_eq!(stats.snapshot(), snapshot);
+    }
+
+    #[test]
+    fn reset_returns_counts_then_zeroes() {
+        let stats = Stats::new();
+        stats.record_tx(2);
+        stats.record_rx(&Err(TransferError::Parity));
+        stats.record_irq(IrqCause::Rx);
+
+        let before = stats.reset();
+        assert_eq!(before.tx_bytes, 2);
+        assert_eq!(before.parity, 1);
+        assert_eq!(before.irq_rx, 1);
+        assert_eq!(stats.snapshot(), StatsSnapshot::default());
+        assert_eq!(stats.reset(), StatsSnapshot::default());
+    }
+
+    #[test]
+    fn rx_errors_are_classified() {
+        let stats = Stats::new();
+        stats.record_rx(&Ok(b'a'));
+        stats.record_rx(&Err(TransferError::Overrun(b'b')));
+        stats.record_rx(&Err(TransferError::Parity));
+        stats.record_rx(&Err(TransferError::Framing));
+        stats.record_rx(&Err(TransferError::Framing));
+        stats.record_rx(&Err(TransferError::Break));
+        // 关闭不是线路错误，不计数
+        stats.record_rx(&Err(TransferError::Closed));
+
+        let snapshot = stats.snapshot();
+        // 溢出携带的数据计入接收字节
+        assert_eq!(snapshot.rx_bytes, 2);
+        assert_eq!(snapshot.overrun, 1);
+        assert_eq!(snapshot.parity, 1);
+        assert_eq!(snapshot.framing, 2);
+        assert_eq!(snapshot.brk, 1);
+        assert_eq!(snapshot.rx_errors(), 5);
+    }
+
+    #[test]
+    fn irqs_are_counted_by_cause() {
+        let stats = Stats::new();
+        for (cause, times) in [
+            (IrqCause::Rx, 1),
+            (IrqCause::Tx, 2),
+            (IrqCause::RxTimeout, 3),
+            (IrqCause::LineStatus, 4),
+            (IrqCause::ModemStatus, 5),
+        ] {
+            for _ in 0..times {
+                stats.record_irq(cause);
+            }
+        }
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.irq_rx, 1);
+        assert_eq!(snapshot.irq_tx, 2);
+        assert_eq!(snapshot.irq_rx_timeout, 3);
+        assert_eq!(snapshot.irq_line_status, 4);
+        assert_eq!(snapshot.irq_modem_status, 5);
+        assert_eq!(snapshot.interrupts(), 15);
+        assert_eq!(snapshot.rx_errors(), 0);
+    }
+}
@@ -0,0 +1,365 @@
+//! 类型状态封装
+//!
+//! `InterfaceRaw` 不限制调用顺序：`open` 之前就能取出发送端写数据（PL011 会写进禁用的
+//! UART，16550 的 DTR/RTS 尚未置位），收发端在外时也能切换回环。[`Uart`] 把驱动状态
+//! 编码进类型参数，只在 [`Open`]/[`Loopback`] 状态下交出收发端和中断句柄：
+//!
+//! ```text
+//! Closed --configure--> Configured --open--> Open <--enter/exit_loopback--> Loopback
+//!                           ^    \--open_loopback--------------------------->  |
+//!                           \-----------------------close----------------------/
+//! ```
+//!
+//! 关闭和切换回环前收发端必须已经通过 `set_tx`/`set_rx` 归还，否则返回
+//! [`StateError::HalvesTaken`] 并原样交回封装。中断句柄取出后不能归还，不受此限制。
+//! 封装建立在驱动的 `InterfaceRaw` 实现之上，适用于 `Pl011`、`Ns16550<T>` 等所有驱动。
+
+use core::marker::PhantomData;
+
+use rdif_serial::{Config, ConfigError, InterfaceRaw, InterruptMask, SetBackError};
+
+use crate::{reconfig::halves_home, ConfigWhen, ReconfigError, Reconfigure};
+
+/// 已关闭，尚未配置
+pub struct Closed;
+/// 已配置，尚未打开
+pub struct Configured;
+/// 已打开
+pub struct Open;
+/// 已打开并处于回环模式
+pub struct Loopback;
+
+mod sealed {
+    pub trait Active {}
+}
+
+impl sealed::Active for Open {}
+impl sealed::Active for Loopback {}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateError {
+    #[error("sender or reciever has not been returned")]
+    HalvesTaken,
+    #[error("invalid configuration: {0:?}")]
+    Config(ConfigError),
+}
+
+/// 状态转换失败时交回原封装
+pub type Transition<D, From, To> = Result<Uart<D, To>, (Uart<D, From>, StateError)>;
+
+/// 带类型状态的驱动封装
+pub struct Uart<D: InterfaceRaw, S> {
+    dev: D,
+    _state: PhantomData<S>,
+}
+
+impl<D: InterfaceRaw, S> Uart<D, S> {
+    fn into_state<T>(self) -> Uart<D, T> {
+        Uart {
+            dev: self.dev,
+            _state: PhantomData,
+        }
+    }
+
+    /// 只读访问驱动，用于查询波特率等
+    pub fn inner(&self) -> &D {
+        &self.dev
+    }
+
+    /// 取回驱动，放弃状态检查
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    /// 收发端都已归还时执行 `f` 并转换到状态 `T`
+    fn transition<T>(mut self, f: impl FnOnce(&mut D)) -> Transition<D, S, T> {
+        if !halves_home(&mut self.dev) {
+            return Err((self, StateError::HalvesTaken));
+        }
+        f(&mut self.dev);
+        Ok(self.into_state())
+    }
+}
+
+impl<D: InterfaceRaw> Uart<D, Closed> {
+    /// 关闭驱动并封装
+    pub fn new(mut dev: D) -> Self {
+        dev.close();
+        Self {
+            dev,
+            _state: PhantomData,
+        }
+    }
+
+    pub fn configure(mut self, config: &Config) -> Transition<D, Closed, Configured> {
+        match self.dev.set_config(config) {
+            Ok(()) => Ok(self.into_state()),
+            Err(e) => Err((self, StateError::Config(e))),
+        }
+    }
+}
+
+impl<D: InterfaceRaw> Uart<D, Configured> {
+    pub fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        self.dev.set_config(config)
+    }
+
+    pub fn open(mut self) -> Uart<D, Open> {
+        self.dev.disable_loopback();
+        self.dev.open();
+        self.into_state()
+    }
+
+    /// 以回环模式打开，回环在打开前设置，线路上不会出现数据
+    pub fn open_loopback(mut self) -> Uart<D, Loopback> {
+        self.dev.enable_loopback();
+        self.dev.open();
+        self.into_state()
+    }
+}
+
+impl<D: InterfaceRaw> Uart<D, Open> {
+    pub fn enter_loopback(self) -> Transition<D, Open, Loopback> {
+        self.transition(|dev| dev.enable_loopback())
+    }
+
+    pub fn close(self) -> Transition<D, Open, Configured> {
+        self.transition(|dev| dev.close())
+    }
+}
+
+impl<D: InterfaceRaw> Uart<D, Loopback> {
+    pub fn exit_loopback(self) -> Transition<D, Loopback, Open> {
+        self.transition(|dev| dev.disable_loopback())
+    }
+
+    /// 关闭并退出回环
+    pub fn close(self) -> Transition<D, Loopback, Configured> {
+        self.transition(|dev| {
+            dev.close();
+            dev.disable_loopback();
+        })
+    }
+}
+
+impl<D: InterfaceRaw, S: sealed::Active> Uart<D, S> {
+    pub fn take_tx(&mut self) -> Option<D::Sender> {
+        self.dev.take_tx()
+    }
+
+    pub fn take_rx(&mut self) -> Option<D::Reciever> {
+        self.dev.take_rx()
+    }
+
+    pub fn set_tx(&mut self, tx: D::Sender) -> Result<(), SetBackError> {
+        self.dev.set_tx(tx)
+    }
+
+    pub fn set_rx(&mut self, rx: D::Reciever) -> Result<(), SetBackError> {
+        self.dev.set_rx(rx)
+    }
+
+    pub fn irq_handler(&mut self) -> Option<D::IrqHandler> {
+        self.dev.irq_handler()
+    }
+
+    pub fn set_irq_mask(&mut self, mask: InterruptMask) {
+        self.dev.set_irq_mask(mask)
+    }
+
+    pub fn get_irq_mask(&self) -> InterruptMask {
+        self.dev.get_irq_mask()
+    }
+}
+
+impl<D: Reconfigure, S: sealed::Active> Uart<D, S> {
+    /// 打开状态下按指定时机重新配置，已取出的发送端在切换期间暂停写入
+    pub fn set_config_when(
+        &mut self,
+        config: &Config,
+        when: ConfigWhen,
+    ) -> Result<(), ReconfigError> {
+        self.dev.set_config_when(config, when)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZeroU32;
+
+    use rdif_serial::{DataBits, Parity, StopBits, TIrqHandler, TReciever, TSender, TransferError};
+
+    use super::*;
+
+    struct Half;
+
+    impl TSender for Half {
+        fn write_byte(&mut self, _byte: u8) -> bool {
+            true
+        }
+    }
+
+    impl TReciever for Half {
+        fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+            None
+        }
+    }
+
+    impl TIrqHandler for Half {
+        fn clean_interrupt_status(&self) -> InterruptMask {
+            InterruptMask::empty()
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeDev {
+        open: bool,
+        loopback: bool,
+        baudrate: u32,
+        tx: Option<Half>,
+        rx: Option<Half>,
+    }
+
+    impl InterfaceRaw for FakeDev {
+        type IrqHandler = Half;
+        type Sender = Half;
+        type Reciever = Half;
+
+        fn base_addr(&self) -> usize {
+            0
+        }
+
+        fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+            match config.baudrate {
+                Some(0) => Err(ConfigError::InvalidBaudrate),
+                Some(baudrate) => {
+                    self.baudrate = baudrate;
+                    Ok(())
+                }
+                None => Ok(()),
+            }
+        }
+
+        fn baudrate(&self) -> u32 {
+            self.baudrate
+        }
+
+        fn data_bits(&self) -> DataBits {
+            DataBits::Eight
+        }
+
+        fn stop_bits(&self) -> StopBits {
+            StopBits::One
+        }
+
+        fn parity(&self) -> Parity {
+            Parity::None
+        }
+
+        fn clock_freq(&self) -> Option<NonZeroU32> {
+            None
+        }
+
+        fn open(&mut self) {
+            self.open = true;
+        }
+
+        fn close(&mut self) {
+            self.open = false;
+        }
+
+        fn enable_loopback(&mut self) {
+            self.loopback = true;
+        }
+
+        fn disable_loopback(&mut self) {
+            self.loopback = false;
+        }
+
+        fn is_loopback_enabled(&self) -> bool {
+            self.loopback
+        }
+
+        fn set_irq_mask(&mut self, _mask: InterruptMask) {}
+
+        fn get_irq_mask(&self) -> InterruptMask {
+            InterruptMask::empty()
+        }
+
+        fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+            Some(Half)
+        }
+
+        fn take_tx(&mut self) -> Option<Self::Sender> {
+            self.tx.take()
+        }
+
+        fn take_rx(&mut self) -> Option<Self::Reciever> {
+            self.rx.take()
+        }
+
+        fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+            self.tx = Some(tx);
+            Ok(())
+        }
+
+        fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+            self.rx = Some(rx);
+            Ok(())
+        }
+    }
+
+    fn dev() -> FakeDev {
+        FakeDev {
+            open: true,
+            tx: Some(Half),
+            rx: Some(Half),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn transitions_follow_device_state() {
+        let uart = Uart::new(dev());
+        assert!(!uart.inner().open);
+
+        let (uart, err) = uart.configure(&Config::new().baudrate(0)).err().unwrap();
+        assert_eq!(err, StateError::Config(ConfigError::InvalidBaudrate));
+
+        let uart = uart
+            .configure(&Config::new().baudrate(115200))
+            .ok()
+            .unwrap()
+            .open();
+        assert!(uart.inner().open);
+        assert_eq!(uart.inner().baudrate(), 115200);
+
+        let uart = uart.enter_loopback().ok().unwrap();
+        assert!(uart.inner().loopback);
+        let uart = uart.close().ok().unwrap();
+        assert!(!uart.inner().open && !uart.inner().loopback);
+
+        let uart = uart.open_loopback();
+        assert!(uart.inner().open && uart.inner().loopback);
+    }
+
+    #[test]
+    fn taken_halves_block_transitions() {
+        let mut uart = Uart::new(dev())
+            .configure(&Config::new())
+            .ok()
+            .unwrap()
+            .open();
+        let tx = uart.take_tx().unwrap();
+
+        let (uart, err) = uart.enter_loopback().err().unwrap();
+        assert_eq!(err, StateError::HalvesTaken);
+        let (mut uart, _) = uart.close().err().unwrap();
+        assert!(uart.inner().open && !uart.inner().loopback);
+        // 检查时临时取出的接收端已放回
+        assert!(uart.inner().rx.is_some());
+
+        uart.set_tx(tx).ok().unwrap();
+        assert!(uart.close().is_ok());
+    }
+}
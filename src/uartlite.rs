@@ -0,0 +1,662 @@
+//! Xilinx AXI UART Lite 驱动 (`xlnx,xps-uartlite-1.00.a`)
+//!
+//! UART Lite 的波特率、数据位和校验在 FPGA 综合时确定，运行时不可修改，
+//! 因此构造时需要传入与硬件一致的固定参数（通常来自设备树的 `current-speed`、
+//! `xlnx,data-bits`、`xlnx,use-parity` 和 `xlnx,odd-parity`）。
+//! 收发各有 16 字节 FIFO，中断只有一个总使能位。
+
+use alloc::sync::Arc;
+use core::{
+    num::NonZeroU32,
+    ptr::NonNull,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use rdif_serial::{BSerial, InterfaceRaw, SerialDyn, SetBackError, TIrqHandler, TSender};
+use tock_registers::{
+    interfaces::*, register_bitfields, register_structs, registers::*, LocalRegisterCopy,
+};
+
+use crate::{
+    claim::{self, Claim, ClaimError, Region},
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, IrqCause, Stats},
+    Capabilities, Caps, Config, ConfigError, DataBits, Features, FifoThreshold, InterruptMask,
+    Parity, RawReciever, RawSender, StopBits, Suspend, TransferError,
+};
+
+register_bitfields! [
+    u32,
+
+    /// Receive / Transmit FIFO
+    FIFO [
+        DATA OFFSET(0) NUMBITS(8) []
+    ],
+
+    /// Status Register
+    STAT [
+        RX_VALID OFFSET(0) NUMBITS(1) [],
+        RX_FULL OFFSET(1) NUMBITS(1) [],
+        TX_EMPTY OFFSET(2) NUMBITS(1) [],
+        TX_FULL OFFSET(3) NUMBITS(1) [],
+        INTR_ENABLED OFFSET(4) NUMBITS(1) [],
+        OVERRUN_ERROR OFFSET(5) NUMBITS(1) [],
+        FRAME_ERROR OFFSET(6) NUMBITS(1) [],
+        PARITY_ERROR OFFSET(7) NUMBITS(1) []
+    ],
+
+    /// Control Register
+    CTRL [
+        RST_TX OFFSET(0) NUMBITS(1) [],
+        RST_RX OFFSET(1) NUMBITS(1) [],
+        ENABLE_INTR OFFSET(4) NUMBITS(1) []
+    ]
+];
+
+register_structs! {
+    pub UartLiteRegisters {
+        (0x00 => rx_fifo: ReadOnly<u32, FIFO::Register>),     // 接收 FIFO
+        (0x04 => tx_fifo: WriteOnly<u32, FIFO::Register>),    // 发送 FIFO
+        (0x08 => stat: ReadOnly<u32, STAT::Register>),        // 状态寄存器（读取清除错误位）
+        (0x0c => ctrl: WriteOnly<u32, CTRL::Register>),       // 控制寄存器
+        (0x10 => @END),
+    }
+}
+
+// SAFETY: 寄存器访问均为单次 32 位读写
+unsafe impl Sync for UartLiteRegisters {}
+
+/// 收发 FIFO 深度
+const FIFO_DEPTH: u16 = 16;
+
+/// STAT 中读取即清除的接收错误位
+const STAT_ERRORS: u32 =
+    STAT::OVERRUN_ERROR::SET.value | STAT::FRAME_ERROR::SET.value | STAT::PARITY_ERROR::SET.value;
+
+/// 等待发送 FIFO 清空的最大轮询次数
+const BUSY_SPIN_LIMIT: usize = 1_000_000;
+
+/// UART Lite 驱动结构体
+pub struct UartLite {
+    base: Reg,
+    baudrate: u32,
+    data_bits: DataBits,
+    parity: Parity,
+    /// CTRL 只写，中断使能状态由软件记录
+    irq_enabled: bool,
+    /// 中断处理读 STAT 时清除、尚未由接收端上报的错误位，与收发句柄共享
+    pending_errors: Arc<AtomicU32>,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    tx_pause: TxPause,
+    tx: Option<UartLiteSender>,
+    rx: Option<UartLiteReciever>,
+    irq: Option<UartLiteIrqHandler>,
+    /// 通过 `try_new` 系列创建时持有的区域占用，与各句柄共享
+    claim: Option<Arc<Claim>>,
+}
+
+impl UartLite {
+    /// 创建 UART Lite 实例，`baudrate`、`data_bits`、`parity` 必须与综合参数一致
+    ///
+    /// 不占用寄存器区域，需要防止同一设备被重复创建时使用 [`Self::try_new`]
+    pub fn new(base: NonNull<u8>, baudrate: u32, data_bits: DataBits, parity: Parity) -> Self {
+        Self::with_claim(base, baudrate, data_bits, parity, None)
+    }
+
+    pub fn new_boxed(
+        base: NonNull<u8>,
+        baudrate: u32,
+        data_bits: DataBits,
+        parity: Parity,
+    ) -> BSerial {
+        SerialDyn::new_boxed(Self::new(base, baudrate, data_bits, parity))
+    }
+
+    /// 占用寄存器区域后创建实例，区域与已有实例重叠时返回错误
+    pub fn try_new(
+        base: NonNull<u8>,
+        baudrate: u32,
+        data_bits: DataBits,
+        parity: Parity,
+    ) -> Result<Self, ClaimError> {
+        let claim = claim::claim(Region::mmio(base, size_of::<UartLiteRegisters>()))?;
+        Ok(Self::with_claim(
+            base,
+            baudrate,
+            data_bits,
+            parity,
+            Some(claim),
+        ))
+    }
+
+    pub fn try_new_boxed(
+        base: NonNull<u8>,
+        baudrate: u32,
+        data_bits: DataBits,
+        parity: Parity,
+    ) -> Result<BSerial, ClaimError> {
+        Ok(SerialDyn::new_boxed(Self::try_new(
+            base, baudrate, data_bits, parity,
+        )?))
+    }
+
+    /// 通过 `try_new` 系列创建时持有的寄存器区域占用
+    pub fn claim(&self) -> Option<&Claim> {
+        self.claim.as_deref()
+    }
+
+    fn with_claim(
+        base: NonNull<u8>,
+        baudrate: u32,
+        data_bits: DataBits,
+        parity: Parity,
+        claim: Option<Arc<Claim>>,
+    ) -> Self {
+        let base = Reg(base.cast());
+        let pending_errors = Arc::new(AtomicU32::new(0));
+        let tx_pause = TxPause::default();
+
+        Self {
+            base,
+            baudrate,
+            data_bits,
+            parity,
+            irq_enabled: false,
+            pending_errors: pending_errors.clone(),
+            tx_pause: tx_pause.clone(),
+            tx: Some(UartLiteSender {
+                base,
+                stats: None,
+                tx_pause: tx_pause.clone(),
+                _claim: claim.clone(),
+            }),
+            rx: Some(UartLiteReciever {
+                base,
+                pending_errors: pending_errors.clone(),
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            irq: Some(UartLiteIrqHandler {
+                base,
+                pending_errors,
+                stats: None,
+                _claim: claim.clone(),
+            }),
+            claim,
+        }
+    }
+
+    fn registers(&self) -> &UartLiteRegisters {
+        self.base.registers()
+    }
+
+    /// 挂接统计计数器，只对尚未取出的收发和中断句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+        if let Some(irq) = self.irq.as_mut() {
+            irq.stats = Some(stats);
+        }
+    }
+
+    /// CTRL 只写，每次写入都带上中断使能位，`reset` 指定需要复位的 FIFO
+    fn write_ctrl(&self, reset: Option<FlushQueue>) {
+        let mut ctrl = CTRL::ENABLE_INTR.val(self.irq_enabled as u32);
+        match reset {
+            Some(FlushQueue::Rx) => ctrl += CTRL::RST_RX::SET,
+            Some(FlushQueue::Tx) => ctrl += CTRL::RST_TX::SET,
+            Some(FlushQueue::Both) => ctrl += CTRL::RST_TX::SET + CTRL::RST_RX::SET,
+            None => {}
+        }
+        self.registers().ctrl.write(ctrl);
+    }
+}
+
+impl InterfaceRaw for UartLite {
+    type IrqHandler = UartLiteIrqHandler;
+
+    type Sender = crate::Sender;
+
+    type Reciever = crate::Reciever;
+
+    fn base_addr(&self) -> usize {
+        self.base.0.as_ptr() as usize
+    }
+
+    /// 只接受与综合参数一致的配置，不修改任何寄存器
+    fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        self.capabilities().validate(config)
+    }
+
+    fn baudrate(&self) -> u32 {
+        self.baudrate
+    }
+
+    fn data_bits(&self) -> DataBits {
+        self.data_bits
+    }
+
+    fn stop_bits(&self) -> StopBits {
+        StopBits::One
+    }
+
+    fn parity(&self) -> Parity {
+        self.parity
+    }
+
+    fn clock_freq(&self) -> Option<NonZeroU32> {
+        None
+    }
+
+    fn open(&mut self) {
+        self.irq_enabled = false;
+        self.write_ctrl(Some(FlushQueue::Both));
+    }
+
+    fn close(&mut self) {
+        self.irq_enabled = false;
+        self.write_ctrl(None);
+    }
+
+    // UART Lite 没有回环模式
+    fn enable_loopback(&mut self) {}
+
+    fn disable_loopback(&mut self) {}
+
+    fn is_loopback_enabled(&self) -> bool {
+        false
+    }
+
+    /// 硬件只有一个中断总使能位，任一中断被请求时即打开
+    fn set_irq_mask(&mut self, mask: InterruptMask) {
+        self.irq_enabled = !mask.is_empty();
+        self.write_ctrl(None);
+    }
+
+    fn get_irq_mask(&self) -> InterruptMask {
+        if self.irq_enabled {
+            InterruptMask::RX_AVAILABLE | InterruptMask::TX_EMPTY
+        } else {
+            InterruptMask::empty()
+        }
+    }
+
+    fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+        self.irq.take()
+    }
+
+    fn take_tx(&mut self) -> Option<Self::Sender> {
+        self.tx.take().map(crate::Sender::UartLiteSender)
+    }
+
+    fn take_rx(&mut self) -> Option<Self::Reciever> {
+        self.rx.take().map(crate::Reciever::UartLiteReciever)
+    }
+
+    fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+        let tx = match tx {
+            crate::Sender::UartLiteSender(s) => s,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    tx.external_base().unwrap_or(0), // 不匹配的发送器类型
+                ));
+            }
+        };
+
+        if self.base != tx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                tx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.tx = Some(tx);
+        Ok(())
+    }
+
+    fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+        let rx = match rx {
+            crate::Reciever::UartLiteReciever(r) => r,
+            _ => {
+                return Err(SetBackError::new(
+                    self.base.0.as_ptr() as _,
+                    rx.external_base().unwrap_or(0), // 不匹配的接收器类型
+                ));
+            }
+        };
+
+        if self.base != rx.base {
+            return Err(SetBackError::new(
+                self.base.0.as_ptr() as _,
+                rx.base.0.as_ptr() as _,
+            ));
+        }
+
+        self.rx = Some(rx);
+        Ok(())
+    }
+}
+
+impl Caps for UartLite {
+    fn capabilities(&self) -> Capabilities {
+        let data_bits: &'static [DataBits] = match self.data_bits {
+            DataBits::Five => &[DataBits::Five],
+            DataBits::Six => &[DataBits::Six],
+            DataBits::Seven => &[DataBits::Seven],
+            DataBits::Eight => &[DataBits::Eight],
+        };
+        let parity: &'static [Parity] = match self.parity {
+            Parity::None => &[Parity::None],
+            Parity::Even => &[Parity::Even],
+            Parity::Odd => &[Parity::Odd],
+            Parity::Mark => &[Parity::Mark],
+            Parity::Space => &[Parity::Space],
+        };
+
+        Capabilities {
+            fifo_depth: FIFO_DEPTH,
+            min_baudrate: self.baudrate,
+            max_baudrate: self.baudrate,
+            data_bits,
+            stop_bits: &[StopBits::One],
+            parity,
+            features: Features::empty(),
+        }
+    }
+}
+
+impl FifoThreshold for UartLite {}
+
+/// 硬件没有移位寄存器状态，排空只能等到发送 FIFO 为空，最后一个字符可能仍在发送
+impl Flush for UartLite {
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError> {
+        self.write_ctrl(Some(queue));
+        if queue.rx() {
+            // 错误标志在读取 STAT 时清除，中断处理暂存的错误一并丢弃
+            self.registers().stat.get();
+            self.pending_errors.store(0, Ordering::Release);
+        }
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), FlushError> {
+        for _ in 0..BUSY_SPIN_LIMIT {
+            if self.registers().stat.is_set(STAT::TX_EMPTY) {
+                return Ok(());
+            }
+            core::hint::spin_loop();
+        }
+        Err(FlushError::Timeout)
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// UART Lite 挂起时保存的上下文，只有中断使能状态
+#[derive(Debug, Clone, Copy)]
+pub struct UartLiteContext {
+    irq_enabled: bool,
+}
+
+impl Suspend for UartLite {
+    type Context = UartLiteContext;
+
+    fn save_context(&self) -> Self::Context {
+        UartLiteContext {
+            irq_enabled: self.irq_enabled,
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        self.irq_enabled = ctx.irq_enabled;
+        self.write_ctrl(Some(FlushQueue::Both));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Reg(NonNull<UartLiteRegisters>);
+
+unsafe impl Send for Reg {}
+
+impl Reg {
+    fn registers(&self) -> &UartLiteRegisters {
+        unsafe { self.0.as_ref() }
+    }
+}
+
+pub struct UartLiteSender {
+    base: Reg,
+    stats: Option<&'static Stats>,
+    tx_pause: TxPause,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for UartLiteSender {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl TSender for UartLiteSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        RawSender::write_byte(self, byte)
+    }
+}
+
+impl RawSender for UartLiteSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return false;
+        };
+        if self.base.registers().stat.is_set(STAT::TX_FULL) {
+            return false;
+        }
+
+        self.base
+            .registers()
+            .tx_fifo
+            .write(FIFO::DATA.val(byte as _));
+        if let Some(stats) = self.stats {
+            stats.record_tx(1);
+        }
+
+        true
+    }
+}
+
+pub struct UartLiteReciever {
+    base: Reg,
+    /// 中断处理读取 STAT 时清除的错误位，留给下一次读取上报
+    pending_errors: Arc<AtomicU32>,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+impl AttachStats for UartLiteReciever {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl UartLiteReciever {
+    fn read_raw(&mut self) -> Option<Result<u8, TransferError>> {
+        // 错误位在读取 STAT 时清除，必须与 RX_VALID 一起读出
+        let stat = self.base.registers().stat.extract();
+        if !stat.is_set(STAT::RX_VALID) {
+            return None;
+        }
+        let stat = LocalRegisterCopy::<u32, STAT::Register>::new(
+            stat.get() | self.pending_errors.swap(0, Ordering::AcqRel),
+        );
+
+        let data = self.base.registers().rx_fifo.read(FIFO::DATA) as u8;
+
+        let result = if stat.is_set(STAT::PARITY_ERROR) {
+            Err(TransferError::Parity)
+        } else if stat.is_set(STAT::FRAME_ERROR) {
+            Err(TransferError::Framing)
+        } else if stat.is_set(STAT::OVERRUN_ERROR) {
+            Err(TransferError::Overrun(data))
+        } else {
+            Ok(data)
+        };
+
+        Some(result)
+    }
+}
+
+impl RawReciever for UartLiteReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        let result = self.read_raw()?;
+        if let Some(stats) = self.stats {
+            stats.record_rx(&result);
+        }
+        Some(result)
+    }
+}
+
+pub struct UartLiteIrqHandler {
+    base: Reg,
+    pending_errors: Arc<AtomicU32>,
+    stats: Option<&'static Stats>,
+    _claim: Option<Arc<Claim>>,
+}
+
+unsafe impl Sync for UartLiteIrqHandler {}
+
+impl TIrqHandler for UartLiteIrqHandler {
+    /// 硬件没有中断状态寄存器，根据 FIFO 状态推断中断原因。
+    ///
+    /// 读取 STAT 会清除错误位，清除的错误暂存起来，由接收端在下一次 `read_byte` 上报。
+    fn clean_interrupt_status(&self) -> InterruptMask {
+        let stat = self.base.registers().stat.extract();
+        let errors = stat.get() & STAT_ERRORS;
+        if errors != 0 {
+            self.pending_errors.fetch_or(errors, Ordering::AcqRel);
+        }
+        let mut mask = InterruptMask::empty();
+
+        if stat.is_set(STAT::RX_VALID) {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if stat.is_set(STAT::TX_EMPTY) {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        if let Some(stats) = self.stats {
+            let causes = [
+                (STAT::RX_VALID, IrqCause::Rx),
+                (STAT::TX_EMPTY, IrqCause::Tx),
+                (STAT::OVERRUN_ERROR, IrqCause::LineStatus),
+                (STAT::FRAME_ERROR, IrqCause::LineStatus),
+                (STAT::PARITY_ERROR, IrqCause::LineStatus),
+            ];
+            for (field, cause) in causes {
+                if stat.is_set(field) {
+                    stats.record_irq(cause);
+                }
+            }
+        }
+
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake_mmio::FakeMmio;
+
+    fn uart(regs: &FakeMmio<4>) -> UartLite {
+        UartLite::new(regs.base(), 115200, DataBits::Eight, Parity::None)
+    }
+
+    #[test]
+    fn config_must_match_synthesis() {
+        let regs = FakeMmio::<4>::new();
+        let mut uart = uart(&regs);
+
+        uart.set_config(&Config::new().baudrate(115200)).unwrap();
+        assert_eq!(
+            uart.set_config(&Config::new().baudrate(9600)),
+            Err(ConfigError::InvalidBaudrate)
+        );
+        assert_eq!(
+            uart.set_config(&Config::new().parity(Parity::Odd)),
+            Err(ConfigError::UnsupportedParity)
+        );
+    }
+
+    #[test]
+    fn irq_keeps_errors_for_next_read() {
+        let regs = FakeMmio::<4>::new();
+        let mut uart = uart(&regs);
+        let irq = uart.irq_handler().unwrap();
+        let mut rx = uart.rx.take().unwrap();
+
+        uart.set_irq_mask(InterruptMask::RX_AVAILABLE);
+        regs.set(
+            0x08,
+            STAT::RX_VALID::SET.value | STAT::PARITY_ERROR::SET.value,
+        );
+        assert_eq!(
+            irq.clean_interrupt_status().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+
+        // 硬件在中断读取 STAT 时已清除错误位
+        regs.set(0x08, STAT::RX_VALID::SET.value);
+        regs.set(0x00, 0x55);
+        assert_eq!(
+            RawReciever::read_byte(&mut rx),
+            Some(Err(TransferError::Parity))
+        );
+        assert_eq!(RawReciever::read_byte(&mut rx), Some(Ok(0x55)));
+    }
+
+    #[test]
+    fn rx_flush_drops_pending_errors() {
+        let regs = FakeMmio::<4>::new();
+        let mut uart = uart(&regs);
+        let irq = uart.irq_handler().unwrap();
+
+        regs.set(0x08, STAT::OVERRUN_ERROR::SET.value);
+        irq.clean_interrupt_status();
+        uart.flush(FlushQueue::Rx).unwrap();
+        assert_eq!(regs.get(0x0c), CTRL::RST_RX::SET.value);
+
+        let mut rx = uart.rx.take().unwrap();
+        regs.set(0x08, STAT::RX_VALID::SET.value);
+        regs.set(0x00, 0x12);
+        assert_eq!(RawReciever::read_byte(&mut rx), Some(Ok(0x12)));
+    }
+
+    #[test]
+    fn write_respects_tx_full_and_ctrl_keeps_irq_enable() {
+        let regs = FakeMmio::<4>::new();
+        let mut uart = uart(&regs);
+        let mut tx = uart.tx.take().unwrap();
+
+        regs.set(0x08, STAT::TX_FULL::SET.value);
+        assert!(!RawSender::write_byte(&mut tx, b'a'));
+        regs.set(0x08, 0);
+        assert!(RawSender::write_byte(&mut tx, b'b'));
+        assert_eq!(regs.get(0x04), b'b' as u32);
+
+        uart.set_irq_mask(InterruptMask::RX_AVAILABLE);
+        assert_eq!(regs.get(0x0c), CTRL::ENABLE_INTR::SET.value);
+        uart.flush(FlushQueue::Tx).unwrap();
+        assert_eq!(
+            regs.get(0x0c),
+            CTRL::ENABLE_INTR::SET.value | CTRL::RST_TX::SET.value
+        );
+    }
+}
@@ -0,0 +1,1516 @@
+//! virtio-console 驱动（virtio-mmio 传输，`virtio,mmio` + DeviceID 3）
+//!
+//! 用于 QEMU、Firecracker 等虚拟机中的控制台。只使用端口 0 的 receiveq(0)/transmitq(1)，
+//! 协商 `VIRTIO_CONSOLE_F_SIZE` 和 `VIRTIO_CONSOLE_F_EMERG_WRITE`，同时支持 legacy（版本 1）
+//! 和 modern（版本 2）两种 virtio-mmio 寄存器布局。
+//!
+//! 队列内存和收发缓冲区通过 `dma-api` 分配，调用方需要先用 `dma_api::init` 注册平台的
+//! 地址映射实现。寄存器访问经过 [`VirtioTransport`]，主机测试中可以替换为模拟设备。
+//!
+//! 虚拟链路没有波特率和帧格式：`set_config` 只记录波特率，帧格式固定为 8N1。
+
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+    cell::UnsafeCell,
+    ptr::NonNull,
+    sync::atomic::{fence, AtomicBool, AtomicU16, Ordering},
+};
+
+use bitflags::bitflags;
+use dma_api::{DVec, Direction};
+use rdif_serial::{
+    BSerial, InterfaceRaw, SerialDyn, SetBackError, TIrqHandler, TReciever, TSender,
+};
+
+use crate::{
+    claim::{Claim, ClaimError, Region},
+    flush::{Flush, FlushError, FlushQueue},
+    reconfig::TxPause,
+    stats::{AttachStats, IrqCause, Stats},
+    Capabilities, Caps, Config, ConfigError, DataBits, Features, FifoThreshold, InterruptMask,
+    Parity, RawReciever, RawSender, StopBits, Suspend, TransferError,
+};
+
+/// virtio-mmio 寄存器偏移
+mod offset {
+    pub const MAGIC: usize = 0x000;
+    pub const VERSION: usize = 0x004;
+    pub const DEVICE_ID: usize = 0x008;
+    pub const DEVICE_FEATURES: usize = 0x010;
+    pub const DEVICE_FEATURES_SEL: usize = 0x014;
+    pub const DRIVER_FEATURES: usize = 0x020;
+    pub const DRIVER_FEATURES_SEL: usize = 0x024;
+    pub const GUEST_PAGE_SIZE: usize = 0x028;
+    pub const QUEUE_SEL: usize = 0x030;
+    pub const QUEUE_NUM_MAX: usize = 0x034;
+    pub const QUEUE_NUM: usize = 0x038;
+    pub const QUEUE_ALIGN: usize = 0x03c;
+    pub const QUEUE_PFN: usize = 0x040;
+    pub const QUEUE_READY: usize = 0x044;
+    pub const QUEUE_NOTIFY: usize = 0x050;
+    pub const INTERRUPT_STATUS: usize = 0x060;
+    pub const INTERRUPT_ACK: usize = 0x064;
+    pub const STATUS: usize = 0x070;
+    pub const QUEUE_DESC_LOW: usize = 0x080;
+    pub const QUEUE_DESC_HIGH: usize = 0x084;
+    pub const QUEUE_DRIVER_LOW: usize = 0x090;
+    pub const QUEUE_DRIVER_HIGH: usize = 0x094;
+    pub const QUEUE_DEVICE_LOW: usize = 0x0a0;
+    pub const QUEUE_DEVICE_HIGH: usize = 0x0a4;
+    pub const CONFIG_GENERATION: usize = 0x0fc;
+    /// 控制台配置空间：cols(u16) rows(u16) max_nr_ports(u32) emerg_wr(u32)
+    pub const CONFIG: usize = 0x100;
+    pub const CONFIG_EMERG_WR: usize = 0x108;
+}
+
+/// "virt" 的小端表示
+const MAGIC_VALUE: u32 = 0x7472_6976;
+
+/// virtio 控制台设备 ID
+const DEVICE_ID_CONSOLE: u32 = 3;
+
+/// legacy 队列布局使用的页大小
+const PAGE_SIZE: usize = 4096;
+
+/// 每个队列使用的最大描述符数
+const MAX_QUEUE_SIZE: u16 = 16;
+
+/// 每个描述符对应的缓冲区大小
+const BUF_SIZE: usize = 64;
+
+/// 等待设备复位的最大轮询次数
+const BUSY_SPIN_LIMIT: usize = 1_000_000;
+
+/// 接收队列和发送队列的编号
+const RX_QUEUE: u16 = 0;
+const TX_QUEUE: u16 = 1;
+
+/// 描述符标志：设备可写
+const DESC_F_WRITE: u16 = 2;
+
+/// avail.flags：不需要设备发送中断
+const AVAIL_F_NO_INTERRUPT: u16 = 1;
+
+/// InterruptStatus：已用缓冲区通知
+const INT_USED_BUFFER: u32 = 1;
+
+bitflags! {
+    /// virtio 设备状态
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct DeviceStatus: u32 {
+        const ACKNOWLEDGE = 1;
+        const DRIVER = 2;
+        const DRIVER_OK = 4;
+        const FEATURES_OK = 8;
+        const NEEDS_RESET = 64;
+        const FAILED = 128;
+    }
+}
+
+bitflags! {
+    /// virtio-console 特性位
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ConsoleFeatures: u64 {
+        /// 配置空间中的 cols/rows 有效
+        const SIZE = 1 << 0;
+        /// 多端口，本驱动不使用
+        const MULTIPORT = 1 << 1;
+        /// 支持通过配置空间紧急写出字符
+        const EMERG_WRITE = 1 << 2;
+        /// modern 设备必须协商的版本位
+        const VERSION_1 = 1 << 32;
+    }
+}
+
+/// virtio 设备探测错误
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VirtioError {
+    #[error("Bad magic value {0:#x}")]
+    BadMagic(u32),
+    #[error("Unsupported virtio-mmio version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Device id {0} is not a console")]
+    NotConsole(u32),
+    #[error("Queue {0} is not available")]
+    QueueUnavailable(u16),
+    #[error("DMA allocation failed")]
+    Dma,
+    #[error(transparent)]
+    Claim(#[from] ClaimError),
+}
+
+/// virtio-mmio 寄存器访问接口
+///
+/// 真实设备使用 [`VirtioConsole::new`] 内部的 MMIO 实现；主机测试可以实现该 trait
+/// 模拟设备对寄存器写入（例如 QueueNotify）的响应。
+pub trait VirtioTransport: Send + Sync + 'static {
+    /// 读取 `offset` 处的 32 位寄存器
+    fn read(&self, offset: usize) -> u32;
+    /// 写入 `offset` 处的 32 位寄存器
+    fn write(&self, offset: usize, value: u32);
+}
+
+/// virtio-mmio 寄存器区域大小，包含 0x100 起的设备配置空间
+const MMIO_REGION_LEN: usize = 0x200;
+
+/// 持有寄存器区域占用，随传输层一起由驱动和各句柄共享
+struct MmioTransport {
+    base: NonNull<u8>,
+    _claim: Option<Claim>,
+}
+
+unsafe impl Send for MmioTransport {}
+unsafe impl Sync for MmioTransport {}
+
+impl VirtioTransport for MmioTransport {
+    fn read(&self, offset: usize) -> u32 {
+        unsafe { self.base.add(offset).cast::<u32>().read_volatile() }
+    }
+
+    fn write(&self, offset: usize, value: u32) {
+        unsafe { self.base.add(offset).cast::<u32>().write_volatile(value) }
+    }
+}
+
+#[derive(Clone)]
+struct Transport {
+    inner: Arc<dyn VirtioTransport>,
+    /// 寄存器区域基地址，只用于上报
+    base: usize,
+}
+
+impl Transport {
+    fn read(&self, offset: usize) -> u32 {
+        self.inner.read(offset)
+    }
+
+    fn write(&self, offset: usize, value: u32) {
+        self.inner.write(offset, value)
+    }
+
+    fn addr(&self) -> usize {
+        self.base
+    }
+
+    fn status(&self) -> DeviceStatus {
+        DeviceStatus::from_bits_retain(self.read(offset::STATUS))
+    }
+
+    fn add_status(&self, status: DeviceStatus) {
+        self.write(offset::STATUS, (self.status() | status).bits());
+    }
+
+    fn notify(&self, queue: u16) {
+        // 通知前确保环上的写入对设备可见
+        fence(Ordering::SeqCst);
+        self.write(offset::QUEUE_NOTIFY, queue as u32);
+    }
+}
+
+impl PartialEq for Transport {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+/// 描述符表项
+#[repr(C)]
+struct Desc {
+    addr: u64,
+    len: u32,
+    flags: u16,
+    next: u16,
+}
+
+/// 队列的驱动侧状态，收发端和驱动的清空/重建都要先取得队列锁
+struct QueueState {
+    /// 空闲描述符（发送队列）
+    free: Vec<u16>,
+    /// 正在读取的已用缓冲区：(描述符, 长度, 已读位置)（接收队列）
+    pending: Option<(u16, usize, usize)>,
+}
+
+/// 单个 virtqueue，环按 legacy 布局连续分配，modern 设备同样可用
+struct VirtQueue {
+    index: u16,
+    size: u16,
+    ring: DVec<u8>,
+    bufs: DVec<u8>,
+    ready: AtomicBool,
+    /// 下一个写入 avail 环的位置，只在持有锁时修改
+    avail_idx: AtomicU16,
+    /// 已处理到的 used 环位置，只在持有锁时修改，中断处理无锁读取
+    last_used: AtomicU16,
+    lock: AtomicBool,
+    state: UnsafeCell<QueueState>,
+}
+
+// SAFETY: state 和环的修改只在持有 lock 时进行
+unsafe impl Sync for VirtQueue {}
+
+impl VirtQueue {
+    fn new(
+        index: u16,
+        size: u16,
+        dma_mask: u64,
+        direction: Direction,
+    ) -> Result<Self, VirtioError> {
+        let (_, _, total) = Self::layout(size);
+        let ring = DVec::zeros(dma_mask, total, PAGE_SIZE, Direction::Bidirectional)
+            .map_err(|_| VirtioError::Dma)?;
+        let bufs = DVec::zeros(dma_mask, size as usize * BUF_SIZE, BUF_SIZE, direction)
+            .map_err(|_| VirtioError::Dma)?;
+
+        Ok(Self {
+            index,
+            size,
+            ring,
+            bufs,
+            ready: AtomicBool::new(false),
+            avail_idx: AtomicU16::new(0),
+            last_used: AtomicU16::new(0),
+            lock: AtomicBool::new(false),
+            state: UnsafeCell::new(QueueState {
+                free: Vec::new(),
+                pending: None,
+            }),
+        })
+    }
+
+    /// 返回 (avail 偏移, used 偏移, 总大小)
+    const fn layout(size: u16) -> (usize, usize, usize) {
+        let size = size as usize;
+        let avail = 16 * size;
+        let used = (avail + 6 + 2 * size).next_multiple_of(PAGE_SIZE);
+        let total = used + (6 + 8 * size).next_multiple_of(PAGE_SIZE);
+        (avail, used, total)
+    }
+
+    fn desc_addr(&self) -> u64 {
+        self.ring.bus_addr()
+    }
+
+    fn avail_addr(&self) -> u64 {
+        self.ring.bus_addr() + Self::layout(self.size).0 as u64
+    }
+
+    fn used_addr(&self) -> u64 {
+        self.ring.bus_addr() + Self::layout(self.size).1 as u64
+    }
+
+    fn ptr<T>(&self, offset: usize) -> *mut T {
+        unsafe { self.ring.as_ptr().add(offset).cast() }
+    }
+
+    fn avail_ptr<T>(&self, offset: usize) -> *mut T {
+        self.ptr(Self::layout(self.size).0 + offset)
+    }
+
+    fn used_ptr<T>(&self, offset: usize) -> *mut T {
+        self.ptr(Self::layout(self.size).1 + offset)
+    }
+
+    fn buf_ptr(&self, id: u16) -> *mut u8 {
+        unsafe { self.bufs.as_ptr().add(id as usize * BUF_SIZE) }
+    }
+
+    /// 持有队列锁访问驱动侧状态，收发端与 flush/open 可能在不同上下文中并发调用
+    fn with_state<R>(&self, f: impl FnOnce(&mut QueueState) -> R) -> R {
+        while self
+            .lock
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        let result = f(unsafe { &mut *self.state.get() });
+        self.lock.store(false, Ordering::Release);
+        result
+    }
+
+    /// 清空环并重建描述符表，接收队列把所有缓冲区交给设备
+    fn reset(&self, device_writable: bool) {
+        self.with_state(|state| self.reset_locked(state, device_writable));
+    }
+
+    fn reset_locked(&self, state: &mut QueueState, device_writable: bool) {
+        unsafe {
+            core::ptr::write_bytes(self.ring.as_ptr(), 0, self.ring.len());
+
+            for id in 0..self.size {
+                let desc = Desc {
+                    addr: self.bufs.bus_addr() + (id as usize * BUF_SIZE) as u64,
+                    len: BUF_SIZE as u32,
+                    flags: if device_writable { DESC_F_WRITE } else { 0 },
+                    next: 0,
+                };
+                self.ptr::<Desc>(id as usize * size_of::<Desc>())
+                    .write_volatile(desc);
+            }
+
+            self.avail_idx.store(0, Ordering::Relaxed);
+            self.last_used.store(0, Ordering::Relaxed);
+            state.pending = None;
+            state.free.clear();
+            if !device_writable {
+                state.free.extend((0..self.size).rev());
+            }
+        }
+        self.ring.confirm_write_all();
+
+        if device_writable {
+            for id in 0..self.size {
+                self.post(id, BUF_SIZE);
+            }
+        }
+    }
+
+    /// 把描述符放入 avail 环，调用方需持有队列锁
+    fn post(&self, id: u16, len: usize) {
+        unsafe {
+            let desc = self.ptr::<Desc>(id as usize * size_of::<Desc>());
+            core::ptr::addr_of_mut!((*desc).len).write_volatile(len as u32);
+
+            let avail_idx = self.avail_idx.load(Ordering::Relaxed);
+            let slot = (avail_idx % self.size) as usize;
+            self.avail_ptr::<u16>(4 + 2 * slot).write_volatile(id);
+            self.ring.confirm_write_all();
+
+            // 环内容必须先于 idx 对设备可见
+            fence(Ordering::SeqCst);
+            let avail_idx = avail_idx.wrapping_add(1);
+            self.avail_idx.store(avail_idx, Ordering::Release);
+            self.avail_ptr::<u16>(2).write_volatile(avail_idx);
+        }
+        self.ring.confirm_write_all();
+    }
+
+    fn used_idx(&self) -> u16 {
+        self.ring.prepare_read_all();
+        unsafe { self.used_ptr::<u16>(2).read_volatile() }
+    }
+
+    /// 取出下一个已用描述符：(描述符, 长度)，调用方需持有队列锁
+    fn pop_used(&self) -> Option<(u16, usize)> {
+        let used_idx = self.used_idx();
+        let last_used = self.last_used.load(Ordering::Relaxed);
+        if used_idx == last_used {
+            return None;
+        }
+        fence(Ordering::SeqCst);
+
+        let slot = (last_used % self.size) as usize;
+        let (id, len) = unsafe {
+            (
+                self.used_ptr::<u32>(4 + 8 * slot).read_volatile() as u16,
+                self.used_ptr::<u32>(8 + 8 * slot).read_volatile() as usize,
+            )
+        };
+        self.last_used
+            .store(last_used.wrapping_add(1), Ordering::Release);
+        Some((id, len))
+    }
+
+    /// 是否有尚未处理的已用描述符，供中断处理使用
+    ///
+    /// 不取队列锁，被中断的收发端可能正持有它。
+    fn has_used(&self) -> bool {
+        self.used_idx() != self.last_used.load(Ordering::Acquire)
+    }
+
+    /// 设备是否已处理完 avail 环上的全部描述符，供排空发送队列使用
+    fn all_used(&self) -> bool {
+        self.used_idx() == self.avail_idx.load(Ordering::Acquire)
+    }
+
+    /// 丢弃接收队列中已收到但未读取的数据，缓冲区全部交还设备，返回是否交还了缓冲区
+    fn discard_used(&self) -> bool {
+        self.with_state(|state| {
+            let mut recycled = false;
+            if let Some((id, _, _)) = state.pending.take() {
+                self.post(id, BUF_SIZE);
+                recycled = true;
+            }
+            while let Some((id, _)) = self.pop_used() {
+                self.post(id, BUF_SIZE);
+                recycled = true;
+            }
+            recycled
+        })
+    }
+
+    fn set_interrupt(&self, enable: bool) {
+        let flags = if enable { 0 } else { AVAIL_F_NO_INTERRUPT };
+        unsafe { self.avail_ptr::<u16>(0).write_volatile(flags) };
+        self.ring.confirm_write_all();
+    }
+
+    fn interrupt_enabled(&self) -> bool {
+        self.ring.prepare_read_all();
+        unsafe { self.avail_ptr::<u16>(0).read_volatile() & AVAIL_F_NO_INTERRUPT == 0 }
+    }
+}
+
+/// virtio-console 驱动结构体
+pub struct VirtioConsole {
+    transport: Transport,
+    version: u32,
+    features: ConsoleFeatures,
+    baudrate: u32,
+    rx_queue: Arc<VirtQueue>,
+    tx_queue: Arc<VirtQueue>,
+    /// 重新配置期间暂停发送端写入，与发送端共享
+    tx_pause: TxPause,
+    tx: Option<VirtioConsoleSender>,
+    rx: Option<VirtioConsoleReciever>,
+    irq: Option<VirtioConsoleIrqHandler>,
+}
+
+impl VirtioConsole {
+    /// 探测 `base` 处的 virtio-mmio 设备并分配队列
+    pub fn new(base: NonNull<u8>) -> Result<Self, VirtioError> {
+        Self::with_transport(
+            Arc::new(MmioTransport { base, _claim: None }),
+            base.as_ptr() as usize,
+        )
+    }
+
+    pub fn new_boxed(base: NonNull<u8>) -> Result<BSerial, VirtioError> {
+        Ok(SerialDyn::new_boxed(Self::new(base)?))
+    }
+
+    /// 先占用 `base` 处的寄存器区域，与已有实例重叠时返回 [`VirtioError::Claim`]
+    pub fn try_new(base: NonNull<u8>) -> Result<Self, VirtioError> {
+        let claim = Claim::new(Region::mmio(base, MMIO_REGION_LEN))?;
+        Self::with_transport(
+            Arc::new(MmioTransport {
+                base,
+                _claim: Some(claim),
+            }),
+            base.as_ptr() as usize,
+        )
+    }
+
+    pub fn try_new_boxed(base: NonNull<u8>) -> Result<BSerial, VirtioError> {
+        Ok(SerialDyn::new_boxed(Self::try_new(base)?))
+    }
+
+    /// 使用自定义寄存器访问实现创建实例，不参与区域占用登记
+    ///
+    /// `base` 是设备寄存器区域的基地址，由 [`InterfaceRaw::base_addr`] 返回。
+    pub fn with_transport(
+        transport: Arc<dyn VirtioTransport>,
+        base: usize,
+    ) -> Result<Self, VirtioError> {
+        let transport = Transport {
+            inner: transport,
+            base,
+        };
+
+        let magic = transport.read(offset::MAGIC);
+        if magic != MAGIC_VALUE {
+            return Err(VirtioError::BadMagic(magic));
+        }
+        let version = transport.read(offset::VERSION);
+        if !(1..=2).contains(&version) {
+            return Err(VirtioError::UnsupportedVersion(version));
+        }
+        let device_id = transport.read(offset::DEVICE_ID);
+        if device_id != DEVICE_ID_CONSOLE {
+            return Err(VirtioError::NotConsole(device_id));
+        }
+
+        // legacy 的 QueuePFN 只有 32 位页号
+        let dma_mask = if version == 1 {
+            (1 << 44) - 1
+        } else {
+            u64::MAX
+        };
+
+        let queue_size = |index: u16| {
+            transport.write(offset::QUEUE_SEL, index as u32);
+            match transport.read(offset::QUEUE_NUM_MAX) {
+                0 => Err(VirtioError::QueueUnavailable(index)),
+                max => Ok((max as u16).min(MAX_QUEUE_SIZE)),
+            }
+        };
+        let rx_queue = Arc::new(VirtQueue::new(
+            RX_QUEUE,
+            queue_size(RX_QUEUE)?,
+            dma_mask,
+            Direction::FromDevice,
+        )?);
+        let tx_queue = Arc::new(VirtQueue::new(
+            TX_QUEUE,
+            queue_size(TX_QUEUE)?,
+            dma_mask,
+            Direction::ToDevice,
+        )?);
+        let tx_pause = TxPause::default();
+
+        Ok(Self {
+            tx_pause: tx_pause.clone(),
+            tx: Some(VirtioConsoleSender {
+                transport: transport.clone(),
+                queue: tx_queue.clone(),
+                stats: None,
+                tx_pause: tx_pause.clone(),
+            }),
+            rx: Some(VirtioConsoleReciever {
+                transport: transport.clone(),
+                queue: rx_queue.clone(),
+                stats: None,
+            }),
+            irq: Some(VirtioConsoleIrqHandler {
+                transport: transport.clone(),
+                rx_queue: rx_queue.clone(),
+                tx_queue: tx_queue.clone(),
+                stats: None,
+            }),
+            transport,
+            version,
+            features: ConsoleFeatures::empty(),
+            baudrate: 115200,
+            rx_queue,
+            tx_queue,
+        })
+    }
+
+    /// 挂接统计计数器，只对尚未取出的收发和中断句柄生效
+    pub fn attach_stats(&mut self, stats: &'static Stats) {
+        if let Some(tx) = self.tx.as_mut() {
+            tx.attach_stats(stats);
+        }
+        if let Some(rx) = self.rx.as_mut() {
+            rx.attach_stats(stats);
+        }
+        if let Some(irq) = self.irq.as_mut() {
+            irq.stats = Some(stats);
+        }
+    }
+
+    /// 已协商的特性
+    pub fn features(&self) -> ConsoleFeatures {
+        self.features
+    }
+
+    /// 控制台尺寸 (cols, rows)，未协商 `SIZE` 时返回 `None`
+    pub fn console_size(&self) -> Option<(u16, u16)> {
+        if !self.features.contains(ConsoleFeatures::SIZE) {
+            return None;
+        }
+
+        // 配置空间可能在读取过程中被设备更新，按 generation 重读
+        loop {
+            let generation = self.transport.read(offset::CONFIG_GENERATION);
+            let size = self.transport.read(offset::CONFIG);
+            if generation == self.transport.read(offset::CONFIG_GENERATION) {
+                return Some((size as u16, (size >> 16) as u16));
+            }
+        }
+    }
+
+    /// 通过配置空间紧急写出一个字符，不经过队列，适合在 panic 等场景使用
+    ///
+    /// 未协商 `EMERG_WRITE` 时返回 false。
+    pub fn emergency_write(&self, byte: u8) -> bool {
+        if !self.features.contains(ConsoleFeatures::EMERG_WRITE) {
+            return false;
+        }
+        self.transport.write(offset::CONFIG_EMERG_WR, byte as u32);
+        true
+    }
+
+    fn read_device_features(&self) -> u64 {
+        self.transport.write(offset::DEVICE_FEATURES_SEL, 0);
+        let low = self.transport.read(offset::DEVICE_FEATURES) as u64;
+        self.transport.write(offset::DEVICE_FEATURES_SEL, 1);
+        let high = self.transport.read(offset::DEVICE_FEATURES) as u64;
+        (high << 32) | low
+    }
+
+    fn write_driver_features(&self, features: u64) {
+        self.transport.write(offset::DRIVER_FEATURES_SEL, 0);
+        self.transport
+            .write(offset::DRIVER_FEATURES, features as u32);
+        self.transport.write(offset::DRIVER_FEATURES_SEL, 1);
+        self.transport
+            .write(offset::DRIVER_FEATURES, (features >> 32) as u32);
+    }
+
+    /// 向设备登记队列地址
+    fn setup_queue(&self, queue: &VirtQueue) {
+        let t = &self.transport;
+        t.write(offset::QUEUE_SEL, queue.index as u32);
+        t.write(offset::QUEUE_NUM, queue.size as u32);
+
+        if self.version == 1 {
+            t.write(offset::QUEUE_ALIGN, PAGE_SIZE as u32);
+            t.write(
+                offset::QUEUE_PFN,
+                (queue.desc_addr() / PAGE_SIZE as u64) as u32,
+            );
+        } else {
+            let regs = [
+                (
+                    offset::QUEUE_DESC_LOW,
+                    offset::QUEUE_DESC_HIGH,
+                    queue.desc_addr(),
+                ),
+                (
+                    offset::QUEUE_DRIVER_LOW,
+                    offset::QUEUE_DRIVER_HIGH,
+                    queue.avail_addr(),
+                ),
+                (
+                    offset::QUEUE_DEVICE_LOW,
+                    offset::QUEUE_DEVICE_HIGH,
+                    queue.used_addr(),
+                ),
+            ];
+            for (low, high, addr) in regs {
+                t.write(low, addr as u32);
+                t.write(high, (addr >> 32) as u32);
+            }
+            t.write(offset::QUEUE_READY, 1);
+        }
+    }
+
+    /// 复位设备并等待复位完成
+    fn reset_device(&self) -> bool {
+        self.rx_queue.ready.store(false, Ordering::Release);
+        self.tx_queue.ready.store(false, Ordering::Release);
+
+        self.transport.write(offset::STATUS, 0);
+        for _ in 0..BUSY_SPIN_LIMIT {
+            if self.transport.read(offset::STATUS) == 0 {
+                return true;
+            }
+            core::hint::spin_loop();
+        }
+        false
+    }
+
+    /// 发送队列是否已被设备处理完，队列未启用时视为已排空
+    fn drained(&self) -> bool {
+        !self.tx_queue.ready.load(Ordering::Acquire) || self.tx_queue.all_used()
+    }
+
+    /// 按 virtio 规范的初始化顺序启动设备
+    fn init(&mut self) {
+        let t = self.transport.clone();
+
+        if !self.reset_device() {
+            log::error!("virtio-console: device reset timed out");
+            return;
+        }
+        t.add_status(DeviceStatus::ACKNOWLEDGE);
+        t.add_status(DeviceStatus::DRIVER);
+
+        // 协商特性
+        let device = ConsoleFeatures::from_bits_retain(self.read_device_features());
+        let mut wanted = ConsoleFeatures::SIZE | ConsoleFeatures::EMERG_WRITE;
+        if self.version == 2 {
+            if !device.contains(ConsoleFeatures::VERSION_1) {
+                log::error!("virtio-console: modern device without VERSION_1");
+                t.add_status(DeviceStatus::FAILED);
+                return;
+            }
+            wanted |= ConsoleFeatures::VERSION_1;
+        }
+        let features = device & wanted;
+        self.write_driver_features(features.bits());
+
+        if self.version == 2 {
+            t.add_status(DeviceStatus::FEATURES_OK);
+            if !t.status().contains(DeviceStatus::FEATURES_OK) {
+                log::error!("virtio-console: feature negotiation rejected");
+                t.add_status(DeviceStatus::FAILED);
+                return;
+            }
+        } else {
+            t.write(offset::GUEST_PAGE_SIZE, PAGE_SIZE as u32);
+        }
+        self.features = features;
+
+        // 队列内容必须在登记之前准备好
+        self.rx_queue.reset(true);
+        self.tx_queue.reset(false);
+        self.setup_queue(&self.rx_queue);
+        self.setup_queue(&self.tx_queue);
+
+        t.add_status(DeviceStatus::DRIVER_OK);
+        self.rx_queue.ready.store(true, Ordering::Release);
+        self.tx_queue.ready.store(true, Ordering::Release);
+
+        t.notify(RX_QUEUE);
+    }
+}
+
+impl InterfaceRaw for VirtioConsole {
+    type IrqHandler = VirtioConsoleIrqHandler;
+
+    type Sender = crate::Sender;
+
+    type Reciever = crate::Reciever;
+
+    fn base_addr(&self) -> usize {
+        self.transport.addr()
+    }
+
+    fn set_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+        self.capabilities().validate(config)?;
+        if let Some(baudrate) = config.baudrate {
+            self.baudrate = baudrate;
+        }
+        Ok(())
+    }
+
+    /// 虚拟链路没有真实波特率，返回最近一次配置的值
+    fn baudrate(&self) -> u32 {
+        self.baudrate
+    }
+
+    fn data_bits(&self) -> DataBits {
+        DataBits::Eight
+    }
+
+    fn stop_bits(&self) -> StopBits {
+        StopBits::One
+    }
+
+    fn parity(&self) -> Parity {
+        Parity::None
+    }
+
+    fn clock_freq(&self) -> Option<core::num::NonZeroU32> {
+        None
+    }
+
+    fn open(&mut self) {
+        self.init()
+    }
+
+    fn close(&mut self) {
+        self.reset_device();
+    }
+
+    // virtio-console 没有回环
+    fn enable_loopback(&mut self) {}
+
+    fn disable_loopback(&mut self) {}
+
+    fn is_loopback_enabled(&self) -> bool {
+        false
+    }
+
+    fn set_irq_mask(&mut self, mask: InterruptMask) {
+        self.rx_queue
+            .set_interrupt(mask.contains(InterruptMask::RX_AVAILABLE));
+        self.tx_queue
+            .set_interrupt(mask.contains(InterruptMask::TX_EMPTY));
+    }
+
+    fn get_irq_mask(&self) -> InterruptMask {
+        let mut mask = InterruptMask::empty();
+
+        if self.rx_queue.interrupt_enabled() {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if self.tx_queue.interrupt_enabled() {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        mask
+    }
+
+    fn irq_handler(&mut self) -> Option<Self::IrqHandler> {
+        self.irq.take()
+    }
+
+    fn take_tx(&mut self) -> Option<Self::Sender> {
+        self.tx.take().map(crate::Sender::VirtioConsoleSender)
+    }
+
+    fn take_rx(&mut self) -> Option<Self::Reciever> {
+        self.rx.take().map(crate::Reciever::VirtioConsoleReciever)
+    }
+
+    fn set_tx(&mut self, tx: Self::Sender) -> Result<(), SetBackError> {
+        let tx = match tx {
+            crate::Sender::VirtioConsoleSender(s) => s,
+            _ => {
+                return Err(SetBackError::new(
+                    self.transport.addr(),
+                    tx.external_base().unwrap_or(0), // 不匹配的发送器类型
+                ));
+            }
+        };
+
+        if self.transport != tx.transport {
+            return Err(SetBackError::new(
+                self.transport.addr(),
+                tx.transport.addr(),
+            ));
+        }
+
+        self.tx = Some(tx);
+        Ok(())
+    }
+
+    fn set_rx(&mut self, rx: Self::Reciever) -> Result<(), SetBackError> {
+        let rx = match rx {
+            crate::Reciever::VirtioConsoleReciever(r) => r,
+            _ => {
+                return Err(SetBackError::new(
+                    self.transport.addr(),
+                    rx.external_base().unwrap_or(0), // 不匹配的接收器类型
+                ));
+            }
+        };
+
+        if self.transport != rx.transport {
+            return Err(SetBackError::new(
+                self.transport.addr(),
+                rx.transport.addr(),
+            ));
+        }
+
+        self.rx = Some(rx);
+        Ok(())
+    }
+}
+
+impl Caps for VirtioConsole {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            fifo_depth: self.tx_queue.size * BUF_SIZE as u16,
+            min_baudrate: 1,
+            max_baudrate: u32::MAX,
+            data_bits: &[DataBits::Eight],
+            stop_bits: &[StopBits::One],
+            parity: &[Parity::None],
+            features: Features::empty(),
+        }
+    }
+}
+
+impl FifoThreshold for VirtioConsole {}
+
+/// 已放入发送队列的描述符无法从设备撤回，清空发送方向返回
+/// [`FlushError::TxUnsupported`]；排空等待设备处理完全部发送描述符。
+impl Flush for VirtioConsole {
+    fn flush(&mut self, queue: FlushQueue) -> Result<(), FlushError> {
+        if queue.rx() && self.rx_queue.ready.load(Ordering::Acquire) && self.rx_queue.discard_used()
+        {
+            self.transport.notify(RX_QUEUE);
+        }
+        if queue.tx() && !self.drained() {
+            return Err(FlushError::TxUnsupported);
+        }
+        Ok(())
+    }
+
+    fn drain(&mut self) -> Result<(), FlushError> {
+        for _ in 0..BUSY_SPIN_LIMIT {
+            if self.drained() {
+                return Ok(());
+            }
+            core::hint::spin_loop();
+        }
+        Err(FlushError::Timeout)
+    }
+
+    fn tx_pause(&self) -> Option<&TxPause> {
+        Some(&self.tx_pause)
+    }
+}
+
+/// virtio-console 挂起时保存的上下文，恢复时重新初始化设备
+#[derive(Debug, Clone, Copy)]
+pub struct VirtioConsoleContext {
+    irq_mask: InterruptMask,
+    baudrate: u32,
+}
+
+impl Suspend for VirtioConsole {
+    type Context = VirtioConsoleContext;
+
+    fn save_context(&self) -> Self::Context {
+        VirtioConsoleContext {
+            irq_mask: self.get_irq_mask(),
+            baudrate: self.baudrate,
+        }
+    }
+
+    fn restore_context(&mut self, ctx: &Self::Context) {
+        // 设备状态无法逐个寄存器恢复，按打开流程重新协商并重建队列
+        self.init();
+        self.baudrate = ctx.baudrate;
+        self.set_irq_mask(ctx.irq_mask);
+    }
+}
+
+pub struct VirtioConsoleSender {
+    transport: Transport,
+    queue: Arc<VirtQueue>,
+    stats: Option<&'static Stats>,
+    tx_pause: TxPause,
+}
+
+impl AttachStats for VirtioConsoleSender {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl TSender for VirtioConsoleSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        RawSender::write_byte(self, byte)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> usize {
+        RawSender::write_bytes(self, bytes)
+    }
+}
+
+impl RawSender for VirtioConsoleSender {
+    fn write_byte(&mut self, byte: u8) -> bool {
+        RawSender::write_bytes(self, &[byte]) == 1
+    }
+
+    /// 每个描述符最多携带 `BUF_SIZE` 字节，描述符用完时返回已写入的字节数
+    fn write_bytes(&mut self, bytes: &[u8]) -> usize {
+        let Some(_writing) = self.tx_pause.enter() else {
+            return 0;
+        };
+        let queue = &self.queue;
+        if !queue.ready.load(Ordering::Acquire) {
+            return 0;
+        }
+
+        let written = queue.with_state(|state| {
+            while let Some((id, _)) = queue.pop_used() {
+                state.free.push(id);
+            }
+
+            let mut written = 0;
+            for chunk in bytes.chunks(BUF_SIZE) {
+                let Some(id) = state.free.pop() else {
+                    break;
+                };
+
+                unsafe {
+                    core::ptr::copy_nonoverlapping(chunk.as_ptr(), queue.buf_ptr(id), chunk.len());
+                }
+                queue.bufs.confirm_write_all();
+                queue.post(id, chunk.len());
+                written += chunk.len();
+            }
+            written
+        });
+
+        if written > 0 {
+            self.transport.notify(queue.index);
+            if let Some(stats) = self.stats {
+                stats.record_tx(written);
+            }
+        }
+
+        written
+    }
+}
+
+pub struct VirtioConsoleReciever {
+    transport: Transport,
+    queue: Arc<VirtQueue>,
+    stats: Option<&'static Stats>,
+}
+
+impl AttachStats for VirtioConsoleReciever {
+    fn attach_stats(&mut self, stats: &'static Stats) {
+        self.stats = Some(stats);
+    }
+}
+
+impl VirtioConsoleReciever {
+    fn read_raw(&mut self) -> Option<Result<u8, TransferError>> {
+        let queue = &self.queue;
+        if !queue.ready.load(Ordering::Acquire) {
+            return None;
+        }
+
+        // 读完的缓冲区在锁内重新放入 avail 环，释放锁后再通知设备
+        let mut recycled = false;
+        let byte = queue.with_state(|state| loop {
+            let (id, len, pos) = match state.pending {
+                Some(pending) => pending,
+                None => {
+                    let (id, len) = queue.pop_used()?;
+                    (id, len.min(BUF_SIZE), 0)
+                }
+            };
+
+            if pos < len {
+                queue.bufs.prepare_read_all();
+                let byte = unsafe { queue.buf_ptr(id).add(pos).read_volatile() };
+                state.pending = if pos + 1 < len {
+                    Some((id, len, pos + 1))
+                } else {
+                    None
+                };
+                if state.pending.is_none() {
+                    queue.post(id, BUF_SIZE);
+                    recycled = true;
+                }
+                return Some(byte);
+            }
+
+            // 空缓冲区直接归还
+            state.pending = None;
+            queue.post(id, BUF_SIZE);
+            recycled = true;
+        });
+
+        if recycled {
+            self.transport.notify(queue.index);
+        }
+        byte.map(Ok)
+    }
+}
+
+impl TReciever for VirtioConsoleReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        RawReciever::read_byte(self)
+    }
+}
+
+impl RawReciever for VirtioConsoleReciever {
+    fn read_byte(&mut self) -> Option<Result<u8, TransferError>> {
+        let result = self.read_raw()?;
+        if let Some(stats) = self.stats {
+            stats.record_rx(&result);
+        }
+        Some(result)
+    }
+}
+
+pub struct VirtioConsoleIrqHandler {
+    transport: Transport,
+    rx_queue: Arc<VirtQueue>,
+    tx_queue: Arc<VirtQueue>,
+    stats: Option<&'static Stats>,
+}
+
+impl TIrqHandler for VirtioConsoleIrqHandler {
+    /// 应答 InterruptStatus 中的全部位；已用缓冲区通知不区分队列，
+    /// 按各队列是否有未处理的 used 项判断原因。配置变更只应答不上报。
+    fn clean_interrupt_status(&self) -> InterruptMask {
+        let status = self.transport.read(offset::INTERRUPT_STATUS);
+        if status != 0 {
+            self.transport.write(offset::INTERRUPT_ACK, status);
+        }
+
+        let mut mask = InterruptMask::empty();
+        if status & INT_USED_BUFFER == 0 {
+            return mask;
+        }
+
+        if self.rx_queue.interrupt_enabled() && self.rx_queue.has_used() {
+            mask |= InterruptMask::RX_AVAILABLE;
+        }
+        if self.tx_queue.interrupt_enabled() && self.tx_queue.has_used() {
+            mask |= InterruptMask::TX_EMPTY;
+        }
+
+        if let Some(stats) = self.stats {
+            if mask.contains(InterruptMask::RX_AVAILABLE) {
+                stats.record_irq(IrqCause::Rx);
+            }
+            if mask.contains(InterruptMask::TX_EMPTY) {
+                stats.record_irq(IrqCause::Tx);
+            }
+        }
+
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::{collections::VecDeque, sync::Mutex, vec::Vec};
+
+    use super::{
+        offset, ConsoleFeatures, Desc, DeviceStatus, VirtQueue, VirtioConsole, VirtioError,
+        VirtioTransport, BUF_SIZE, DESC_F_WRITE, DEVICE_ID_CONSOLE, INT_USED_BUFFER, MAGIC_VALUE,
+        PAGE_SIZE, RX_QUEUE,
+    };
+    use crate::{Flush, FlushQueue, InterruptMask};
+    use alloc::sync::Arc;
+    use core::ptr::NonNull;
+    use dma_api::Direction;
+    use rdif_serial::{InterfaceRaw, SetBackError, TIrqHandler, TReciever, TSender};
+
+    /// 模拟设备的寄存器基地址
+    const FAKE_BASE: usize = 0x0a00_3e00;
+
+    /// 模拟 IOMMU：总线地址从低地址开始分配，保证 legacy 的 32 位 PFN 放得下
+    struct FakeIommu;
+
+    /// (总线地址, 虚拟地址, 大小)
+    static MAPPINGS: Mutex<Vec<(u64, usize, usize)>> = Mutex::new(Vec::new());
+
+    impl dma_api::Osal for FakeIommu {
+        fn map(&self, addr: NonNull<u8>, size: usize, _direction: Direction) -> u64 {
+            let mut mappings = MAPPINGS.lock().unwrap();
+            let next = mappings
+                .iter()
+                .map(|&(bus, _, size)| bus + size as u64)
+                .max()
+                .unwrap_or(0x1000_0000);
+            // 保留页内偏移，使对齐要求不变
+            let page_offset = addr.as_ptr() as u64 % PAGE_SIZE as u64;
+            let bus = next.next_multiple_of(PAGE_SIZE as u64) + page_offset;
+            mappings.push((bus, addr.as_ptr() as usize, size));
+            bus
+        }
+
+        fn unmap(&self, addr: NonNull<u8>, _size: usize) {
+            let mut mappings = MAPPINGS.lock().unwrap();
+            mappings.retain(|&(_, virt, _)| virt != addr.as_ptr() as usize);
+        }
+
+        fn flush(&self, _addr: NonNull<u8>, _size: usize) {}
+
+        fn invalidate(&self, _addr: NonNull<u8>, _size: usize) {}
+    }
+
+    static OSAL: FakeIommu = FakeIommu;
+
+    /// 设备侧把总线地址转换回主机指针
+    fn host<T>(bus: u64) -> *mut T {
+        let mappings = MAPPINGS.lock().unwrap();
+        let &(base, virt, _) = mappings
+            .iter()
+            .find(|&&(base, _, size)| (base..base + size as u64).contains(&bus))
+            .expect("unmapped bus address");
+        (virt + (bus - base) as usize) as *mut T
+    }
+
+    #[derive(Default)]
+    struct FakeQueue {
+        num: u32,
+        desc: u64,
+        avail: u64,
+        used: u64,
+        last_avail: u16,
+    }
+
+    #[derive(Default)]
+    struct FakeState {
+        version: u32,
+        device_id: u32,
+        device_features: u64,
+        device_features_sel: u32,
+        driver_features: u64,
+        driver_features_sel: u32,
+        status: u32,
+        queue_sel: usize,
+        queues: [FakeQueue; 2],
+        interrupt_status: u32,
+        size: u32,
+        emergency: Vec<u8>,
+        output: Vec<u8>,
+        input: VecDeque<u8>,
+    }
+
+    /// 只实现控制台用到的寄存器语义，设备与驱动共享同一地址空间
+    struct FakeConsole(Mutex<FakeState>);
+
+    impl FakeConsole {
+        fn new(version: u32, device_id: u32) -> Arc<Self> {
+            dma_api::init(&OSAL);
+            Arc::new(Self(Mutex::new(FakeState {
+                version,
+                device_id,
+                device_features: (ConsoleFeatures::SIZE
+                    | ConsoleFeatures::MULTIPORT
+                    | ConsoleFeatures::EMERG_WRITE
+                    | ConsoleFeatures::VERSION_1)
+                    .bits(),
+                size: (25 << 16) | 80,
+                ..Default::default()
+            })))
+        }
+
+        fn push_input(&self, bytes: &[u8]) {
+            let mut state = self.0.lock().unwrap();
+            state.input.extend(bytes);
+            Self::process(&mut state, RX_QUEUE as usize);
+        }
+
+        fn output(&self) -> Vec<u8> {
+            self.0.lock().unwrap().output.clone()
+        }
+
+        /// 处理 avail 环中的描述符，并在 used 环中归还
+        fn process(state: &mut FakeState, index: usize) {
+            if state.status & DeviceStatus::DRIVER_OK.bits() == 0 {
+                return;
+            }
+
+            let mut completed = false;
+            loop {
+                if index == RX_QUEUE as usize && state.input.is_empty() {
+                    break;
+                }
+
+                let q = &state.queues[index];
+                let avail_idx = unsafe { host::<u16>(q.avail + 2).read_volatile() };
+                if q.last_avail == avail_idx {
+                    break;
+                }
+
+                let slot = (q.last_avail as u32 % q.num) as u64;
+                let id = unsafe { host::<u16>(q.avail + 4 + 2 * slot).read_volatile() };
+                let desc = unsafe { host::<Desc>(q.desc + 16 * id as u64).read_volatile() };
+
+                let len = if index == RX_QUEUE as usize {
+                    assert_ne!(desc.flags & DESC_F_WRITE, 0);
+                    let n = (desc.len as usize).min(state.input.len());
+                    for i in 0..n {
+                        let byte = state.input.pop_front().unwrap();
+                        unsafe { host::<u8>(desc.addr + i as u64).write_volatile(byte) };
+                    }
+                    n as u32
+                } else {
+                    let data =
+                        unsafe { core::slice::from_raw_parts(host(desc.addr), desc.len as usize) };
+                    state.output.extend_from_slice(data);
+                    0
+                };
+
+                let q = &mut state.queues[index];
+                unsafe {
+                    let used_idx = host::<u16>(q.used + 2).read_volatile();
+                    let slot = (used_idx as u32 % q.num) as u64;
+                    host::<u32>(q.used + 4 + 8 * slot).write_volatile(id as u32);
+                    host::<u32>(q.used + 8 + 8 * slot).write_volatile(len);
+                    host::<u16>(q.used + 2).write_volatile(used_idx.wrapping_add(1));
+                }
+                q.last_avail = q.last_avail.wrapping_add(1);
+                completed = true;
+            }
+
+            if completed {
+                state.interrupt_status |= INT_USED_BUFFER;
+            }
+        }
+    }
+
+    impl VirtioTransport for FakeConsole {
+        fn read(&self, offset: usize) -> u32 {
+            let state = self.0.lock().unwrap();
+            match offset {
+                offset::MAGIC => MAGIC_VALUE,
+                offset::VERSION => state.version,
+                offset::DEVICE_ID => state.device_id,
+                offset::DEVICE_FEATURES => {
+                    (state.device_features >> (32 * state.device_features_sel)) as u32
+                }
+                offset::QUEUE_NUM_MAX => 8,
+                offset::INTERRUPT_STATUS => state.interrupt_status,
+                offset::STATUS => state.status,
+                offset::CONFIG_GENERATION => 0,
+                offset::CONFIG => state.size,
+                _ => 0,
+            }
+        }
+
+        fn write(&self, offset: usize, value: u32) {
+            let mut state = self.0.lock().unwrap();
+            let sel = state.queue_sel;
+            match offset {
+                offset::DEVICE_FEATURES_SEL => state.device_features_sel = value,
+                offset::DRIVER_FEATURES_SEL => state.driver_features_sel = value,
+                offset::DRIVER_FEATURES => {
+                    let shift = 32 * state.driver_features_sel;
+                    state.driver_features &= !(0xffff_ffff << shift);
+                    state.driver_features |= (value as u64) << shift;
+                }
+                offset::QUEUE_SEL => state.queue_sel = value as usize,
+                offset::QUEUE_NUM => state.queues[sel].num = value,
+                offset::QUEUE_PFN => {
+                    let q = &mut state.queues[sel];
+                    let (avail, used, _) = VirtQueue::layout(q.num as u16);
+                    q.desc = value as u64 * PAGE_SIZE as u64;
+                    q.avail = q.desc + avail as u64;
+                    q.used = q.desc + used as u64;
+                }
+                offset::QUEUE_DESC_LOW => state.queues[sel].desc = value as u64,
+                offset::QUEUE_DRIVER_LOW => state.queues[sel].avail = value as u64,
+                offset::QUEUE_DEVICE_LOW => state.queues[sel].used = value as u64,
+                offset::QUEUE_DESC_HIGH => state.queues[sel].desc |= (value as u64) << 32,
+                offset::QUEUE_DRIVER_HIGH => state.queues[sel].avail |= (value as u64) << 32,
+                offset::QUEUE_DEVICE_HIGH => state.queues[sel].used |= (value as u64) << 32,
+                offset::QUEUE_NOTIFY => Self::process(&mut state, value as usize),
+                offset::INTERRUPT_ACK => state.interrupt_status &= !value,
+                offset::STATUS => {
+                    state.status = value;
+                    if value == 0 {
+                        state.queues = Default::default();
+                    }
+                }
+                offset::CONFIG_EMERG_WR => state.emergency.push(value as u8),
+                _ => {}
+            }
+        }
+    }
+
+    fn open(device: &Arc<FakeConsole>) -> VirtioConsole {
+        let mut console = VirtioConsole::with_transport(device.clone(), FAKE_BASE).unwrap();
+        console.open();
+        console
+    }
+
+    #[test]
+    fn probe_rejects_other_devices() {
+        let device = FakeConsole::new(2, 2);
+        assert_eq!(
+            VirtioConsole::with_transport(device, FAKE_BASE).err(),
+            Some(VirtioError::NotConsole(2))
+        );
+    }
+
+    #[test]
+    fn reports_mmio_base() {
+        let device = FakeConsole::new(2, DEVICE_ID_CONSOLE);
+        let mut console = open(&device);
+        assert_eq!(console.base_addr(), FAKE_BASE);
+
+        // 其他实例的发送端即使基地址相同也不能放回
+        let mut other = open(&FakeConsole::new(2, DEVICE_ID_CONSOLE));
+        let err = console.set_tx(other.take_tx().unwrap()).unwrap_err();
+        assert_eq!(
+            std::format!("{err:?}"),
+            std::format!("{:?}", SetBackError::new(FAKE_BASE, FAKE_BASE))
+        );
+    }
+
+    #[test]
+    fn negotiates_features() {
+        let device = FakeConsole::new(2, DEVICE_ID_CONSOLE);
+        let console = open(&device);
+
+        let expected =
+            ConsoleFeatures::SIZE | ConsoleFeatures::EMERG_WRITE | ConsoleFeatures::VERSION_1;
+        assert_eq!(console.features(), expected);
+        assert_eq!(device.0.lock().unwrap().driver_features, expected.bits());
+        assert_eq!(console.console_size(), Some((80, 25)));
+
+        assert!(console.emergency_write(b'!'));
+        assert_eq!(device.0.lock().unwrap().emergency, b"!");
+    }
+
+    #[test]
+    fn transmit_reaches_device() {
+        let device = FakeConsole::new(2, DEVICE_ID_CONSOLE);
+        let mut console = open(&device);
+        let mut tx = console.take_tx().unwrap();
+
+        // 超过单个缓冲区的数据会拆成多个描述符
+        let data: Vec<u8> = (0..200u8).collect();
+        assert_eq!(tx.write_bytes(&data), data.len());
+        assert!(tx.write_byte(b'x'));
+
+        let mut expected = data.clone();
+        expected.push(b'x');
+        assert_eq!(device.output(), expected);
+    }
+
+    #[test]
+    fn receive_recycles_buffers() {
+        let device = FakeConsole::new(2, DEVICE_ID_CONSOLE);
+        let mut console = open(&device);
+        let mut rx = console.take_rx().unwrap();
+
+        assert!(rx.read_byte().is_none());
+
+        // 总量超过队列中全部缓冲区，只有归还后设备才能继续投递
+        let data: Vec<u8> = (0..8 * BUF_SIZE + 10).map(|i| i as u8).collect();
+        device.push_input(&data);
+
+        let mut received = Vec::new();
+        while let Some(byte) = rx.read_byte() {
+            received.push(byte.unwrap());
+        }
+        assert_eq!(received, data);
+    }
+
+    #[test]
+    fn flush_discards_received_data() {
+        let device = FakeConsole::new(2, DEVICE_ID_CONSOLE);
+        let mut console = open(&device);
+        let mut rx = console.take_rx().unwrap();
+        let mut tx = console.take_tx().unwrap();
+
+        // 读取到一半的缓冲区和尚未读取的缓冲区都被丢弃并交还设备
+        device.push_input(&[b'a'; 3 * BUF_SIZE]);
+        assert_eq!(rx.read_byte(), Some(Ok(b'a')));
+        console.flush(FlushQueue::Rx).unwrap();
+        assert!(rx.read_byte().is_none());
+
+        device.push_input(b"z");
+        assert_eq!(rx.read_byte(), Some(Ok(b'z')));
+
+        tx.write_bytes(b"out");
+        assert_eq!(console.drain(), Ok(()));
+        assert_eq!(console.flush(FlushQueue::Both), Ok(()));
+    }
+
+    #[test]
+    fn irq_reports_used_queues() {
+        let device = FakeConsole::new(2, DEVICE_ID_CONSOLE);
+        let mut console = open(&device);
+        let handler = console.irq_handler().unwrap();
+        let mut tx = console.take_tx().unwrap();
+
+        console.set_irq_mask(InterruptMask::RX_AVAILABLE);
+        assert_eq!(
+            console.get_irq_mask().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+
+        device.push_input(b"a");
+        assert_eq!(
+            handler.clean_interrupt_status().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+        assert_eq!(device.0.lock().unwrap().interrupt_status, 0);
+
+        // 数据读走后不再上报接收，发送中断被屏蔽时也不上报
+        let mut rx = console.take_rx().unwrap();
+        assert_eq!(rx.read_byte(), Some(Ok(b'a')));
+        tx.write_byte(b'b');
+        assert!(handler.clean_interrupt_status().is_empty());
+    }
+
+    #[test]
+    fn legacy_transport() {
+        let device = FakeConsole::new(1, DEVICE_ID_CONSOLE);
+        let mut console = open(&device);
+
+        assert!(!console.features().contains(ConsoleFeatures::VERSION_1));
+
+        let mut tx = console.take_tx().unwrap();
+        let mut rx = console.take_rx().unwrap();
+        tx.write_bytes(b"legacy");
+        assert_eq!(device.output(), b"legacy");
+
+        device.push_input(b"ok");
+        assert_eq!(rx.read_byte(), Some(Ok(b'o')));
+        assert_eq!(rx.read_byte(), Some(Ok(b'k')));
+    }
+}
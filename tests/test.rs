@@ -29,7 +29,7 @@ mod tests {
         GetIrqConfig,
     };
     use log::{debug, info};
-    use some_serial::{DataBits, InterruptMask, Parity, StopBits};
+    use some_serial::{samsung::SamsungVariant, DataBits, InterruptMask, Parity, StopBits};
 
     // === 全局中断计数器 ===
 
@@ -265,6 +265,130 @@ mod tests {
         info!("=== Interrupt Mask Control Test Completed ===");
     }
 
+    /// 配置校验测试 - 超出硬件能力的配置应被整体拒绝
+    #[test]
+    fn test_config_rejected_by_capabilities() {
+        info!("=== Config Validation Test ===");
+
+        let mut serial = create_test_serial();
+        serial.open().expect("Failed to open serial");
+
+        let before = (serial.baudrate(), serial.data_bits(), serial.parity());
+
+        // 波特率远超 clock / 16，同时携带其他合法设置
+        let config = some_serial::Config::new()
+            .baudrate(u32::MAX)
+            .data_bits(DataBits::Seven)
+            .parity(Parity::Even);
+        let err = serial.set_config(&config).unwrap_err();
+        assert_eq!(err, some_serial::ConfigError::InvalidBaudrate);
+
+        let after = (serial.baudrate(), serial.data_bits(), serial.parity());
+        assert_eq!(before, after, "rejected config must not touch registers");
+
+        info!("=== Config Validation Test Completed ===");
+    }
+
+    /// 挂起/恢复测试 - 恢复后配置应与保存时一致
+    #[test]
+    fn test_suspend_resume_context() {
+        info!("=== Suspend/Resume Context Test ===");
+
+        let uart_info =
+            find_best_uart_for_testing().expect("No suitable UART device found for testing");
+
+        match uart_info.driver_type {
+            UartDriverType::PL011 => check_suspend_resume(some_serial::pl011::Pl011::new(
+                uart_info.base,
+                uart_info.clk,
+            )),
+            UartDriverType::Ns16550Mmio => check_suspend_resume(
+                some_serial::ns16550::Ns16550::new_mmio(uart_info.base, uart_info.clk, 4),
+            ),
+            UartDriverType::Samsung(variant) => {
+                check_suspend_resume(new_samsung_uart(&uart_info, variant))
+            }
+        }
+
+        info!("=== Suspend/Resume Context Test Completed ===");
+    }
+
+    fn check_suspend_resume<T: some_serial::Suspend>(mut uart: T) {
+        uart.open();
+        let saved_config = some_serial::Config::new()
+            .baudrate(115200)
+            .data_bits(DataBits::Eight)
+            .stop_bits(StopBits::One)
+            .parity(Parity::None);
+        uart.set_config(&saved_config).unwrap();
+        let expected = (uart.baudrate(), uart.data_bits(), uart.parity());
+
+        let ctx = uart.suspend(true);
+        assert_eq!(
+            uart.get_irq_mask().bits(),
+            InterruptMask::RX_AVAILABLE.bits()
+        );
+
+        // 模拟掉电后寄存器被改写
+        let scratch = some_serial::Config::new()
+            .baudrate(9600)
+            .data_bits(DataBits::Seven)
+            .parity(Parity::Even);
+        uart.set_config(&scratch).unwrap();
+
+        uart.resume(&ctx);
+        assert_eq!((uart.baudrate(), uart.data_bits(), uart.parity()), expected);
+    }
+
+    /// 统计计数测试 - 回环收发后字节计数应与数据长度一致
+    #[test]
+    fn test_port_stats() {
+        info!("=== Port Stats Test ===");
+
+        static STATS: some_serial::Stats = some_serial::Stats::new();
+
+        let uart_info =
+            find_best_uart_for_testing().expect("No suitable UART device found for testing");
+
+        let mut serial: BSerial = match uart_info.driver_type {
+            UartDriverType::PL011 => {
+                let mut uart = some_serial::pl011::Pl011::new(uart_info.base, uart_info.clk);
+                uart.attach_stats(&STATS);
+                rdif_serial::SerialDyn::new_boxed(uart)
+            }
+            UartDriverType::Ns16550Mmio => {
+                let mut uart =
+                    some_serial::ns16550::Ns16550::new_mmio(uart_info.base, uart_info.clk, 4);
+                uart.attach_stats(&STATS);
+                rdif_serial::SerialDyn::new_boxed(uart)
+            }
+            UartDriverType::Samsung(variant) => {
+                let mut uart = new_samsung_uart(&uart_info, variant);
+                uart.attach_stats(&STATS);
+                rdif_serial::SerialDyn::new_boxed(uart)
+            }
+        };
+        serial.open().expect("Failed to open serial");
+
+        let mut tx = serial.take_tx().unwrap();
+        let mut rx = serial.take_rx().unwrap();
+        clean_rx(&mut rx);
+        STATS.reset();
+
+        let test_data = b"stats";
+        test_serial_tx_rx_one(&mut serial, &mut tx, &mut rx, test_data)
+            .expect("loopback should succeed");
+
+        let snapshot = STATS.snapshot();
+        info!("Stats: {:?}", snapshot);
+        assert_eq!(snapshot.tx_bytes, test_data.len());
+        // 辅助函数发送前还会清空一次接收端，残留数据也会被计入
+        assert!(snapshot.rx_bytes >= test_data.len());
+        assert_eq!(snapshot.rx_errors(), 0);
+
+        info!("=== Port Stats Test Completed ===");
+    }
+
     // /// Serial 压力测试 - 多次连续回环操作
     // #[test]
     // fn test_serial_stress_loopback() {
@@ -336,6 +460,7 @@ mod tests {
     enum UartDriverType {
         PL011,
         Ns16550Mmio,
+        Samsung(SamsungVariant),
     }
 
     /// 从兼容性字符串获取对应的驱动类型
@@ -344,6 +469,10 @@ mod tests {
             match comp.as_str() {
                 "arm,pl011" => return Some(UartDriverType::PL011),
                 "snps,dw-apb-uart" => return Some(UartDriverType::Ns16550Mmio), // DesignWare APB UART 兼容 NS16550
+                "samsung,s5pv210-uart" | "samsung,exynos4210-uart" => {
+                    return Some(UartDriverType::Samsung(SamsungVariant::Exynos))
+                }
+                "apple,s5l-uart" => return Some(UartDriverType::Samsung(SamsungVariant::AppleS5l)),
                 _ => {}
             }
         }
@@ -367,6 +496,9 @@ mod tests {
             UartDriverType::Ns16550Mmio => {
                 some_serial::ns16550::Ns16550::new_mmio_boxed(uart_info.base, uart_info.clk, 4)
             }
+            UartDriverType::Samsung(variant) => {
+                rdif_serial::SerialDyn::new_boxed(new_samsung_uart(&uart_info, variant))
+            }
         };
 
         uart.open().expect("Failed to initialize UART");
@@ -378,6 +510,16 @@ mod tests {
         uart
     }
 
+    /// 创建 Samsung UART 实例
+    ///
+    /// 调试串口对应的端口号未知，FIFO 深度按各端口中最小的 16 字节处理。
+    fn new_samsung_uart(
+        uart_info: &UartDeviceInfo,
+        variant: SamsungVariant,
+    ) -> some_serial::samsung::SamsungUart {
+        some_serial::samsung::SamsungUart::new(uart_info.base, uart_info.clk, variant, 16)
+    }
+
     /// UART 设备信息
     struct UartDeviceInfo {
         base: core::ptr::NonNull<u8>,
@@ -415,6 +557,8 @@ mod tests {
                 match driver_type {
                     UartDriverType::PL011 => 24_000_000,
                     UartDriverType::Ns16550Mmio => 1_843_200,
+                    UartDriverType::Samsung(SamsungVariant::Exynos) => 100_000_000,
+                    UartDriverType::Samsung(SamsungVariant::AppleS5l) => 24_000_000,
                 }
             });
 